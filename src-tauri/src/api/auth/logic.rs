@@ -1,84 +1,50 @@
-use std::{str::FromStr, sync::Arc};
+use std::str::FromStr;
 
 use base64::{prelude::BASE64_STANDARD, Engine};
-use reqwest::{
-    cookie::{self, CookieStore, Jar},
-    Response, StatusCode,
-};
+use reqwest::{cookie::CookieStore, Response, StatusCode};
 
 use crate::definitions::AuthCookies;
 
-use crate::api::common::{
-    check_rate_limit, get_reqwest_client, handle_api_response, record_rate_limit, reset_backoff,
-    API_BASE_URL,
-};
+use crate::api::common::ApiContext;
+use crate::errors::{ApiError, AppError, NetworkError};
 
 use super::definitions::{
     CurrentUser, RequiresTwoFactorAuth, TwoFactorAuthVerified, VRChatAuthPhase, VRChatAuthStatus,
 };
 
 pub struct VRChatAPIClientAuthenticator {
-    client: reqwest::Client,
-    cookie: Arc<cookie::Jar>,
+    context: ApiContext,
     username: String,
     phase: VRChatAuthPhase,
 }
 
 impl VRChatAPIClientAuthenticator {
-    pub fn new<T: AsRef<str>>(username: T) -> Self {
-        let cookie = Arc::new(cookie::Jar::default());
-        let client = get_reqwest_client(&cookie);
-
+    /// Creates an authenticator for the given cookie jar and API server
+    pub fn from_context(context: ApiContext) -> Self {
         VRChatAPIClientAuthenticator {
-            client,
-            cookie,
-            username: username.as_ref().to_string(),
+            context,
+            username: String::new(),
             phase: VRChatAuthPhase::None,
         }
     }
 
-    pub fn from_cookie_store(cookie_store: Arc<Jar>) -> Self {
-        let client = get_reqwest_client(&cookie_store);
-
-        VRChatAPIClientAuthenticator {
-            client,
-            cookie: cookie_store,
-            username: String::new(),
-            phase: VRChatAuthPhase::None,
-        }
+    pub fn get_context(&self) -> ApiContext {
+        self.context.clone()
     }
 
     pub fn update_user_info(&mut self, username: String) {
         self.username = username;
     }
 
-    pub fn get_cookies(&self) -> Arc<Jar> {
-        self.cookie.clone()
-    }
-
-    pub async fn verify_token(&mut self) -> Result<VRChatAuthStatus, String> {
+    pub async fn verify_token(&mut self) -> Result<VRChatAuthStatus, AppError> {
         const OPERATION: &str = "verify_token";
 
-        check_rate_limit(OPERATION)?;
-
         log::info!("Verifying token...");
-        let result = self
-            .client
-            .get(format!("{}/auth/user", API_BASE_URL))
-            .send()
-            .await
-            .map_err(|e| format!("Failed to send auth request: {}", e))?;
-
-        let result = match handle_api_response(result, OPERATION).await {
-            Ok(response) => response,
-            Err(e) => {
-                log::error!("Failed to handle API response: {}", e);
-                record_rate_limit(OPERATION);
-                return Err(e);
-            }
-        };
-
-        reset_backoff(OPERATION);
+        let request = self
+            .context
+            .client()
+            .get(format!("{}/auth/user", self.context.base_url()));
+        let result = self.context.execute(request, OPERATION).await?;
 
         if result.status() == StatusCode::UNAUTHORIZED {
             log::info!("Token is invalid or expired.");
@@ -86,10 +52,7 @@ impl VRChatAPIClientAuthenticator {
         }
 
         if result.status() == StatusCode::OK {
-            let text = result
-                .text()
-                .await
-                .map_err(|e| format!("Failed to read response: {}", e))?;
+            let text = read_text(result).await?;
 
             if let Ok(requires_2fa) = serde_json::from_str::<RequiresTwoFactorAuth>(&text) {
                 let email_otp = requires_2fa
@@ -109,18 +72,23 @@ impl VRChatAPIClientAuthenticator {
                 });
             }
 
-            let current_user = serde_json::from_str::<CurrentUser>(&text)
-                .map_err(|e| format!("Failed to parse user data: {}", e))?;
+            let current_user = serde_json::from_str::<CurrentUser>(&text).map_err(|e| {
+                log::error!("Failed to parse user data: {}", e);
+                NetworkError::InvalidResponse
+            })?;
 
-            let url = reqwest::Url::from_str(API_BASE_URL).unwrap();
+            let url = reqwest::Url::from_str(self.context.base_url())
+                .map_err(|e| ApiError::ResponseError(format!("Invalid API base URL: {}", e)))?;
             let cookie_str = self
-                .cookie
+                .context
+                .cookie()
                 .cookies(&url)
                 .map(|c| c.to_str().unwrap_or_default().to_string())
                 .unwrap_or_default();
 
             let auth_cookies = AuthCookies::from_cookie_str(&cookie_str);
             self.phase = VRChatAuthPhase::LoggedIn;
+            self.context.session().mark_valid();
 
             log::info!("Logged in successfully.");
             return Ok(VRChatAuthStatus::Success(auth_cookies, current_user));
@@ -134,44 +102,27 @@ impl VRChatAPIClientAuthenticator {
     pub async fn login_with_password<T: AsRef<str>>(
         &mut self,
         password: T,
-    ) -> Result<VRChatAuthStatus, String> {
+    ) -> Result<VRChatAuthStatus, AppError> {
         const OPERATION: &str = "login_with_password";
 
-        check_rate_limit(OPERATION)?;
-
         log::info!("Logging in with password...");
         let password = password.as_ref().to_string();
 
         let auth_header_value = self.generate_auth_header(&password);
 
-        let result = self
-            .client
-            .get(format!("{}/auth/user", API_BASE_URL))
-            .header("Authorization", &auth_header_value)
-            .send()
-            .await
-            .map_err(|e| format!("Failed to send auth request: {}", e))?;
-
-        let result = match handle_api_response(result, OPERATION).await {
-            Ok(response) => response,
-            Err(e) => {
-                log::error!("Failed to handle API response: {}", e);
-                record_rate_limit(OPERATION);
-                return Err(e);
-            }
-        };
-
-        reset_backoff(OPERATION);
+        let request = self
+            .context
+            .client()
+            .get(format!("{}/auth/user", self.context.base_url()))
+            .header("Authorization", &auth_header_value);
+        let result = self.context.execute(request, OPERATION).await?;
 
         if result.status() == StatusCode::UNAUTHORIZED {
             return Ok(VRChatAuthStatus::InvalidCredentials);
         }
 
         if result.status() == StatusCode::OK {
-            let text = match result.text().await {
-                Ok(text) => text,
-                Err(e) => return Err(format!("Failed to read response text: {}", e.to_string())),
-            };
+            let text = read_text(result).await?;
 
             if let Ok(requires_2fa) = serde_json::from_str::<RequiresTwoFactorAuth>(&text) {
                 let email_otp = requires_2fa
@@ -187,18 +138,30 @@ impl VRChatAPIClientAuthenticator {
                 }
             }
 
-            let url = reqwest::Url::from_str(API_BASE_URL).unwrap();
-            let header_value = self.cookie.cookies(&url);
+            let url = reqwest::Url::from_str(self.context.base_url())
+                .map_err(|e| ApiError::ResponseError(format!("Invalid API base URL: {}", e)))?;
+            let header_value = self.context.cookie().cookies(&url);
             let cookie_str = match header_value.as_ref() {
                 Some(value) => match value.to_str() {
                     Ok(cookie) => cookie,
-                    Err(e) => return Err(format!("Failed to convert cookie to string: {}", e)),
+                    Err(e) => {
+                        return Err(ApiError::ResponseError(format!(
+                            "Failed to convert cookie to string: {}",
+                            e
+                        ))
+                        .into())
+                    }
                 },
-                None => return Err("No cookies found for the given URL".to_string()),
+                None => {
+                    return Err(
+                        ApiError::InvalidRequest("No cookies found for the given URL").into(),
+                    )
+                }
             };
             let auth_cookies = AuthCookies::from_cookie_str(cookie_str);
 
             self.phase = VRChatAuthPhase::LoggedIn;
+            self.context.session().mark_valid();
             let current_user = CurrentUser {
                 id: String::new(),
                 username: String::new(),
@@ -208,50 +171,36 @@ impl VRChatAPIClientAuthenticator {
             return Ok(VRChatAuthStatus::Success(auth_cookies, current_user));
         }
 
-        match result.text().await {
-            Ok(text) => Ok(VRChatAuthStatus::UnknownError(format!(
-                "Unknown error occurred: {}",
-                text
-            ))),
-            Err(e) => Err(format!("Failed to read response text: {}", e.to_string())),
-        }
+        let text = read_text(result).await?;
+        Ok(VRChatAuthStatus::UnknownError(format!(
+            "Unknown error occurred: {}",
+            text
+        )))
     }
 
     pub async fn login_with_email_2fa<T: AsRef<str>>(
         &mut self,
         code: T,
-    ) -> Result<VRChatAuthStatus, String> {
+    ) -> Result<VRChatAuthStatus, AppError> {
         const OPERATION: &str = "login_with_2fa";
 
         log::info!("Logging in with email 2FA...");
         if self.phase != VRChatAuthPhase::Email2FA {
-            return Err("Not in email 2FA phase".to_string());
+            return Err(ApiError::InvalidRequest("Not in email 2FA phase").into());
         }
 
         let code = code.as_ref().to_string();
 
-        let response = self
-            .client
+        let request = self
+            .context
+            .client()
             .post(format!(
                 "{}/auth/twofactorauth/emailotp/verify",
-                API_BASE_URL
+                self.context.base_url()
             ))
             .header("Content-Type", "application/json")
-            .body(format!(r#"{{"code":"{}"}}"#, code))
-            .send()
-            .await
-            .map_err(|e| format!("Failed to send login request: {}", e))?;
-
-        let response = match handle_api_response(response, OPERATION).await {
-            Ok(response) => response,
-            Err(e) => {
-                log::error!("Failed to handle API response: {}", e);
-                record_rate_limit(OPERATION);
-                return Err(e);
-            }
-        };
-
-        reset_backoff(OPERATION);
+            .body(format!(r#"{{"code":"{}"}}"#, code));
+        let response = self.context.execute(request, OPERATION).await?;
 
         self.process_2fa_response(response).await
     }
@@ -259,37 +208,26 @@ impl VRChatAPIClientAuthenticator {
     pub async fn login_with_2fa<T: AsRef<str>>(
         &mut self,
         code: T,
-    ) -> Result<VRChatAuthStatus, String> {
+    ) -> Result<VRChatAuthStatus, AppError> {
         const OPERATION: &str = "login_with_2fa";
 
-        check_rate_limit(OPERATION)?;
-
         log::info!("Logging in with 2FA...");
         if self.phase != VRChatAuthPhase::TwoFactorAuth {
-            return Err("Not in 2FA phase".to_string());
+            return Err(ApiError::InvalidRequest("Not in 2FA phase").into());
         }
 
         let code = code.as_ref().to_string();
 
-        let response = self
-            .client
-            .post(format!("{}/auth/twofactorauth/totp/verify", API_BASE_URL))
+        let request = self
+            .context
+            .client()
+            .post(format!(
+                "{}/auth/twofactorauth/totp/verify",
+                self.context.base_url()
+            ))
             .header("Content-Type", "application/json")
-            .body(format!(r#"{{"code":"{}"}}"#, code))
-            .send()
-            .await
-            .map_err(|e| format!("Failed to send login request: {}", e))?;
-
-        let response = match handle_api_response(response, OPERATION).await {
-            Ok(response) => response,
-            Err(e) => {
-                log::error!("Failed to handle API response: {}", e);
-                record_rate_limit(OPERATION);
-                return Err(e);
-            }
-        };
-
-        reset_backoff(OPERATION);
+            .body(format!(r#"{{"code":"{}"}}"#, code));
+        let response = self.context.execute(request, OPERATION).await?;
 
         self.process_2fa_response(response).await
     }
@@ -305,31 +243,34 @@ impl VRChatAPIClientAuthenticator {
     async fn process_2fa_response(
         &mut self,
         response: Response,
-    ) -> Result<VRChatAuthStatus, String> {
+    ) -> Result<VRChatAuthStatus, AppError> {
         if response.status() == StatusCode::OK {
-            let text = response
-                .text()
-                .await
-                .map_err(|e| format!("Failed to read response text: {}", e))?;
+            let text = read_text(response).await?;
 
-            let verified = serde_json::from_str::<TwoFactorAuthVerified>(&text)
-                .map_err(|e| format!("Failed to parse response: {}", e.to_string()))?;
+            let verified = serde_json::from_str::<TwoFactorAuthVerified>(&text).map_err(|e| {
+                log::error!("Failed to parse response: {}", e);
+                NetworkError::InvalidResponse
+            })?;
 
             if !verified.is_verified {
                 return Ok(VRChatAuthStatus::InvalidCredentials);
             }
 
-            let url = reqwest::Url::from_str(API_BASE_URL).unwrap();
-            let header_value = self.cookie.cookies(&url);
+            let url = reqwest::Url::from_str(self.context.base_url())
+                .map_err(|e| ApiError::ResponseError(format!("Invalid API base URL: {}", e)))?;
+            let header_value = self.context.cookie().cookies(&url);
             let cookie_str = match header_value.as_ref() {
-                Some(value) => value
-                    .to_str()
-                    .map_err(|e| format!("Failed to convert cookie to string: {}", e))?,
-                None => return Err("No cookies found in the response".to_string()),
+                Some(value) => value.to_str().map_err(|e| {
+                    ApiError::ResponseError(format!("Failed to convert cookie to string: {}", e))
+                })?,
+                None => {
+                    return Err(ApiError::InvalidRequest("No cookies found in the response").into())
+                }
             };
             let auth_cookies = AuthCookies::from_cookie_str(cookie_str);
 
             self.phase = VRChatAuthPhase::LoggedIn;
+            self.context.session().mark_valid();
 
             let current_user = CurrentUser {
                 id: String::new(),
@@ -340,48 +281,30 @@ impl VRChatAPIClientAuthenticator {
             return Ok(VRChatAuthStatus::Success(auth_cookies, current_user));
         }
 
-        match response.text().await {
-            Ok(text) => Ok(VRChatAuthStatus::UnknownError(format!(
-                "Unknown error occurred: {}",
-                text
-            ))),
-            Err(e) => Err(format!("Failed to read response text: {}", e.to_string())),
-        }
+        let text = read_text(response).await?;
+        Ok(VRChatAuthStatus::UnknownError(format!(
+            "Unknown error occurred: {}",
+            text
+        )))
     }
 }
 
-pub async fn logout(jar: &Arc<Jar>) -> Result<(), String> {
-    const OPERATION: &str = "logout";
+async fn read_text(response: Response) -> Result<String, AppError> {
+    response.text().await.map_err(|e| {
+        log::error!("Failed to read response text: {}", e);
+        NetworkError::InvalidResponse.into()
+    })
+}
 
-    check_rate_limit(OPERATION)?;
+pub async fn logout(context: &ApiContext) -> Result<(), AppError> {
+    const OPERATION: &str = "logout";
 
     log::info!("Logging out...");
-    let client = get_reqwest_client(&jar);
-
-    let result = client
-        .put(format!("{}/logout", API_BASE_URL))
-        .send()
-        .await
-        .map_err(|e| format!("Failed to send logout request: {}", e))?;
-
-    let result = match handle_api_response(result, OPERATION).await {
-        Ok(response) => response,
-        Err(e) => {
-            log::error!("Failed to handle API response: {}", e);
-            record_rate_limit(OPERATION);
-            return Err(e);
-        }
-    };
+    let request = context
+        .client()
+        .put(format!("{}/logout", context.base_url()));
+    context.send(request, OPERATION).await?;
 
-    reset_backoff(OPERATION);
-
-    if result.status() == StatusCode::OK {
-        log::info!("Logout successful");
-        return Ok(());
-    }
-
-    match result.text().await {
-        Ok(text) => Err(format!("Failed to logout: {}", text)),
-        Err(e) => Err(format!("Failed to read response text: {}", e.to_string())),
-    }
+    log::info!("Logout successful");
+    Ok(())
 }
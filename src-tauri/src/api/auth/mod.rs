@@ -1,7 +1,6 @@
 mod definitions;
 mod logic;
 
-pub use definitions::VRChatAuthPhase;
 pub use definitions::VRChatAuthStatus;
 pub use logic::VRChatAPIClientAuthenticator;
 
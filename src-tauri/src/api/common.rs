@@ -1,24 +1,300 @@
 use chrono::Utc;
-use reqwest::{cookie::Jar, Response, StatusCode};
-use std::sync::Arc;
-use tauri::{AppHandle, Manager};
+use reqwest::{cookie::Jar, RequestBuilder, Response, StatusCode};
+use serde::de::DeserializeOwned;
+use std::sync::{Arc, RwLock};
 use tokio::time::{sleep, Duration};
 
+use crate::api::rate_limiter::{self, BackoffPolicy, EndpointClass, RequestLimiter};
+use crate::api::session::SessionMonitor;
+use crate::api::HttpClientFactory;
 use crate::api::RateLimitStore;
-use crate::RATE_LIMIT_STORE;
+use crate::errors::{ApiError, AppError, NetworkError};
 
 pub const API_BASE_URL: &str = "https://api.vrchat.cloud/api/1";
 
-const USER_AGENT: &str = "VRC Worlds Manager v2 (tauri)/1.3.1 discord:raifa";
+/// Environment variable used to point the api module at another server,
+/// e.g. a local mock or a recorded-fixture server
+pub const API_BASE_URL_ENV: &str = "VRC_WORLDS_MANAGER_API_BASE_URL";
 
-pub fn get_reqwest_client(cookies: &Arc<Jar>) -> reqwest::Client {
-    reqwest::ClientBuilder::new()
-        .user_agent(USER_AGENT)
+/// How many times a request is sent before a transient failure is returned
+const MAX_ATTEMPTS: u32 = 3;
+
+/// The delay before the first retry, doubled for every following one
+const RETRY_BASE_DELAY_MS: u64 = 1000;
+
+pub fn get_reqwest_client(cookies: &Arc<Jar>, factory: &HttpClientFactory) -> reqwest::Client {
+    factory
+        .builder()
         .cookie_provider(cookies.clone())
         .build()
         .expect("Failed to create reqwest client")
 }
 
+/// Returns the base URL for the VRChat API, honouring the environment override
+pub fn api_base_url() -> String {
+    match std::env::var(API_BASE_URL_ENV) {
+        Ok(url) if !url.trim().is_empty() => url.trim().trim_end_matches('/').to_string(),
+        _ => API_BASE_URL.to_string(),
+    }
+}
+
+/// The cookie jar, base URL, rate limits and network settings a request to the VRChat API
+/// is made with
+///
+/// Every function in the api module takes one of these, so tests can point
+/// the api module at a mock server with its own rate limit store.
+#[derive(Debug, Clone)]
+pub struct ApiContext {
+    cookie: Arc<Jar>,
+    base_url: String,
+    rate_limits: Arc<RwLock<RateLimitStore>>,
+    limiter: Arc<RequestLimiter>,
+    session: Arc<SessionMonitor>,
+    /// Builds the clients with the network settings, shared with the app state
+    http: Arc<RwLock<HttpClientFactory>>,
+    /// Whether the requests are made by a bulk job, which is spaced and backs off differently
+    bulk: bool,
+}
+
+impl ApiContext {
+    pub fn new<S: AsRef<str>>(
+        cookie: Arc<Jar>,
+        base_url: S,
+        rate_limits: Arc<RwLock<RateLimitStore>>,
+        http: Arc<RwLock<HttpClientFactory>>,
+    ) -> Self {
+        ApiContext {
+            cookie,
+            base_url: base_url.as_ref().trim_end_matches('/').to_string(),
+            rate_limits,
+            limiter: Arc::new(RequestLimiter::new()),
+            session: Arc::new(SessionMonitor::new()),
+            http,
+            bulk: false,
+        }
+    }
+
+    /// Returns a context for the same server and rate limits, using another cookie jar
+    pub fn with_cookie(&self, cookie: Arc<Jar>) -> Self {
+        ApiContext {
+            cookie,
+            base_url: self.base_url.clone(),
+            rate_limits: self.rate_limits.clone(),
+            limiter: self.limiter.clone(),
+            session: self.session.clone(),
+            http: self.http.clone(),
+            bulk: self.bulk,
+        }
+    }
+
+    /// Returns a context for the same session, for the requests of a bulk job
+    /// such as a favorites import or a metadata refresh
+    pub fn bulk(&self) -> Self {
+        ApiContext {
+            bulk: true,
+            ..self.clone()
+        }
+    }
+
+    pub fn cookie(&self) -> &Arc<Jar> {
+        &self.cookie
+    }
+
+    pub fn base_url(&self) -> &str {
+        &self.base_url
+    }
+
+    pub fn rate_limits(&self) -> &RwLock<RateLimitStore> {
+        &self.rate_limits
+    }
+
+    /// Whether the session was rejected, shared with the contexts made from this one
+    pub fn session(&self) -> &Arc<SessionMonitor> {
+        &self.session
+    }
+
+    pub fn client(&self) -> reqwest::Client {
+        get_reqwest_client(&self.cookie, &HttpClientFactory::current(&self.http))
+    }
+
+    /// Sends a request, and returns the response whatever its status
+    ///
+    /// The request is spaced by the limiter, and a rate limit is recorded for the operation.
+    /// Requests that only read data are retried with backoff on timeouts, connection failures
+    /// and 502/503/504. The others are sent once, as they may have reached the server.
+    ///
+    /// # Errors
+    /// Returns an error if the operation is rate limited, or the request failed after retrying
+    pub async fn execute(
+        &self,
+        request: RequestBuilder,
+        operation: &str,
+    ) -> Result<Response, AppError> {
+        self.throttle(operation).await?;
+
+        let idempotent = EndpointClass::of(operation) == EndpointClass::Read;
+        let mut request = request;
+        let mut attempt = 1;
+        let response = loop {
+            let retry = if idempotent && attempt < MAX_ATTEMPTS {
+                request.try_clone()
+            } else {
+                None
+            };
+            let result = request.send().await;
+
+            let transient = match &result {
+                Ok(response) => is_transient_status(response.status()),
+                Err(e) => e.is_timeout() || e.is_connect(),
+            };
+            match retry {
+                Some(retry) if transient => {
+                    let delay = apply_jitter(RETRY_BASE_DELAY_MS << (attempt - 1));
+                    log::warn!(
+                        "Transient failure for {} on attempt {}, retrying in {}ms",
+                        operation,
+                        attempt,
+                        delay
+                    );
+                    sleep(Duration::from_millis(delay)).await;
+                    self.throttle(operation).await?;
+                    request = retry;
+                    attempt += 1;
+                }
+                _ => break result.map_err(|e| network_error(e, operation))?,
+            }
+        };
+
+        let response = self.check_response(response, operation).await?;
+        reset_backoff(&self.rate_limits, operation, self.backoff_policy());
+        Ok(response)
+    }
+
+    /// Sends a request like `execute`, turning an unsuccessful status into an error
+    ///
+    /// # Errors
+    /// Returns the errors of `execute` and `check_status`
+    pub async fn send(
+        &self,
+        request: RequestBuilder,
+        operation: &str,
+    ) -> Result<Response, AppError> {
+        let response = self.execute(request, operation).await?;
+        self.check_status(response, operation).await
+    }
+
+    /// Turns an unsuccessful response into an error
+    /// A 401 also marks the session as expired, so it can be verified again.
+    ///
+    /// # Errors
+    /// Returns `ApiError::AuthenticationFailed` for 401, `ApiError::NotFound` for 404,
+    /// `NetworkError::HttpError` for server errors, and `ApiError::ResponseError` otherwise
+    pub async fn check_status(
+        &self,
+        response: Response,
+        operation: &str,
+    ) -> Result<Response, AppError> {
+        let status = response.status();
+        if status.is_success() {
+            return Ok(response);
+        }
+
+        let text = response.text().await.unwrap_or_default();
+        log::info!("{} failed: {} {}", operation, status, text);
+        Err(match status {
+            StatusCode::UNAUTHORIZED => {
+                self.session.mark_expired();
+                ApiError::AuthenticationFailed.into()
+            }
+            StatusCode::NOT_FOUND => ApiError::NotFound.into(),
+            status if status.is_server_error() => NetworkError::HttpError(status.as_u16()).into(),
+            status => ApiError::ResponseError(format!("{} {}", status, text)).into(),
+        })
+    }
+
+    /// Waits until a request can be sent
+    /// Fails right away if the operation is still backing off from a rate limit
+    async fn throttle(&self, operation: &str) -> Result<(), AppError> {
+        check_rate_limit(&self.rate_limits, operation)?;
+        if self.bulk {
+            self.limiter.acquire(EndpointClass::Bulk).await;
+        }
+        self.limiter.acquire(EndpointClass::of(operation)).await;
+        Ok(())
+    }
+
+    /// The backoff followed when the server rate limits a request of this context
+    fn backoff_policy(&self) -> BackoffPolicy {
+        if self.bulk {
+            BackoffPolicy::BULK
+        } else {
+            BackoffPolicy::INTERACTIVE
+        }
+    }
+
+    /// Checks a response for a rate limit, and records it with the delay asked by the server
+    async fn check_response(
+        &self,
+        response: Response,
+        operation: &str,
+    ) -> Result<Response, AppError> {
+        let retry_after = rate_limiter::retry_after(&response, Utc::now());
+        match handle_api_response(response, operation).await {
+            Ok(response) => Ok(response),
+            Err(e) => {
+                log::error!("Failed to handle API response: {}", e);
+                let backoff_ms = record_rate_limit_with(
+                    &self.rate_limits,
+                    operation,
+                    self.backoff_policy(),
+                    retry_after,
+                );
+                Err(ApiError::RateLimitExceeded {
+                    operation: operation.to_string(),
+                    retry_in_seconds: backoff_ms.div_ceil(1000),
+                }
+                .into())
+            }
+        }
+    }
+}
+
+/// Whether a response status is a temporary failure of VRChat or its proxies
+fn is_transient_status(status: StatusCode) -> bool {
+    matches!(
+        status,
+        StatusCode::BAD_GATEWAY | StatusCode::SERVICE_UNAVAILABLE | StatusCode::GATEWAY_TIMEOUT
+    )
+}
+
+fn network_error(error: reqwest::Error, operation: &str) -> AppError {
+    log::error!("Request for {} failed: {}", operation, error);
+    if error.is_timeout() {
+        NetworkError::Timeout.into()
+    } else {
+        NetworkError::ConnectionFailed.into()
+    }
+}
+
+/// Reads a response as JSON
+///
+/// # Errors
+/// Returns `NetworkError::InvalidResponse` if the body could not be read or parsed
+pub async fn parse_response<T: DeserializeOwned>(
+    response: Response,
+    what: &str,
+) -> Result<T, AppError> {
+    let text = response.text().await.map_err(|e| {
+        log::error!("Failed to read {}: {}", what, e);
+        NetworkError::InvalidResponse
+    })?;
+    serde_json::from_str(&text).map_err(|e| {
+        log::error!("Failed to parse {}: {}", what, e);
+        log::info!("Response: {}", text);
+        NetworkError::InvalidResponse.into()
+    })
+}
+
 /// Helper to handle response status and extract rate limit information
 pub async fn handle_api_response(response: Response, operation: &str) -> Result<Response, String> {
     let status = response.status();
@@ -33,8 +309,20 @@ pub async fn handle_api_response(response: Response, operation: &str) -> Result<
 }
 
 /// Record a rate limit for an endpoint and calculate backoff
-pub fn record_rate_limit(endpoint: &str) -> u64 {
-    let mut store = RATE_LIMIT_STORE.get().write().unwrap();
+#[cfg(test)]
+pub fn record_rate_limit(store: &RwLock<RateLimitStore>, endpoint: &str) -> u64 {
+    record_rate_limit_with(store, endpoint, BackoffPolicy::INTERACTIVE, None)
+}
+
+/// Record a rate limit for an endpoint, backing off for as long as the server asked
+/// through Retry-After, or exponentially following the policy otherwise
+pub fn record_rate_limit_with(
+    store: &RwLock<RateLimitStore>,
+    endpoint: &str,
+    policy: BackoffPolicy,
+    retry_after_ms: Option<u64>,
+) -> u64 {
+    let mut store = store.write().unwrap();
     let temp;
     {
         let data = store.endpoints.entry(endpoint.to_string()).or_default();
@@ -42,19 +330,24 @@ pub fn record_rate_limit(endpoint: &str) -> u64 {
         data.last_rate_limited = Some(Utc::now());
         data.consecutive_failures += 1;
 
-        // Calculate new backoff with exponential increase
-        let base_backoff = 600000; // 10 minutes in milliseconds
-        let max_backoff = 3600000; // Max 1 hour
-
-        // Use equal jitter algorithm for exponential backoff
-        let backoff = if data.consecutive_failures > 0 {
-            base_backoff * (2u64.pow((data.consecutive_failures - 1) as u32))
+        if let Some(retry_after_ms) = retry_after_ms {
+            // The server knows when the limit is lifted, so no jitter shortens the wait
+            data.current_backoff_ms = retry_after_ms;
+            temp = retry_after_ms;
         } else {
-            base_backoff
-        };
+            // Calculate new backoff with exponential increase
+            // Use equal jitter algorithm for exponential backoff
+            let backoff = if data.consecutive_failures > 0 {
+                policy
+                    .base_ms
+                    .saturating_mul(2u64.saturating_pow(data.consecutive_failures - 1))
+            } else {
+                policy.base_ms
+            };
 
-        data.current_backoff_ms = backoff.min(max_backoff);
-        temp = apply_jitter(data.current_backoff_ms);
+            data.current_backoff_ms = backoff.min(policy.max_ms);
+            temp = apply_jitter(data.current_backoff_ms);
+        }
         log::warn!(
             "Rate limit recorded for {}: {} consecutive failures, backoff: {}ms",
             endpoint,
@@ -70,8 +363,8 @@ pub fn record_rate_limit(endpoint: &str) -> u64 {
 }
 
 /// Check if we should wait before making a request
-pub fn should_backoff(endpoint: &str) -> Option<u64> {
-    let store = RATE_LIMIT_STORE.get().read().unwrap();
+pub fn should_backoff(store: &RwLock<RateLimitStore>, endpoint: &str) -> Option<u64> {
+    let store = store.read().unwrap();
 
     if let Some(data) = store.endpoints.get(endpoint) {
         if let Some(last_limited) = data.last_rate_limited {
@@ -88,15 +381,16 @@ pub fn should_backoff(endpoint: &str) -> Option<u64> {
     None
 }
 
-/// Reset the backoff for an endpoint after successful request
-pub fn reset_backoff(endpoint: &str) {
-    let mut store = RATE_LIMIT_STORE.get().write().unwrap();
+/// Reset the backoff for an endpoint after successful request,
+/// back to the base backoff of the policy it is rate limited with
+pub fn reset_backoff(store: &RwLock<RateLimitStore>, endpoint: &str, policy: BackoffPolicy) {
+    let mut store = store.write().unwrap();
 
     if let Some(data) = store.endpoints.get_mut(endpoint) {
         // Only reset if we had failures
         if data.consecutive_failures > 0 {
             data.consecutive_failures = 0;
-            data.current_backoff_ms = 600000; // Reset to base
+            data.current_backoff_ms = policy.base_ms;
             data.last_rate_limited = None; // Clear last rate limited time
             store.save();
             log::info!("Reset rate limit backoff for {}", endpoint);
@@ -104,14 +398,14 @@ pub fn reset_backoff(endpoint: &str) {
     }
 }
 
-/// Check if an endpoint is rate limited and return a formatted error if it is
-pub fn check_rate_limit(endpoint: &str) -> Result<(), String> {
-    if let Some(backoff_ms) = should_backoff(endpoint) {
-        let seconds = (backoff_ms / 1000) + 1; // Round up to nearest second
-        return Err(format!(
-            "Rate limit active for {}. Please try again in {} seconds.",
-            endpoint, seconds
-        ));
+/// Check if an endpoint is rate limited and return an error if it is
+pub fn check_rate_limit(store: &RwLock<RateLimitStore>, endpoint: &str) -> Result<(), AppError> {
+    if let Some(backoff_ms) = should_backoff(store, endpoint) {
+        return Err(ApiError::RateLimitExceeded {
+            operation: endpoint.to_string(),
+            retry_in_seconds: (backoff_ms / 1000) + 1, // Round up to nearest second
+        }
+        .into());
     }
     Ok(())
 }
@@ -125,7 +419,7 @@ pub fn apply_jitter(backoff_ms: u64) -> u64 {
     // This reduces average wait time while still preventing retry storms
 
     let half_backoff = backoff_ms / 2;
-    let jitter = rand::thread_rng().gen_range(0..=half_backoff);
+    let jitter = rand::rng().random_range(0..=half_backoff);
 
     half_backoff + jitter
 }
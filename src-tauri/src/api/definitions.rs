@@ -19,7 +19,7 @@ impl Default for RateLimitData {
     }
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Serialize, Deserialize, Default)]
 pub struct RateLimitStore {
     pub endpoints: HashMap<String, RateLimitData>,
     #[serde(skip)]
@@ -29,7 +29,7 @@ pub struct RateLimitStore {
 impl RateLimitStore {
     pub fn load(path: PathBuf) -> Self {
         let mut store = if path.exists() {
-            match fs::read_to_string(&path.clone()) {
+            match fs::read_to_string(path.clone()) {
                 Ok(data) => match serde_json::from_str::<Self>(&data) {
                     Ok(mut loaded) => {
                         loaded.data_path = Some(path.clone());
@@ -71,12 +71,3 @@ impl RateLimitStore {
         }
     }
 }
-
-impl Default for RateLimitStore {
-    fn default() -> Self {
-        Self {
-            endpoints: HashMap::new(),
-            data_path: None,
-        }
-    }
-}
@@ -0,0 +1,45 @@
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Serialize)]
+pub enum FavoriteType {
+    #[serde(rename = "world")]
+    World,
+    #[serde(rename = "friend")]
+    Friend,
+    #[serde(rename = "avatar")]
+    Avatar,
+}
+
+#[derive(Debug, Serialize)]
+pub struct AddFavoriteRequest {
+    #[serde(rename = "type")]
+    pub favorite_type: FavoriteType,
+    /// The ID of the favorited object, e.g. a world ID
+    #[serde(rename = "favoriteId")]
+    pub favorite_id: String,
+    /// The favorite group to add to, e.g. `worlds1`
+    pub tags: Vec<String>,
+}
+
+/// A favorite, as VRChat returns it once added
+#[derive(Debug, Deserialize)]
+#[allow(dead_code)]
+pub struct Favorite {
+    pub id: String,
+    #[serde(rename = "type")]
+    pub favorite_type: FavoriteType,
+    #[serde(rename = "favoriteId")]
+    pub favorite_id: String,
+    pub tags: Vec<String>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct FavoriteGroup {
+    /// The name used to refer to the group, e.g. `worlds1`
+    pub name: String,
+    /// The name shown in game, which the user can change
+    #[serde(rename = "displayName")]
+    pub display_name: String,
+    #[serde(rename = "type")]
+    pub favorite_type: FavoriteType,
+}
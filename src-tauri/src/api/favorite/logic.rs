@@ -0,0 +1,70 @@
+use super::definitions::{AddFavoriteRequest, Favorite, FavoriteGroup, FavoriteType};
+use crate::api::common::{parse_response, ApiContext};
+use crate::errors::{ApiError, AppError};
+
+/// Favorites a world in one of the user's world favorite groups
+/// VRChat rejects the request if the group is full, or if the world is
+/// already favorited in any group
+pub async fn add_world_favorite(
+    context: &ApiContext,
+    world_id: &str,
+    group: &str,
+) -> Result<Favorite, AppError> {
+    const OPERATION: &str = "add_favorite";
+
+    let client = context.client();
+
+    let request = AddFavoriteRequest {
+        favorite_type: FavoriteType::World,
+        favorite_id: world_id.to_string(),
+        tags: vec![group.to_string()],
+    };
+
+    let body = match serde_json::to_string(&request) {
+        Ok(body) => body,
+        Err(e) => {
+            log::info!("Failed to serialize request: {}", e);
+            return Err(ApiError::InvalidRequest("Failed to serialize request").into());
+        }
+    };
+
+    let request = client
+        .post(format!("{}/favorites", context.base_url()))
+        .header("Content-Type", "application/json")
+        .body(body);
+    let result = context.send(request, OPERATION).await?;
+
+    parse_response(result, "favorite").await
+}
+
+/// Removes a favorite, identified by the ID of the favorited object
+pub async fn remove_favorite(context: &ApiContext, object_id: &str) -> Result<(), AppError> {
+    const OPERATION: &str = "remove_favorite";
+
+    let request =
+        context
+            .client()
+            .delete(format!("{}/favorites/{}", context.base_url(), object_id));
+    context.send(request, OPERATION).await?;
+
+    Ok(())
+}
+
+/// Lists the world favorite groups of the logged in user
+pub async fn get_world_favorite_groups(
+    context: &ApiContext,
+) -> Result<Vec<FavoriteGroup>, AppError> {
+    const OPERATION: &str = "get_favorite_groups";
+
+    let request = context
+        .client()
+        .get(format!("{}/favorite/groups?n=100", context.base_url()));
+    let result = context.send(request, OPERATION).await?;
+
+    let groups: Vec<FavoriteGroup> = parse_response(result, "favorite groups").await?;
+
+    Ok(groups
+        .into_iter()
+        .filter(|group| group.favorite_type == FavoriteType::World)
+        .collect())
+}
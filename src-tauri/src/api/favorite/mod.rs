@@ -0,0 +1,6 @@
+mod definitions;
+mod logic;
+
+pub use logic::add_world_favorite;
+pub use logic::get_world_favorite_groups;
+pub use logic::remove_favorite;
@@ -1,115 +1,55 @@
-use std::sync::Arc;
-
-use reqwest::cookie::Jar;
-
-use crate::api::common::{
-    check_rate_limit, get_reqwest_client, handle_api_response, record_rate_limit, reset_backoff,
-    API_BASE_URL,
-};
+use crate::api::common::{parse_response, ApiContext};
+use crate::errors::{ApiError, AppError, NetworkError};
 
 use super::definitions::{
     GroupDetails, GroupInstanceCreatePermission, GroupInstancePermissionInfo, GroupPermission,
     UserGroup,
 };
 
-pub async fn get_user_groups<J: Into<Arc<Jar>>>(
-    cookie: J,
+pub async fn get_user_groups(
+    context: &ApiContext,
     user_id: &str,
-) -> Result<Vec<UserGroup>, String> {
+) -> Result<Vec<UserGroup>, AppError> {
     const OPERATION: &str = "get_user_groups";
 
-    check_rate_limit(OPERATION)?;
-
-    let cookie_jar: Arc<Jar> = cookie.into();
-    let client = get_reqwest_client(&cookie_jar);
+    let client = context.client();
+    let base_url = context.base_url();
 
     log::info!("Fetching groups for user: {}", user_id);
 
     if user_id.contains("/") {
-        return Err("User ID cannot contain '/'".to_string());
+        return Err(ApiError::InvalidRequest("User ID cannot contain '/'").into());
     }
 
-    let result = client
-        .get(format!("{API_BASE_URL}/users/{user_id}/groups"))
-        .send()
-        .await
-        .map_err(|e| e.to_string())?;
-
-    let result = match handle_api_response(result, OPERATION).await {
-        Ok(response) => response,
-        Err(e) => {
-            log::error!("Failed to handle API response: {}", e);
-            record_rate_limit(OPERATION);
-            return Err(e);
-        }
-    };
-
-    reset_backoff(OPERATION);
+    let request = client.get(format!("{base_url}/users/{user_id}/groups"));
+    let result = context.send(request, OPERATION).await?;
 
     log::info!("API Response status: {}", result.status());
 
-    let text = result.text().await;
-
-    if let Err(e) = text {
-        log::info!("Failed to read response text: {}", e);
-        return Err(format!("Failed to get user groups: {}", e.to_string()));
-    }
-
-    let text = text.unwrap();
-
-    let parsed: Vec<UserGroup> = match serde_json::from_str::<Vec<UserGroup>>(&text) {
-        Ok(groups) => {
-            log::info!("Successfully parsed {} groups", groups.len());
-            groups
-        }
-        Err(e) => {
-            log::info!("Failed to parse user groups: {}", e);
-            log::info!("Response that failed parsing: {}", text);
-            return Err(format!("Failed to parse user groups: {}", e.to_string()));
-        }
-    };
+    let parsed: Vec<UserGroup> = parse_response(result, "user groups").await?;
+    log::info!("Successfully parsed {} groups", parsed.len());
 
     Ok(parsed)
 }
 
 pub async fn get_permission_for_create_group_instance(
-    cookie: Arc<Jar>,
+    context: &ApiContext,
     group_id: &str,
-) -> Result<GroupInstancePermissionInfo, String> {
+) -> Result<GroupInstancePermissionInfo, AppError> {
     const OPERATION: &str = "get_permission_for_create_group_instance";
 
-    check_rate_limit(OPERATION)?;
-
     log::info!("Fetching permissions for group: {}", group_id);
-    let client = get_reqwest_client(&cookie);
-
-    let result = client
-        .get(format!(
-            "{API_BASE_URL}/groups/{group_id}?includeRoles=true"
-        ))
-        .send()
-        .await
-        .map_err(|e| {
-            log::info!("Failed to send request: {}", e);
-            format!("Failed to fetch group: {}", e)
-        })?;
-
-    let result = match handle_api_response(result, OPERATION).await {
-        Ok(response) => response,
-        Err(e) => {
-            log::error!("Failed to handle API response: {}", e);
-            record_rate_limit(OPERATION);
-            return Err(e);
-        }
-    };
+    let client = context.client();
+    let base_url = context.base_url();
 
-    reset_backoff(OPERATION);
+    let request = client.get(format!("{base_url}/groups/{group_id}?includeRoles=true"));
+    let result = context.send(request, OPERATION).await?;
 
     log::info!("API Response status: {}", result.status());
 
     let text = result.text().await.map_err(|e| {
         log::info!("Failed to read response text: {}", e);
-        format!("Failed to read response: {}", e)
+        NetworkError::InvalidResponse
     })?;
 
     let details: GroupDetails = match serde_json::from_str(&text) {
@@ -117,7 +57,7 @@ pub async fn get_permission_for_create_group_instance(
         Err(e) => {
             // Parse the JSON into a Value for inspection
             let parsed: serde_json::Value =
-                serde_json::from_str(&text).unwrap_or_else(|_| serde_json::Value::Null);
+                serde_json::from_str(&text).unwrap_or(serde_json::Value::Null);
             if let Some(obj) = parsed.as_object() {
                 // Inspect myMember object
                 if let Some(member) = obj.get("myMember") {
@@ -131,12 +71,13 @@ pub async fn get_permission_for_create_group_instance(
                 }
             }
 
-            return Err(format!(
+            log::error!(
                 "Failed to parse group details: {} at line {} column {}",
                 e,
                 e.line(),
                 e.column()
-            ));
+            );
+            return Err(NetworkError::InvalidResponse.into());
         }
     };
 
@@ -1,11 +1,7 @@
 mod definitions;
 mod logic;
 
-pub use definitions::GroupInstanceCreateAllowedType;
-pub use definitions::GroupInstanceCreatePermission;
 pub use definitions::GroupInstancePermissionInfo;
-pub use definitions::GroupMemberVisibility;
-pub use definitions::GroupRole;
 pub use definitions::UserGroup;
 
 pub use logic::get_permission_for_create_group_instance;
@@ -0,0 +1,164 @@
+use std::sync::RwLock;
+use std::time::Duration;
+
+use reqwest::{Certificate, Client, ClientBuilder, Proxy};
+
+use crate::definitions::NetworkSettings;
+
+const USER_AGENT: &str = concat!(
+    "VRC Worlds Manager v2 (tauri)/",
+    env!("CARGO_PKG_VERSION"),
+    " discord:raifa"
+);
+
+/// The network settings, with the proxy and certificates already parsed
+#[derive(Debug, Clone)]
+struct ClientConfig {
+    proxy: Option<Proxy>,
+    connect_timeout: Duration,
+    read_timeout: Duration,
+    certificates: Vec<Certificate>,
+}
+
+/// Builds the HTTP clients of every request the app makes, to VRChat or any other server
+///
+/// The clients share the user agent, and the proxy, timeouts and extra root
+/// certificates of the network preferences. The app state holds the factory made from
+/// the preferences, and shares it with the API contexts, so new settings apply to the
+/// next requests.
+#[derive(Debug, Clone)]
+pub struct HttpClientFactory {
+    config: ClientConfig,
+}
+
+impl Default for HttpClientFactory {
+    fn default() -> Self {
+        Self::new(&NetworkSettings::default()).expect("Default network settings are valid")
+    }
+}
+
+impl HttpClientFactory {
+    /// Creates a factory for clients with the given network settings
+    ///
+    /// # Arguments
+    /// * `settings` - The network settings of the preferences
+    ///
+    /// # Errors
+    /// Returns an error message if the proxy URL is invalid, or a certificate could not be read
+    pub fn new(settings: &NetworkSettings) -> Result<Self, String> {
+        Ok(Self {
+            config: Self::parse(settings)?,
+        })
+    }
+
+    /// Get the factory currently shared, or one with the default settings if its lock is poisoned
+    pub fn current(shared: &RwLock<HttpClientFactory>) -> HttpClientFactory {
+        match shared.read() {
+            Ok(factory) => factory.clone(),
+            Err(_) => {
+                log::error!("Failed to acquire read lock for network settings");
+                HttpClientFactory::default()
+            }
+        }
+    }
+
+    fn parse(settings: &NetworkSettings) -> Result<ClientConfig, String> {
+        let proxy = match settings.proxy_url.as_deref().map(str::trim) {
+            Some(url) if !url.is_empty() => {
+                Some(Proxy::all(url).map_err(|e| format!("Invalid proxy URL: {}", e))?)
+            }
+            _ => None,
+        };
+
+        let mut certificates = vec![];
+        for path in &settings.extra_root_certificates {
+            let pem = std::fs::read(path)
+                .map_err(|e| format!("Failed to read certificate {}: {}", path, e))?;
+            let certificate = Certificate::from_pem(&pem)
+                .map_err(|e| format!("Invalid certificate {}: {}", path, e))?;
+            certificates.push(certificate);
+        }
+
+        Ok(ClientConfig {
+            proxy,
+            connect_timeout: Duration::from_secs(settings.connect_timeout_seconds.max(1) as u64),
+            read_timeout: Duration::from_secs(settings.read_timeout_seconds.max(1) as u64),
+            certificates,
+        })
+    }
+
+    /// Get a client builder with the network settings applied,
+    /// for callers that need to configure more, e.g. a cookie store
+    pub fn builder(&self) -> ClientBuilder {
+        let config = self.config.clone();
+        let mut builder = ClientBuilder::new()
+            .user_agent(USER_AGENT)
+            .connect_timeout(config.connect_timeout)
+            .read_timeout(config.read_timeout);
+        if let Some(proxy) = config.proxy {
+            builder = builder.proxy(proxy);
+        }
+        for certificate in config.certificates {
+            builder = builder.add_root_certificate(certificate);
+        }
+        builder
+    }
+
+    /// Get a client with the network settings applied
+    pub fn client(&self) -> Client {
+        self.builder()
+            .build()
+            .expect("Failed to create reqwest client")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_invalid_settings_are_rejected() {
+        let settings = NetworkSettings {
+            proxy_url: Some("not a proxy".to_string()),
+            ..NetworkSettings::default()
+        };
+        assert!(HttpClientFactory::parse(&settings).is_err());
+
+        let settings = NetworkSettings {
+            extra_root_certificates: vec!["/nonexistent/corporate-ca.pem".to_string()],
+            ..NetworkSettings::default()
+        };
+        assert!(HttpClientFactory::parse(&settings).is_err());
+    }
+
+    #[test]
+    fn test_proxy_settings() {
+        for url in ["http://proxy.corp:8080", "socks5://127.0.0.1:1080"] {
+            let settings = NetworkSettings {
+                proxy_url: Some(url.to_string()),
+                ..NetworkSettings::default()
+            };
+            let config = HttpClientFactory::parse(&settings).unwrap();
+            assert!(config.proxy.is_some(), "{} should be a valid proxy", url);
+        }
+
+        let settings = NetworkSettings {
+            proxy_url: Some("  ".to_string()),
+            ..NetworkSettings::default()
+        };
+        assert!(HttpClientFactory::parse(&settings).unwrap().proxy.is_none());
+    }
+
+    #[test]
+    fn test_shared_factory_follows_new_settings() {
+        let shared = RwLock::new(HttpClientFactory::default());
+        assert!(HttpClientFactory::current(&shared).config.proxy.is_none());
+
+        let settings = NetworkSettings {
+            proxy_url: Some("http://proxy.corp:8080".to_string()),
+            ..NetworkSettings::default()
+        };
+        *shared.write().unwrap() = HttpClientFactory::new(&settings).unwrap();
+        assert!(HttpClientFactory::current(&shared).config.proxy.is_some());
+    }
+}
@@ -1,3 +1,4 @@
+use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 use specta::Type;
 
@@ -35,6 +36,7 @@ impl InstanceType {
     pub fn group_plus<S: AsRef<str>>(group_id: S) -> Self {
         InstanceType::GroupPlus(group_id.as_ref().to_string())
     }
+    #[allow(dead_code)]
     pub fn group_only<S: AsRef<str>>(group_id: S, allowed_roles: Option<Vec<String>>) -> Self {
         InstanceType::GroupOnly(GroupOnlyInstanceConfig {
             group_id: group_id.as_ref().to_string(),
@@ -49,7 +51,7 @@ pub struct GroupOnlyInstanceConfig {
     pub allowed_roles: Option<Vec<String>>,
 }
 
-#[derive(Debug, Serialize, Deserialize, Clone, Copy, Type)]
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Type)]
 pub enum InstanceRegion {
     #[serde(rename = "us")]
     UsWest,
@@ -66,6 +68,13 @@ pub struct CreateInstanceRequestBuilder {
     pub world_id: String,
     pub region: InstanceRegion,
     pub queue_enabled: bool,
+    pub display_name: Option<String>,
+    pub age_gate: bool,
+    pub closed_at: Option<DateTime<Utc>>,
+    pub hard_close: bool,
+    /// Overrides whether users can ask to be invited, which depends on the type otherwise
+    pub can_request_invite: Option<bool>,
+    pub invite_only: bool,
 }
 
 impl CreateInstanceRequestBuilder {
@@ -80,9 +89,49 @@ impl CreateInstanceRequestBuilder {
             world_id,
             region,
             queue_enabled,
+            display_name: None,
+            age_gate: false,
+            closed_at: None,
+            hard_close: false,
+            can_request_invite: None,
+            invite_only: false,
         }
     }
 
+    pub fn display_name<S: AsRef<str>>(mut self, display_name: S) -> Self {
+        self.display_name = Some(display_name.as_ref().to_string());
+        self
+    }
+
+    /// Only lets age verified users join
+    pub fn age_gate(mut self, age_gate: bool) -> Self {
+        self.age_gate = age_gate;
+        self
+    }
+
+    /// Closes the instance to new users at the given time
+    pub fn closed_at(mut self, closed_at: DateTime<Utc>) -> Self {
+        self.closed_at = Some(closed_at);
+        self
+    }
+
+    /// Also removes the users still in the instance once it is closed
+    pub fn hard_close(mut self, hard_close: bool) -> Self {
+        self.hard_close = hard_close;
+        self
+    }
+
+    pub fn can_request_invite(mut self, can_request_invite: bool) -> Self {
+        self.can_request_invite = Some(can_request_invite);
+        self
+    }
+
+    /// Only lets invited users join, on top of the access type
+    pub fn invite_only(mut self, invite_only: bool) -> Self {
+        self.invite_only = invite_only;
+        self
+    }
+
     pub fn build(self) -> CreateInstanceRequest {
         let (instance_type, owner_id, role_ids, group_access_type, can_request_invite) =
             match self.instance_type {
@@ -142,7 +191,12 @@ impl CreateInstanceRequestBuilder {
             role_ids,
             group_access_type,
             queue_enabled: self.queue_enabled,
-            can_request_invite,
+            can_request_invite: self.can_request_invite.unwrap_or(can_request_invite),
+            display_name: self.display_name,
+            age_gate: self.age_gate,
+            closed_at: self.closed_at,
+            hard_close: self.hard_close,
+            invite_only: self.invite_only,
         }
     }
 }
@@ -165,9 +219,19 @@ pub struct CreateInstanceRequest {
     pub queue_enabled: bool,
     #[serde(rename = "canRequestInvite")]
     pub can_request_invite: bool,
+    #[serde(rename = "displayName", skip_serializing_if = "Option::is_none")]
+    pub display_name: Option<String>,
+    #[serde(rename = "ageGate")]
+    pub age_gate: bool,
+    #[serde(rename = "closedAt", skip_serializing_if = "Option::is_none")]
+    pub closed_at: Option<DateTime<Utc>>,
+    #[serde(rename = "hardClose")]
+    pub hard_close: bool,
+    #[serde(rename = "inviteOnly")]
+    pub invite_only: bool,
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, Type)]
 pub struct Instance {
     #[serde(rename = "id")]
     pub id: String,
@@ -184,6 +248,95 @@ pub struct Instance {
     pub short_name: Option<String>,
     #[serde(rename = "worldId")]
     pub world_id: String,
+    /// The access type as VRChat names it: public, hidden, friends, private or group
+    #[serde(rename = "type", default)]
+    pub instance_type: String,
+    /// The access type of a group instance: public, plus or members
+    #[serde(rename = "groupAccessType", default)]
+    pub group_access_type: Option<String>,
+    #[serde(rename = "n_users", default)]
+    pub user_count: u32,
+    #[serde(rename = "capacity", default)]
+    pub capacity: u32,
+}
+
+impl Instance {
+    /// Builds an instance from an entry of the instance list of a world
+    /// The access type, owner and region are read from the tags of the instance ID,
+    /// e.g. `12345~group(grp_x)~groupAccessType(public)~region(jp)`
+    ///
+    /// # Arguments
+    /// * `world_id` - The ID of the world
+    /// * `instance_id` - The ID of the instance, with its tags
+    /// * `user_count` - The number of users in the instance
+    /// * `capacity` - The capacity of the world
+    pub fn from_listing(world_id: &str, instance_id: &str, user_count: u32, capacity: u32) -> Self {
+        let mut instance_type = "public".to_string();
+        let mut owner_id = None;
+        let mut group_access_type = None;
+        // VRChat uses the US West region for instances without a region tag
+        let mut region = InstanceRegion::UsWest;
+
+        for tag in instance_id.split('~').skip(1) {
+            let Some((name, value)) = tag.strip_suffix(')').and_then(|tag| tag.split_once('('))
+            else {
+                continue;
+            };
+            match name {
+                "hidden" | "friends" | "private" | "group" => {
+                    instance_type = name.to_string();
+                    owner_id = Some(value.to_string());
+                }
+                "groupAccessType" => group_access_type = Some(value.to_string()),
+                "region" => {
+                    match serde_json::from_value(serde_json::Value::String(value.to_string())) {
+                        Ok(parsed) => region = parsed,
+                        Err(_) => log::warn!("Unknown instance region: {}", value),
+                    }
+                }
+                _ => {}
+            }
+        }
+
+        Instance {
+            id: format!("{}:{}", world_id, instance_id),
+            instance_id: instance_id.to_string(),
+            owner_id,
+            photon_region: region,
+            region,
+            short_name: None,
+            world_id: world_id.to_string(),
+            instance_type,
+            group_access_type,
+            user_count,
+            capacity,
+        }
+    }
+
+    /// Whether anyone can join the instance, which holds for public instances
+    /// and public group instances
+    pub fn is_open(&self) -> bool {
+        match self.instance_type.as_str() {
+            "public" => true,
+            "group" => self.group_access_type.as_deref() == Some("public"),
+            _ => false,
+        }
+    }
+
+    /// Whether the instance is open and not full
+    pub fn is_joinable(&self) -> bool {
+        self.is_open() && self.user_count < self.capacity
+    }
+}
+
+/// The part of a world that lists its active instances
+#[derive(Debug, Deserialize)]
+pub struct WorldInstancesResponse {
+    #[serde(rename = "capacity")]
+    pub capacity: u32,
+    /// The ID and user count of each public instance
+    #[serde(rename = "instances", default)]
+    pub instances: Vec<(String, u32)>,
 }
 
 #[derive(Debug, Deserialize)]
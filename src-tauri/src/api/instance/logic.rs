@@ -1,121 +1,82 @@
-use std::sync::Arc;
-
-use reqwest::cookie::Jar;
-
 use crate::api::{
-    common::{
-        check_rate_limit, get_reqwest_client, handle_api_response, record_rate_limit,
-        reset_backoff, API_BASE_URL,
-    },
-    instance::definitions::GetInstanceShortNameResponse,
-    world,
+    common::{parse_response, ApiContext},
+    instance::definitions::{GetInstanceShortNameResponse, WorldInstancesResponse},
 };
 
+use crate::errors::{ApiError, AppError};
+
 use super::definitions::{CreateInstanceRequest, Instance};
 
-pub async fn create_instance<J: Into<Arc<Jar>>>(
-    cookie: J,
+pub async fn create_instance(
+    context: &ApiContext,
     request: CreateInstanceRequest,
-) -> Result<Instance, String> {
+) -> Result<Instance, AppError> {
     const OPERATION: &str = "create_instance";
 
-    check_rate_limit(OPERATION)?;
-
-    let cookie_jar: Arc<Jar> = cookie.into();
-    let client = get_reqwest_client(&cookie_jar);
+    let client = context.client();
+    let base_url = context.base_url();
 
     let body = match serde_json::to_string(&request) {
         Ok(body) => body,
         Err(e) => {
-            log::info!("Failed to serialize request: {}", e.to_string());
-            return Err(format!("Failed to serialize request: {}", e.to_string()));
+            log::info!("Failed to serialize request: {}", e);
+            return Err(ApiError::InvalidRequest("Failed to serialize request").into());
         }
     };
 
-    let result = client
-        .post(format!("{API_BASE_URL}/instances"))
+    let request = client
+        .post(format!("{base_url}/instances"))
         .header("Content-Type", "application/json")
-        .body(body)
-        .send()
-        .await
-        .map_err(|e| format!("Failed to send create instance request: {}", e))?;
+        .body(body);
+    let result = context.send(request, OPERATION).await?;
 
-    let result = match handle_api_response(result, OPERATION).await {
-        Ok(response) => response,
-        Err(e) => {
-            log::error!("Failed to handle API response: {}", e);
-            record_rate_limit(OPERATION);
-            return Err(e);
-        }
-    };
-
-    reset_backoff(OPERATION);
-
-    let text = result
-        .text()
-        .await
-        .map_err(|e| format!("Failed to create instance: {}", e.to_string()))?;
-    let parsed: Instance = match serde_json::from_str(&text) {
-        Ok(instance) => instance,
-        Err(e) => {
-            log::info!("Failed to parse instance: {}", e.to_string());
-            log::info!("Response: {text}");
-            return Err(format!("Failed to parse instance: {}", e.to_string()));
-        }
-    };
+    let parsed: Instance = parse_response(result, "instance").await?;
 
     Ok(parsed)
 }
 
-pub async fn get_instance_short_name<J: Into<Arc<Jar>>>(
-    cookie: J,
+pub async fn get_instance_short_name(
+    context: &ApiContext,
     world_id: &str,
     instance_id: &str,
-) -> Result<String, String> {
+) -> Result<String, AppError> {
     const OPERATION: &str = "get_instance_short_name";
 
-    check_rate_limit(OPERATION)?;
+    let client = context.client();
+    let base_url = context.base_url();
 
-    let cookie_jar: Arc<Jar> = cookie.into();
-    let client = get_reqwest_client(&cookie_jar);
+    let url = format!("{base_url}/instances/{world_id}:{instance_id}/shortName");
+    let result = context.send(client.get(&url), OPERATION).await?;
 
-    let url = format!("{API_BASE_URL}/instances/{world_id}:{instance_id}/shortName");
-    let result = client
-        .get(&url)
-        .send()
-        .await
-        .map_err(|e| format!("Failed to send get instance short name request: {}", e))?;
+    let parsed: GetInstanceShortNameResponse =
+        parse_response(result, "get instance short name response").await?;
 
-    let result = match handle_api_response(result, OPERATION).await {
-        Ok(response) => response,
-        Err(e) => {
-            log::error!("Failed to handle API response: {}", e);
-            record_rate_limit(OPERATION);
-            return Err(e);
-        }
-    };
+    // if short name is None, return the secure name
+    Ok(parsed.short_name.unwrap_or(parsed.secure_name))
+}
 
-    reset_backoff(OPERATION);
+/// Lists the active instances of a world that anyone can join, which are its
+/// public instances and public group instances
+pub async fn get_world_instances(
+    context: &ApiContext,
+    world_id: &str,
+) -> Result<Vec<Instance>, AppError> {
+    const OPERATION: &str = "get_world_instances";
 
-    let text = result
-        .text()
-        .await
-        .map_err(|e| format!("Failed to get instance short name: {}", e.to_string()))?;
-    let parsed: GetInstanceShortNameResponse = match serde_json::from_str(&text) {
-        Ok(response) => response,
-        Err(e) => {
-            log::info!(
-                "Failed to parse get instance short name response: {}",
-                e.to_string()
-            );
-            log::info!("Response: {text}");
-            return Err(format!(
-                "Failed to parse get instance short name response: {}",
-                e.to_string()
-            ));
-        }
-    };
+    let client = context.client();
+    let base_url = context.base_url();
 
-    // if short name is None, return the secure name
-    Ok(parsed.short_name.unwrap_or(parsed.secure_name))
+    let request = client.get(format!("{base_url}/worlds/{world_id}"));
+    let result = context.send(request, OPERATION).await?;
+
+    let parsed: WorldInstancesResponse = parse_response(result, "world instances").await?;
+
+    Ok(parsed
+        .instances
+        .into_iter()
+        .map(|(instance_id, user_count)| {
+            Instance::from_listing(world_id, &instance_id, user_count, parsed.capacity)
+        })
+        .filter(Instance::is_open)
+        .collect())
 }
@@ -1,7 +1,9 @@
 use serde::{Deserialize, Serialize};
 
+/// A notification sent by the user, as VRChat returns it for invites and invite requests
 #[derive(Debug, Deserialize)]
-pub struct SelfInviteResponse {
+#[allow(dead_code)]
+pub struct SentNotification {
     pub created_at: String,
     pub details: serde_json::Value,
     pub id: String,
@@ -32,3 +34,25 @@ pub enum NotificationType {
     #[serde(rename = "votetokick")]
     VoteToKick,
 }
+
+#[derive(Debug, Serialize)]
+pub struct InviteRequest {
+    /// The location to invite to, as `world_id:instance_id`
+    #[serde(rename = "instanceId")]
+    pub instance_id: String,
+    /// The slot of the invite message to send, which VRChat defaults to the first
+    #[serde(rename = "messageSlot", skip_serializing_if = "Option::is_none")]
+    pub message_slot: Option<u32>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct RequestInviteRequest {
+    #[serde(rename = "requestSlot", skip_serializing_if = "Option::is_none")]
+    pub request_slot: Option<u32>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct InviteResponseRequest {
+    #[serde(rename = "responseSlot")]
+    pub response_slot: u32,
+}
@@ -1,62 +1,88 @@
-use super::definitions::SelfInviteResponse;
-use crate::api::common::{
-    check_rate_limit, get_reqwest_client, handle_api_response, record_rate_limit, reset_backoff,
-    API_BASE_URL,
+use serde::Serialize;
+
+use super::definitions::{
+    InviteRequest, InviteResponseRequest, RequestInviteRequest, SentNotification,
 };
-use reqwest::cookie::Jar;
-use std::sync::Arc;
+use crate::api::common::{parse_response, ApiContext};
+use crate::errors::{ApiError, AppError};
+
+pub async fn invite_self_to_instance(
+    context: &ApiContext,
+    world_id: &str,
+    instance_id: &str,
+) -> Result<SentNotification, AppError> {
+    let url = format!(
+        "{}/invite/myself/to/{}:{}",
+        context.base_url(),
+        world_id,
+        instance_id
+    );
+    send_notification::<()>(context, "invite_self_to_instance", url, None).await
+}
 
-pub async fn invite_self_to_instance<J: Into<Arc<Jar>>>(
-    cookie: J,
+/// Invites another user to an instance
+/// VRChat only delivers invites to friends of the user
+pub async fn invite_user(
+    context: &ApiContext,
+    user_id: &str,
     world_id: &str,
     instance_id: &str,
-) -> Result<SelfInviteResponse, String> {
-    const OPERATION: &str = "invite_self_to_instance";
-
-    check_rate_limit(OPERATION)?;
-
-    let cookie_jar: Arc<Jar> = cookie.into();
-    let client = get_reqwest_client(&cookie_jar);
-
-    let result = client
-        .post(format!(
-            "{}/invite/myself/to/{}:{}",
-            API_BASE_URL, world_id, instance_id
-        ))
-        .send()
-        .await
-        .map_err(|e| e.to_string())?;
-
-    let result = match handle_api_response(result, OPERATION).await {
-        Ok(response) => response,
-        Err(e) => {
-            log::error!("Failed to handle API response: {}", e);
-            record_rate_limit(OPERATION);
-            return Err(e);
-        }
+    message_slot: Option<u32>,
+) -> Result<SentNotification, AppError> {
+    let url = format!("{}/invite/{}", context.base_url(), user_id);
+    let request = InviteRequest {
+        instance_id: format!("{}:{}", world_id, instance_id),
+        message_slot,
     };
+    send_notification(context, "invite_user", url, Some(&request)).await
+}
 
-    reset_backoff(OPERATION);
+/// Asks another user to invite the user to their instance
+pub async fn request_invite(
+    context: &ApiContext,
+    user_id: &str,
+    request_slot: Option<u32>,
+) -> Result<SentNotification, AppError> {
+    let url = format!("{}/requestInvite/{}", context.base_url(), user_id);
+    let request = RequestInviteRequest { request_slot };
+    send_notification(context, "request_invite", url, Some(&request)).await
+}
 
-    let text = result.text().await;
+/// Answers a received invite or invite request with one of the user's response messages
+pub async fn respond_to_invite(
+    context: &ApiContext,
+    notification_id: &str,
+    response_slot: u32,
+) -> Result<SentNotification, AppError> {
+    let url = format!("{}/invite/{}/response", context.base_url(), notification_id);
+    let request = InviteResponseRequest { response_slot };
+    send_notification(context, "respond_to_invite", url, Some(&request)).await
+}
 
-    if let Err(e) = text {
-        return Err(format!("Failed to send invite request: {}", e.to_string()));
+/// Posts a request that makes VRChat send a notification, and parses the sent notification
+async fn send_notification<T: Serialize>(
+    context: &ApiContext,
+    operation: &str,
+    url: String,
+    request: Option<&T>,
+) -> Result<SentNotification, AppError> {
+    let client = context.client();
+
+    let mut builder = client.post(url);
+    if let Some(request) = request {
+        let body = match serde_json::to_string(request) {
+            Ok(body) => body,
+            Err(e) => {
+                log::info!("Failed to serialize request: {}", e);
+                return Err(ApiError::InvalidRequest("Failed to serialize request").into());
+            }
+        };
+        builder = builder
+            .header("Content-Type", "application/json")
+            .body(body);
     }
 
-    let text = text.unwrap();
-
-    let response: SelfInviteResponse = match serde_json::from_str(&text) {
-        Ok(response) => response,
-        Err(e) => {
-            log::info!("Failed to parse invite response: {}", e.to_string());
-            log::info!("Response: {}", text);
-            return Err(format!(
-                "Failed to parse invite response: {}",
-                e.to_string()
-            ));
-        }
-    };
+    let result = context.send(builder, operation).await?;
 
-    Ok(response)
+    parse_response(result, "invite response").await
 }
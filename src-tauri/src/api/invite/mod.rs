@@ -1,7 +1,7 @@
 mod definitions;
 mod logic;
 
-pub use definitions::NotificationType;
-pub use definitions::SelfInviteResponse;
-
 pub use logic::invite_self_to_instance;
+pub use logic::invite_user;
+pub use logic::request_invite;
+pub use logic::respond_to_invite;
@@ -1,10 +1,17 @@
 mod common;
 mod definitions;
+mod http_client;
+mod rate_limiter;
+mod session;
 #[cfg(test)]
 mod tests;
 
+pub use common::{api_base_url, should_backoff, ApiContext};
 pub use definitions::RateLimitStore;
+pub use http_client::HttpClientFactory;
+pub use session::SessionMonitor;
 pub mod auth;
+pub mod favorite;
 pub mod group;
 pub mod instance;
 pub mod invite;
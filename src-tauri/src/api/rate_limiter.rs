@@ -0,0 +1,194 @@
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use chrono::{DateTime, Utc};
+use reqwest::{header::RETRY_AFTER, Response};
+
+/// The kinds of requests that share a request budget
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum EndpointClass {
+    /// Logging in and out, which VRChat limits the most
+    Auth,
+    /// Requests that change something, like creating instances or sending invites
+    Write,
+    /// Requests that only read data
+    Read,
+    /// Requests made by bulk jobs, taken on top of their own class
+    Bulk,
+}
+
+impl EndpointClass {
+    /// Get the class of an operation, by the operation name used for its rate limit
+    pub fn of(operation: &str) -> Self {
+        match operation {
+            "verify_token" | "login_with_password" | "login_with_2fa" | "logout" => {
+                EndpointClass::Auth
+            }
+            "add_favorite"
+            | "remove_favorite"
+            | "create_instance"
+            | "invite_self_to_instance"
+            | "invite_user"
+            | "request_invite"
+            | "respond_to_invite" => EndpointClass::Write,
+            _ => EndpointClass::Read,
+        }
+    }
+
+    /// The number of requests that can be sent at once, and how fast that budget refills
+    fn budget(self) -> (f64, f64) {
+        match self {
+            EndpointClass::Auth => (2.0, 0.2),
+            EndpointClass::Write => (3.0, 0.5),
+            EndpointClass::Read => (10.0, 1.0),
+            EndpointClass::Bulk => (1.0, 0.5),
+        }
+    }
+}
+
+/// How long to back off for after the server rate limited a request
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BackoffPolicy {
+    pub base_ms: u64,
+    pub max_ms: u64,
+}
+
+impl BackoffPolicy {
+    /// Requests the user is waiting for: VRChat is given plenty of time to recover
+    pub const INTERACTIVE: BackoffPolicy = BackoffPolicy {
+        base_ms: 600000, // 10 minutes
+        max_ms: 3600000, // 1 hour
+    };
+
+    /// Requests of bulk jobs, which wait between their requests and resume on their own
+    pub const BULK: BackoffPolicy = BackoffPolicy {
+        base_ms: 60000, // 1 minute
+        max_ms: 900000, // 15 minutes
+    };
+}
+
+#[derive(Debug)]
+struct TokenBucket {
+    capacity: f64,
+    refill_per_second: f64,
+    tokens: f64,
+    updated: Instant,
+}
+
+impl TokenBucket {
+    fn new(capacity: f64, refill_per_second: f64, now: Instant) -> Self {
+        Self {
+            capacity,
+            refill_per_second,
+            tokens: capacity,
+            updated: now,
+        }
+    }
+
+    /// Takes a token if one is available
+    ///
+    /// # Returns
+    /// None if a token was taken, otherwise how long until the next one is available
+    fn take(&mut self, now: Instant) -> Option<Duration> {
+        let elapsed = now.saturating_duration_since(self.updated).as_secs_f64();
+        self.tokens = (self.tokens + elapsed * self.refill_per_second).min(self.capacity);
+        self.updated = now;
+
+        if self.tokens >= 1.0 {
+            self.tokens -= 1.0;
+            None
+        } else {
+            Some(Duration::from_secs_f64(
+                (1.0 - self.tokens) / self.refill_per_second,
+            ))
+        }
+    }
+}
+
+/// Spaces requests to the VRChat API before they are sent, with a token bucket per endpoint class
+///
+/// One limiter is shared by every context made from the same session, so the
+/// background jobs and the requests made by the user draw from the same budget.
+#[derive(Debug, Default)]
+pub struct RequestLimiter {
+    buckets: Mutex<HashMap<EndpointClass, TokenBucket>>,
+}
+
+impl RequestLimiter {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Get how long to wait before a request of a class can be sent, taking a token if none
+    fn reserve(&self, class: EndpointClass, now: Instant) -> Option<Duration> {
+        let mut buckets = self.buckets.lock().unwrap_or_else(|e| e.into_inner());
+        let (capacity, refill_per_second) = class.budget();
+        buckets
+            .entry(class)
+            .or_insert_with(|| TokenBucket::new(capacity, refill_per_second, now))
+            .take(now)
+    }
+
+    /// Waits until a request of a class can be sent
+    pub async fn acquire(&self, class: EndpointClass) {
+        while let Some(wait) = self.reserve(class, Instant::now()) {
+            log::debug!("Spacing {:?} request by {}ms", class, wait.as_millis());
+            tokio::time::sleep(wait).await;
+        }
+    }
+}
+
+/// Get how long the server asked to wait through the Retry-After header
+/// The header holds either a number of seconds, or an HTTP date
+pub fn retry_after(response: &Response, now: DateTime<Utc>) -> Option<u64> {
+    let value = response.headers().get(RETRY_AFTER)?.to_str().ok()?.trim();
+
+    if let Ok(seconds) = value.parse::<u64>() {
+        return Some(seconds * 1000);
+    }
+    let date = DateTime::parse_from_rfc2822(value).ok()?;
+    Some((date.with_timezone(&Utc) - now).num_milliseconds().max(0) as u64)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_bucket_spaces_requests_after_burst() {
+        let start = Instant::now();
+        let mut bucket = TokenBucket::new(2.0, 0.5, start);
+
+        assert_eq!(bucket.take(start), None);
+        assert_eq!(bucket.take(start), None);
+        assert_eq!(bucket.take(start), Some(Duration::from_secs(2)));
+
+        // Half of the next token has refilled
+        let wait = bucket.take(start + Duration::from_secs(1)).unwrap();
+        assert_eq!(wait, Duration::from_secs(1));
+        assert_eq!(bucket.take(start + Duration::from_secs(2)), None);
+
+        // The budget never grows past the capacity
+        let later = start + Duration::from_secs(3600);
+        assert_eq!(bucket.take(later), None);
+        assert_eq!(bucket.take(later), None);
+        assert!(bucket.take(later).is_some());
+    }
+
+    #[test]
+    fn test_endpoint_classes() {
+        assert_eq!(EndpointClass::of("verify_token"), EndpointClass::Auth);
+        // Only reads are retried, so every request that changes something must be a write
+        for operation in [
+            "create_instance",
+            "invite_user",
+            "request_invite",
+            "add_favorite",
+            "remove_favorite",
+        ] {
+            assert_eq!(EndpointClass::of(operation), EndpointClass::Write);
+        }
+        assert_eq!(EndpointClass::of("get_world_by_id"), EndpointClass::Read);
+    }
+}
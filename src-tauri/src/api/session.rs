@@ -0,0 +1,59 @@
+use std::sync::atomic::{AtomicBool, Ordering};
+
+use tokio::sync::Notify;
+
+/// Tracks whether VRChat rejected the session the requests are made with
+///
+/// One monitor is shared by every context made from the same authenticator, so a
+/// 401 on any endpoint is noticed once, by whatever task waits on `expired`.
+#[derive(Debug, Default)]
+pub struct SessionMonitor {
+    expired: AtomicBool,
+    notify: Notify,
+    renewed: Notify,
+}
+
+impl SessionMonitor {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Marks the session as expired
+    /// Only the first call wakes the waiting task, until the session is valid again
+    pub fn mark_expired(&self) {
+        if !self.expired.swap(true, Ordering::SeqCst) {
+            log::warn!("The session was rejected by VRChat");
+            self.notify.notify_one();
+        }
+    }
+
+    /// Marks the session as valid, once it was verified or the user logged in again
+    /// Wakes every task waiting on `valid`
+    pub fn mark_valid(&self) {
+        if self.expired.swap(false, Ordering::SeqCst) {
+            self.renewed.notify_waiters();
+        }
+    }
+
+    pub fn is_expired(&self) -> bool {
+        self.expired.load(Ordering::SeqCst)
+    }
+
+    /// Waits until the session is marked as expired
+    pub async fn expired(&self) {
+        self.notify.notified().await;
+    }
+
+    /// Waits until the session is valid, returning at once if it already is
+    pub async fn valid(&self) {
+        loop {
+            let mut renewed = std::pin::pin!(self.renewed.notified());
+            // Registers the waiter before the check, so a renewal in between is not missed
+            renewed.as_mut().enable();
+            if !self.is_expired() {
+                return;
+            }
+            renewed.await;
+        }
+    }
+}
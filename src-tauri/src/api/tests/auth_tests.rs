@@ -0,0 +1,156 @@
+use super::test_context;
+use crate::api::auth::{VRChatAPIClientAuthenticator, VRChatAuthStatus};
+use crate::errors::{ApiError, AppError};
+use wiremock::{
+    matchers::{body_json, header, method, path},
+    Mock, MockServer, ResponseTemplate,
+};
+
+// base64("tester:secret")
+const BASIC_AUTH_HEADER: &str = "Basic dGVzdGVyOnNlY3JldA==";
+
+async fn setup() -> (MockServer, VRChatAPIClientAuthenticator) {
+    let mock_server = MockServer::start().await;
+    let mut authenticator = VRChatAPIClientAuthenticator::from_context(test_context(&mock_server));
+    authenticator.update_user_info("tester".to_string());
+    (mock_server, authenticator)
+}
+
+#[tokio::test]
+async fn test_login_with_password_success() {
+    let (mock_server, mut authenticator) = setup().await;
+
+    Mock::given(method("GET"))
+        .and(path("/api/1/auth/user"))
+        .and(header("Authorization", BASIC_AUTH_HEADER))
+        .respond_with(
+            ResponseTemplate::new(200)
+                .insert_header("Set-Cookie", "auth=authcookie_test; Path=/")
+                .set_body_json(serde_json::json!({
+                    "id": "usr_test",
+                    "displayName": "tester"
+                })),
+        )
+        .expect(1)
+        .mount(&mock_server)
+        .await;
+
+    let status = authenticator
+        .login_with_password("secret")
+        .await
+        .expect("Login request failed");
+
+    match status {
+        VRChatAuthStatus::Success(cookies, _) => {
+            assert_eq!(cookies.auth_token, Some("authcookie_test".to_string()));
+            assert_eq!(cookies.two_factor_auth, None);
+        }
+        other => panic!("Expected successful login, got {:?}", other),
+    }
+}
+
+#[tokio::test]
+async fn test_login_with_password_invalid_credentials() {
+    let (mock_server, mut authenticator) = setup().await;
+
+    Mock::given(method("GET"))
+        .and(path("/api/1/auth/user"))
+        .respond_with(ResponseTemplate::new(401))
+        .mount(&mock_server)
+        .await;
+
+    let status = authenticator
+        .login_with_password("wrong")
+        .await
+        .expect("Login request failed");
+
+    assert_eq!(status, VRChatAuthStatus::InvalidCredentials);
+}
+
+#[tokio::test]
+async fn test_login_with_email_2fa_flow() {
+    let (mock_server, mut authenticator) = setup().await;
+
+    Mock::given(method("GET"))
+        .and(path("/api/1/auth/user"))
+        .and(header("Authorization", BASIC_AUTH_HEADER))
+        .respond_with(
+            ResponseTemplate::new(200)
+                .insert_header("Set-Cookie", "auth=authcookie_test; Path=/")
+                .set_body_json(serde_json::json!({
+                    "requiresTwoFactorAuth": ["emailOtp"]
+                })),
+        )
+        .mount(&mock_server)
+        .await;
+
+    Mock::given(method("POST"))
+        .and(path("/api/1/auth/twofactorauth/emailotp/verify"))
+        .and(body_json(serde_json::json!({ "code": "123456" })))
+        .respond_with(
+            ResponseTemplate::new(200)
+                .insert_header("Set-Cookie", "twoFactorAuth=twofactor_test; Path=/")
+                .set_body_json(serde_json::json!({ "verified": true })),
+        )
+        .expect(1)
+        .mount(&mock_server)
+        .await;
+
+    let status = authenticator
+        .login_with_password("secret")
+        .await
+        .expect("Login request failed");
+    assert_eq!(status, VRChatAuthStatus::RequiresEmail2FA);
+
+    let status = authenticator
+        .login_with_email_2fa("123456")
+        .await
+        .expect("2FA request failed");
+
+    match status {
+        VRChatAuthStatus::Success(cookies, _) => {
+            assert_eq!(cookies.auth_token, Some("authcookie_test".to_string()));
+            assert_eq!(cookies.two_factor_auth, Some("twofactor_test".to_string()));
+        }
+        other => panic!("Expected successful login, got {:?}", other),
+    }
+}
+
+#[tokio::test]
+async fn test_login_with_2fa_rejected_outside_2fa_phase() {
+    let (_mock_server, mut authenticator) = setup().await;
+
+    let result = authenticator.login_with_2fa("123456").await;
+
+    assert!(matches!(
+        result,
+        Err(AppError::Api(ApiError::InvalidRequest("Not in 2FA phase")))
+    ));
+}
+
+#[tokio::test]
+async fn test_verify_token_returns_current_user() {
+    let (mock_server, mut authenticator) = setup().await;
+
+    Mock::given(method("GET"))
+        .and(path("/api/1/auth/user"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+            "id": "usr_test",
+            "displayName": "tester"
+        })))
+        .mount(&mock_server)
+        .await;
+
+    let status = authenticator
+        .verify_token()
+        .await
+        .expect("Verify request failed");
+
+    match status {
+        VRChatAuthStatus::Success(_, user) => {
+            assert_eq!(user.id, "usr_test");
+            assert_eq!(user.username, "tester");
+        }
+        other => panic!("Expected successful verification, got {:?}", other),
+    }
+}
@@ -0,0 +1,116 @@
+use super::test_context;
+use crate::api::common::ApiContext;
+use crate::api::favorite::{add_world_favorite, get_world_favorite_groups, remove_favorite};
+use wiremock::{
+    matchers::{body_partial_json, method, path},
+    Mock, MockServer, ResponseTemplate,
+};
+
+async fn setup() -> (MockServer, ApiContext) {
+    let mock_server = MockServer::start().await;
+    let context = test_context(&mock_server);
+    (mock_server, context)
+}
+
+#[tokio::test]
+async fn test_add_world_favorite_sends_group() {
+    let (mock_server, context) = setup().await;
+
+    Mock::given(method("POST"))
+        .and(path("/api/1/favorites"))
+        .and(body_partial_json(serde_json::json!({
+            "type": "world",
+            "favoriteId": "wrld_test",
+            "tags": ["worlds2"]
+        })))
+        .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+            "id": "fvrt_test",
+            "type": "world",
+            "favoriteId": "wrld_test",
+            "tags": ["worlds2"]
+        })))
+        .expect(1)
+        .mount(&mock_server)
+        .await;
+
+    let favorite = add_world_favorite(&context, "wrld_test", "worlds2")
+        .await
+        .expect("Failed to add favorite");
+
+    assert_eq!(favorite.id, "fvrt_test");
+    assert_eq!(favorite.tags, vec!["worlds2"]);
+}
+
+#[tokio::test]
+async fn test_add_world_favorite_rejected() {
+    let (mock_server, context) = setup().await;
+
+    Mock::given(method("POST"))
+        .and(path("/api/1/favorites"))
+        .respond_with(ResponseTemplate::new(400).set_body_json(serde_json::json!({
+            "error": { "message": "You already have 100 favorites in this group", "status_code": 400 }
+        })))
+        .mount(&mock_server)
+        .await;
+
+    let result = add_world_favorite(&context, "wrld_test", "worlds1").await;
+
+    assert!(result.unwrap_err().to_string().contains("400"));
+}
+
+#[tokio::test]
+async fn test_remove_favorite() {
+    let (mock_server, context) = setup().await;
+
+    Mock::given(method("DELETE"))
+        .and(path("/api/1/favorites/wrld_test"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+            "success": { "message": "Favorite removed", "status_code": 200 }
+        })))
+        .expect(1)
+        .mount(&mock_server)
+        .await;
+
+    remove_favorite(&context, "wrld_test")
+        .await
+        .expect("Failed to remove favorite");
+}
+
+#[tokio::test]
+async fn test_get_world_favorite_groups_skips_other_types() {
+    let (mock_server, context) = setup().await;
+
+    Mock::given(method("GET"))
+        .and(path("/api/1/favorite/groups"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!([
+            {
+                "id": "fvgrp_1",
+                "ownerId": "usr_test",
+                "name": "worlds1",
+                "displayName": "Chill",
+                "type": "world",
+                "visibility": "private",
+                "tags": []
+            },
+            {
+                "id": "fvgrp_2",
+                "ownerId": "usr_test",
+                "name": "group_0",
+                "displayName": "Friends",
+                "type": "friend",
+                "visibility": "private",
+                "tags": []
+            }
+        ])))
+        .expect(1)
+        .mount(&mock_server)
+        .await;
+
+    let groups = get_world_favorite_groups(&context)
+        .await
+        .expect("Failed to fetch favorite groups");
+
+    assert_eq!(groups.len(), 1);
+    assert_eq!(groups[0].name, "worlds1");
+    assert_eq!(groups[0].display_name, "Chill");
+}
@@ -0,0 +1,201 @@
+use super::test_context;
+use crate::api::common::ApiContext;
+use crate::api::instance::{
+    create_instance, get_instance_short_name, get_world_instances, CreateInstanceRequestBuilder,
+    InstanceRegion, InstanceType,
+};
+use crate::errors::{ApiError, AppError};
+use wiremock::{
+    matchers::{body_partial_json, method, path},
+    Mock, MockServer, ResponseTemplate,
+};
+
+async fn setup() -> (MockServer, ApiContext) {
+    let mock_server = MockServer::start().await;
+    let context = test_context(&mock_server);
+    (mock_server, context)
+}
+
+#[tokio::test]
+async fn test_create_instance_sends_request_and_parses_instance() {
+    let (mock_server, context) = setup().await;
+
+    Mock::given(method("POST"))
+        .and(path("/api/1/instances"))
+        .and(body_partial_json(serde_json::json!({
+            "worldId": "wrld_test",
+            "type": "hidden",
+            "region": "jp",
+            "ownerId": "usr_test",
+            "queueEnabled": false
+        })))
+        .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+            "id": "wrld_test:12345~hidden(usr_test)~region(jp)",
+            "instanceId": "12345~hidden(usr_test)~region(jp)",
+            "ownerId": "usr_test",
+            "photonRegion": "jp",
+            "region": "jp",
+            "shortName": null,
+            "worldId": "wrld_test"
+        })))
+        .expect(1)
+        .mount(&mock_server)
+        .await;
+
+    let request = CreateInstanceRequestBuilder::new(
+        InstanceType::friends_plus("usr_test"),
+        "wrld_test".to_string(),
+        InstanceRegion::JP,
+        false,
+    )
+    .build();
+
+    let instance = create_instance(&context, request)
+        .await
+        .expect("Failed to create instance");
+
+    assert_eq!(instance.world_id, "wrld_test");
+    assert_eq!(instance.instance_id, "12345~hidden(usr_test)~region(jp)");
+    assert_eq!(instance.owner_id, Some("usr_test".to_string()));
+}
+
+#[tokio::test]
+async fn test_create_instance_invalid_response() {
+    let (mock_server, context) = setup().await;
+
+    Mock::given(method("POST"))
+        .and(path("/api/1/instances"))
+        .respond_with(ResponseTemplate::new(403).set_body_json(serde_json::json!({
+            "error": { "message": "Forbidden", "status_code": 403 }
+        })))
+        .mount(&mock_server)
+        .await;
+
+    let request = CreateInstanceRequestBuilder::new(
+        InstanceType::public(),
+        "wrld_test".to_string(),
+        InstanceRegion::UsWest,
+        false,
+    )
+    .build();
+
+    let result = create_instance(&context, request).await;
+
+    assert!(matches!(
+        result,
+        Err(AppError::Api(ApiError::ResponseError(message))) if message.contains("403")
+    ));
+}
+
+#[tokio::test]
+async fn test_get_instance_short_name_falls_back_to_secure_name() {
+    let (mock_server, context) = setup().await;
+
+    Mock::given(method("GET"))
+        .and(path("/api/1/instances/wrld_test:12345/shortName"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+            "secureName": "secure123",
+            "shortName": null
+        })))
+        .mount(&mock_server)
+        .await;
+
+    let short_name = get_instance_short_name(&context, "wrld_test", "12345")
+        .await
+        .expect("Failed to get short name");
+
+    assert_eq!(short_name, "secure123");
+}
+
+#[tokio::test]
+async fn test_get_world_instances_lists_open_instances() {
+    let (mock_server, context) = setup().await;
+
+    Mock::given(method("GET"))
+        .and(path("/api/1/worlds/wrld_test"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+            "id": "wrld_test",
+            "capacity": 16,
+            "instances": [
+                ["12345~region(jp)", 16],
+                ["23456~group(grp_test)~groupAccessType(public)~region(eu)", 3],
+                ["34567~group(grp_test)~groupAccessType(members)~region(eu)", 5],
+                ["45678", 1]
+            ]
+        })))
+        .expect(1)
+        .mount(&mock_server)
+        .await;
+
+    let instances = get_world_instances(&context, "wrld_test")
+        .await
+        .expect("Failed to get world instances");
+
+    assert_eq!(instances.len(), 3);
+    assert_eq!(instances[0].instance_type, "public");
+    assert!(matches!(instances[0].region, InstanceRegion::JP));
+    assert!(!instances[0].is_joinable());
+
+    assert_eq!(instances[1].instance_type, "group");
+    assert_eq!(instances[1].owner_id, Some("grp_test".to_string()));
+    assert_eq!(
+        instances[1].id,
+        "wrld_test:23456~group(grp_test)~groupAccessType(public)~region(eu)"
+    );
+    assert!(matches!(instances[1].region, InstanceRegion::EU));
+    assert_eq!(instances[1].user_count, 3);
+    assert!(instances[1].is_joinable());
+
+    assert!(matches!(instances[2].region, InstanceRegion::UsWest));
+}
+
+#[tokio::test]
+async fn test_create_instance_sends_creation_options() {
+    let (mock_server, context) = setup().await;
+
+    Mock::given(method("POST"))
+        .and(path("/api/1/instances"))
+        .and(body_partial_json(serde_json::json!({
+            "type": "group",
+            "ownerId": "grp_test",
+            "groupAccessType": "plus",
+            "queueEnabled": true,
+            "displayName": "Friday meetup",
+            "ageGate": true,
+            "closedAt": "2024-01-05T22:00:00Z",
+            "hardClose": true,
+            "canRequestInvite": true
+        })))
+        .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+            "id": "wrld_test:12345~group(grp_test)~groupAccessType(plus)~region(jp)",
+            "instanceId": "12345~group(grp_test)~groupAccessType(plus)~region(jp)",
+            "ownerId": "grp_test",
+            "photonRegion": "jp",
+            "region": "jp",
+            "shortName": null,
+            "worldId": "wrld_test"
+        })))
+        .expect(1)
+        .mount(&mock_server)
+        .await;
+
+    let closed_at = chrono::DateTime::parse_from_rfc3339("2024-01-05T22:00:00Z")
+        .unwrap()
+        .with_timezone(&chrono::Utc);
+    let request = CreateInstanceRequestBuilder::new(
+        InstanceType::group_plus("grp_test"),
+        "wrld_test".to_string(),
+        InstanceRegion::JP,
+        true,
+    )
+    .display_name("Friday meetup")
+    .age_gate(true)
+    .closed_at(closed_at)
+    .hard_close(true)
+    .can_request_invite(true)
+    .build();
+
+    create_instance(&context, request)
+        .await
+        .expect("Failed to create instance");
+}
@@ -0,0 +1,109 @@
+use super::test_context;
+use crate::api::common::ApiContext;
+use crate::api::invite::{invite_user, request_invite, respond_to_invite};
+use wiremock::{
+    matchers::{body_partial_json, method, path},
+    Mock, MockServer, ResponseTemplate,
+};
+
+async fn setup() -> (MockServer, ApiContext) {
+    let mock_server = MockServer::start().await;
+    let context = test_context(&mock_server);
+    (mock_server, context)
+}
+
+fn notification(notification_type: &str, receiver: &str) -> serde_json::Value {
+    serde_json::json!({
+        "created_at": "2024-01-01T00:00:00.000Z",
+        "details": {},
+        "id": "not_test",
+        "message": "",
+        "receiverUserId": receiver,
+        "senderUserId": "usr_me",
+        "type": notification_type
+    })
+}
+
+#[tokio::test]
+async fn test_invite_user_sends_location() {
+    let (mock_server, context) = setup().await;
+
+    Mock::given(method("POST"))
+        .and(path("/api/1/invite/usr_friend"))
+        .and(body_partial_json(serde_json::json!({
+            "instanceId": "wrld_test:12345~private(usr_me)~region(jp)"
+        })))
+        .respond_with(
+            ResponseTemplate::new(200).set_body_json(notification("invite", "usr_friend")),
+        )
+        .expect(1)
+        .mount(&mock_server)
+        .await;
+
+    let sent = invite_user(
+        &context,
+        "usr_friend",
+        "wrld_test",
+        "12345~private(usr_me)~region(jp)",
+        None,
+    )
+    .await
+    .expect("Failed to invite user");
+
+    assert_eq!(sent.receiver_user_id, "usr_friend");
+}
+
+#[tokio::test]
+async fn test_invite_user_rejected() {
+    let (mock_server, context) = setup().await;
+
+    Mock::given(method("POST"))
+        .and(path("/api/1/invite/usr_stranger"))
+        .respond_with(ResponseTemplate::new(403).set_body_json(serde_json::json!({
+            "error": { "message": "You must be friends to invite", "status_code": 403 }
+        })))
+        .mount(&mock_server)
+        .await;
+
+    let result = invite_user(&context, "usr_stranger", "wrld_test", "12345", None).await;
+
+    assert!(result.unwrap_err().to_string().contains("403"));
+}
+
+#[tokio::test]
+async fn test_request_invite() {
+    let (mock_server, context) = setup().await;
+
+    Mock::given(method("POST"))
+        .and(path("/api/1/requestInvite/usr_friend"))
+        .and(body_partial_json(serde_json::json!({ "requestSlot": 1 })))
+        .respond_with(
+            ResponseTemplate::new(200).set_body_json(notification("requestInvite", "usr_friend")),
+        )
+        .expect(1)
+        .mount(&mock_server)
+        .await;
+
+    request_invite(&context, "usr_friend", Some(1))
+        .await
+        .expect("Failed to request invite");
+}
+
+#[tokio::test]
+async fn test_respond_to_invite() {
+    let (mock_server, context) = setup().await;
+
+    Mock::given(method("POST"))
+        .and(path("/api/1/invite/not_received/response"))
+        .and(body_partial_json(serde_json::json!({ "responseSlot": 2 })))
+        .respond_with(
+            ResponseTemplate::new(200).set_body_json(notification("inviteResponse", "usr_friend")),
+        )
+        .expect(1)
+        .mount(&mock_server)
+        .await;
+
+    respond_to_invite(&context, "not_received", 2)
+        .await
+        .expect("Failed to respond to invite");
+}
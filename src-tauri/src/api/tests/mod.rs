@@ -1,2 +1,35 @@
 #[cfg(test)]
+mod auth_tests;
+#[cfg(test)]
+mod favorite_tests;
+#[cfg(test)]
+mod instance_tests;
+#[cfg(test)]
+mod invite_tests;
+#[cfg(test)]
 mod rate_limit_tests;
+#[cfg(test)]
+mod session_tests;
+#[cfg(test)]
+mod world_tests;
+
+use std::sync::{Arc, RwLock};
+
+use reqwest::cookie::Jar;
+
+use crate::api::{common::ApiContext, HttpClientFactory, RateLimitStore};
+
+/// Creates a context pointing at the given mock server, with its own rate limit store
+fn test_context(mock_server: &wiremock::MockServer) -> ApiContext {
+    let rate_limits = RateLimitStore {
+        endpoints: std::collections::HashMap::new(),
+        data_path: None,
+    };
+
+    ApiContext::new(
+        Arc::new(Jar::default()),
+        format!("{}/api/1", mock_server.uri()),
+        Arc::new(RwLock::new(rate_limits)),
+        Arc::new(RwLock::new(HttpClientFactory::default())),
+    )
+}
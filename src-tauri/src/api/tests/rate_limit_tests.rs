@@ -1,8 +1,11 @@
+use super::test_context;
 use crate::api::common::{
     check_rate_limit, get_reqwest_client, handle_api_response, record_rate_limit, reset_backoff,
 };
-use crate::{api::RateLimitStore, RATE_LIMIT_STORE};
-use chrono::Utc;
+use crate::api::rate_limiter::BackoffPolicy;
+use crate::api::world::find_world_by_id;
+use crate::api::{HttpClientFactory, RateLimitStore};
+use crate::errors::{ApiError, AppError};
 use reqwest::cookie::Jar;
 use std::sync::{Arc, RwLock};
 use std::time::Duration;
@@ -18,24 +21,22 @@ async fn setup_mock_server() -> MockServer {
     MockServer::start().await
 }
 
-// Initialize the global store for tests
-fn init_rate_limit_store() {
+// Create a fresh store for each test, so tests can run in parallel
+fn init_rate_limit_store() -> RwLock<RateLimitStore> {
     // Create a temporary directory for test data
     let temp_dir = tempdir().expect("Failed to create temp directory");
     let file_path = temp_dir.path().join("rate_limits_test.json");
 
-    // Initialize the global store
-    // The 'set_once' option allows us to set it multiple times during tests
-    let _ = RATE_LIMIT_STORE.set(RwLock::new(RateLimitStore {
+    RwLock::new(RateLimitStore {
         endpoints: std::collections::HashMap::new(),
         data_path: Some(file_path),
-    }));
+    })
 }
 
 #[tokio::test]
 async fn test_rate_limit_detection() {
     // Initialize store before test
-    init_rate_limit_store();
+    let store = init_rate_limit_store();
 
     // Start mock server
     let mock_server = setup_mock_server().await;
@@ -49,7 +50,7 @@ async fn test_rate_limit_detection() {
 
     // Create test client
     let jar = Arc::new(Jar::default());
-    let client = get_reqwest_client(&jar);
+    let client = get_reqwest_client(&jar, &HttpClientFactory::default());
 
     // Make a request to the mock server
     let endpoint = "test_world_api";
@@ -68,65 +69,82 @@ async fn test_rate_limit_detection() {
     assert!(error_msg.contains("Rate limit exceeded"));
 
     // Record manually for subsequent tests
-    record_rate_limit(endpoint);
+    record_rate_limit(&store, endpoint);
+}
+
+/// Equal jitter keeps at least half of the backoff, and never more than all of it
+fn assert_jittered(backoff_ms: u64, expected_ms: u64) {
+    assert!(
+        (expected_ms / 2..=expected_ms).contains(&backoff_ms),
+        "{}ms is not a jittered {}ms",
+        backoff_ms,
+        expected_ms
+    );
 }
 
 #[tokio::test]
 async fn test_exponential_backoff() {
     // Initialize store before test
-    init_rate_limit_store();
+    let store = init_rate_limit_store();
 
     let endpoint = "test_exponential_backoff";
 
     // Record first rate limit
-    let first_backoff = record_rate_limit(endpoint);
-    assert_eq!(first_backoff, 600000); // First backoff should be the base value (10 minutes)
+    let first_backoff = record_rate_limit(&store, endpoint);
+    assert_jittered(first_backoff, 600000); // First backoff should be the base value (10 minutes)
 
     // Record second rate limit
-    let second_backoff = record_rate_limit(endpoint);
-    assert_eq!(second_backoff, 1200000); // Second should be doubled (20 minutes)
+    let second_backoff = record_rate_limit(&store, endpoint);
+    assert_jittered(second_backoff, 1200000); // Second should be doubled (20 minutes)
 
     // Record third rate limit
-    let third_backoff = record_rate_limit(endpoint);
-    assert_eq!(third_backoff, 2400000); // Third should be doubled again (40 minutes)
+    let third_backoff = record_rate_limit(&store, endpoint);
+    assert_jittered(third_backoff, 2400000); // Third should be doubled again (40 minutes)
 
     // Record fourth rate limit - should cap at 1 hour
-    let fourth_backoff = record_rate_limit(endpoint);
-    assert_eq!(fourth_backoff, 3600000); // Should cap at 1 hour (3600000 ms)
+    let fourth_backoff = record_rate_limit(&store, endpoint);
+    assert_jittered(fourth_backoff, 3600000); // Should cap at 1 hour (3600000 ms)
 
     // Clean up
-    reset_backoff(endpoint);
+    reset_backoff(&store, endpoint, BackoffPolicy::INTERACTIVE);
 }
 
 #[tokio::test]
 async fn test_check_rate_limit() {
     // Initialize store before test
-    init_rate_limit_store();
+    let store = init_rate_limit_store();
 
     let endpoint = "test_check_rate_limit";
 
     // Initially, there should be no rate limit
-    let result = check_rate_limit(endpoint);
+    let result = check_rate_limit(&store, endpoint);
     assert!(result.is_ok());
 
     // Record a rate limit
-    record_rate_limit(endpoint);
+    record_rate_limit(&store, endpoint);
 
     // Now check_rate_limit should return an error
-    let result = check_rate_limit(endpoint);
+    let result = check_rate_limit(&store, endpoint);
     assert!(result.is_err());
-    let error = result.unwrap_err();
-    assert!(error.contains("Rate limit active"));
-    assert!(error.contains("Please try again in"));
+    match result.unwrap_err() {
+        AppError::Api(ApiError::RateLimitExceeded {
+            operation,
+            retry_in_seconds,
+        }) => {
+            assert_eq!(operation, endpoint);
+            assert!(retry_in_seconds > 0);
+        }
+        e => panic!("Expected a rate limit error, got: {}", e),
+    }
 
     // Clean up
-    reset_backoff(endpoint);
+    reset_backoff(&store, endpoint, BackoffPolicy::INTERACTIVE);
 }
 
 #[tokio::test]
 async fn test_full_flow() {
     // Initialize store before test
-    init_rate_limit_store();
+    let store = init_rate_limit_store();
 
     // Start mock server
     let mock_server = setup_mock_server().await;
@@ -142,16 +160,17 @@ async fn test_full_flow() {
 
     // Create test client
     let jar = Arc::new(Jar::default());
-    let client = get_reqwest_client(&jar);
+    let client = get_reqwest_client(&jar, &HttpClientFactory::default());
 
     // Helper function to make API calls with rate limit handling
     async fn make_api_call(
+        store: &RwLock<RateLimitStore>,
         client: &reqwest::Client,
         url: &str,
         endpoint: &str,
     ) -> Result<String, String> {
         // Check for rate limit first
-        check_rate_limit(endpoint)?;
+        check_rate_limit(store, endpoint)?;
 
         // Make the request
         let response = client
@@ -164,12 +183,13 @@ async fn test_full_flow() {
         let response = handle_api_response(response, endpoint).await?;
 
         // Success
-        reset_backoff(endpoint);
+        reset_backoff(store, endpoint, BackoffPolicy::INTERACTIVE);
         Ok(response.status().to_string())
     }
 
     // First call should trigger rate limit
     let result = make_api_call(
+        &store,
         &client,
         &format!("{}/api/1/test", mock_server.uri()),
         endpoint,
@@ -179,20 +199,21 @@ async fn test_full_flow() {
     assert!(result.unwrap_err().contains("Rate limit exceeded"));
 
     // Record the rate limit
-    record_rate_limit(endpoint);
+    record_rate_limit(&store, endpoint);
 
     // Let's add a small delay to ensure any async operations complete
     sleep(Duration::from_millis(10)).await;
 
     // Debug the store state
     {
-        let store = RATE_LIMIT_STORE.get().read().unwrap();
+        let store = store.read().unwrap();
         let data = store.endpoints.get(endpoint);
         println!("Rate limit data before second call: {:?}", data);
     }
 
     // Second call should be rejected due to active rate limit
     let result = make_api_call(
+        &store,
         &client,
         &format!("{}/api/1/test", mock_server.uri()),
         endpoint,
@@ -201,14 +222,14 @@ async fn test_full_flow() {
     assert!(result.is_err());
     let err_msg = result.unwrap_err();
     println!("Second call error: {}", err_msg);
-    assert!(err_msg.contains("Rate limit active"));
+    assert!(err_msg.contains("rate limit exceeded"));
 
     // Manually reset the backoff to test successful call
-    reset_backoff(endpoint);
+    reset_backoff(&store, endpoint, BackoffPolicy::INTERACTIVE);
 
     // Debug after reset
     {
-        let store = RATE_LIMIT_STORE.get().read().unwrap();
+        let store = store.read().unwrap();
         let data = store.endpoints.get(endpoint);
         println!("Rate limit data after reset: {:?}", data);
 
@@ -234,6 +255,7 @@ async fn test_full_flow() {
 
     println!("Making third API call...");
     let result = make_api_call(
+        &store,
         &client,
         &format!("{}/api/1/test", mock_server.uri()),
         endpoint,
@@ -251,16 +273,16 @@ async fn test_full_flow() {
 #[tokio::test]
 async fn test_reset_backoff() {
     // Initialize store before test
-    init_rate_limit_store();
+    let store = init_rate_limit_store();
 
     let endpoint = "test_reset";
 
     // Record a rate limit
-    record_rate_limit(endpoint);
+    record_rate_limit(&store, endpoint);
 
     // Verify it's recorded
     {
-        let store = RATE_LIMIT_STORE.get().read().unwrap();
+        let store = store.read().unwrap();
         let data = store
             .endpoints
             .get(endpoint)
@@ -270,11 +292,11 @@ async fn test_reset_backoff() {
     }
 
     // Reset the backoff
-    reset_backoff(endpoint);
+    reset_backoff(&store, endpoint, BackoffPolicy::INTERACTIVE);
 
     // Verify it's fully reset
     {
-        let store = RATE_LIMIT_STORE.get().read().unwrap();
+        let store = store.read().unwrap();
         let data = store
             .endpoints
             .get(endpoint)
@@ -284,5 +306,96 @@ async fn test_reset_backoff() {
             "Timestamp should be cleared"
         );
         assert_eq!(data.consecutive_failures, 0, "Failures should be reset");
+        assert_eq!(
+            data.current_backoff_ms,
+            BackoffPolicy::INTERACTIVE.base_ms,
+            "Backoff should be back to the base of the policy"
+        );
+    }
+
+    // Bulk jobs go back to their own, shorter base
+    record_rate_limit(&store, endpoint);
+    reset_backoff(&store, endpoint, BackoffPolicy::BULK);
+    assert_eq!(
+        store.read().unwrap().endpoints[endpoint].current_backoff_ms,
+        BackoffPolicy::BULK.base_ms
+    );
+}
+
+#[tokio::test]
+async fn test_retry_after_header_sets_backoff() {
+    let mock_server = setup_mock_server().await;
+    let context = test_context(&mock_server);
+
+    Mock::given(method("GET"))
+        .and(path("/api/1/worlds/wrld_limited"))
+        .respond_with(ResponseTemplate::new(429).insert_header("Retry-After", "30"))
+        .expect(1)
+        .mount(&mock_server)
+        .await;
+
+    let result = find_world_by_id(&context, "wrld_limited").await;
+    assert!(matches!(
+        result,
+        Err(AppError::Api(ApiError::RateLimitExceeded {
+            retry_in_seconds: 30,
+            ..
+        }))
+    ));
+
+    {
+        let store = context.rate_limits().read().unwrap();
+        let data = store
+            .endpoints
+            .get("get_world_by_id")
+            .expect("Rate limit should be recorded");
+        assert_eq!(data.current_backoff_ms, 30000);
+        assert_eq!(data.consecutive_failures, 1);
+    }
+
+    // The next request is not sent while the server asked to wait
+    let result = find_world_by_id(&context, "wrld_limited").await;
+    assert!(matches!(
+        result,
+        Err(AppError::Api(ApiError::RateLimitExceeded { .. }))
+    ));
+}
+
+#[tokio::test]
+async fn test_bulk_jobs_back_off_shorter() {
+    let mock_server = setup_mock_server().await;
+    let context = test_context(&mock_server);
+
+    Mock::given(method("GET"))
+        .and(path("/api/1/worlds/wrld_bulk"))
+        .respond_with(ResponseTemplate::new(429))
+        .mount(&mock_server)
+        .await;
+
+    let result = find_world_by_id(&context.bulk(), "wrld_bulk").await;
+    assert!(result.is_err());
+
+    let store = context.rate_limits().read().unwrap();
+    let data = store.endpoints.get("get_world_by_id").unwrap();
+    // One minute base backoff, instead of the ten minutes of interactive requests
+    assert_eq!(data.current_backoff_ms, 60000);
+}
+
+#[tokio::test]
+async fn test_rate_limits_persist_across_loads() {
+    let temp_dir = tempdir().expect("Failed to create temp directory");
+    let file_path = temp_dir.path().join("rate_limits.json");
+    let endpoint = "test_persist";
+
+    let store = RwLock::new(RateLimitStore::load(file_path.clone()));
+    record_rate_limit(&store, endpoint);
+    let recorded = store.read().unwrap().endpoints[endpoint].last_rate_limited;
+
+    let reloaded = RwLock::new(RateLimitStore::load(file_path));
+    {
+        let data = &reloaded.read().unwrap().endpoints[endpoint];
+        assert_eq!(data.last_rate_limited, recorded);
+        assert_eq!(data.consecutive_failures, 1);
     }
+    assert!(check_rate_limit(&reloaded, endpoint).is_err());
 }
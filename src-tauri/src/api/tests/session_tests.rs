@@ -0,0 +1,76 @@
+use super::test_context;
+use crate::api::auth::{VRChatAPIClientAuthenticator, VRChatAuthStatus};
+use crate::api::world::{get_favorite_worlds, get_world_if_modified, Validators};
+use wiremock::{
+    matchers::{method, path},
+    Mock, MockServer, ResponseTemplate,
+};
+
+#[tokio::test]
+async fn test_unauthorized_response_expires_session() {
+    let mock_server = MockServer::start().await;
+    let context = test_context(&mock_server);
+
+    Mock::given(method("GET"))
+        .and(path("/api/1/worlds/wrld_deleted"))
+        .respond_with(ResponseTemplate::new(404))
+        .mount(&mock_server)
+        .await;
+    Mock::given(method("GET"))
+        .and(path("/api/1/worlds/favorites"))
+        .respond_with(ResponseTemplate::new(401))
+        .mount(&mock_server)
+        .await;
+
+    let result = get_world_if_modified(&context, "wrld_deleted", &Validators::default()).await;
+    assert!(result.is_err());
+    assert!(!context.session().is_expired());
+
+    assert!(get_favorite_worlds(&context).await.is_err());
+    // Shared with the contexts made from the same session
+    assert!(context.bulk().session().is_expired());
+}
+
+#[tokio::test]
+async fn test_verified_token_makes_session_valid_again() {
+    let mock_server = MockServer::start().await;
+    let context = test_context(&mock_server);
+    context.session().mark_expired();
+
+    Mock::given(method("GET"))
+        .and(path("/api/1/auth/user"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+            "id": "usr_test",
+            "displayName": "tester"
+        })))
+        .mount(&mock_server)
+        .await;
+
+    let mut authenticator = VRChatAPIClientAuthenticator::from_context(context.clone());
+    let status = authenticator
+        .verify_token()
+        .await
+        .expect("Verify request failed");
+
+    assert!(matches!(status, VRChatAuthStatus::Success(_, _)));
+    assert!(!context.session().is_expired());
+}
+
+#[tokio::test]
+async fn test_valid_waits_for_the_session_to_be_renewed() {
+    let mock_server = MockServer::start().await;
+    let context = test_context(&mock_server);
+    context.session().valid().await;
+
+    context.session().mark_expired();
+    let session = context.session().clone();
+    let waiter = tokio::spawn(async move { session.valid().await });
+    tokio::task::yield_now().await;
+    assert!(!waiter.is_finished());
+
+    context.session().mark_valid();
+    tokio::time::timeout(std::time::Duration::from_secs(1), waiter)
+        .await
+        .expect("The waiter was not woken")
+        .expect("The waiter panicked");
+}
@@ -0,0 +1,280 @@
+use super::test_context;
+use crate::api::common::ApiContext;
+use crate::api::world::{
+    find_world_by_id, get_favorite_worlds, get_world_if_modified, ConditionalWorld, ReleaseStatus,
+    Validators,
+};
+use crate::errors::{ApiError, AppError, NetworkError};
+use wiremock::{
+    matchers::{header, method, path, query_param},
+    Mock, MockServer, ResponseTemplate,
+};
+
+fn favorite_world_json(id: &str) -> serde_json::Value {
+    serde_json::json!({
+        "authorId": "usr_author",
+        "authorName": "Author",
+        "capacity": 32,
+        "description": "A test world",
+        "recommendedCapacity": 16,
+        "created_at": "2024-01-01T00:00:00.000Z",
+        "favorites": 10,
+        "favoriteGroup": "worlds1",
+        "visits": 100,
+        "id": id,
+        "imageUrl": "https://example.com/image.png",
+        "name": format!("World {}", id),
+        "publicationDate": "2024-01-02T00:00:00.000Z",
+        "releaseStatus": "public",
+        "tags": ["system_approved", "author_tag_game"],
+        "thumbnailImageUrl": "https://example.com/thumb.png",
+        "unityPackages": [{ "platform": "standalonewindows" }],
+        "updated_at": "2024-01-03T00:00:00.000Z",
+        "version": 1
+    })
+}
+
+fn hidden_world_json(id: &str) -> serde_json::Value {
+    serde_json::json!({
+        "authorName": "???",
+        "capacity": 0,
+        "favoriteGroup": "worlds1",
+        "id": id,
+        "imageUrl": "",
+        "name": "???",
+        "releaseStatus": "hidden",
+        "thumbnailImageUrl": ""
+    })
+}
+
+async fn setup() -> (MockServer, ApiContext) {
+    let mock_server = MockServer::start().await;
+    let context = test_context(&mock_server);
+    (mock_server, context)
+}
+
+#[tokio::test]
+async fn test_get_favorite_worlds_separates_hidden_worlds() {
+    let (mock_server, context) = setup().await;
+
+    Mock::given(method("GET"))
+        .and(path("/api/1/worlds/favorites"))
+        .and(query_param("offset", "0"))
+        .and(query_param("n", "100"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!([
+            favorite_world_json("wrld_1"),
+            hidden_world_json("wrld_hidden"),
+            favorite_world_json("wrld_2"),
+        ])))
+        .expect(1)
+        .mount(&mock_server)
+        .await;
+
+    let (worlds, hidden) = get_favorite_worlds(&context)
+        .await
+        .expect("Failed to fetch favorites");
+
+    assert_eq!(worlds.len(), 2);
+    assert_eq!(worlds[0].id, "wrld_1");
+    assert_eq!(worlds[1].id, "wrld_2");
+    assert_eq!(worlds[0].release_status, ReleaseStatus::Public);
+    assert_eq!(worlds[0].favorite_group, "worlds1");
+    assert_eq!(hidden.len(), 1);
+    assert_eq!(hidden[0].id, "wrld_hidden");
+    assert_eq!(hidden[0].release_status, ReleaseStatus::Hidden);
+}
+
+#[tokio::test]
+async fn test_get_favorite_worlds_fetches_all_pages() {
+    let (mock_server, context) = setup().await;
+
+    let first_page: Vec<_> = (0..100)
+        .map(|i| favorite_world_json(&format!("wrld_{}", i)))
+        .collect();
+
+    Mock::given(method("GET"))
+        .and(path("/api/1/worlds/favorites"))
+        .and(query_param("offset", "0"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(first_page))
+        .expect(1)
+        .mount(&mock_server)
+        .await;
+
+    Mock::given(method("GET"))
+        .and(path("/api/1/worlds/favorites"))
+        .and(query_param("offset", "100"))
+        .respond_with(
+            ResponseTemplate::new(200)
+                .set_body_json(serde_json::json!([favorite_world_json("wrld_last")])),
+        )
+        .expect(1)
+        .mount(&mock_server)
+        .await;
+
+    let (worlds, hidden) = get_favorite_worlds(&context)
+        .await
+        .expect("Failed to fetch favorites");
+
+    assert_eq!(worlds.len(), 101);
+    assert!(hidden.is_empty());
+    assert_eq!(worlds.last().unwrap().id, "wrld_last");
+}
+
+#[tokio::test]
+async fn test_get_favorite_worlds_invalid_response() {
+    let (mock_server, context) = setup().await;
+
+    Mock::given(method("GET"))
+        .and(path("/api/1/worlds/favorites"))
+        .respond_with(ResponseTemplate::new(200).set_body_string("not json"))
+        .mount(&mock_server)
+        .await;
+
+    let result = get_favorite_worlds(&context).await;
+
+    assert!(matches!(
+        result,
+        Err(AppError::Network(NetworkError::InvalidResponse))
+    ));
+}
+
+#[tokio::test]
+async fn test_find_world_by_id_not_found() {
+    let (mock_server, context) = setup().await;
+
+    Mock::given(method("GET"))
+        .and(path("/api/1/worlds/wrld_deleted"))
+        .respond_with(ResponseTemplate::new(404).set_body_json(serde_json::json!({
+            "error": { "message": "World wrld_deleted not found", "status_code": 404 }
+        })))
+        .expect(1)
+        .mount(&mock_server)
+        .await;
+
+    let world = find_world_by_id(&context, "wrld_deleted")
+        .await
+        .expect("A missing world is not an error");
+
+    assert!(world.is_none());
+}
+
+#[tokio::test]
+async fn test_get_world_distinguishes_expired_session_from_missing_world() {
+    let (mock_server, context) = setup().await;
+
+    Mock::given(method("GET"))
+        .and(path("/api/1/worlds/wrld_deleted"))
+        .respond_with(ResponseTemplate::new(404))
+        .mount(&mock_server)
+        .await;
+    Mock::given(method("GET"))
+        .and(path("/api/1/worlds/wrld_test"))
+        .respond_with(ResponseTemplate::new(401).set_body_json(serde_json::json!({
+            "error": { "message": "Missing Credentials", "status_code": 401 }
+        })))
+        .mount(&mock_server)
+        .await;
+
+    let result = get_world_if_modified(&context, "wrld_deleted", &Validators::default()).await;
+    assert!(matches!(result, Err(AppError::Api(ApiError::NotFound))));
+
+    let result = get_world_if_modified(&context, "wrld_test", &Validators::default()).await;
+    assert!(matches!(
+        result,
+        Err(AppError::Api(ApiError::AuthenticationFailed))
+    ));
+}
+
+#[tokio::test]
+async fn test_transient_failures_are_retried() {
+    let (mock_server, context) = setup().await;
+
+    Mock::given(method("GET"))
+        .and(path("/api/1/worlds/favorites"))
+        .respond_with(ResponseTemplate::new(503))
+        .up_to_n_times(1)
+        .expect(1)
+        .mount(&mock_server)
+        .await;
+    Mock::given(method("GET"))
+        .and(path("/api/1/worlds/favorites"))
+        .respond_with(
+            ResponseTemplate::new(200)
+                .set_body_json(serde_json::json!([favorite_world_json("wrld_1")])),
+        )
+        .expect(1)
+        .mount(&mock_server)
+        .await;
+
+    let (favorites, _) = get_favorite_worlds(&context)
+        .await
+        .expect("The request should succeed once retried");
+
+    assert_eq!(favorites.len(), 1);
+}
+
+#[tokio::test]
+async fn test_persistent_server_errors_give_up() {
+    let (mock_server, context) = setup().await;
+
+    Mock::given(method("GET"))
+        .and(path("/api/1/worlds/favorites"))
+        .respond_with(ResponseTemplate::new(502))
+        .expect(3)
+        .mount(&mock_server)
+        .await;
+
+    let result = get_favorite_worlds(&context).await;
+
+    assert!(matches!(
+        result,
+        Err(AppError::Network(NetworkError::HttpError(502)))
+    ));
+}
+
+#[tokio::test]
+async fn test_get_world_if_modified_revalidates_with_etag() {
+    let (mock_server, context) = setup().await;
+
+    Mock::given(method("GET"))
+        .and(path("/api/1/worlds/wrld_1"))
+        .and(header("If-None-Match", "\"v1\""))
+        .respond_with(ResponseTemplate::new(304))
+        .expect(1)
+        .mount(&mock_server)
+        .await;
+    Mock::given(method("GET"))
+        .and(path("/api/1/worlds/wrld_1"))
+        .respond_with(
+            ResponseTemplate::new(200)
+                .insert_header("ETag", "\"v1\"")
+                .insert_header("Last-Modified", "Wed, 01 Jan 2025 00:00:00 GMT")
+                .set_body_json(favorite_world_json("wrld_1")),
+        )
+        .expect(1)
+        .mount(&mock_server)
+        .await;
+
+    let validators = match get_world_if_modified(&context, "wrld_1", &Validators::default())
+        .await
+        .expect("Failed to fetch world")
+    {
+        ConditionalWorld::Modified {
+            world, validators, ..
+        } => {
+            assert_eq!(world.id, "wrld_1");
+            validators
+        }
+        ConditionalWorld::NotModified => panic!("Expected the world without validators"),
+    };
+    assert_eq!(validators.etag.as_deref(), Some("\"v1\""));
+    assert_eq!(
+        validators.last_modified.as_deref(),
+        Some("Wed, 01 Jan 2025 00:00:00 GMT")
+    );
+
+    let result = get_world_if_modified(&context, "wrld_1", &validators)
+        .await
+        .expect("Failed to revalidate world");
+    assert!(matches!(result, ConditionalWorld::NotModified));
+}
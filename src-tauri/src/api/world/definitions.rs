@@ -2,13 +2,13 @@ use chrono::DateTime;
 use serde::{Deserialize, Serialize};
 use specta::Type;
 
-use crate::definitions::{Platform, WorldApiData, WorldDisplayData};
+use crate::definitions::{Platform, WorldApiData, WorldAvailability, WorldDisplayData};
 use std::collections::HashSet;
 
 fn map_platforms(platforms: &[Platform]) -> Vec<Platform> {
     let mut mapped: Vec<Platform> = platforms.to_vec();
 
-    mapped.retain(|p| *p != Platform::UnknownPlatform);
+    mapped.retain(|p| *p != Platform::Unknown);
 
     if mapped.is_empty() {
         mapped.push(Platform::StandaloneWindows);
@@ -28,19 +28,15 @@ fn extract_platforms(unity_packages: &[UnityPackage]) -> Vec<Platform> {
 
 #[derive(Debug, Eq, PartialEq, Hash, Deserialize, Serialize, Clone, Type)]
 #[serde(rename_all = "camelCase")]
+#[derive(Default)]
 pub enum ReleaseStatus {
+    #[default]
     Public,
     Private,
     Hidden,
     All,
 }
 
-impl Default for ReleaseStatus {
-    fn default() -> Self {
-        Self::Public
-    }
-}
-
 #[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize, Type)]
 pub struct UnityPackage {
     #[serde(rename = "platform")]
@@ -98,9 +94,8 @@ impl TryInto<WorldApiData> for FavoriteWorld {
         } else {
             Some(
                 DateTime::parse_from_rfc3339(&self.publication_date)
-                    .map_err(|e| {
+                    .inspect_err(|&e| {
                         log::info!("Failed to parse publication_date: {}", e);
-                        e
                     })?
                     .with_timezone(&chrono::Utc),
             )
@@ -211,9 +206,8 @@ impl TryInto<WorldApiData> for WorldDetails {
         } else {
             Some(
                 DateTime::parse_from_rfc3339(&self.publication_date)
-                    .map_err(|e| {
+                    .inspect_err(|&e| {
                         log::info!("Failed to parse publication_date: {}", e);
-                        e
                     })?
                     .with_timezone(&chrono::Utc),
             )
@@ -302,6 +296,7 @@ impl TryInto<WorldDisplayData> for VRChatWorld {
             folders: Vec::new(),
             tags: self.tags.clone(),
             capacity: self.capacity,
+            availability: WorldAvailability::Available,
         })
     }
 }
@@ -353,6 +348,7 @@ pub struct WorldSearchParametersBuilder {
     pub search: Option<String>,
 }
 
+#[allow(dead_code)]
 impl WorldSearchParametersBuilder {
     pub fn new() -> Self {
         Self {
@@ -401,6 +397,7 @@ impl WorldSearchParametersBuilder {
 }
 
 #[derive(Debug, PartialEq, Serialize)]
+#[allow(dead_code)]
 pub enum SearchWorldSort {
     #[serde(rename = "popularity")]
     Popularity,
@@ -1,26 +1,26 @@
-use std::sync::Arc;
-
 use log::info;
-use reqwest::cookie::Jar;
-use serde::Deserialize;
+use reqwest::header::{ETAG, IF_MODIFIED_SINCE, IF_NONE_MATCH, LAST_MODIFIED};
+use reqwest::StatusCode;
 
-use crate::api::common::{
-    check_rate_limit, get_reqwest_client, handle_api_response, record_rate_limit, reset_backoff,
-    API_BASE_URL,
-};
+use crate::api::common::{parse_response, ApiContext};
+use crate::errors::{ApiError, AppError, NetworkError};
 
 use super::definitions::{
-    FavoriteWorld, FavoriteWorldParser, VRChatWorld, WorldDetails, WorldSearchParameters,
+    FavoriteWorld, FavoriteWorldParser, HiddenWorld, VRChatWorld, WorldDetails,
+    WorldSearchParameters,
 };
 
-pub async fn get_favorite_worlds<J: Into<Arc<Jar>>>(
-    cookie: J,
-) -> Result<Vec<FavoriteWorld>, String> {
+/// Fetches the favorite worlds of the logged in user
+/// Worlds that were privated or deleted are listed by VRChat without their
+/// details, and are returned separately
+pub async fn get_favorite_worlds(
+    context: &ApiContext,
+) -> Result<(Vec<FavoriteWorld>, Vec<HiddenWorld>), AppError> {
     const OPERATION: &str = "get_favorite_worlds";
 
-    let cookie_jar: Arc<Jar> = cookie.into();
-    let client = get_reqwest_client(&cookie_jar);
+    let client = context.client();
     let mut all_favorites = Vec::new();
+    let mut hidden_favorites = Vec::new();
     let mut offset = 0;
     let n = 100; // Set page size to 100
     let mut current_page = 0;
@@ -33,51 +33,23 @@ pub async fn get_favorite_worlds<J: Into<Arc<Jar>>>(
             offset
         );
 
-        check_rate_limit(OPERATION)?;
-
-        let result = client
-            .get(format!(
-                "{}/worlds/favorites?offset={}&n={}",
-                API_BASE_URL, offset, n
-            ))
-            .send()
-            .await
-            .map_err(|e| e.to_string())?;
-
-        let result = match handle_api_response(result, OPERATION).await {
-            Ok(response) => response,
-            Err(e) => {
-                log::error!("Failed to handle API response: {}", e);
-                record_rate_limit(OPERATION);
-                return Err(e);
-            }
-        };
-
-        reset_backoff(OPERATION);
-
-        let text = result
-            .text()
-            .await
-            .map_err(|e| format!("Failed to get favorite worlds: {}", e.to_string()))?;
-
-        let parsed: Vec<FavoriteWorldParser> = match serde_json::from_str(&text) {
-            Ok(worlds) => worlds,
-            Err(e) => {
-                log::error!("Failed to parse favorite worlds: {}", e.to_string());
-                log::info!("Response: {}", text);
-                return Err(format!(
-                    "Failed to parse favorite worlds: {}",
-                    e.to_string()
-                ));
-            }
-        };
+        let request = client.get(format!(
+            "{}/worlds/favorites?offset={}&n={}",
+            context.base_url(),
+            offset,
+            n
+        ));
+        let result = context.send(request, OPERATION).await?;
+        let parsed: Vec<FavoriteWorldParser> = parse_response(result, "favorite worlds").await?;
 
         // Process this page of results
         let page_size = parsed.len();
         for world in parsed {
             match world {
                 FavoriteWorldParser::World(favorite_world) => all_favorites.push(favorite_world),
-                FavoriteWorldParser::HiddenWorld(_) => (),
+                FavoriteWorldParser::HiddenWorld(hidden_world) => {
+                    hidden_favorites.push(hidden_world)
+                }
             }
         }
 
@@ -99,123 +71,149 @@ pub async fn get_favorite_worlds<J: Into<Arc<Jar>>>(
     }
 
     log::info!(
-        "Fetched {} favorite worlds from {} pages",
+        "Fetched {} favorite worlds ({} unavailable) from {} pages",
         all_favorites.len(),
+        hidden_favorites.len(),
         current_page
     );
-    Ok(all_favorites)
+    Ok((all_favorites, hidden_favorites))
 }
 
-pub async fn get_recently_visited_worlds<J: Into<Arc<Jar>>>(
-    cookie: J,
-) -> Result<Vec<VRChatWorld>, String> {
+pub async fn get_recently_visited_worlds(
+    context: &ApiContext,
+) -> Result<Vec<VRChatWorld>, AppError> {
     const OPERATION: &str = "get_recently_visited_worlds";
 
-    // Check for rate limit
-    check_rate_limit(OPERATION)?;
+    let request = context
+        .client()
+        .get(format!("{}/worlds/recent?n=100", context.base_url()));
+    let result = context.send(request, OPERATION).await?;
+    let worlds: Vec<VRChatWorld> = parse_response(result, "vrchat worlds").await?;
 
-    let cookie_jar: Arc<Jar> = cookie.into();
-    let client = get_reqwest_client(&cookie_jar);
+    Ok(worlds)
+}
 
-    let result = client
-        .get(format!("{}/worlds/recent?n=100", API_BASE_URL))
-        .send()
-        .await
-        .map_err(|e| format!("Failed to get recently visited worlds: {}", e.to_string()))?;
+/// Fetches a world, returning None if VRChat answers that it does not exist,
+/// which is the case for deleted worlds
+pub async fn find_world_by_id<S: AsRef<str>>(
+    context: &ApiContext,
+    id: S,
+) -> Result<Option<WorldDetails>, AppError> {
+    const OPERATION: &str = "get_world_by_id";
 
-    let result = match handle_api_response(result, OPERATION).await {
-        Ok(response) => response,
-        Err(e) => {
-            log::error!("Failed to handle API response: {}", e);
-            record_rate_limit(OPERATION);
-            return Err(e);
+    let request = context
+        .client()
+        .get(format!("{}/worlds/{}", context.base_url(), id.as_ref()));
+    let result = match context.send(request, OPERATION).await {
+        Ok(result) => result,
+        Err(AppError::Api(ApiError::NotFound)) => {
+            log::info!("World {} not found", id.as_ref());
+            return Ok(None);
         }
+        Err(e) => return Err(e),
     };
 
-    reset_backoff(OPERATION);
-
-    let text = result.text().await;
-
-    if let Err(e) = text {
-        return Err(format!(
-            "Failed to get recently visited worlds: {}",
-            e.to_string()
-        ));
-    }
-
-    let text = text.unwrap();
+    let world: WorldDetails = parse_response(result, "vrchat world").await?;
+    Ok(Some(world))
+}
 
-    let worlds: Vec<VRChatWorld> = match serde_json::from_str(&text) {
-        Ok(worlds) => worlds,
-        Err(e) => {
-            log::error!("Failed to parse vrchat worlds: {}", e.to_string());
-            log::info!("Response: {}", text);
-            return Err(format!("Failed to parse vrchat worlds: {}", e.to_string()));
-        }
-    };
+/// Validators of a cached response, sent so the world is only returned if it changed
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Validators {
+    pub etag: Option<String>,
+    pub last_modified: Option<String>,
+}
 
-    Ok(worlds)
+/// The answer to a conditional request for a world
+#[derive(Debug)]
+pub enum ConditionalWorld {
+    /// The world did not change since the response the validators are from
+    NotModified,
+    /// The world changed, with the response body and its validators to cache
+    Modified {
+        world: Box<WorldDetails>,
+        body: String,
+        validators: Validators,
+    },
 }
 
-pub async fn get_world_by_id<J: Into<Arc<Jar>>, S: AsRef<str>>(
-    cookie: J,
+/// Fetches a world, unless it did not change since a cached response
+///
+/// # Arguments
+/// * `context` - The API context to use
+/// * `id` - The ID of the world
+/// * `validators` - The ETag and Last-Modified of the cached response, if any
+///
+/// # Errors
+/// Returns `ApiError::NotFound` if the world does not exist
+pub async fn get_world_if_modified<S: AsRef<str>>(
+    context: &ApiContext,
     id: S,
-) -> Result<WorldDetails, String> {
+    validators: &Validators,
+) -> Result<ConditionalWorld, AppError> {
     const OPERATION: &str = "get_world_by_id";
 
-    check_rate_limit(OPERATION)?;
-
-    let cookie_jar: Arc<Jar> = cookie.into();
-    let client = get_reqwest_client(&cookie_jar);
-
-    let result = client
-        .get(format!("{}/worlds/{}", API_BASE_URL, id.as_ref()))
-        .send()
-        .await
-        .map_err(|e| format!("Failed to get world by ID: {}", e.to_string()))?;
-
-    let result = match handle_api_response(result, OPERATION).await {
-        Ok(response) => response,
-        Err(e) => {
-            log::error!("Failed to handle API response: {}", e);
-            record_rate_limit(OPERATION);
-            return Err(e);
-        }
-    };
-
-    reset_backoff(OPERATION);
-
-    let text = result.text().await;
-
-    if let Err(e) = text {
-        return Err(format!("Failed to get world by ID: {}", e.to_string()));
+    let mut request =
+        context
+            .client()
+            .get(format!("{}/worlds/{}", context.base_url(), id.as_ref()));
+    if let Some(etag) = &validators.etag {
+        request = request.header(IF_NONE_MATCH, etag);
+    }
+    if let Some(last_modified) = &validators.last_modified {
+        request = request.header(IF_MODIFIED_SINCE, last_modified);
     }
 
-    let text = text.unwrap();
-
-    let world: WorldDetails = match serde_json::from_str(&text) {
-        Ok(world) => world,
-        Err(e) => {
-            log::error!("Failed to parse vrchat world: {}", e.to_string());
-            log::info!("Response: {}", text);
-            return Err(format!("Failed to parse vrchat world: {}", e.to_string()));
-        }
+    let result = context.execute(request, OPERATION).await?;
+    if result.status() == StatusCode::NOT_MODIFIED {
+        log::info!("World {} not modified", id.as_ref());
+        return Ok(ConditionalWorld::NotModified);
+    }
+    let result = context.check_status(result, OPERATION).await?;
+
+    let header = |name| {
+        result
+            .headers()
+            .get(name)
+            .and_then(|value| value.to_str().ok())
+            .map(str::to_string)
+    };
+    let validators = Validators {
+        etag: header(ETAG),
+        last_modified: header(LAST_MODIFIED),
     };
 
-    Ok(world)
+    let body = result.text().await.map_err(|e| {
+        log::error!("Failed to read vrchat world: {}", e);
+        NetworkError::InvalidResponse
+    })?;
+    let world = parse_world(&body)?;
+    Ok(ConditionalWorld::Modified {
+        world: Box::new(world),
+        body,
+        validators,
+    })
+}
+
+/// Parses a world, as returned by VRChat or cached from one of its responses
+///
+/// # Errors
+/// Returns `NetworkError::InvalidResponse` if the body is not a world
+pub fn parse_world(body: &str) -> Result<WorldDetails, AppError> {
+    serde_json::from_str(body).map_err(|e| {
+        log::error!("Failed to parse vrchat world: {}", e);
+        NetworkError::InvalidResponse.into()
+    })
 }
 
-pub async fn search_worlds<J: Into<Arc<Jar>>>(
-    cookie: J,
+pub async fn search_worlds(
+    context: &ApiContext,
     search_parameters: &WorldSearchParameters,
     page: usize,
-) -> Result<Vec<VRChatWorld>, String> {
+) -> Result<Vec<VRChatWorld>, AppError> {
     const OPERATION: &str = "search_worlds";
 
-    check_rate_limit(OPERATION)?;
-
-    let cookie_jar: Arc<Jar> = cookie.into();
-    let client = get_reqwest_client(&cookie_jar);
+    let client = context.client();
 
     let offset = page.saturating_sub(1) * 100;
 
@@ -225,45 +223,19 @@ pub async fn search_worlds<J: Into<Arc<Jar>>>(
 
     info!(
         "URL: {}/worlds?offset={}&n=100&{}",
-        API_BASE_URL, offset, search_parameters_string
+        context.base_url(),
+        offset,
+        search_parameters_string
     );
 
-    let result = client
-        .get(format!(
-            "{}/worlds?offset={}&n=100&{}",
-            API_BASE_URL, offset, search_parameters_string
-        ))
-        .send()
-        .await
-        .expect("Failed to search worlds");
-
-    let result = match handle_api_response(result, OPERATION).await {
-        Ok(response) => response,
-        Err(e) => {
-            log::error!("Failed to handle API response: {}", e);
-            record_rate_limit(OPERATION);
-            return Err(e);
-        }
-    };
-
-    reset_backoff(OPERATION);
-
-    let text = result.text().await;
-
-    if let Err(e) = text {
-        return Err(format!("Failed to search worlds: {}", e.to_string()));
-    }
-
-    let text = text.unwrap();
-
-    let worlds: Vec<VRChatWorld> = match serde_json::from_str(&text) {
-        Ok(worlds) => worlds,
-        Err(e) => {
-            log::error!("Failed to parse vrchat worlds: {}", e.to_string());
-            log::info!("Response: {}", text);
-            return Err(format!("Failed to parse vrchat worlds: {}", e.to_string()));
-        }
-    };
+    let request = client.get(format!(
+        "{}/worlds?offset={}&n=100&{}",
+        context.base_url(),
+        offset,
+        search_parameters_string
+    ));
+    let result = context.send(request, OPERATION).await?;
+    let worlds: Vec<VRChatWorld> = parse_response(result, "vrchat worlds").await?;
 
     Ok(worlds)
 }
@@ -2,14 +2,17 @@ mod definitions;
 mod logic;
 
 pub use definitions::FavoriteWorld;
+pub use definitions::HiddenWorld;
 pub use definitions::ReleaseStatus;
 pub use definitions::SearchWorldSort;
-pub use definitions::VRChatWorld;
 pub use definitions::WorldDetails;
-pub use definitions::WorldSearchParameters;
 pub use definitions::WorldSearchParametersBuilder;
 
+pub use logic::find_world_by_id;
 pub use logic::get_favorite_worlds;
 pub use logic::get_recently_visited_worlds;
-pub use logic::get_world_by_id;
+pub use logic::get_world_if_modified;
+pub use logic::parse_world;
 pub use logic::search_worlds;
+pub use logic::ConditionalWorld;
+pub use logic::Validators;
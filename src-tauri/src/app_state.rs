@@ -0,0 +1,125 @@
+use std::sync::{Arc, RwLock};
+
+use reqwest::cookie::Jar;
+use uuid::Uuid;
+
+use crate::api::auth::VRChatAPIClientAuthenticator;
+use crate::api::{api_base_url, ApiContext, HttpClientFactory, RateLimitStore};
+use crate::definitions::{FolderModel, InitState, PreferenceModel, ScheduledInstance, WorldModel};
+use crate::services::{EncryptionService, Outbox, Storage, ThumbnailCache, WorldCache};
+
+/// Everything that belongs to one loaded library: preferences, folders, worlds,
+/// the storage they are persisted to, and the logged in VRChat session (which
+/// owns the API rate limits)
+///
+/// The application manages a single `Arc<AppState>` through `app.manage`, and
+/// commands receive it as `State<'_, Arc<AppState>>`. The services layer never
+/// reaches for global state; it is handed the fields it needs.
+pub struct AppState {
+    pub preferences: RwLock<PreferenceModel>,
+    pub folders: RwLock<Vec<FolderModel>>,
+    pub worlds: RwLock<Vec<WorldModel>>,
+    pub init_state: tokio::sync::RwLock<InitState>,
+    pub authenticator: tokio::sync::RwLock<VRChatAPIClientAuthenticator>,
+    pub storage: Box<dyn Storage>,
+    /// Builds the HTTP clients with the network settings, shared with the API contexts
+    pub http: Arc<RwLock<HttpClientFactory>>,
+    /// Encrypts the stored sessions, holding the key once it is loaded or unlocked
+    pub encryption: EncryptionService,
+    /// The ID of the background world refresh task, once it has been started
+    pub refresh_task: tokio::sync::Mutex<Option<Uuid>>,
+    /// The instances to create later, loaded when the scheduler starts
+    pub schedules: RwLock<Vec<ScheduledInstance>>,
+    /// The world details responses of VRChat, kept across restarts
+    pub world_cache: RwLock<WorldCache>,
+    /// The resized world thumbnails, served to the webview through the URI scheme
+    pub thumbnail_cache: Arc<ThumbnailCache>,
+    /// The network-bound actions queued while offline
+    pub outbox: RwLock<Outbox>,
+    /// The ID of the outbox replay task, once it has been started
+    pub outbox_task: tokio::sync::Mutex<Option<Uuid>>,
+    /// Wakes the outbox replay task, e.g. once an action was queued
+    pub outbox_wake: tokio::sync::Notify,
+}
+
+impl AppState {
+    /// Creates a new state from already loaded data
+    ///
+    /// # Arguments
+    /// * `preferences` - The user's preferences
+    /// * `folders` - The folders of the library
+    /// * `worlds` - The worlds of the library
+    /// * `init_state` - The result of loading the library from disk
+    /// * `cookie_store` - The cookie jar holding the VRChat session
+    /// * `rate_limits` - The rate limit store used for every API request
+    /// * `world_cache` - The cache of world details responses
+    /// * `thumbnail_cache` - The cache of world thumbnails
+    /// * `outbox` - The network-bound actions queued while offline
+    /// * `storage` - The storage the library is persisted to
+    /// * `encryption` - The service the stored sessions are encrypted with
+    ///
+    /// # Returns
+    /// Returns the new state, with an authenticator pointed at the configured API server,
+    /// and the HTTP clients built with the network settings of the preferences
+    pub fn new(
+        preferences: PreferenceModel,
+        folders: Vec<FolderModel>,
+        worlds: Vec<WorldModel>,
+        init_state: InitState,
+        cookie_store: Arc<Jar>,
+        rate_limits: RateLimitStore,
+        world_cache: WorldCache,
+        thumbnail_cache: ThumbnailCache,
+        outbox: Outbox,
+        storage: Box<dyn Storage>,
+        encryption: EncryptionService,
+    ) -> Self {
+        let http = HttpClientFactory::new(&preferences.network).unwrap_or_else(|e| {
+            log::error!("Failed to apply network settings: {}", e);
+            HttpClientFactory::default()
+        });
+        let http = Arc::new(RwLock::new(http));
+        let context = ApiContext::new(
+            cookie_store,
+            api_base_url(),
+            Arc::new(RwLock::new(rate_limits)),
+            Arc::clone(&http),
+        );
+
+        AppState {
+            preferences: RwLock::new(preferences),
+            folders: RwLock::new(folders),
+            worlds: RwLock::new(worlds),
+            init_state: tokio::sync::RwLock::new(init_state),
+            authenticator: tokio::sync::RwLock::new(VRChatAPIClientAuthenticator::from_context(
+                context,
+            )),
+            storage,
+            http,
+            encryption,
+            refresh_task: tokio::sync::Mutex::new(None),
+            schedules: RwLock::new(vec![]),
+            world_cache: RwLock::new(world_cache),
+            thumbnail_cache: Arc::new(thumbnail_cache),
+            outbox: RwLock::new(outbox),
+            outbox_task: tokio::sync::Mutex::new(None),
+            outbox_wake: tokio::sync::Notify::new(),
+        }
+    }
+
+    /// Returns the API context of the current VRChat session
+    pub async fn api_context(&self) -> ApiContext {
+        self.authenticator.read().await.get_context()
+    }
+
+    /// Returns a client built with the current network settings,
+    /// for requests to servers other than the VRChat API
+    pub fn http_client(&self) -> reqwest::Client {
+        HttpClientFactory::current(&self.http).client()
+    }
+
+    /// Returns the ID of the logged in user, or an empty string if not logged in
+    pub async fn user_id(&self) -> String {
+        self.init_state.read().await.user_id.clone()
+    }
+}
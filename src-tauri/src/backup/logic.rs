@@ -1,9 +1,8 @@
 use crate::backup::BackupMetaData;
-use crate::services::FileService;
-use crate::FolderModel;
-use crate::WorldModel;
+use crate::definitions::{FolderModel, WorldModel};
+use crate::services::{Changes, Storage};
 use chrono::Utc;
-use log;
+use std::collections::HashMap;
 use std::fs::{self, File};
 use std::io::{BufReader, BufWriter};
 use std::path::Path;
@@ -13,6 +12,7 @@ pub fn restore_from_backup(
     backup_path: String,
     worlds: &RwLock<Vec<WorldModel>>,
     folders: &RwLock<Vec<FolderModel>>,
+    storage: &dyn Storage,
 ) -> Result<(), String> {
     log::info!("Restoring from backup: {}", backup_path);
     let backup_dir = Path::new(&backup_path);
@@ -22,7 +22,7 @@ pub fn restore_from_backup(
     if worlds_path.exists() && folders_path.exists() {
         let file = File::open(&worlds_path).map_err(|e| e.to_string())?;
         let reader = BufReader::new(file);
-        let worlds_data: Vec<WorldModel> = serde_json::from_reader(reader)
+        let mut worlds_data: Vec<WorldModel> = serde_json::from_reader(reader)
             .map_err(|e| format!("Failed to parse worlds.json: {}", e))?;
 
         let file = File::open(&folders_path).map_err(|e| e.to_string())?;
@@ -35,6 +35,20 @@ pub fn restore_from_backup(
                 log::error!("Failed to acquire write lock for worlds: {}", e);
                 "Failed to acquire write lock for worlds".to_string()
             })?;
+            // Backups made before memos were stored with the worlds carry no memos,
+            // so keep the current memo of any world the backup has none for
+            let current_memos: HashMap<&str, &str> = worlds_lock
+                .iter()
+                .filter(|w| !w.user_data.memo.is_empty())
+                .map(|w| (w.api_data.world_id.as_str(), w.user_data.memo.as_str()))
+                .collect();
+            for world in worlds_data.iter_mut() {
+                if world.user_data.memo.is_empty() {
+                    if let Some(memo) = current_memos.get(world.api_data.world_id.as_str()) {
+                        world.user_data.memo = memo.to_string();
+                    }
+                }
+            }
             worlds_lock.clear();
             log::info!("Cleared existing worlds data");
         }
@@ -43,7 +57,9 @@ pub fn restore_from_backup(
             "Failed to acquire write lock for worlds".to_string()
         })?;
         worlds_lock.extend(worlds_data);
-        FileService::write_worlds(&*worlds_lock).map_err(|e| e.to_string())?;
+        storage
+            .write_worlds(&worlds_lock, Changes::All)
+            .map_err(|e| e.to_string())?;
         log::info!("Restored {} worlds", worlds_lock.len());
 
         {
@@ -59,7 +75,9 @@ pub fn restore_from_backup(
             "Failed to acquire write lock for folders".to_string()
         })?;
         folders_lock.extend(folders_data);
-        FileService::write_folders(&*folders_lock).map_err(|e| e.to_string())?;
+        storage
+            .write_folders(&folders_lock, Changes::All)
+            .map_err(|e| e.to_string())?;
         log::info!("Restored {} folders", folders_lock.len());
     } else {
         log::error!("Backup files not found in the specified path");
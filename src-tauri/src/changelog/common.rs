@@ -2,6 +2,8 @@ use std::time::Duration;
 
 use semver::Version;
 
+use crate::api::HttpClientFactory;
+
 use super::definitions::{ChangelogEntry, ChangelogVersion, LocalizedChanges};
 
 const URL: &str = "https://releases.raifaworks.com/manifests/changelog.json";
@@ -11,15 +13,17 @@ const VERSION: &str = env!("CARGO_PKG_VERSION");
 #[cfg(test)]
 const VERSION: &str = "1.5.0";
 
-pub async fn fetch_and_parse_changelog() -> Result<Vec<ChangelogVersion>, String> {
-    let changelog_body = fetch_changelogs().await?;
+pub async fn fetch_and_parse_changelog(
+    factory: &HttpClientFactory,
+) -> Result<Vec<ChangelogVersion>, String> {
+    let changelog_body = fetch_changelogs(factory).await?;
     let changelog = parse_changelog(changelog_body).await?;
 
     Ok(changelog)
 }
 
-async fn fetch_changelogs() -> Result<String, String> {
-    let client = get_reqwest_client()?;
+async fn fetch_changelogs(factory: &HttpClientFactory) -> Result<String, String> {
+    let client = get_reqwest_client(factory)?;
 
     let response = client
         .get(URL)
@@ -50,7 +54,7 @@ where
 pub async fn pick_changes_in_preferred_lang<S>(
     changelog: Vec<ChangelogVersion>,
     target_version: S,
-    preferred_language: &String,
+    preferred_language: &str,
     skip_pre_releases: bool,
 ) -> Result<Vec<LocalizedChanges>, String>
 where
@@ -88,9 +92,9 @@ where
             continue;
         }
 
-        let features = localize_entries(&item.features, &preferred_language);
-        let fixes = localize_entries(&item.fixes, &preferred_language);
-        let others = localize_entries(&item.others, &preferred_language);
+        let features = localize_entries(&item.features, preferred_language);
+        let fixes = localize_entries(&item.fixes, preferred_language);
+        let others = localize_entries(&item.others, preferred_language);
 
         changes.push(LocalizedChanges::new(
             item.version.clone(),
@@ -106,14 +110,14 @@ where
     Ok(changes)
 }
 
-fn localize_entries(entries: &[ChangelogEntry], preferred_language: &String) -> Vec<String> {
+fn localize_entries(entries: &[ChangelogEntry], preferred_language: &str) -> Vec<String> {
     entries
         .iter()
         .map(|entry| {
             let localized = entry
                 .langs
                 .iter()
-                .find(|lang| lang.lang.is_language_supported(&preferred_language))
+                .find(|lang| lang.lang.is_language_supported(preferred_language))
                 .map(|lang| lang.text.clone());
 
             localized.unwrap_or_else(|| entry.text.clone())
@@ -121,9 +125,9 @@ fn localize_entries(entries: &[ChangelogEntry], preferred_language: &String) ->
         .collect()
 }
 
-fn get_reqwest_client() -> Result<reqwest::Client, String> {
-    reqwest::Client::builder()
-        .user_agent(format!("VRC-Worlds-Manager-v2/{}", VERSION))
+fn get_reqwest_client(factory: &HttpClientFactory) -> Result<reqwest::Client, String> {
+    factory
+        .builder()
         .timeout(Duration::from_secs(5))
         .build()
         .map_err(|e| format!("Failed to create reqwest client: {}", e))
@@ -0,0 +1,99 @@
+use crate::services::account_service::AccountInfo;
+use crate::services::{AccountService, FileService};
+use crate::AppState;
+use std::sync::Arc;
+use tauri::State;
+
+#[tauri::command]
+#[specta::specta]
+pub async fn list_accounts(state: State<'_, Arc<AppState>>) -> Result<Vec<AccountInfo>, String> {
+    let user_id = state.user_id().await;
+    AccountService::list(&user_id, &state.encryption).map_err(|e| {
+        log::error!("Failed to list accounts: {}", e);
+        format!("Failed to list accounts: {}", e)
+    })
+}
+
+/// Detaches the active account so another one can log in through the usual login flow
+#[tauri::command]
+#[specta::specta]
+pub async fn add_account(state: State<'_, Arc<AppState>>) -> Result<(), String> {
+    AccountService::add(&state.authenticator, &state.init_state, &state.encryption)
+        .await
+        .map_err(|e| {
+            log::error!("Failed to add account: {}", e);
+            format!("Failed to add account: {}", e)
+        })
+}
+
+#[tauri::command]
+#[specta::specta]
+pub async fn switch_account(
+    user_id: String,
+    state: State<'_, Arc<AppState>>,
+) -> Result<(), String> {
+    AccountService::switch(
+        &user_id,
+        &state.authenticator,
+        &state.init_state,
+        &state.encryption,
+    )
+    .await
+    .map_err(|e| {
+        log::error!("Failed to switch account: {}", e);
+        e
+    })
+}
+
+#[tauri::command]
+#[specta::specta]
+pub async fn remove_account(
+    user_id: String,
+    state: State<'_, Arc<AppState>>,
+) -> Result<(), String> {
+    AccountService::remove(
+        &user_id,
+        &state.authenticator,
+        &state.init_state,
+        &state.encryption,
+    )
+    .await
+    .map_err(|e| {
+        log::error!("Failed to remove account: {}", e);
+        format!("Failed to remove account: {}", e)
+    })
+}
+
+/// Whether the stored sessions are protected by a passphrase that was not entered yet
+#[tauri::command]
+#[specta::specta]
+pub fn is_secret_store_locked(state: State<'_, Arc<AppState>>) -> bool {
+    state.encryption.is_locked()
+}
+
+#[tauri::command]
+#[specta::specta]
+pub async fn unlock_secret_store(
+    passphrase: String,
+    state: State<'_, Arc<AppState>>,
+) -> Result<(), String> {
+    AccountService::unlock(&passphrase, &state.authenticator, &state.encryption)
+        .await
+        .map_err(|e| {
+            log::error!("Failed to unlock stored sessions: {}", e);
+            e
+        })
+}
+
+/// Protects the stored sessions with a passphrase, or removes it when none is given
+#[tauri::command]
+#[specta::specta]
+pub fn set_secret_passphrase(
+    passphrase: Option<String>,
+    state: State<'_, Arc<AppState>>,
+) -> Result<(), String> {
+    FileService::set_secret_passphrase(passphrase.as_deref(), &state.encryption).map_err(|e| {
+        log::error!("Failed to set passphrase: {}", e);
+        format!("Failed to set passphrase: {}", e)
+    })
+}
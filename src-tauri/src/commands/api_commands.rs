@@ -1,76 +1,95 @@
+use std::sync::Arc;
+
 use tauri::AppHandle;
 use tauri::State;
 
 use crate::api::group::GroupInstancePermissionInfo;
 use crate::api::group::UserGroup;
+use crate::api::instance::Instance;
+use crate::definitions::InstanceOptions;
+use crate::definitions::WorldAvailability;
 use crate::definitions::WorldDetails;
 use crate::definitions::WorldDisplayData;
-use crate::services::api_service::InstanceInfo;
-use crate::services::FolderManager;
+use crate::services::api_service::{InstanceInfo, InstanceInvites, InviteResult};
+use crate::services::outbox::OutboxOperation;
+use crate::services::thumbnail_cache::ThumbnailCacheStats;
+use crate::services::world_cache::WorldCacheStats;
+use crate::services::{FetchedWorld, FolderManager, OutboxService};
 use crate::ApiService;
-use crate::AUTHENTICATOR;
-use crate::INITSTATE;
-use crate::WORLDS;
+use crate::AppState;
 
 #[tauri::command]
 #[specta::specta]
-pub async fn try_login() -> Result<(), String> {
+pub async fn try_login(state: State<'_, Arc<AppState>>) -> Result<(), String> {
     log::info!("Trying to login...");
-    ApiService::login_with_token(AUTHENTICATOR.get(), INITSTATE.get())
+    ApiService::login_with_token(&state.authenticator, &state.init_state, &state.encryption)
         .await
         .map_err(|e| e.to_string())
         .map(|_| {
             log::info!("Login successful");
         })
-        .or_else(|e| {
+        .map_err(|e| {
             log::error!("Login failed: {}", e);
-            Err(format!("Login failed: {}", e))
+            format!("Login failed: {}", e)
         })
 }
 
 #[tauri::command]
 #[specta::specta]
-pub async fn login_with_credentials(username: String, password: String) -> Result<(), String> {
-    ApiService::login_with_credentials(username, password, AUTHENTICATOR.get())
+pub async fn login_with_credentials(
+    username: String,
+    password: String,
+    state: State<'_, Arc<AppState>>,
+) -> Result<(), String> {
+    ApiService::login_with_credentials(username, password, &state.authenticator, &state.encryption)
         .await
         .map_err(|e| e.to_string())
 }
 
 #[tauri::command]
 #[specta::specta]
-pub async fn login_with_2fa(code: String, two_factor_type: String) -> Result<(), String> {
+pub async fn login_with_2fa(
+    code: String,
+    two_factor_type: String,
+    state: State<'_, Arc<AppState>>,
+) -> Result<(), String> {
     if two_factor_type == "emailOtp" {
-        ApiService::login_with_email_2fa(code, AUTHENTICATOR.get())
-            .await
-            .map_err(|e| e.to_string())?;
+        ApiService::login_with_email_2fa(
+            code,
+            &state.authenticator,
+            &state.init_state,
+            &state.encryption,
+        )
+        .await
+        .map_err(|e| e.to_string())?;
     } else {
-        ApiService::login_with_2fa(code, AUTHENTICATOR.get())
+        ApiService::login_with_2fa(code, &state.authenticator, &state.encryption)
             .await
             .map_err(|e| e.to_string())?;
     }
     // call login_with_token to set user id information
-    ApiService::login_with_token(AUTHENTICATOR.get(), INITSTATE.get())
+    ApiService::login_with_token(&state.authenticator, &state.init_state, &state.encryption)
         .await
         .map_err(|e| e.to_string())
 }
 
 #[tauri::command]
 #[specta::specta]
-pub async fn logout() -> Result<(), String> {
-    ApiService::logout(AUTHENTICATOR.get())
+pub async fn logout(state: State<'_, Arc<AppState>>) -> Result<(), String> {
+    ApiService::logout(&state.authenticator, &state.init_state, &state.encryption)
         .await
         .map_err(|e| e.to_string())
 }
 
 #[tauri::command]
 #[specta::specta]
-pub async fn get_favorite_worlds() -> Result<(), String> {
-    let cookie_store = AUTHENTICATOR.get().read().await.get_cookies();
+pub async fn get_favorite_worlds(state: State<'_, Arc<AppState>>) -> Result<(), String> {
+    let context = state.api_context().await;
 
-    let user_id = INITSTATE.get().read().await.user_id.clone();
+    let user_id = state.user_id().await;
 
-    let worlds = match ApiService::get_favorite_worlds(cookie_store, user_id).await {
-        Ok(worlds) => worlds,
+    let (worlds, unavailable) = match ApiService::get_favorite_worlds(&context, user_id).await {
+        Ok(favorites) => favorites,
         Err(e) => {
             log::info!("Failed to fetch favorite worlds: {}", e);
             return Err(format!("Failed to fetch favorite worlds: {}", e));
@@ -81,36 +100,79 @@ pub async fn get_favorite_worlds() -> Result<(), String> {
 
     // Reverse the order to preserve the original date added order
     let worlds = worlds.into_iter().rev().collect::<Vec<_>>();
+    let available = worlds.iter().map(|w| w.world_id.clone()).collect();
 
-    match FolderManager::add_worlds(WORLDS.get(), worlds) {
-        Ok(_) => Ok(()),
-        Err(e) => {
-            log::info!("Failed to add worlds to folder: {}", e);
-            Err(format!("Failed to add worlds to folder: {}", e))
-        }
+    state.thumbnail_cache.prefetch(&worlds, state.http_client());
+    if let Err(e) = FolderManager::add_worlds(&state.worlds, worlds, &*state.storage) {
+        log::info!("Failed to add worlds to folder: {}", e);
+        return Err(format!("Failed to add worlds to folder: {}", e));
     }
+
+    // Favorites that are no longer public are only marked if they are already in the library
+    FolderManager::set_world_availability(
+        available,
+        WorldAvailability::Available,
+        &state.worlds,
+        &*state.storage,
+    )
+    .and_then(|_| {
+        FolderManager::set_world_availability(
+            unavailable,
+            WorldAvailability::Private,
+            &state.worlds,
+            &*state.storage,
+        )
+    })
+    .map_err(|e| {
+        log::info!("Failed to update world availability: {}", e);
+        format!("Failed to update world availability: {}", e)
+    })
 }
 
+/// Get the details of a world, saving it to the library unless `dont_save_to_local` is set
+///
+/// While VRChat can not be reached, the details are read from the library or the world cache,
+/// and the world is queued to be refreshed once the connection is back.
 #[tauri::command]
 #[specta::specta]
 pub async fn get_world(
     world_id: String,
     dont_save_to_local: Option<bool>,
+    app_handle: AppHandle,
+    state: State<'_, Arc<AppState>>,
 ) -> Result<WorldDetails, String> {
-    let cookie_store = AUTHENTICATOR.get().read().await.get_cookies();
-    let world_copy = WORLDS.get().read().unwrap().clone();
+    let context = state.api_context().await;
+    let world_copy = state.worlds.read().unwrap().clone();
 
-    let user_id = INITSTATE.get().read().await.user_id.clone();
+    let user_id = state.user_id().await;
 
     let world = match ApiService::get_world_by_id(
         world_id.clone(),
-        cookie_store,
+        &context,
         world_copy,
         user_id,
+        &state.world_cache,
+        OutboxService::is_offline(&state),
     )
     .await
     {
-        Ok(world) => world,
+        Ok(FetchedWorld::Current(world)) => world,
+        Ok(FetchedWorld::Offline(world)) => {
+            let in_library =
+                FolderManager::get_world_details(world.world_id.clone(), &state.worlds);
+            if in_library.is_ok() && dont_save_to_local != Some(true) {
+                if let Err(e) = OutboxService::enqueue(
+                    &state,
+                    &app_handle,
+                    OutboxOperation::RefreshWorld {
+                        world_id: world.world_id.clone(),
+                    },
+                ) {
+                    log::error!("Failed to queue world refresh: {}", e);
+                }
+            }
+            return Ok(in_library.unwrap_or_else(|_| world.to_world_details()));
+        }
         Err(e) => {
             log::info!("Failed to fetch world: {}", e);
             return Err(format!("Failed to fetch world: {}", e));
@@ -122,7 +184,7 @@ pub async fn get_world(
         // If the flag is set to true, skip saving the world to local storage.
         if dont_save {
             log::info!("Not saving world to local storage");
-            return match FolderManager::get_world_details(world.world_id.clone(), WORLDS.get()) {
+            return match FolderManager::get_world_details(world.world_id.clone(), &state.worlds) {
                 Ok(details) => Ok(details),
                 Err(e) => {
                     log::info!("Failed to read world from folder: {}", e);
@@ -131,8 +193,11 @@ pub async fn get_world(
             };
         }
     }
-    match FolderManager::add_worlds(WORLDS.get(), vec![world.clone()]) {
-        Ok(_) => FolderManager::get_world_details(world.world_id, WORLDS.get()).map_err(|e| {
+    state
+        .thumbnail_cache
+        .prefetch(std::slice::from_ref(&world), state.http_client());
+    match FolderManager::add_worlds(&state.worlds, vec![world.clone()], &*state.storage) {
+        Ok(_) => FolderManager::get_world_details(world.world_id, &state.worlds).map_err(|e| {
             log::info!("Failed to read world after save: {}", e);
             format!("Failed to read world after save: {}", e)
         }),
@@ -145,15 +210,26 @@ pub async fn get_world(
 
 #[tauri::command]
 #[specta::specta]
-pub async fn check_world_info(world_id: String) -> Result<WorldDetails, String> {
-    let cookie_store = AUTHENTICATOR.get().read().await.get_cookies();
-    let world_copy = WORLDS.get().read().unwrap().clone();
+pub async fn check_world_info(
+    world_id: String,
+    state: State<'_, Arc<AppState>>,
+) -> Result<WorldDetails, String> {
+    let context = state.api_context().await;
+    let world_copy = state.worlds.read().unwrap().clone();
 
-    let user_id = INITSTATE.get().read().await.user_id.clone();
+    let user_id = state.user_id().await;
 
-    let world = match ApiService::get_world_by_id(world_id, cookie_store, world_copy, user_id).await
+    let world = match ApiService::get_world_by_id(
+        world_id,
+        &context,
+        world_copy,
+        user_id,
+        &state.world_cache,
+        OutboxService::is_offline(&state),
+    )
+    .await
     {
-        Ok(world) => world,
+        Ok(world) => world.into_inner(),
         Err(e) => {
             log::info!("Failed to fetch world: {}", e);
             return Err(format!("Failed to fetch world: {}", e));
@@ -163,7 +239,7 @@ pub async fn check_world_info(world_id: String) -> Result<WorldDetails, String>
     log::info!("Received world: {:#?}", world); // Debug print the world
     let mut details = world.to_world_details();
 
-    if let Ok(custom_tags) = FolderManager::get_custom_tags(details.world_id.clone(), WORLDS.get())
+    if let Ok(custom_tags) = FolderManager::get_custom_tags(details.world_id.clone(), &state.worlds)
     {
         for tag in custom_tags {
             if !details.tags.contains(&tag) {
@@ -175,12 +251,83 @@ pub async fn check_world_info(world_id: String) -> Result<WorldDetails, String>
     Ok(details)
 }
 
+/// Get how many worlds the world cache holds, and how it served them
 #[tauri::command]
 #[specta::specta]
-pub async fn get_recently_visited_worlds() -> Result<Vec<WorldDisplayData>, String> {
-    let cookie_store = AUTHENTICATOR.get().read().await.get_cookies();
+pub fn get_world_cache_stats(state: State<'_, Arc<AppState>>) -> Result<WorldCacheStats, String> {
+    let cache = state.world_cache.read().map_err(|_| {
+        log::error!("Failed to acquire read lock for world cache");
+        "Failed to acquire read lock for world cache".to_string()
+    })?;
+    Ok(cache.stats())
+}
 
-    let worlds = match ApiService::get_recently_visited_worlds(cookie_store).await {
+/// Drops every cached world, so the next details opened are fetched in full
+#[tauri::command]
+#[specta::specta]
+pub fn clear_world_cache(state: State<'_, Arc<AppState>>) -> Result<(), String> {
+    let mut cache = state.world_cache.write().map_err(|_| {
+        log::error!("Failed to acquire write lock for world cache");
+        "Failed to acquire write lock for world cache".to_string()
+    })?;
+    cache.clear();
+    Ok(())
+}
+
+/// Get how many thumbnails the thumbnail cache holds, and how much space they take
+#[tauri::command]
+#[specta::specta]
+pub fn get_thumbnail_cache_stats(
+    state: State<'_, Arc<AppState>>,
+) -> Result<ThumbnailCacheStats, String> {
+    Ok(state.thumbnail_cache.stats())
+}
+
+/// Drops every thumbnail, so they are downloaded again when the worlds are shown
+#[tauri::command]
+#[specta::specta]
+pub fn clear_thumbnail_cache(state: State<'_, Arc<AppState>>) -> Result<(), String> {
+    state.thumbnail_cache.clear().map_err(|e| {
+        log::error!("Failed to clear thumbnail cache: {}", e);
+        e.to_string()
+    })
+}
+
+/// Favorites a world in one of the user's VRChat favorite groups
+///
+/// # Returns
+/// True if the world was favorited, false if it was queued in the outbox while offline
+#[tauri::command]
+#[specta::specta]
+pub async fn add_world_to_favorites(
+    world_id: String,
+    group: String,
+    app_handle: AppHandle,
+    state: State<'_, Arc<AppState>>,
+) -> Result<bool, String> {
+    let operation = OutboxOperation::AddFavorite {
+        world_id: world_id.clone(),
+        group: group.clone(),
+    };
+    OutboxService::perform(&state, &app_handle, operation, || {
+        OutboxService::add_favorite(&state, &world_id, &group)
+    })
+    .await
+    .map(|done| done.is_some())
+    .map_err(|e| {
+        log::error!("Failed to add world to favorites: {}", e);
+        e.to_string()
+    })
+}
+
+#[tauri::command]
+#[specta::specta]
+pub async fn get_recently_visited_worlds(
+    state: State<'_, Arc<AppState>>,
+) -> Result<Vec<WorldDisplayData>, String> {
+    let context = state.api_context().await;
+
+    let worlds = match ApiService::get_recently_visited_worlds(&context).await {
         Ok(worlds) => worlds,
         Err(e) => {
             log::info!("Failed to fetch recently visited worlds: {}", e);
@@ -199,8 +346,9 @@ pub async fn search_worlds(
     exclude_tags: Vec<String>,
     search: String,
     page: usize,
+    state: State<'_, Arc<AppState>>,
 ) -> Result<Vec<WorldDisplayData>, String> {
-    let cookie_store = AUTHENTICATOR.get().read().await.get_cookies();
+    let context = state.api_context().await;
 
     let sort = if sort.is_empty() { None } else { Some(sort) };
 
@@ -217,8 +365,7 @@ pub async fn search_worlds(
     };
 
     let worlds =
-        match ApiService::search_worlds(cookie_store, sort, tags, exclude_tags, search, page).await
-        {
+        match ApiService::search_worlds(&context, sort, tags, exclude_tags, search, page).await {
             Ok(worlds) => worlds,
             Err(e) => {
                 log::info!("Failed to fetch worlds: {}", e);
@@ -236,15 +383,16 @@ pub async fn create_world_instance(
     instance_type_str: String,
     region_str: String,
     handle: State<'_, AppHandle>,
+    state: State<'_, Arc<AppState>>,
 ) -> Result<InstanceInfo, String> {
-    let cookie_store = AUTHENTICATOR.get().read().await.get_cookies();
-    let user_id = INITSTATE.get().read().await.user_id.clone();
+    let context = state.api_context().await;
+    let user_id = state.user_id().await;
 
     let result = ApiService::create_world_instance(
         world_id,
         instance_type_str,
         region_str,
-        cookie_store,
+        &context,
         user_id,
         (*handle).clone(),
     )
@@ -261,11 +409,146 @@ pub async fn create_world_instance(
 
 #[tauri::command]
 #[specta::specta]
-pub async fn get_user_groups() -> Result<Vec<UserGroup>, String> {
-    let cookie_store = AUTHENTICATOR.get().read().await.get_cookies();
-    let user_id = INITSTATE.get().read().await.user_id.clone();
+pub async fn create_world_instance_with_options(
+    world_id: String,
+    options: InstanceOptions,
+    state: State<'_, Arc<AppState>>,
+) -> Result<InstanceInfo, String> {
+    let context = state.api_context().await;
+    let user_id = state.user_id().await;
+
+    ApiService::create_instance_with_options(world_id, options, &context, user_id, true)
+        .await
+        .map_err(|e| {
+            log::info!("Failed to create world instance: {}", e);
+            e
+        })
+}
+
+/// Creates an instance of a world with the options of a saved preset
+#[tauri::command]
+#[specta::specta]
+pub async fn create_world_instance_from_preset(
+    world_id: String,
+    preset_name: String,
+    state: State<'_, Arc<AppState>>,
+) -> Result<InstanceInfo, String> {
+    let options = {
+        let preferences = state.preferences.read().map_err(|e| {
+            log::error!("Failed to acquire read lock for preferences: {}", e);
+            "Failed to acquire read lock for preferences".to_string()
+        })?;
+        preferences
+            .instance_presets
+            .iter()
+            .find(|p| p.name == preset_name)
+            .map(|p| p.options.clone())
+            .ok_or_else(|| format!("Instance preset not found: {}", preset_name))?
+    };
+
+    create_world_instance_with_options(world_id, options, state).await
+}
+
+/// Creates an instance and invites every listed user to it, reporting each invite
+#[tauri::command]
+#[specta::specta]
+pub async fn create_world_instance_and_invite(
+    world_id: String,
+    instance_type_str: String,
+    region_str: String,
+    user_ids: Vec<String>,
+    handle: State<'_, AppHandle>,
+    state: State<'_, Arc<AppState>>,
+) -> Result<InstanceInvites, String> {
+    let context = state.api_context().await;
+    let user_id = state.user_id().await;
+
+    ApiService::create_world_instance_and_invite(
+        world_id,
+        instance_type_str,
+        region_str,
+        user_ids,
+        &context,
+        user_id,
+        (*handle).clone(),
+    )
+    .await
+    .map_err(|e| {
+        log::info!("Failed to create world instance: {}", e);
+        format!("Failed to create world instance: {}", e)
+    })
+}
+
+#[tauri::command]
+#[specta::specta]
+pub async fn invite_users_to_instance(
+    world_id: String,
+    instance_id: String,
+    user_ids: Vec<String>,
+    state: State<'_, Arc<AppState>>,
+) -> Result<Vec<InviteResult>, String> {
+    let context = state.api_context().await;
 
-    let groups = match ApiService::get_user_groups(cookie_store, user_id).await {
+    Ok(ApiService::invite_users_to_instance(&context, &world_id, &instance_id, user_ids).await)
+}
+
+#[tauri::command]
+#[specta::specta]
+pub async fn request_invite(
+    user_id: String,
+    request_slot: Option<u32>,
+    state: State<'_, Arc<AppState>>,
+) -> Result<(), String> {
+    let context = state.api_context().await;
+
+    ApiService::request_invite(&context, user_id, request_slot)
+        .await
+        .map_err(|e| {
+            log::info!("{}", e);
+            e
+        })
+}
+
+#[tauri::command]
+#[specta::specta]
+pub async fn respond_to_invite(
+    notification_id: String,
+    response_slot: u32,
+    state: State<'_, Arc<AppState>>,
+) -> Result<(), String> {
+    let context = state.api_context().await;
+
+    ApiService::respond_to_invite(&context, notification_id, response_slot)
+        .await
+        .map_err(|e| {
+            log::info!("{}", e);
+            e
+        })
+}
+
+#[tauri::command]
+#[specta::specta]
+pub async fn get_world_instances(
+    world_id: String,
+    state: State<'_, Arc<AppState>>,
+) -> Result<Vec<Instance>, String> {
+    let context = state.api_context().await;
+
+    ApiService::get_world_instances(world_id, &context)
+        .await
+        .map_err(|e| {
+            log::info!("Failed to fetch world instances: {}", e);
+            e
+        })
+}
+
+#[tauri::command]
+#[specta::specta]
+pub async fn get_user_groups(state: State<'_, Arc<AppState>>) -> Result<Vec<UserGroup>, String> {
+    let context = state.api_context().await;
+    let user_id = state.user_id().await;
+
+    let groups = match ApiService::get_user_groups(&context, user_id).await {
         Ok(groups) => groups,
         Err(e) => {
             log::info!("Failed to fetch user groups: {}", e);
@@ -280,10 +563,11 @@ pub async fn get_user_groups() -> Result<Vec<UserGroup>, String> {
 #[specta::specta]
 pub async fn get_permission_for_create_group_instance(
     group_id: String,
+    state: State<'_, Arc<AppState>>,
 ) -> Result<GroupInstancePermissionInfo, String> {
-    let cookie_store = AUTHENTICATOR.get().read().await.get_cookies();
+    let context = state.api_context().await;
     let permission =
-        match ApiService::get_permission_for_create_group_instance(cookie_store, group_id).await {
+        match ApiService::get_permission_for_create_group_instance(&context, group_id).await {
             Ok(permission) => permission,
             Err(e) => {
                 log::info!("Failed to fetch group instance create permission: {}", e);
@@ -307,8 +591,9 @@ pub async fn create_group_instance(
     region_str: String,
     queue_enabled: bool,
     handle: State<'_, AppHandle>,
+    state: State<'_, Arc<AppState>>,
 ) -> Result<InstanceInfo, String> {
-    let cookie_store = AUTHENTICATOR.get().read().await.get_cookies();
+    let context = state.api_context().await;
 
     let result = ApiService::create_group_instance(
         world_id,
@@ -317,7 +602,7 @@ pub async fn create_group_instance(
         allowed_roles,
         region_str,
         queue_enabled,
-        cookie_store,
+        &context,
         (*handle).clone(),
     )
     .await;
@@ -337,9 +622,9 @@ pub async fn open_instance_in_client(
     world_id: String,
     instance_id: String,
     handle: State<'_, AppHandle>,
+    state: State<'_, Arc<AppState>>,
 ) -> Result<String, String> {
-    let cookie_store = AUTHENTICATOR.get().read().await.get_cookies();
+    let context = state.api_context().await;
 
-    ApiService::open_instance_in_client(cookie_store, &world_id, &instance_id, (*handle).clone())
-        .await
+    ApiService::open_instance_in_client(&context, &world_id, &instance_id, (*handle).clone()).await
 }
@@ -4,15 +4,17 @@ use tauri::async_runtime::Mutex;
 use tauri::State;
 
 use crate::{
+    api::HttpClientFactory,
     changelog::{fetch_and_parse_changelog, pick_changes_in_preferred_lang, LocalizedChanges},
     updater::update_handler::{UpdateChannel, UpdateHandler},
-    PREFERENCES,
+    AppState,
 };
 
 #[tauri::command]
 #[specta::specta]
 pub async fn get_changelog(
     update_handler: State<'_, Arc<Mutex<UpdateHandler>>>,
+    state: State<'_, Arc<AppState>>,
 ) -> Result<Vec<LocalizedChanges>, String> {
     let mut handler = update_handler.lock().await;
     if !handler.is_initialized() {
@@ -24,11 +26,13 @@ pub async fn get_changelog(
     let raw_changelog = if let Some(changelog) = handler.get_changelog() {
         changelog.clone()
     } else {
-        let changelog = fetch_and_parse_changelog().await.map_err(|e| {
-            let err = format!("Failed to fetch and parse changelog: {}", e);
-            log::error!("{}", err);
-            err
-        })?;
+        let changelog = fetch_and_parse_changelog(&HttpClientFactory::current(&state.http))
+            .await
+            .map_err(|e| {
+                let err = format!("Failed to fetch and parse changelog: {}", e);
+                log::error!("{}", err);
+                err
+            })?;
 
         handler.set_changelog(changelog.clone());
 
@@ -45,7 +49,7 @@ pub async fn get_changelog(
     };
 
     let (preferred_language, skip_pre_releases) = {
-        let preferences_lock = PREFERENCES.get().read().map_err(|e| {
+        let preferences_lock = state.preferences.read().map_err(|e| {
             let err = format!("Failed to read preferences: {}", e);
             log::error!("{}", err);
             err
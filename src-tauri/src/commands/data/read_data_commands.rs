@@ -1,16 +1,18 @@
 use crate::backup;
 use crate::migration;
 use crate::services;
-use crate::{FOLDERS, PREFERENCES, WORLDS};
+use crate::AppState;
 use directories::BaseDirs;
+use std::sync::Arc;
+use tauri::State;
 
 /// Checks if the app is being run for the first time
 /// As this is called every time / is loaded from the frontend, cache result in the state
 #[tauri::command]
 #[specta::specta]
-pub async fn require_initial_setup() -> bool {
+pub fn require_initial_setup(state: State<'_, Arc<AppState>>) -> bool {
     //check if the result is already cached
-    let mut preferences_lock = PREFERENCES.get().write();
+    let mut preferences_lock = state.preferences.write();
     let preference = preferences_lock.as_mut().unwrap();
     if !preference.first_time {
         log::info!("Not first time, cached result");
@@ -38,8 +40,8 @@ pub async fn require_initial_setup() -> bool {
 /// Returns a tuple containing a boolean indicating if the files have been loaded, and an error message
 #[tauri::command]
 #[specta::specta]
-pub async fn check_files_loaded() -> Result<bool, String> {
-    let init_state_lock = crate::INITSTATE.get().read();
+pub async fn check_files_loaded(state: State<'_, Arc<AppState>>) -> Result<bool, String> {
+    let init_state_lock = state.init_state.read();
     let init_state = init_state_lock.await;
     match init_state.success {
         true => Ok(true),
@@ -55,8 +57,8 @@ pub async fn detect_old_installation() -> Result<(String, String), String> {
 
 #[tauri::command]
 #[specta::specta]
-pub async fn check_existing_data() -> Result<(bool, bool), String> {
-    migration::MigrationService::check_existing_data().map_err(|e| e.to_string())
+pub async fn check_existing_data(state: State<'_, Arc<AppState>>) -> Result<(bool, bool), String> {
+    migration::MigrationService::check_existing_data(&*state.storage).map_err(|e| e.to_string())
 }
 
 /// Passes the paths to the frontend
@@ -1,8 +1,9 @@
 use crate::backup;
-use crate::definitions::CardSize;
 use crate::migration::MigrationService;
 use crate::services::{self, ExportService};
-use crate::{FOLDERS, WORLDS};
+use crate::AppState;
+use std::sync::Arc;
+use tauri::State;
 
 #[tauri::command]
 #[specta::specta]
@@ -12,22 +13,27 @@ pub async fn create_empty_auth() -> Result<(), String> {
 
 #[tauri::command]
 #[specta::specta]
-pub async fn create_empty_files() -> Result<(), String> {
-    services::FileService::create_empty_folders_file()
-        .and_then(|_| services::FileService::create_empty_worlds_file())
-        .map_err(|e| e.to_string())
+pub async fn create_empty_files(state: State<'_, Arc<AppState>>) -> Result<(), String> {
+    state.storage.create_empty().map_err(|e| e.to_string())
 }
 
 #[tauri::command]
 #[specta::specta]
-pub async fn create_backup(backup_path: String) -> Result<(), String> {
-    backup::create_backup(backup_path, WORLDS.get(), FOLDERS.get()).map_err(|e| e.to_string())
+pub async fn create_backup(
+    backup_path: String,
+    state: State<'_, Arc<AppState>>,
+) -> Result<(), String> {
+    backup::create_backup(backup_path, &state.worlds, &state.folders).map_err(|e| e.to_string())
 }
 
 #[tauri::command]
 #[specta::specta]
-pub async fn restore_from_backup(backup_path: String) -> Result<(), String> {
-    backup::restore_from_backup(backup_path, WORLDS.get(), FOLDERS.get()).map_err(|e| e.to_string())
+pub async fn restore_from_backup(
+    backup_path: String,
+    state: State<'_, Arc<AppState>>,
+) -> Result<(), String> {
+    backup::restore_from_backup(backup_path, &state.worlds, &state.folders, &*state.storage)
+        .map_err(|e| e.to_string())
 }
 
 #[tauri::command]
@@ -36,11 +42,12 @@ pub fn export_to_portal_library_system(
     folders: Vec<String>,
     sort_field: String,
     sort_direction: String,
+    state: State<'_, Arc<AppState>>,
 ) -> Result<(), String> {
     ExportService::export_to_portal_library_system(
         folders,
-        FOLDERS.get(),
-        WORLDS.get(),
+        &state.folders,
+        &state.worlds,
         sort_field,
         sort_direction,
     )
@@ -49,16 +56,26 @@ pub fn export_to_portal_library_system(
 
 #[tauri::command]
 #[specta::specta]
-pub async fn migrate_old_data(worlds_path: String, folders_path: String) -> Result<(), String> {
-    MigrationService::migrate_old_data(worlds_path, folders_path, WORLDS.get(), FOLDERS.get())
-        .await
-        .map_err(|e| e.to_string())
+pub async fn migrate_old_data(
+    worlds_path: String,
+    folders_path: String,
+    state: State<'_, Arc<AppState>>,
+) -> Result<(), String> {
+    MigrationService::migrate_old_data(
+        worlds_path,
+        folders_path,
+        &state.worlds,
+        &state.folders,
+        &*state.storage,
+    )
+    .await
+    .map_err(|e| e.to_string())
 }
 
 #[tauri::command]
 #[specta::specta]
-pub async fn delete_data() -> Result<(), String> {
-    services::delete_data(WORLDS.get(), FOLDERS.get())
+pub async fn delete_data(state: State<'_, Arc<AppState>>) -> Result<(), String> {
+    services::delete_data(&state.worlds, &state.folders, &*state.storage)
         .await
         .map_err(|e| e.to_string())
 }
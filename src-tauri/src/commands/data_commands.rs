@@ -1,11 +1,12 @@
-use reqwest::Client;
-
 use crate::definitions::{PatreonData, WorldBlacklist};
+use crate::AppState;
+use std::sync::Arc;
+use tauri::State;
 
 #[tauri::command]
 #[specta::specta]
-pub async fn fetch_patreon_data() -> Result<PatreonData, String> {
-    let client = Client::new();
+pub async fn fetch_patreon_data(state: State<'_, Arc<AppState>>) -> Result<PatreonData, String> {
+    let client = state.http_client();
     let response = client
         .get("https://data.raifaworks.com/data/patreons.json")
         .send()
@@ -22,8 +23,8 @@ pub async fn fetch_patreon_data() -> Result<PatreonData, String> {
 
 #[tauri::command]
 #[specta::specta]
-pub async fn fetch_blacklist() -> Result<WorldBlacklist, String> {
-    let client = Client::new();
+pub async fn fetch_blacklist(state: State<'_, Arc<AppState>>) -> Result<WorldBlacklist, String> {
+    let client = state.http_client();
     let response = client
         .get("https://data.raifaworks.com/data/blacklist.json")
         .send()
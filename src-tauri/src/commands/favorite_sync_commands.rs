@@ -0,0 +1,70 @@
+use crate::services::favorite_sync_service::{FavoriteGroupImport, FavoriteSyncPlan};
+use crate::services::{FavoriteSyncService, FolderManager};
+use crate::AppState;
+use std::sync::Arc;
+use tauri::State;
+
+#[tauri::command]
+#[specta::specta]
+pub fn set_folder_favorite_group(
+    folder_name: String,
+    group: Option<String>,
+    state: State<'_, Arc<AppState>>,
+) -> Result<(), String> {
+    FolderManager::set_folder_favorite_group(folder_name, group, &state.folders, &*state.storage)
+        .map_err(|e| {
+            log::error!("Error linking folder to favorite group: {}", e);
+            e.to_string()
+        })
+}
+
+/// Lists the changes a sync would make, and the ones that conflict, without applying them
+#[tauri::command]
+#[specta::specta]
+pub async fn preview_favorite_sync(
+    state: State<'_, Arc<AppState>>,
+) -> Result<Vec<FavoriteSyncPlan>, String> {
+    let context = state.api_context().await;
+    let remote = FavoriteSyncService::fetch_favorites(&context)
+        .await
+        .map_err(|e| e.to_string())?;
+
+    let folders = state.folders.read().map_err(|e| {
+        log::error!("Failed to acquire read lock for folders: {}", e);
+        "Failed to acquire read lock for folders".to_string()
+    })?;
+    let worlds = state.worlds.read().map_err(|e| {
+        log::error!("Failed to acquire read lock for worlds: {}", e);
+        "Failed to acquire read lock for worlds".to_string()
+    })?;
+    Ok(FavoriteSyncService::plan(&folders, &worlds, &remote))
+}
+
+#[tauri::command]
+#[specta::specta]
+pub async fn apply_favorite_sync(
+    state: State<'_, Arc<AppState>>,
+) -> Result<Vec<FavoriteSyncPlan>, String> {
+    let context = state.api_context().await.bulk();
+    FavoriteSyncService::apply(&context, &state.folders, &state.worlds, &*state.storage)
+        .await
+        .map_err(|e| {
+            log::error!("Error syncing favorite groups: {}", e);
+            e.to_string()
+        })
+}
+
+/// Files every favorite into a folder named after its favorite group
+#[tauri::command]
+#[specta::specta]
+pub async fn import_favorite_groups(
+    state: State<'_, Arc<AppState>>,
+) -> Result<Vec<FavoriteGroupImport>, String> {
+    let context = state.api_context().await.bulk();
+    FavoriteSyncService::import_groups(&context, &state.folders, &state.worlds, &*state.storage)
+        .await
+        .map_err(|e| {
+            log::error!("Error importing favorite groups: {}", e);
+            e.to_string()
+        })
+}
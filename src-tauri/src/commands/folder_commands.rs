@@ -1,13 +1,26 @@
-use crate::definitions::{WorldApiData, WorldDisplayData, WorldModel};
+use crate::definitions::{WorldApiData, WorldChange, WorldDisplayData, WorldModel};
 use crate::services::folder_manager::{FolderData, FolderManager};
-use crate::services::share_service;
-use crate::{FOLDERS, PREFERENCES, WORLDS};
+use crate::services::outbox::OutboxOperation;
+use crate::services::{share_service, OutboxService};
+use crate::AppState;
 use std::collections::HashSet;
+use std::sync::Arc;
+use tauri::{AppHandle, State};
 
 #[tauri::command]
 #[specta::specta]
-pub async fn add_world_to_folder(folder_name: String, world_id: String) -> Result<(), String> {
-    match FolderManager::add_world_to_folder(folder_name, world_id, FOLDERS.get(), WORLDS.get()) {
+pub async fn add_world_to_folder(
+    folder_name: String,
+    world_id: String,
+    state: State<'_, Arc<AppState>>,
+) -> Result<(), String> {
+    match FolderManager::add_world_to_folder(
+        folder_name,
+        world_id,
+        &state.folders,
+        &state.worlds,
+        &*state.storage,
+    ) {
         Ok(_) => Ok(()),
         Err(e) => {
             log::error!("Error adding world to folder: {}", e);
@@ -18,12 +31,17 @@ pub async fn add_world_to_folder(folder_name: String, world_id: String) -> Resul
 
 #[tauri::command]
 #[specta::specta]
-pub async fn remove_world_from_folder(folder_name: String, world_id: String) -> Result<(), String> {
+pub async fn remove_world_from_folder(
+    folder_name: String,
+    world_id: String,
+    state: State<'_, Arc<AppState>>,
+) -> Result<(), String> {
     match FolderManager::remove_world_from_folder(
         folder_name,
         world_id,
-        FOLDERS.get(),
-        WORLDS.get(),
+        &state.folders,
+        &state.worlds,
+        &*state.storage,
     ) {
         Ok(_) => Ok(()),
         Err(e) => {
@@ -35,8 +53,8 @@ pub async fn remove_world_from_folder(folder_name: String, world_id: String) ->
 
 #[tauri::command]
 #[specta::specta]
-pub async fn hide_world(world_id: String) -> Result<(), String> {
-    match FolderManager::hide_world(world_id, FOLDERS.get(), WORLDS.get()) {
+pub async fn hide_world(world_id: String, state: State<'_, Arc<AppState>>) -> Result<(), String> {
+    match FolderManager::hide_world(world_id, &state.folders, &state.worlds, &*state.storage) {
         Ok(_) => Ok(()),
         Err(e) => {
             log::error!("Error hiding world: {}", e);
@@ -47,8 +65,8 @@ pub async fn hide_world(world_id: String) -> Result<(), String> {
 
 #[tauri::command]
 #[specta::specta]
-pub async fn unhide_world(world_id: String) -> Result<(), String> {
-    match FolderManager::unhide_world(world_id, FOLDERS.get(), WORLDS.get()) {
+pub async fn unhide_world(world_id: String, state: State<'_, Arc<AppState>>) -> Result<(), String> {
+    match FolderManager::unhide_world(world_id, &state.folders, &state.worlds, &*state.storage) {
         Ok(_) => Ok(()),
         Err(e) => {
             log::error!("Error unhiding world: {}", e);
@@ -59,8 +77,8 @@ pub async fn unhide_world(world_id: String) -> Result<(), String> {
 
 #[tauri::command]
 #[specta::specta]
-pub async fn get_folders() -> Result<Vec<FolderData>, String> {
-    FolderManager::get_folders(FOLDERS.get()).map_err(|e| {
+pub async fn get_folders(state: State<'_, Arc<AppState>>) -> Result<Vec<FolderData>, String> {
+    FolderManager::get_folders(&state.folders, &state.worlds).map_err(|e| {
         log::error!("Error getting folders: {}", e);
         e.to_string()
     })
@@ -68,17 +86,56 @@ pub async fn get_folders() -> Result<Vec<FolderData>, String> {
 
 #[tauri::command]
 #[specta::specta]
-pub async fn create_folder(name: String) -> Result<String, String> {
+pub async fn create_folder(
+    name: String,
+    state: State<'_, Arc<AppState>>,
+) -> Result<String, String> {
     log::info!("Creating folder: {}", name);
-    FolderManager::create_folder(name, FOLDERS.get()).map_err(|e| {
+    FolderManager::create_folder(name, &state.folders, &*state.storage).map_err(|e| {
         log::error!("Error creating folder: {}", e);
         e.to_string()
     })
 }
 #[tauri::command]
 #[specta::specta]
-pub async fn delete_folder(name: String) -> Result<(), String> {
-    FolderManager::delete_folder(name, FOLDERS.get(), WORLDS.get()).map_err(|e| {
+pub async fn create_smart_folder(
+    name: String,
+    query: String,
+    state: State<'_, Arc<AppState>>,
+) -> Result<String, String> {
+    log::info!("Creating smart folder: {}", name);
+    FolderManager::create_smart_folder(name, query, &state.folders, &*state.storage).map_err(|e| {
+        log::error!("Error creating smart folder: {}", e);
+        e.to_string()
+    })
+}
+
+#[tauri::command]
+#[specta::specta]
+pub async fn update_smart_folder(
+    folder_name: String,
+    query: String,
+    state: State<'_, Arc<AppState>>,
+) -> Result<(), String> {
+    FolderManager::update_smart_folder(folder_name, query, &state.folders, &*state.storage).map_err(
+        |e| {
+            log::error!("Error updating smart folder: {}", e);
+            e.to_string()
+        },
+    )
+}
+
+#[tauri::command]
+#[specta::specta]
+pub async fn delete_folder(name: String, state: State<'_, Arc<AppState>>) -> Result<(), String> {
+    FolderManager::delete_folder(
+        name,
+        &state.folders,
+        &state.worlds,
+        &state.preferences,
+        &*state.storage,
+    )
+    .map_err(|e| {
         log::error!("Error deleting folder: {}", e);
         e.to_string()
     })
@@ -86,8 +143,20 @@ pub async fn delete_folder(name: String) -> Result<(), String> {
 
 #[tauri::command]
 #[specta::specta]
-pub async fn move_folder(folder_name: String, new_index: usize) -> Result<(), String> {
-    FolderManager::move_folder(folder_name, new_index, FOLDERS.get()).map_err(|e| {
+pub async fn move_folder(
+    folder_name: String,
+    new_index: usize,
+    state: State<'_, Arc<AppState>>,
+) -> Result<(), String> {
+    FolderManager::move_folder(
+        folder_name,
+        new_index,
+        &state.folders,
+        &state.worlds,
+        &state.preferences,
+        &*state.storage,
+    )
+    .map_err(|e| {
         log::error!("Error moving folder: {}", e);
         e.to_string()
     })
@@ -95,13 +164,18 @@ pub async fn move_folder(folder_name: String, new_index: usize) -> Result<(), St
 
 #[tauri::command]
 #[specta::specta]
-pub async fn rename_folder(old_name: String, new_name: String) -> Result<(), String> {
+pub async fn rename_folder(
+    old_name: String,
+    new_name: String,
+    state: State<'_, Arc<AppState>>,
+) -> Result<(), String> {
     FolderManager::rename_folder(
         old_name,
         new_name,
-        FOLDERS.get(),
-        WORLDS.get(),
-        PREFERENCES.get(),
+        &state.folders,
+        &state.worlds,
+        &state.preferences,
+        &*state.storage,
     )
     .map_err(|e| {
         log::error!("Error renaming folder: {}", e);
@@ -111,8 +185,87 @@ pub async fn rename_folder(old_name: String, new_name: String) -> Result<(), Str
 
 #[tauri::command]
 #[specta::specta]
-pub async fn get_worlds(folder_name: String) -> Result<Vec<WorldDisplayData>, String> {
-    FolderManager::get_worlds(folder_name, FOLDERS.get(), WORLDS.get()).map_err(|e| {
+pub async fn create_folder_at_path(
+    path: String,
+    state: State<'_, Arc<AppState>>,
+) -> Result<String, String> {
+    log::info!("Creating folder at path: {}", path);
+    FolderManager::create_folder_at_path(path, &state.folders, &*state.storage).map_err(|e| {
+        log::error!("Error creating folder: {}", e);
+        e.to_string()
+    })
+}
+
+#[tauri::command]
+#[specta::specta]
+pub async fn delete_folder_at_path(
+    path: String,
+    state: State<'_, Arc<AppState>>,
+) -> Result<(), String> {
+    FolderManager::delete_folder_at_path(
+        path,
+        &state.folders,
+        &state.worlds,
+        &state.preferences,
+        &*state.storage,
+    )
+    .map_err(|e| {
+        log::error!("Error deleting folder: {}", e);
+        e.to_string()
+    })
+}
+
+#[tauri::command]
+#[specta::specta]
+pub async fn move_folder_at_path(
+    path: String,
+    new_parent_path: Option<String>,
+    new_index: usize,
+    state: State<'_, Arc<AppState>>,
+) -> Result<(), String> {
+    FolderManager::move_folder_at_path(
+        path,
+        new_parent_path,
+        new_index,
+        &state.folders,
+        &state.worlds,
+        &state.preferences,
+        &*state.storage,
+    )
+    .map_err(|e| {
+        log::error!("Error moving folder: {}", e);
+        e.to_string()
+    })
+}
+
+#[tauri::command]
+#[specta::specta]
+pub async fn rename_folder_at_path(
+    path: String,
+    new_name: String,
+    state: State<'_, Arc<AppState>>,
+) -> Result<(), String> {
+    FolderManager::rename_folder_at_path(
+        path,
+        new_name,
+        &state.folders,
+        &state.worlds,
+        &state.preferences,
+        &*state.storage,
+    )
+    .map_err(|e| {
+        log::error!("Error renaming folder: {}", e);
+        e.to_string()
+    })
+}
+
+#[tauri::command]
+#[specta::specta]
+pub async fn get_worlds(
+    folder_name: String,
+    state: State<'_, Arc<AppState>>,
+) -> Result<Vec<WorldDisplayData>, String> {
+    FolderManager::get_worlds(folder_name, &state.folders, &state.worlds).map_err(|e| {
         log::error!("Error getting worlds: {}", e);
         e.to_string()
     })
@@ -120,8 +273,10 @@ pub async fn get_worlds(folder_name: String) -> Result<Vec<WorldDisplayData>, St
 
 #[tauri::command]
 #[specta::specta]
-pub async fn get_all_worlds() -> Result<Vec<WorldDisplayData>, String> {
-    FolderManager::get_all_worlds(WORLDS.get()).map_err(|e| {
+pub async fn get_all_worlds(
+    state: State<'_, Arc<AppState>>,
+) -> Result<Vec<WorldDisplayData>, String> {
+    FolderManager::get_all_worlds(&state.worlds).map_err(|e| {
         log::error!("Error getting all worlds: {}", e);
         e.to_string()
     })
@@ -129,8 +284,57 @@ pub async fn get_all_worlds() -> Result<Vec<WorldDisplayData>, String> {
 
 #[tauri::command]
 #[specta::specta]
-pub async fn get_unclassified_worlds() -> Result<Vec<WorldDisplayData>, String> {
-    FolderManager::get_unclassified_worlds(WORLDS.get()).map_err(|e| {
+pub async fn query_worlds(
+    query: String,
+    state: State<'_, Arc<AppState>>,
+) -> Result<Vec<WorldDisplayData>, String> {
+    FolderManager::query_worlds(query, &state.worlds).map_err(|e| {
+        log::error!("Error querying worlds: {}", e);
+        e.to_string()
+    })
+}
+
+#[tauri::command]
+#[specta::specta]
+pub async fn get_world_history(
+    world_id: String,
+    state: State<'_, Arc<AppState>>,
+) -> Result<Vec<WorldChange>, String> {
+    FolderManager::get_world_history(world_id, &state.worlds).map_err(|e| {
+        log::error!("Error getting world history: {}", e);
+        e.to_string()
+    })
+}
+
+#[tauri::command]
+#[specta::specta]
+pub async fn get_worlds_updated_since(
+    since: chrono::DateTime<chrono::Utc>,
+    state: State<'_, Arc<AppState>>,
+) -> Result<Vec<WorldDisplayData>, String> {
+    FolderManager::get_worlds_updated_since(since, &state.worlds).map_err(|e| {
+        log::error!("Error getting updated worlds: {}", e);
+        e.to_string()
+    })
+}
+
+#[tauri::command]
+#[specta::specta]
+pub async fn get_unavailable_worlds(
+    state: State<'_, Arc<AppState>>,
+) -> Result<Vec<WorldDisplayData>, String> {
+    FolderManager::get_unavailable_worlds(&state.worlds).map_err(|e| {
+        log::error!("Error getting unavailable worlds: {}", e);
+        e.to_string()
+    })
+}
+
+#[tauri::command]
+#[specta::specta]
+pub async fn get_unclassified_worlds(
+    state: State<'_, Arc<AppState>>,
+) -> Result<Vec<WorldDisplayData>, String> {
+    FolderManager::get_unclassified_worlds(&state.worlds).map_err(|e| {
         log::error!("Error getting unclassified worlds: {}", e);
         e.to_string()
     })
@@ -138,8 +342,10 @@ pub async fn get_unclassified_worlds() -> Result<Vec<WorldDisplayData>, String>
 
 #[tauri::command]
 #[specta::specta]
-pub async fn get_hidden_worlds() -> Result<Vec<WorldDisplayData>, String> {
-    FolderManager::get_hidden_worlds(WORLDS.get()).map_err(|e| {
+pub async fn get_hidden_worlds(
+    state: State<'_, Arc<AppState>>,
+) -> Result<Vec<WorldDisplayData>, String> {
+    FolderManager::get_hidden_worlds(&state.worlds).map_err(|e| {
         log::error!("Error getting hidden worlds: {}", e);
         e.to_string()
     })
@@ -147,8 +353,8 @@ pub async fn get_hidden_worlds() -> Result<Vec<WorldDisplayData>, String> {
 
 #[tauri::command]
 #[specta::specta]
-pub async fn get_tags_by_count() -> Result<Vec<String>, String> {
-    FolderManager::get_tags_by_count(WORLDS.get()).map_err(|e| {
+pub async fn get_tags_by_count(state: State<'_, Arc<AppState>>) -> Result<Vec<String>, String> {
+    FolderManager::get_tags_by_count(&state.worlds).map_err(|e| {
         log::error!("Error getting tags by count: {}", e);
         e.to_string()
     })
@@ -156,8 +362,8 @@ pub async fn get_tags_by_count() -> Result<Vec<String>, String> {
 
 #[tauri::command]
 #[specta::specta]
-pub async fn get_authors_by_count() -> Result<Vec<String>, String> {
-    FolderManager::get_authors_by_count(WORLDS.get()).map_err(|e| {
+pub async fn get_authors_by_count(state: State<'_, Arc<AppState>>) -> Result<Vec<String>, String> {
+    FolderManager::get_authors_by_count(&state.worlds).map_err(|e| {
         log::error!("Error getting authors by count: {}", e);
         e.to_string()
     })
@@ -165,17 +371,22 @@ pub async fn get_authors_by_count() -> Result<Vec<String>, String> {
 
 #[tauri::command]
 #[specta::specta]
-pub async fn delete_world(world_id: String) -> Result<(), String> {
-    FolderManager::delete_world(world_id, FOLDERS.get(), WORLDS.get()).map_err(|e| {
-        log::error!("Error deleting world: {}", e);
-        e.to_string()
-    })
+pub async fn delete_world(world_id: String, state: State<'_, Arc<AppState>>) -> Result<(), String> {
+    FolderManager::delete_world(world_id, &state.folders, &state.worlds, &*state.storage).map_err(
+        |e| {
+            log::error!("Error deleting world: {}", e);
+            e.to_string()
+        },
+    )
 }
 
 #[tauri::command]
 #[specta::specta]
-pub async fn get_folders_for_world(world_id: String) -> Result<Vec<String>, String> {
-    FolderManager::get_folders_for_world(world_id, WORLDS.get()).map_err(|e| {
+pub async fn get_folders_for_world(
+    world_id: String,
+    state: State<'_, Arc<AppState>>,
+) -> Result<Vec<String>, String> {
+    FolderManager::get_folders_for_world(world_id, &state.worlds).map_err(|e| {
         log::error!("Error getting folders for world: {}", e);
         e.to_string()
     })
@@ -183,8 +394,11 @@ pub async fn get_folders_for_world(world_id: String) -> Result<Vec<String>, Stri
 
 #[tauri::command]
 #[specta::specta]
-pub async fn get_custom_tags(world_id: String) -> Result<Vec<String>, String> {
-    FolderManager::get_custom_tags(world_id, WORLDS.get()).map_err(|e| {
+pub async fn get_custom_tags(
+    world_id: String,
+    state: State<'_, Arc<AppState>>,
+) -> Result<Vec<String>, String> {
+    FolderManager::get_custom_tags(world_id, &state.worlds).map_err(|e| {
         log::error!("Error getting custom tags: {}", e);
         e.to_string()
     })
@@ -192,48 +406,54 @@ pub async fn get_custom_tags(world_id: String) -> Result<Vec<String>, String> {
 
 #[tauri::command]
 #[specta::specta]
-pub async fn set_custom_tags(world_id: String, tags: Vec<String>) -> Result<Vec<String>, String> {
-    FolderManager::set_custom_tags(world_id, tags, WORLDS.get()).map_err(|e| {
+pub async fn set_custom_tags(
+    world_id: String,
+    tags: Vec<String>,
+    state: State<'_, Arc<AppState>>,
+) -> Result<Vec<String>, String> {
+    FolderManager::set_custom_tags(world_id, tags, &state.worlds, &*state.storage).map_err(|e| {
         log::error!("Error saving custom tags: {}", e);
         e.to_string()
     })
 }
 
+/// Uploads a folder to the share worker
+///
+/// # Returns
+/// The share ID of the folder, or None if the share was queued in the outbox while offline
 #[tauri::command]
 #[specta::specta]
-pub async fn share_folder(folder_name: String) -> Result<String, String> {
-    let result: Result<(String, String), String> =
-        share_service::share_folder(&folder_name, FOLDERS.get(), WORLDS.get())
-            .await
-            .map_err(|e| {
-                log::error!("Error sharing folder: {}", e);
-                e.to_string()
-            });
-    let (share_id, ts) = match &result {
-        Ok(s) => s,
-        Err(e) => return Err(e.clone()),
+pub async fn share_folder(
+    folder_name: String,
+    app_handle: AppHandle,
+    state: State<'_, Arc<AppState>>,
+) -> Result<Option<String>, String> {
+    let operation = OutboxOperation::ShareFolder {
+        folder_name: folder_name.clone(),
     };
-    FolderManager::set_folder_share(
-        folder_name.clone(),
-        FOLDERS.get(),
-        share_id.clone(),
-        ts.clone(),
-    )
+    OutboxService::perform(&state, &app_handle, operation, || {
+        OutboxService::share_folder(&state, &folder_name)
+    })
+    .await
     .map_err(|e| {
-        log::error!("Error setting folder share: {}", e);
+        log::error!("Error sharing folder: {}", e);
         e.to_string()
-    })?;
-    Ok(share_id.to_string())
+    })
 }
 
 #[tauri::command]
 #[specta::specta]
-pub async fn update_folder_share(folder_name: String) -> Result<Option<String>, String> {
+pub async fn update_folder_share(
+    folder_name: String,
+    state: State<'_, Arc<AppState>>,
+) -> Result<Option<String>, String> {
     let result: Result<Option<String>, String> =
-        FolderManager::update_folder_share(folder_name, FOLDERS.get()).map_err(|e| {
-            log::error!("Error updating folder share: {}", e);
-            e.to_string()
-        });
+        FolderManager::update_folder_share(folder_name, &state.folders, &*state.storage).map_err(
+            |e| {
+                log::error!("Error updating folder share: {}", e);
+                e.to_string()
+            },
+        );
     result
 }
 
@@ -255,22 +475,24 @@ pub async fn update_folder_share(folder_name: String) -> Result<Option<String>,
 ///
 /// # Errors
 /// Returns an error string if any operation fails, such as downloading the folder, creating the folder, adding worlds, or retrieving hidden worlds.
-pub async fn download_folder(share_id: String) -> Result<(String, Vec<WorldDisplayData>), String> {
+pub async fn download_folder(
+    share_id: String,
+    state: State<'_, Arc<AppState>>,
+) -> Result<(String, Vec<WorldDisplayData>), String> {
     // Download the folder and its worlds
-    let result: Result<(String, Vec<WorldApiData>), String> =
-        share_service::download_folder(&share_id)
-            .await
-            .map_err(|e| {
+    let (folder_name, mut worlds) = share_service::download_folder(&share_id, &state.http_client())
+        .await
+        .map_err(|e| {
+            if e.is_unreachable() {
+                log::warn!("Share server unreachable, folder not downloaded: {}", e);
+            } else {
                 log::error!("Error downloading folder: {}", e);
-                e.to_string()
-            });
-    let (folder_name, mut worlds) = match result {
-        Ok(data) => data,
-        Err(e) => return Err(e),
-    };
+            }
+            e.to_string()
+        })?;
 
     // Get hidden world IDs before adding new worlds
-    let already_hidden = FolderManager::get_hidden_worlds(WORLDS.get()).map_err(|e| {
+    let already_hidden = FolderManager::get_hidden_worlds(&state.worlds).map_err(|e| {
         log::error!("Error getting hidden worlds: {}", e);
         e.to_string()
     })?;
@@ -282,25 +504,34 @@ pub async fn download_folder(share_id: String) -> Result<(String, Vec<WorldDispl
         .partition(|world| !hidden_ids.contains(&world.world_id));
 
     // Add all worlds to the database in one go
-    FolderManager::add_worlds(WORLDS.get(), non_hidden_worlds.clone()).map_err(|e| {
-        log::error!("Error adding worlds: {}", e);
-        e.to_string()
-    })?;
+    state
+        .thumbnail_cache
+        .prefetch(&non_hidden_worlds, state.http_client());
+    FolderManager::add_worlds(&state.worlds, non_hidden_worlds.clone(), &*state.storage).map_err(
+        |e| {
+            log::error!("Error adding worlds: {}", e);
+            e.to_string()
+        },
+    )?;
 
     // Create the folder
+    let folder_name = FolderManager::escape_folder_name(&folder_name);
     let new_folder_name =
-        FolderManager::create_folder(folder_name, FOLDERS.get()).map_err(|e| {
-            log::error!("Error creating folder: {}", e);
-            e.to_string()
-        })?;
+        FolderManager::create_folder(folder_name, &state.folders, &*state.storage).map_err(
+            |e| {
+                log::error!("Error creating folder: {}", e);
+                e.to_string()
+            },
+        )?;
 
     // Add only non-hidden worlds to the folder
     for world in non_hidden_worlds.iter() {
         FolderManager::add_world_to_folder(
             new_folder_name.clone(),
             world.world_id.clone(),
-            FOLDERS.get(),
-            WORLDS.get(),
+            &state.folders,
+            &state.worlds,
+            &*state.storage,
         )
         .map_err(|e| {
             log::error!("Error adding world to folder: {}", e);
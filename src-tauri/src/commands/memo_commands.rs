@@ -1,28 +1,35 @@
-use crate::MEMO_MANAGER;
+use crate::services::FolderManager;
+use crate::AppState;
+use std::sync::Arc;
+use tauri::State;
 
 #[tauri::command]
 #[specta::specta]
-pub fn get_memo(world_id: String) -> Result<String, String> {
-    let memo_manager = MEMO_MANAGER.get().read().map_err(|e| e.to_string())?;
-    let memo = memo_manager.get_memo(&world_id).unwrap_or("");
-    Ok(memo.to_string())
+pub fn get_memo(world_id: String, state: State<'_, Arc<AppState>>) -> Result<String, String> {
+    FolderManager::get_memo(world_id, &state.worlds).map_err(|e| {
+        log::error!("Error getting memo: {}", e);
+        e.to_string()
+    })
 }
 
 #[tauri::command]
 #[specta::specta]
-pub fn set_memo_and_save(world_id: String, memo: String) -> Result<(), String> {
-    let mut memo_manager = MEMO_MANAGER.get().write().map_err(|e| e.to_string())?;
-    memo_manager.set_memo(&world_id, &memo);
-    memo_manager.save().map_err(|e| {
+pub fn set_memo_and_save(
+    world_id: String,
+    memo: String,
+    state: State<'_, Arc<AppState>>,
+) -> Result<(), String> {
+    FolderManager::set_memo(world_id, memo, &state.worlds, &*state.storage).map_err(|e| {
         log::error!("Error saving memo: {}", e);
         e.to_string()
-    })?;
-    Ok(())
+    })
 }
 
 #[tauri::command]
 #[specta::specta]
-pub fn search_memo_text(search_text: String) -> Result<Vec<String>, String> {
-    let memo_manager = MEMO_MANAGER.get().read().map_err(|e| e.to_string())?;
-    Ok(memo_manager.search_memo_text(&search_text))
+pub fn search_memo_text(
+    search_text: String,
+    state: State<'_, Arc<AppState>>,
+) -> Result<Vec<String>, String> {
+    FolderManager::search_memo_text(search_text, &state.worlds).map_err(|e| e.to_string())
 }
@@ -1,12 +1,16 @@
+pub mod account_commands;
 pub mod api_commands;
 pub mod changelog;
 pub mod data;
 pub mod data_commands;
+pub mod favorite_sync_commands;
 pub mod folder_commands;
 pub mod memo_commands;
 pub mod open_folder_commands;
+pub mod outbox_commands;
 pub mod patreon_cache;
 pub mod preferences_commands;
+pub mod schedule_commands;
 pub mod sort_commands;
 pub mod task;
 pub mod update;
@@ -22,6 +26,9 @@ pub fn generate_tauri_specta_builder() -> Builder<tauri::Wry> {
         task::get_task_status,
         task::cancel_task_request,
         task::get_task_error,
+        task::start_world_refresh,
+        task::replay_outbox,
+        task::watch_world_instances,
         update::check_for_update,
         update::download_update,
         update::install_update,
@@ -32,11 +39,28 @@ pub fn generate_tauri_specta_builder() -> Builder<tauri::Wry> {
         folder_commands::unhide_world,
         folder_commands::get_folders,
         folder_commands::create_folder,
+        folder_commands::create_smart_folder,
+        folder_commands::update_smart_folder,
         folder_commands::delete_folder,
         folder_commands::move_folder,
         folder_commands::rename_folder,
+        folder_commands::create_folder_at_path,
+        folder_commands::delete_folder_at_path,
+        folder_commands::move_folder_at_path,
+        folder_commands::rename_folder_at_path,
         folder_commands::get_worlds,
         folder_commands::get_all_worlds,
+        folder_commands::query_worlds,
+        folder_commands::get_world_history,
+        folder_commands::get_worlds_updated_since,
+        folder_commands::get_unavailable_worlds,
+        favorite_sync_commands::set_folder_favorite_group,
+        favorite_sync_commands::preview_favorite_sync,
+        favorite_sync_commands::apply_favorite_sync,
+        favorite_sync_commands::import_favorite_groups,
+        schedule_commands::get_scheduled_instances,
+        schedule_commands::schedule_instance,
+        schedule_commands::delete_scheduled_instance,
         folder_commands::get_unclassified_worlds,
         folder_commands::get_hidden_worlds,
         folder_commands::get_tags_by_count,
@@ -64,16 +88,44 @@ pub fn generate_tauri_specta_builder() -> Builder<tauri::Wry> {
         preferences_commands::set_update_channel,
         preferences_commands::get_sort_preferences,
         preferences_commands::set_sort_preferences,
+        preferences_commands::get_refresh_settings,
+        preferences_commands::set_refresh_settings,
+        preferences_commands::get_offline_mode,
+        preferences_commands::set_offline_mode,
+        preferences_commands::get_network_settings,
+        preferences_commands::set_network_settings,
+        preferences_commands::get_instance_presets,
+        preferences_commands::save_instance_preset,
+        preferences_commands::delete_instance_preset,
         api_commands::try_login,
         api_commands::login_with_credentials,
         api_commands::login_with_2fa,
         api_commands::logout,
+        account_commands::list_accounts,
+        account_commands::add_account,
+        account_commands::switch_account,
+        account_commands::remove_account,
+        account_commands::is_secret_store_locked,
+        account_commands::unlock_secret_store,
+        account_commands::set_secret_passphrase,
         api_commands::get_favorite_worlds,
         api_commands::get_world,
         api_commands::check_world_info,
+        api_commands::get_world_cache_stats,
+        api_commands::clear_world_cache,
+        api_commands::get_thumbnail_cache_stats,
+        api_commands::clear_thumbnail_cache,
+        api_commands::add_world_to_favorites,
         api_commands::get_recently_visited_worlds,
         api_commands::search_worlds,
         api_commands::create_world_instance,
+        api_commands::get_world_instances,
+        api_commands::create_world_instance_with_options,
+        api_commands::create_world_instance_from_preset,
+        api_commands::create_world_instance_and_invite,
+        api_commands::invite_users_to_instance,
+        api_commands::request_invite,
+        api_commands::respond_to_invite,
         api_commands::get_user_groups,
         api_commands::get_permission_for_create_group_instance,
         api_commands::create_group_instance,
@@ -98,5 +150,9 @@ pub fn generate_tauri_specta_builder() -> Builder<tauri::Wry> {
         memo_commands::set_memo_and_save,
         memo_commands::search_memo_text,
         sort_commands::sort_worlds_display,
+        outbox_commands::get_outbox,
+        outbox_commands::retry_outbox_item,
+        outbox_commands::remove_outbox_item,
+        outbox_commands::clear_completed_outbox,
     ])
 }
@@ -0,0 +1,66 @@
+use std::sync::Arc;
+
+use tauri::{AppHandle, State};
+use uuid::Uuid;
+
+use crate::services::outbox::OutboxItem;
+use crate::services::OutboxService;
+use crate::AppState;
+
+/// Lists the queued network-bound actions, with the status of each
+#[tauri::command]
+#[specta::specta]
+pub fn get_outbox(state: State<'_, Arc<AppState>>) -> Result<Vec<OutboxItem>, String> {
+    let outbox = state.outbox.read().map_err(|_| {
+        log::error!("Failed to acquire read lock for outbox");
+        "Failed to acquire read lock for outbox".to_string()
+    })?;
+    Ok(outbox.items())
+}
+
+/// Queues an action the server rejected again
+#[tauri::command]
+#[specta::specta]
+pub fn retry_outbox_item(
+    id: Uuid,
+    app_handle: AppHandle,
+    state: State<'_, Arc<AppState>>,
+) -> Result<(), String> {
+    let item = {
+        let mut outbox = state.outbox.write().map_err(|_| {
+            log::error!("Failed to acquire write lock for outbox");
+            "Failed to acquire write lock for outbox".to_string()
+        })?;
+        outbox.retry(id)
+    };
+    let item = item.ok_or_else(|| {
+        log::error!("Outbox item not found: {}", id);
+        format!("Outbox item not found: {}", id)
+    })?;
+    OutboxService::emit(&item, &app_handle);
+    state.outbox_wake.notify_one();
+    Ok(())
+}
+
+/// Drops a queued action, unless it is being sent
+#[tauri::command]
+#[specta::specta]
+pub fn remove_outbox_item(id: Uuid, state: State<'_, Arc<AppState>>) -> Result<bool, String> {
+    let mut outbox = state.outbox.write().map_err(|_| {
+        log::error!("Failed to acquire write lock for outbox");
+        "Failed to acquire write lock for outbox".to_string()
+    })?;
+    Ok(outbox.remove(id))
+}
+
+/// Drops the actions that were sent
+#[tauri::command]
+#[specta::specta]
+pub fn clear_completed_outbox(state: State<'_, Arc<AppState>>) -> Result<(), String> {
+    let mut outbox = state.outbox.write().map_err(|_| {
+        log::error!("Failed to acquire write lock for outbox");
+        "Failed to acquire write lock for outbox".to_string()
+    })?;
+    outbox.clear_completed();
+    Ok(())
+}
@@ -1,7 +1,8 @@
 use crate::definitions::PatreonVRChatNames;
-use reqwest::Client;
-use std::sync::RwLock;
+use crate::AppState;
+use std::sync::{Arc, RwLock};
 use std::time::{Duration, SystemTime};
+use tauri::State;
 
 pub struct PatreonCache {
     data: Option<PatreonVRChatNames>,
@@ -50,7 +51,9 @@ pub fn init_cache() {
 
 #[tauri::command]
 #[specta::specta]
-pub async fn fetch_patreon_vrchat_names() -> Result<PatreonVRChatNames, String> {
+pub async fn fetch_patreon_vrchat_names(
+    state: State<'_, Arc<AppState>>,
+) -> Result<PatreonVRChatNames, String> {
     // Try to get cached data first
     {
         let cache = PATREON_CACHE
@@ -65,7 +68,7 @@ pub async fn fetch_patreon_vrchat_names() -> Result<PatreonVRChatNames, String>
 
     // Cache is expired or empty, fetch fresh data
     log::info!("Fetching fresh Patreon VRChat names from server");
-    let client = Client::new();
+    let client = state.http_client();
     let response = client
         .get("https://data.raifaworks.com/data/patreons-vrchat-usernames.json")
         .send()
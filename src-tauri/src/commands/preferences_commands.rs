@@ -1,24 +1,29 @@
 use crate::api::instance::InstanceRegion;
+use crate::api::HttpClientFactory;
 use crate::definitions::CardSize;
 use crate::definitions::FilterItemSelectorStarred;
 use crate::definitions::FilterItemSelectorStarredType;
 use crate::definitions::FolderRemovalPreference;
+use crate::definitions::InstancePreset;
+use crate::definitions::NetworkSettings;
 use crate::services::FileService;
 use crate::updater::update_handler::UpdateChannel;
-use crate::PREFERENCES;
+use crate::AppState;
+use std::sync::Arc;
+use tauri::State;
 
 #[tauri::command]
 #[specta::specta]
-pub fn get_theme() -> Result<String, String> {
-    let preferences_lock = PREFERENCES.get().read();
+pub fn get_theme(state: State<'_, Arc<AppState>>) -> Result<String, String> {
+    let preferences_lock = state.preferences.read();
     let preferences = preferences_lock.as_ref().unwrap();
     Ok(preferences.theme.clone())
 }
 
 #[tauri::command]
 #[specta::specta]
-pub fn set_theme(theme: String) -> Result<(), String> {
-    let mut preferences_lock = PREFERENCES.get().write();
+pub fn set_theme(theme: String, state: State<'_, Arc<AppState>>) -> Result<(), String> {
+    let mut preferences_lock = state.preferences.write();
     let preferences = preferences_lock.as_mut().unwrap();
     preferences.theme = theme;
     FileService::write_preferences(preferences).map_err(|e| {
@@ -30,16 +35,16 @@ pub fn set_theme(theme: String) -> Result<(), String> {
 
 #[tauri::command]
 #[specta::specta]
-pub fn get_language() -> Result<String, String> {
-    let preferences_lock = PREFERENCES.get().read();
+pub fn get_language(state: State<'_, Arc<AppState>>) -> Result<String, String> {
+    let preferences_lock = state.preferences.read();
     let preferences = preferences_lock.as_ref().unwrap();
     Ok(preferences.language.clone())
 }
 
 #[tauri::command]
 #[specta::specta]
-pub fn set_language(language: String) -> Result<(), String> {
-    let mut preferences_lock = PREFERENCES.get().write();
+pub fn set_language(language: String, state: State<'_, Arc<AppState>>) -> Result<(), String> {
+    let mut preferences_lock = state.preferences.write();
     let preferences = preferences_lock.as_mut().unwrap();
     preferences.language = language;
     FileService::write_preferences(preferences).map_err(|e| {
@@ -51,16 +56,16 @@ pub fn set_language(language: String) -> Result<(), String> {
 
 #[tauri::command]
 #[specta::specta]
-pub fn get_card_size() -> Result<CardSize, String> {
-    let preferences_lock = PREFERENCES.get().read();
+pub fn get_card_size(state: State<'_, Arc<AppState>>) -> Result<CardSize, String> {
+    let preferences_lock = state.preferences.read();
     let preferences = preferences_lock.as_ref().unwrap();
     Ok(preferences.card_size.clone())
 }
 
 #[tauri::command]
 #[specta::specta]
-pub fn set_card_size(card_size: CardSize) -> Result<(), String> {
-    let mut preferences_lock = PREFERENCES.get().write();
+pub fn set_card_size(card_size: CardSize, state: State<'_, Arc<AppState>>) -> Result<(), String> {
+    let mut preferences_lock = state.preferences.write();
     let preferences = preferences_lock.as_mut().unwrap();
     preferences.card_size = card_size;
     FileService::write_preferences(preferences).map_err(|e| {
@@ -72,16 +77,16 @@ pub fn set_card_size(card_size: CardSize) -> Result<(), String> {
 
 #[tauri::command]
 #[specta::specta]
-pub fn get_region() -> Result<InstanceRegion, String> {
-    let preferences_lock = PREFERENCES.get().read();
+pub fn get_region(state: State<'_, Arc<AppState>>) -> Result<InstanceRegion, String> {
+    let preferences_lock = state.preferences.read();
     let preferences = preferences_lock.as_ref().unwrap();
-    Ok(preferences.region.clone())
+    Ok(preferences.region)
 }
 
 #[tauri::command]
 #[specta::specta]
-pub fn set_region(region: InstanceRegion) -> Result<(), String> {
-    let mut preferences_lock = PREFERENCES.get().write();
+pub fn set_region(region: InstanceRegion, state: State<'_, Arc<AppState>>) -> Result<(), String> {
+    let mut preferences_lock = state.preferences.write();
     let preferences = preferences_lock.as_mut().unwrap();
     preferences.region = region;
     FileService::write_preferences(preferences).map_err(|e| {
@@ -93,8 +98,11 @@ pub fn set_region(region: InstanceRegion) -> Result<(), String> {
 
 #[tauri::command]
 #[specta::specta]
-pub fn get_starred_filter_items(id: FilterItemSelectorStarredType) -> Result<Vec<String>, String> {
-    let preferences_lock = PREFERENCES.get().read();
+pub fn get_starred_filter_items(
+    id: FilterItemSelectorStarredType,
+    state: State<'_, Arc<AppState>>,
+) -> Result<Vec<String>, String> {
+    let preferences_lock = state.preferences.read();
     let preferences = preferences_lock.as_ref().unwrap();
     if let Some(filter_item_selector_starred) = &preferences.filter_item_selector_starred {
         match id {
@@ -119,8 +127,9 @@ pub fn get_starred_filter_items(id: FilterItemSelectorStarredType) -> Result<Vec
 pub fn set_starred_filter_items(
     id: FilterItemSelectorStarredType,
     values: Vec<String>,
+    state: State<'_, Arc<AppState>>,
 ) -> Result<(), String> {
-    let mut preferences_lock = PREFERENCES.get().write();
+    let mut preferences_lock = state.preferences.write();
     let preferences = preferences_lock.as_mut().unwrap();
 
     if preferences.filter_item_selector_starred.is_none() {
@@ -163,8 +172,10 @@ pub fn set_starred_filter_items(
 
 #[tauri::command]
 #[specta::specta]
-pub fn get_folder_removal_preference() -> Result<FolderRemovalPreference, String> {
-    let preferences_lock = PREFERENCES.get().read();
+pub fn get_folder_removal_preference(
+    state: State<'_, Arc<AppState>>,
+) -> Result<FolderRemovalPreference, String> {
+    let preferences_lock = state.preferences.read();
     let preferences = preferences_lock.as_ref().unwrap();
     Ok(preferences.dont_show_remove_from_folder)
 }
@@ -173,8 +184,9 @@ pub fn get_folder_removal_preference() -> Result<FolderRemovalPreference, String
 #[specta::specta]
 pub fn set_folder_removal_preference(
     dont_show_remove_from_folder: FolderRemovalPreference,
+    state: State<'_, Arc<AppState>>,
 ) -> Result<(), String> {
-    let mut preferences_lock = PREFERENCES.get().write();
+    let mut preferences_lock = state.preferences.write();
     let preferences = preferences_lock.as_mut().unwrap();
     preferences.dont_show_remove_from_folder = dont_show_remove_from_folder;
     FileService::write_preferences(preferences).map_err(|e| {
@@ -186,16 +198,19 @@ pub fn set_folder_removal_preference(
 
 #[tauri::command]
 #[specta::specta]
-pub fn get_update_channel() -> Result<UpdateChannel, String> {
-    let preferences_lock = PREFERENCES.get().read();
+pub fn get_update_channel(state: State<'_, Arc<AppState>>) -> Result<UpdateChannel, String> {
+    let preferences_lock = state.preferences.read();
     let preferences = preferences_lock.as_ref().unwrap();
-    Ok(preferences.update_channel.clone())
+    Ok(preferences.update_channel)
 }
 
 #[tauri::command]
 #[specta::specta]
-pub fn set_update_channel(channel: UpdateChannel) -> Result<(), String> {
-    let mut preferences_lock = PREFERENCES.get().write();
+pub fn set_update_channel(
+    channel: UpdateChannel,
+    state: State<'_, Arc<AppState>>,
+) -> Result<(), String> {
+    let mut preferences_lock = state.preferences.write();
     let preferences = preferences_lock.as_mut().unwrap();
     preferences.update_channel = channel;
     FileService::write_preferences(preferences).map_err(|e| {
@@ -207,8 +222,8 @@ pub fn set_update_channel(channel: UpdateChannel) -> Result<(), String> {
 
 #[tauri::command]
 #[specta::specta]
-pub fn get_sort_preferences() -> Result<(String, String), String> {
-    let preferences_lock = PREFERENCES.get().read();
+pub fn get_sort_preferences(state: State<'_, Arc<AppState>>) -> Result<(String, String), String> {
+    let preferences_lock = state.preferences.read();
     let preferences = preferences_lock.as_ref().unwrap();
     Ok((
         preferences.sort_field.clone(),
@@ -218,7 +233,11 @@ pub fn get_sort_preferences() -> Result<(String, String), String> {
 
 #[tauri::command]
 #[specta::specta]
-pub fn set_sort_preferences(sort_field: String, sort_direction: String) -> Result<(), String> {
+pub fn set_sort_preferences(
+    sort_field: String,
+    sort_direction: String,
+    state: State<'_, Arc<AppState>>,
+) -> Result<(), String> {
     let valid_fields = [
         "name",
         "authorName",
@@ -237,7 +256,7 @@ pub fn set_sort_preferences(sort_field: String, sort_direction: String) -> Resul
         return Err(format!("Invalid sort_direction: {}", sort_direction));
     }
 
-    let mut preferences_lock = PREFERENCES.get().write();
+    let mut preferences_lock = state.preferences.write();
     let preferences = preferences_lock.as_mut().unwrap();
     preferences.sort_field = sort_field;
     preferences.sort_direction = sort_direction;
@@ -247,3 +266,157 @@ pub fn set_sort_preferences(sort_field: String, sort_direction: String) -> Resul
     })?;
     Ok(())
 }
+
+#[tauri::command]
+#[specta::specta]
+pub fn get_refresh_settings(state: State<'_, Arc<AppState>>) -> Result<(u32, u32), String> {
+    let preferences_lock = state.preferences.read();
+    let preferences = preferences_lock.as_ref().unwrap();
+    Ok((
+        preferences.refresh_interval_minutes,
+        preferences.refresh_batch_size,
+    ))
+}
+
+#[tauri::command]
+#[specta::specta]
+pub fn set_refresh_settings(
+    interval_minutes: u32,
+    batch_size: u32,
+    state: State<'_, Arc<AppState>>,
+) -> Result<(), String> {
+    if interval_minutes == 0 {
+        return Err("Refresh interval must be at least one minute".to_string());
+    }
+    if batch_size == 0 {
+        return Err("Refresh batch size must be at least one world".to_string());
+    }
+
+    let mut preferences_lock = state.preferences.write();
+    let preferences = preferences_lock.as_mut().unwrap();
+    preferences.refresh_interval_minutes = interval_minutes;
+    preferences.refresh_batch_size = batch_size;
+    FileService::write_preferences(preferences).map_err(|e| {
+        log::error!("Error writing preferences: {}", e);
+        e.to_string()
+    })?;
+    Ok(())
+}
+
+#[tauri::command]
+#[specta::specta]
+pub fn get_network_settings(state: State<'_, Arc<AppState>>) -> Result<NetworkSettings, String> {
+    let preferences_lock = state.preferences.read();
+    let preferences = preferences_lock.as_ref().unwrap();
+    Ok(preferences.network.clone())
+}
+
+/// Saves the proxy, timeouts and root certificates, applying them to the next requests
+#[tauri::command]
+#[specta::specta]
+pub fn set_network_settings(
+    settings: NetworkSettings,
+    state: State<'_, Arc<AppState>>,
+) -> Result<(), String> {
+    let factory = HttpClientFactory::new(&settings).map_err(|e| {
+        log::error!("Error applying network settings: {}", e);
+        e
+    })?;
+    *state
+        .http
+        .write()
+        .map_err(|_| "Failed to acquire write lock for network settings".to_string())? = factory;
+    log::info!("Network settings applied");
+
+    let mut preferences_lock = state.preferences.write();
+    let preferences = preferences_lock.as_mut().unwrap();
+    preferences.network = settings;
+    FileService::write_preferences(preferences).map_err(|e| {
+        log::error!("Error writing preferences: {}", e);
+        e.to_string()
+    })?;
+    Ok(())
+}
+
+#[tauri::command]
+#[specta::specta]
+pub fn get_instance_presets(
+    state: State<'_, Arc<AppState>>,
+) -> Result<Vec<InstancePreset>, String> {
+    let preferences_lock = state.preferences.read();
+    let preferences = preferences_lock.as_ref().unwrap();
+    Ok(preferences.instance_presets.clone())
+}
+
+/// Saves a preset, replacing the preset with the same name if there is one
+#[tauri::command]
+#[specta::specta]
+pub fn save_instance_preset(
+    preset: InstancePreset,
+    state: State<'_, Arc<AppState>>,
+) -> Result<(), String> {
+    if preset.name.trim().is_empty() {
+        return Err("Preset name cannot be empty".to_string());
+    }
+
+    let mut preferences_lock = state.preferences.write();
+    let preferences = preferences_lock.as_mut().unwrap();
+    match preferences
+        .instance_presets
+        .iter_mut()
+        .find(|p| p.name == preset.name)
+    {
+        Some(existing) => *existing = preset,
+        None => preferences.instance_presets.push(preset),
+    }
+    FileService::write_preferences(preferences).map_err(|e| {
+        log::error!("Error writing preferences: {}", e);
+        e.to_string()
+    })?;
+    Ok(())
+}
+
+#[tauri::command]
+#[specta::specta]
+pub fn delete_instance_preset(name: String, state: State<'_, Arc<AppState>>) -> Result<(), String> {
+    let mut preferences_lock = state.preferences.write();
+    let preferences = preferences_lock.as_mut().unwrap();
+    preferences.instance_presets.retain(|p| p.name != name);
+    FileService::write_preferences(preferences).map_err(|e| {
+        log::error!("Error writing preferences: {}", e);
+        e.to_string()
+    })?;
+    Ok(())
+}
+
+#[tauri::command]
+#[specta::specta]
+pub fn get_offline_mode(state: State<'_, Arc<AppState>>) -> Result<bool, String> {
+    let preferences_lock = state.preferences.read();
+    let preferences = preferences_lock.as_ref().unwrap();
+    Ok(preferences.offline_mode)
+}
+
+/// Switches offline mode, in which network-bound actions are queued in the outbox
+/// Leaving offline mode replays the outbox right away
+#[tauri::command]
+#[specta::specta]
+pub fn set_offline_mode(offline: bool, state: State<'_, Arc<AppState>>) -> Result<(), String> {
+    {
+        let mut preferences_lock = state.preferences.write();
+        let preferences = preferences_lock.as_mut().unwrap();
+        preferences.offline_mode = offline;
+        FileService::write_preferences(preferences).map_err(|e| {
+            log::error!("Error writing preferences: {}", e);
+            e.to_string()
+        })?;
+    }
+    log::info!(
+        "Offline mode {}",
+        if offline { "enabled" } else { "disabled" }
+    );
+    if !offline {
+        state.outbox_wake.notify_one();
+    }
+    Ok(())
+}
@@ -0,0 +1,77 @@
+use crate::api::instance::InstanceRegion;
+use crate::definitions::{ScheduleStatus, ScheduledInstance};
+use crate::services::InstanceScheduler;
+use crate::AppState;
+use std::sync::Arc;
+use tauri::State;
+use uuid::Uuid;
+
+#[tauri::command]
+#[specta::specta]
+pub fn get_scheduled_instances(
+    state: State<'_, Arc<AppState>>,
+) -> Result<Vec<ScheduledInstance>, String> {
+    let schedules = state.schedules.read().map_err(|e| {
+        log::error!("Failed to acquire read lock for schedules: {}", e);
+        "Failed to acquire read lock for schedules".to_string()
+    })?;
+    Ok(schedules.clone())
+}
+
+/// Schedules an instance of a world to be created with a preset at a later time
+#[tauri::command]
+#[specta::specta]
+pub fn schedule_instance(
+    world_id: String,
+    preset_name: String,
+    region: Option<InstanceRegion>,
+    group_id: Option<String>,
+    scheduled_at: chrono::DateTime<chrono::Utc>,
+    invite_self: bool,
+    state: State<'_, Arc<AppState>>,
+) -> Result<ScheduledInstance, String> {
+    if scheduled_at <= chrono::Utc::now() {
+        return Err("Scheduled time must be in the future".to_string());
+    }
+    let preset_exists = state
+        .preferences
+        .read()
+        .map_err(|_| "Failed to acquire read lock for preferences".to_string())?
+        .instance_presets
+        .iter()
+        .any(|p| p.name == preset_name);
+    if !preset_exists {
+        return Err(format!("Instance preset not found: {}", preset_name));
+    }
+
+    let schedule = ScheduledInstance {
+        id: Uuid::new_v4(),
+        world_id,
+        preset_name,
+        region,
+        group_id,
+        scheduled_at,
+        invite_self,
+        status: ScheduleStatus::Pending,
+    };
+    state
+        .schedules
+        .write()
+        .map_err(|_| "Failed to acquire write lock for schedules".to_string())?
+        .push(schedule.clone());
+    InstanceScheduler::save(&state.schedules);
+    Ok(schedule)
+}
+
+/// Cancels a pending schedule, or dismisses the result of a past one
+#[tauri::command]
+#[specta::specta]
+pub fn delete_scheduled_instance(id: Uuid, state: State<'_, Arc<AppState>>) -> Result<(), String> {
+    state
+        .schedules
+        .write()
+        .map_err(|_| "Failed to acquire write lock for schedules".to_string())?
+        .retain(|s| s.id != id);
+    InstanceScheduler::save(&state.schedules);
+    Ok(())
+}
@@ -1,9 +1,11 @@
 use std::sync::Arc;
 
-use tauri::{async_runtime::Mutex, State};
+use tauri::{async_runtime::Mutex, AppHandle, State};
 use uuid::Uuid;
 
+use crate::services::{InstanceWatcher, OutboxService, RefreshService};
 use crate::task::{cancellable_task::TaskContainer, definitions::TaskStatus};
+use crate::AppState;
 
 #[tauri::command]
 #[specta::specta]
@@ -73,3 +75,49 @@ pub async fn get_task_error(
         }
     }
 }
+
+/// Starts the background refresh of stale worlds, or returns the running one.
+/// The refresh is started on launch, so this is only needed after cancelling it.
+#[tauri::command]
+#[specta::specta]
+pub async fn start_world_refresh(
+    app_handle: AppHandle,
+    state: State<'_, Arc<AppState>>,
+    task_container: State<'_, Arc<Mutex<TaskContainer>>>,
+) -> Result<Uuid, String> {
+    let mut container = task_container.lock().await;
+    RefreshService::start(&state, &mut container, app_handle).await
+}
+
+/// Starts replaying the outbox, or wakes the running replay to try the queued actions now
+#[tauri::command]
+#[specta::specta]
+pub async fn replay_outbox(
+    app_handle: AppHandle,
+    state: State<'_, Arc<AppState>>,
+    task_container: State<'_, Arc<Mutex<TaskContainer>>>,
+) -> Result<Uuid, String> {
+    let mut container = task_container.lock().await;
+    OutboxService::start(&state, &mut container, app_handle).await
+}
+
+/// Starts polling a world for joinable instances with at least `min_users` users,
+/// emitting InstanceAvailable for each one found. Cancel the task to stop watching.
+#[tauri::command]
+#[specta::specta]
+pub async fn watch_world_instances(
+    world_id: String,
+    min_users: Option<u32>,
+    app_handle: AppHandle,
+    state: State<'_, Arc<AppState>>,
+    task_container: State<'_, Arc<Mutex<TaskContainer>>>,
+) -> Result<Uuid, String> {
+    let mut container = task_container.lock().await;
+    InstanceWatcher::start(
+        world_id,
+        min_users.unwrap_or(1),
+        &state,
+        &mut container,
+        app_handle,
+    )
+}
@@ -15,9 +15,9 @@ pub enum Platform {
     #[serde(rename = "android", alias = "quest")]
     Android,
     #[serde(rename = "ios")]
-    IOS,
+    Ios,
     #[serde(rename = "unknownplatform")]
-    UnknownPlatform,
+    Unknown,
 }
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct WorldApiData {
@@ -81,14 +81,148 @@ pub struct WorldUserData {
     pub hidden: bool,
     #[serde(rename = "customTags", default)]
     pub custom_tags: Vec<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub history: Vec<WorldChange>,
+    #[serde(default)]
+    pub availability: WorldAvailability,
+    #[serde(rename = "lastSeen", default, skip_serializing_if = "Option::is_none")]
+    pub last_seen: Option<DateTime<Utc>>,
+}
+
+/// Whether a world can still be visited, as found by the last refresh or favorites import
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize, specta::Type)]
+pub enum WorldAvailability {
+    #[serde(rename = "available")]
+    Available,
+    #[serde(rename = "private")]
+    Private,
+    #[serde(rename = "deleted")]
+    Deleted,
+    // Worlds added before availability was tracked, until they are refreshed
+    #[default]
+    #[serde(rename = "unknown")]
+    Unknown,
+}
+
+impl WorldAvailability {
+    pub fn is_unavailable(self) -> bool {
+        matches!(
+            self,
+            WorldAvailability::Private | WorldAvailability::Deleted
+        )
+    }
 }
 
+/// The number of changes kept per world, older ones are dropped
+pub const MAX_WORLD_HISTORY: usize = 100;
+
 impl WorldUserData {
     pub fn needs_update(&self) -> bool {
         let now = Utc::now();
         let duration = now.signed_duration_since(self.last_checked);
         duration.num_hours() >= 4
     }
+
+    /// Appends a change to the history, dropping the oldest beyond `MAX_WORLD_HISTORY`
+    pub fn record_change(&mut self, change: WorldChange) {
+        self.history.push(change);
+        if self.history.len() > MAX_WORLD_HISTORY {
+            let excess = self.history.len() - MAX_WORLD_HISTORY;
+            self.history.drain(..excess);
+        }
+    }
+}
+
+/// A value of a world before and after a refresh
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, specta::Type)]
+pub struct FieldChange<T> {
+    pub old: T,
+    pub new: T,
+}
+
+impl<T: PartialEq + Clone> FieldChange<T> {
+    fn between(old: &T, new: &T) -> Option<Self> {
+        (old != new).then(|| FieldChange {
+            old: old.clone(),
+            new: new.clone(),
+        })
+    }
+}
+
+/// What changed in a world between two refreshes
+/// Only the fields that changed are stored, to keep the history compact
+#[derive(Debug, Clone, Serialize, Deserialize, specta::Type)]
+pub struct WorldChange {
+    #[serde(rename = "recordedAt")]
+    pub recorded_at: DateTime<Utc>,
+    #[serde(rename = "updatedAt", default, skip_serializing_if = "Option::is_none")]
+    pub updated_at: Option<FieldChange<DateTime<Utc>>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub name: Option<FieldChange<String>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub description: Option<FieldChange<String>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub capacity: Option<FieldChange<i32>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub platform: Option<FieldChange<Vec<Platform>>>,
+    #[serde(rename = "tagsAdded", default, skip_serializing_if = "Vec::is_empty")]
+    pub tags_added: Vec<String>,
+    #[serde(rename = "tagsRemoved", default, skip_serializing_if = "Vec::is_empty")]
+    pub tags_removed: Vec<String>,
+    #[serde(rename = "visitsDelta", default)]
+    pub visits_delta: i32,
+    #[serde(rename = "favoritesDelta", default)]
+    pub favorites_delta: i32,
+}
+
+impl WorldChange {
+    /// Compares the data of a world before and after a refresh
+    ///
+    /// # Arguments
+    /// * `old` - The data that is being replaced
+    /// * `new` - The freshly fetched data
+    ///
+    /// # Returns
+    /// The change, or None if none of the tracked fields changed
+    pub fn between(old: &WorldApiData, new: &WorldApiData) -> Option<Self> {
+        let change = WorldChange {
+            recorded_at: Utc::now(),
+            updated_at: FieldChange::between(&old.last_update, &new.last_update),
+            name: FieldChange::between(&old.world_name, &new.world_name),
+            description: FieldChange::between(&old.description, &new.description),
+            capacity: FieldChange::between(&old.capacity, &new.capacity),
+            platform: FieldChange::between(&old.platform, &new.platform),
+            tags_added: new
+                .tags
+                .iter()
+                .filter(|tag| !old.tags.contains(tag))
+                .cloned()
+                .collect(),
+            tags_removed: old
+                .tags
+                .iter()
+                .filter(|tag| !new.tags.contains(tag))
+                .cloned()
+                .collect(),
+            visits_delta: new.visits.unwrap_or(0) - old.visits.unwrap_or(0),
+            favorites_delta: new.favorites - old.favorites,
+        };
+
+        let unchanged =
+            !change.is_creator_update() && change.visits_delta == 0 && change.favorites_delta == 0;
+        (!unchanged).then_some(change)
+    }
+
+    /// Whether the creator changed the world, as opposed to only its statistics moving
+    pub fn is_creator_update(&self) -> bool {
+        self.updated_at.is_some()
+            || self.name.is_some()
+            || self.description.is_some()
+            || self.capacity.is_some()
+            || self.platform.is_some()
+            || !self.tags_added.is_empty()
+            || !self.tags_removed.is_empty()
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -110,6 +244,9 @@ impl WorldModel {
                 folders: vec![],
                 hidden: false,
                 custom_tags: vec![],
+                history: vec![],
+                availability: WorldAvailability::Unknown,
+                last_seen: None,
             },
         }
     }
@@ -139,6 +276,7 @@ impl WorldModel {
             folders: self.user_data.folders.clone(),
             tags: merged_tags,
             capacity: self.api_data.capacity,
+            availability: self.user_data.availability,
         }
     }
 
@@ -174,6 +312,7 @@ pub struct WorldDisplayData {
     pub folders: Vec<String>,
     pub tags: Vec<String>,
     pub capacity: i32,
+    pub availability: WorldAvailability,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, specta::Type)]
@@ -203,6 +342,8 @@ pub struct WorldDetails {
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct FolderModel {
+    /// The path of the folder, which is its name for a top-level folder, and
+    /// the path of its parent followed by its name for a subfolder
     #[serde(rename = "name")]
     pub folder_name: String,
     #[serde(rename = "worlds")]
@@ -210,6 +351,30 @@ pub struct FolderModel {
     /// Optional share metadata
     #[serde(rename = "share", skip_serializing_if = "Option::is_none")]
     pub share: Option<ShareInfo>,
+    /// Saved query of a smart folder, whose worlds are computed instead of listed
+    #[serde(rename = "query", default, skip_serializing_if = "Option::is_none")]
+    pub query: Option<String>,
+    /// Path of the parent folder, or None for a top-level folder
+    #[serde(rename = "parent", default, skip_serializing_if = "Option::is_none")]
+    pub parent: Option<String>,
+    /// The VRChat favorite group kept in sync with this folder
+    #[serde(
+        rename = "favoriteSync",
+        default,
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub favorite_sync: Option<FavoriteGroupSync>,
+}
+
+/// Links a folder to a VRChat world favorite group
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FavoriteGroupSync {
+    /// The name of the favorite group, e.g. `worlds1`
+    pub group: String,
+    /// The worlds that were in both the folder and the group after the last sync
+    /// Comparing against this tells which side added or removed a world since
+    #[serde(rename = "syncedWorldIds", default)]
+    pub synced_world_ids: Vec<String>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -226,8 +391,15 @@ impl FolderModel {
             folder_name,
             world_ids: vec![],
             share: None,
+            query: None,
+            parent: None,
+            favorite_sync: None,
         }
     }
+
+    pub fn is_smart(&self) -> bool {
+        self.query.is_some()
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, specta::Type)]
@@ -264,6 +436,139 @@ pub enum FolderRemovalPreference {
     NeverRemove, // Never remove, always keep in the current folder
 }
 
+/// Everything needed to create an instance, apart from the world
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, Type)]
+pub struct InstanceOptions {
+    /// public, friends+, friends, invite+ or invite, or for a group instance
+    /// public, group+ or group
+    #[serde(rename = "instanceType")]
+    pub instance_type: String,
+    pub region: InstanceRegion,
+    /// The group to create a group instance for
+    #[serde(rename = "groupId", default)]
+    pub group_id: Option<String>,
+    /// The roles allowed in a group only instance, or empty for every member
+    #[serde(rename = "roleIds", default)]
+    pub role_ids: Vec<String>,
+    #[serde(rename = "queueEnabled", default)]
+    pub queue_enabled: bool,
+    #[serde(rename = "displayName", default)]
+    pub display_name: Option<String>,
+    #[serde(rename = "ageGate", default)]
+    pub age_gate: bool,
+    /// Minutes after creation at which the instance closes to new users
+    #[serde(rename = "closeAfterMinutes", default)]
+    pub close_after_minutes: Option<u32>,
+    /// Removes the users still in the instance when it closes
+    #[serde(rename = "hardClose", default)]
+    pub hard_close: bool,
+    /// Overrides whether users can ask to be invited, which depends on the type otherwise
+    #[serde(rename = "canRequestInvite", default)]
+    pub can_request_invite: Option<bool>,
+    #[serde(rename = "inviteOnly", default)]
+    pub invite_only: bool,
+}
+
+impl InstanceOptions {
+    pub fn new(instance_type: String, region: InstanceRegion) -> Self {
+        Self {
+            instance_type,
+            region,
+            group_id: None,
+            role_ids: vec![],
+            queue_enabled: false,
+            display_name: None,
+            age_gate: false,
+            close_after_minutes: None,
+            hard_close: false,
+            can_request_invite: None,
+            invite_only: false,
+        }
+    }
+}
+
+/// Instance options saved under a name, to create instances of any world with
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, Type)]
+pub struct InstancePreset {
+    pub name: String,
+    pub options: InstanceOptions,
+}
+
+/// How the app connects to VRChat and every other server
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, Type)]
+pub struct NetworkSettings {
+    /// The proxy every request goes through, e.g. http://proxy:8080 or socks5://proxy:1080
+    #[serde(rename = "proxyUrl", default)]
+    pub proxy_url: Option<String>,
+    /// Seconds to wait for a connection to be established
+    #[serde(
+        rename = "connectTimeoutSeconds",
+        default = "default_connect_timeout_seconds"
+    )]
+    pub connect_timeout_seconds: u32,
+    /// Seconds to wait for data once connected
+    #[serde(
+        rename = "readTimeoutSeconds",
+        default = "default_read_timeout_seconds"
+    )]
+    pub read_timeout_seconds: u32,
+    /// Paths of PEM certificates to trust on top of the system ones,
+    /// e.g. the certificate of a proxy that inspects TLS traffic
+    #[serde(rename = "extraRootCertificates", default)]
+    pub extra_root_certificates: Vec<String>,
+}
+
+fn default_connect_timeout_seconds() -> u32 {
+    10
+}
+
+fn default_read_timeout_seconds() -> u32 {
+    30
+}
+
+impl Default for NetworkSettings {
+    fn default() -> Self {
+        Self {
+            proxy_url: None,
+            connect_timeout_seconds: default_connect_timeout_seconds(),
+            read_timeout_seconds: default_read_timeout_seconds(),
+            extra_root_certificates: vec![],
+        }
+    }
+}
+
+/// What became of a scheduled instance
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, Type)]
+pub enum ScheduleStatus {
+    Pending,
+    /// The instance was created, with this instance ID
+    Created(String),
+    Failed(String),
+    /// The app was not running at the scheduled time, so nothing was created
+    Missed,
+}
+
+/// An instance to create at a later time
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, Type)]
+pub struct ScheduledInstance {
+    pub id: uuid::Uuid,
+    #[serde(rename = "worldId")]
+    pub world_id: String,
+    /// The preset whose options the instance is created with
+    #[serde(rename = "presetName")]
+    pub preset_name: String,
+    /// Overrides the region of the preset
+    pub region: Option<InstanceRegion>,
+    /// Overrides the group of the preset
+    #[serde(rename = "groupId")]
+    pub group_id: Option<String>,
+    #[serde(rename = "scheduledAt")]
+    pub scheduled_at: DateTime<Utc>,
+    #[serde(rename = "inviteSelf")]
+    pub invite_self: bool,
+    pub status: ScheduleStatus,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct PreferenceModel {
     #[serde(rename = "firstTime")]
@@ -290,6 +595,22 @@ pub struct PreferenceModel {
     pub sort_field: String,
     #[serde(rename = "sortDirection", default = "default_sort_direction")]
     pub sort_direction: String,
+    /// Minutes between two batches of the background world refresh
+    #[serde(
+        rename = "refreshIntervalMinutes",
+        default = "default_refresh_interval_minutes"
+    )]
+    pub refresh_interval_minutes: u32,
+    /// Number of stale worlds refreshed per batch
+    #[serde(rename = "refreshBatchSize", default = "default_refresh_batch_size")]
+    pub refresh_batch_size: u32,
+    #[serde(rename = "instancePresets", default)]
+    pub instance_presets: Vec<InstancePreset>,
+    #[serde(default)]
+    pub network: NetworkSettings,
+    /// Whether network-bound actions are queued in the outbox instead of being sent
+    #[serde(rename = "offlineMode", default)]
+    pub offline_mode: bool,
 }
 
 fn default_region() -> InstanceRegion {
@@ -312,6 +633,14 @@ fn default_sort_direction() -> String {
     "desc".to_string()
 }
 
+fn default_refresh_interval_minutes() -> u32 {
+    10
+}
+
+fn default_refresh_batch_size() -> u32 {
+    25
+}
+
 impl PreferenceModel {
     pub fn new() -> Self {
         Self {
@@ -325,6 +654,11 @@ impl PreferenceModel {
             update_channel: UpdateChannel::Stable,
             sort_field: "dateAdded".to_string(),
             sort_direction: "desc".to_string(),
+            refresh_interval_minutes: default_refresh_interval_minutes(),
+            refresh_batch_size: default_refresh_batch_size(),
+            instance_presets: vec![],
+            network: NetworkSettings::default(),
+            offline_mode: false,
         }
     }
 }
@@ -372,16 +706,16 @@ impl AuthCookies {
     }
 }
 
-impl Into<Jar> for AuthCookies {
-    fn into(self) -> Jar {
+impl From<AuthCookies> for Jar {
+    fn from(val: AuthCookies) -> Self {
         let jar = Jar::default();
-        if let Some(auth_token) = self.auth_token {
+        if let Some(auth_token) = val.auth_token {
             jar.add_cookie_str(
                 &format!("auth={}", auth_token),
                 &reqwest::Url::parse("https://api.vrchat.cloud").unwrap(),
             );
         }
-        if let Some(two_factor_auth) = self.two_factor_auth {
+        if let Some(two_factor_auth) = val.two_factor_auth {
             jar.add_cookie_str(
                 &format!("twoFactorAuth={}", two_factor_auth),
                 &reqwest::Url::parse("http://api.vrchat.cloud").unwrap(),
@@ -391,6 +725,16 @@ impl Into<Jar> for AuthCookies {
     }
 }
 
+/// A VRChat session stored to switch back to it later, keyed by the user ID
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct StoredAccount {
+    #[serde(rename = "userId")]
+    pub user_id: String,
+    #[serde(rename = "displayName")]
+    pub display_name: String,
+    pub cookies: AuthCookies,
+}
+
 pub struct InitState {
     pub success: bool,
     pub message: String,
@@ -409,7 +753,7 @@ impl InitState {
     pub fn error(message: String) -> Self {
         Self {
             success: false,
-            message: message,
+            message,
             user_id: "".to_string(),
         }
     }
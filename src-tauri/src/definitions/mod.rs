@@ -1,8 +1,10 @@
 mod entities;
 
 pub use entities::{
-    AuthCookies, CardSize, FilterItemSelectorStarred, FilterItemSelectorStarredType, FolderModel,
-    FolderRemovalPreference, InitState, PatreonData, PatreonVRChatNames, Platform, PreferenceModel,
-    ShareInfo, WorldApiData, WorldBlacklist, WorldDetails, WorldDisplayData, WorldModel,
+    AuthCookies, CardSize, FavoriteGroupSync, FilterItemSelectorStarred,
+    FilterItemSelectorStarredType, FolderModel, FolderRemovalPreference, InitState,
+    InstanceOptions, InstancePreset, NetworkSettings, PatreonData, PatreonVRChatNames, Platform,
+    PreferenceModel, ScheduleStatus, ScheduledInstance, ShareInfo, StoredAccount, WorldApiData,
+    WorldAvailability, WorldBlacklist, WorldChange, WorldDetails, WorldDisplayData, WorldModel,
     WorldUserData,
 };
@@ -9,6 +9,7 @@ pub enum AppError {
     Network(NetworkError),
     Api(ApiError),
     Entity(EntityError),
+    Query(QueryError),
 }
 
 /// Represents errors that can occur during file operations
@@ -29,6 +30,8 @@ pub enum FileError {
     AccessDenied,
     /// Error occurred while writing to a file
     FileWriteError,
+    /// The library database could not be opened, read or written
+    DatabaseError(String),
 }
 
 #[derive(Debug, Serialize, Clone)]
@@ -61,10 +64,15 @@ pub enum NetworkError {
 
 #[derive(Debug, Serialize, Clone)]
 pub enum ApiError {
-    /// API authentication failed
+    /// API authentication failed, e.g. because the session expired
     AuthenticationFailed,
-    /// API rate limit exceeded
-    RateLimitExceeded,
+    /// API rate limit exceeded, or still backing off from it
+    RateLimitExceeded {
+        operation: String,
+        retry_in_seconds: u64,
+    },
+    /// The requested object does not exist, e.g. a deleted world
+    NotFound,
     /// Invalid API request parameters
     InvalidRequest(&'static str),
     /// API returned error response
@@ -89,9 +97,20 @@ pub enum EntityError {
     InvalidTimestamp(String),
 }
 
-pub enum ServiceErrors {
-    /// Failed to lock mutex
-    LockError,
+#[derive(Debug, Serialize, Clone)]
+pub enum QueryError {
+    /// The query ended where a value or closing parenthesis was expected
+    UnexpectedEnd,
+    /// A quoted value is missing its closing quote
+    UnterminatedQuote(usize),
+    /// A token appeared where it is not allowed
+    UnexpectedToken { position: usize, token: String },
+    /// The query refers to a field that does not exist
+    UnknownField(String),
+    /// The value can't be used with the field
+    InvalidValue { field: String, value: String },
+    /// The comparison can't be used with the field
+    InvalidOperator { field: String, operator: String },
 }
 
 impl std::error::Error for FileError {}
@@ -124,6 +143,7 @@ impl fmt::Display for FileError {
             FileError::DecryptionError => write!(f, "failed to decrypt file"),
             FileError::AccessDenied => write!(f, "access to file denied"),
             FileError::FileWriteError => write!(f, "failed to write file"),
+            FileError::DatabaseError(msg) => write!(f, "database error: {}", msg),
         }
     }
 }
@@ -169,7 +189,15 @@ impl fmt::Display for ApiError {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match self {
             ApiError::AuthenticationFailed => write!(f, "authentication failed"),
-            ApiError::RateLimitExceeded => write!(f, "rate limit exceeded"),
+            ApiError::RateLimitExceeded {
+                operation,
+                retry_in_seconds,
+            } => write!(
+                f,
+                "rate limit exceeded for {}, please try again in {} seconds",
+                operation, retry_in_seconds
+            ),
+            ApiError::NotFound => write!(f, "not found"),
             ApiError::InvalidRequest(msg) => write!(f, "invalid request: {}", msg),
             ApiError::ResponseError(msg) => write!(f, "API error: {}", msg),
             ApiError::VersionMismatch => write!(f, "API version mismatch"),
@@ -192,6 +220,44 @@ impl fmt::Display for EntityError {
     }
 }
 
+impl std::error::Error for QueryError {}
+
+impl fmt::Display for QueryError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            QueryError::UnexpectedEnd => write!(f, "unexpected end of query"),
+            QueryError::UnterminatedQuote(pos) => {
+                write!(f, "unterminated quote at position {}", pos)
+            }
+            QueryError::UnexpectedToken { position, token } => {
+                write!(f, "unexpected '{}' at position {}", token, position)
+            }
+            QueryError::UnknownField(field) => write!(f, "unknown field: {}", field),
+            QueryError::InvalidValue { field, value } => {
+                write!(f, "invalid value for {}: {}", field, value)
+            }
+            QueryError::InvalidOperator { field, operator } => {
+                write!(f, "'{}' can't be used with {}", operator, field)
+            }
+        }
+    }
+}
+
+impl AppError {
+    /// Whether the error means the server could not be reached, so the request can be retried
+    /// once the connection is back, rather than that the server rejected it
+    pub fn is_unreachable(&self) -> bool {
+        matches!(
+            self,
+            AppError::Network(
+                NetworkError::Timeout
+                    | NetworkError::ConnectionFailed
+                    | NetworkError::HttpError(502..=504)
+            )
+        )
+    }
+}
+
 impl std::error::Error for AppError {}
 
 impl fmt::Display for AppError {
@@ -203,6 +269,7 @@ impl fmt::Display for AppError {
             AppError::Network(e) => write!(f, "Network error: {}", e),
             AppError::Api(e) => write!(f, "API error: {}", e),
             AppError::Entity(e) => write!(f, "Entity error: {}", e),
+            AppError::Query(e) => write!(f, "Query error: {}", e),
         }
     }
 }
@@ -237,12 +304,25 @@ impl From<ApiError> for AppError {
     }
 }
 
+/// Lets functions that still report errors as strings use `?` on typed errors
+impl From<AppError> for String {
+    fn from(error: AppError) -> Self {
+        error.to_string()
+    }
+}
+
 impl From<EntityError> for AppError {
     fn from(error: EntityError) -> Self {
         AppError::Entity(error)
     }
 }
 
+impl From<QueryError> for AppError {
+    fn from(error: QueryError) -> Self {
+        AppError::Query(error)
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -278,6 +358,27 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_rate_limit_error_display() {
+        let error: AppError = ApiError::RateLimitExceeded {
+            operation: "get_world_by_id".to_string(),
+            retry_in_seconds: 30,
+        }
+        .into();
+        assert_eq!(
+            String::from(error),
+            "API error: rate limit exceeded for get_world_by_id, please try again in 30 seconds"
+        );
+    }
+
+    #[test]
+    fn test_unreachable_errors() {
+        assert!(AppError::from(NetworkError::ConnectionFailed).is_unreachable());
+        assert!(AppError::from(NetworkError::HttpError(503)).is_unreachable());
+        assert!(!AppError::from(NetworkError::HttpError(500)).is_unreachable());
+        assert!(!AppError::from(ApiError::NotFound).is_unreachable());
+    }
+
     #[test]
     fn test_error_debug_format() {
         assert!(format!("{:?}", NetworkError::Timeout).contains("Timeout"));
@@ -1,22 +1,30 @@
-use api::auth::VRChatAPIClientAuthenticator;
+use app_state::AppState;
 use commands::generate_tauri_specta_builder;
-use definitions::{FolderModel, InitState, PreferenceModel, WorldModel};
-use directories::BaseDirs;
-use services::ApiService;
+use definitions::{InitState, PreferenceModel};
+use reqwest::cookie::Jar;
+use services::{
+    ApiService, EncryptionService, FileService, InstanceScheduler, Outbox, OutboxService,
+    RefreshService, SessionWatcher, ThumbnailCache, WorldCache,
+};
 use specta_typescript::{BigIntExportBehavior, Typescript};
-use state::InitCell;
-use std::sync::{Arc, RwLock};
+use std::sync::Arc;
 use tauri::async_runtime::Mutex;
+use tauri::http::{header, Response, StatusCode, Uri};
 use tauri::{AppHandle, Manager};
-use tauri_plugin_updater::UpdaterExt;
 use tauri_specta::collect_events;
 
-use crate::services::memo_manager::MemoManager;
+use crate::services::instance_scheduler::ScheduledInstanceResult;
+use crate::services::instance_watcher::InstanceAvailable;
+use crate::services::outbox_service::OutboxItemChanged;
+use crate::services::refresh_service::WorldRefreshProgress;
+use crate::services::session_watcher::SessionExpired;
+use crate::services::thumbnail_cache::{self, THUMBNAIL_SCHEME};
 use crate::task::cancellable_task::TaskContainer;
 use crate::task::definitions::TaskStatusChanged;
 use crate::updater::update_handler::{UpdateChannel, UpdateHandler, UpdateProgress};
 
 mod api;
+mod app_state;
 mod backup;
 mod changelog;
 mod commands;
@@ -28,19 +36,18 @@ mod services;
 mod task;
 mod updater;
 
-static PREFERENCES: InitCell<RwLock<PreferenceModel>> = InitCell::new();
-static FOLDERS: InitCell<RwLock<Vec<FolderModel>>> = InitCell::new();
-static WORLDS: InitCell<RwLock<Vec<WorldModel>>> = InitCell::new();
-static INITSTATE: InitCell<tokio::sync::RwLock<InitState>> = InitCell::new();
-static AUTHENTICATOR: InitCell<tokio::sync::RwLock<VRChatAPIClientAuthenticator>> = InitCell::new();
-static RATE_LIMIT_STORE: InitCell<RwLock<api::RateLimitStore>> = InitCell::new();
-static MEMO_MANAGER: InitCell<RwLock<MemoManager>> = InitCell::new();
-
 /// Application entry point for all platforms
 #[cfg_attr(mobile, tauri::mobile_entry_point)]
 pub fn run() {
-    let builder =
-        generate_tauri_specta_builder().events(collect_events![TaskStatusChanged, UpdateProgress]);
+    let builder = generate_tauri_specta_builder().events(collect_events![
+        TaskStatusChanged,
+        UpdateProgress,
+        WorldRefreshProgress,
+        InstanceAvailable,
+        ScheduledInstanceResult,
+        SessionExpired,
+        OutboxItemChanged
+    ]);
 
     #[cfg(debug_assertions)]
     builder
@@ -61,7 +68,7 @@ pub fn run() {
             // when defining deep link schemes at runtime, you must also check `argv` here
         }));
         tauri_builder =
-            tauri_builder.plugin(tauri_plugin_single_instance::init(|app, args, cwd| {
+            tauri_builder.plugin(tauri_plugin_single_instance::init(|app, _args, _cwd| {
                 let _ = app
                     .get_webview_window("main")
                     .expect("no main window")
@@ -77,6 +84,12 @@ pub fn run() {
         .plugin(tauri_plugin_dialog::init())
         .plugin(tauri_plugin_shell::init())
         .invoke_handler(builder.invoke_handler())
+        .register_asynchronous_uri_scheme_protocol(THUMBNAIL_SCHEME, |ctx, request, responder| {
+            let app = ctx.app_handle().clone();
+            tauri::async_runtime::spawn(async move {
+                responder.respond(serve_thumbnail(&app, request.uri()).await);
+            });
+        })
         .plugin(
             tauri_plugin_log::Builder::new()
                 .target({
@@ -95,9 +108,8 @@ pub fn run() {
             builder.mount_events(app);
             app.manage(app.handle().clone());
 
-            app.manage(Arc::new(Mutex::new(TaskContainer::new(
-                app.handle().clone(),
-            ))));
+            let task_container = Arc::new(Mutex::new(TaskContainer::new(app.handle().clone())));
+            app.manage(Arc::clone(&task_container));
 
             let handle = app.handle().clone();
             let logs_dir = handle.path().app_log_dir().unwrap();
@@ -110,77 +122,198 @@ pub fn run() {
                 std::env::temp_dir()
             });
             let rate_limit_path = app_data_dir.join("rate_limits.json");
-            RATE_LIMIT_STORE.set(RwLock::new(api::RateLimitStore::load(rate_limit_path)));
+            let rate_limits = api::RateLimitStore::load(rate_limit_path);
             log::info!("Rate limit store initialized");
 
+            let world_cache = WorldCache::load(app_data_dir.join("world_cache.json"));
+            log::info!("World cache loaded");
+
+            let thumbnail_cache = ThumbnailCache::load(
+                app_data_dir.join("thumbnails"),
+                thumbnail_cache::BUDGET_BYTES,
+            );
+            log::info!("Thumbnail cache loaded");
+
+            let outbox = Outbox::load(app_data_dir.join("outbox.json"));
+            log::info!("Outbox loaded");
+
             commands::patreon_cache::init_cache();
             log::info!("Patreon cache initialized");
 
-            if let Err(e) = initialize_app() {
-                log::error!("Failed to initialize app: {}", e);
-            }
+            let app_state = initialize_app(rate_limits, world_cache, thumbnail_cache, outbox);
+            let update_channel = app_state
+                .preferences
+                .read()
+                .expect("Failed to read preferences")
+                .update_channel;
+            let app_state = Arc::new(app_state);
+            app.manage(Arc::clone(&app_state));
 
-            app.manage(Arc::new(Mutex::new(
-                (get_update_handler(
-                    app.handle().clone(),
-                    &PREFERENCES
-                        .get()
-                        .read()
-                        .expect("Failed to read preferences")
-                        .update_channel,
-                )),
-            )));
+            let refresh_handle = app.handle().clone();
+            tauri::async_runtime::spawn(async move {
+                let mut tasks = task_container.lock().await;
+                if let Err(e) =
+                    RefreshService::start(&app_state, &mut tasks, refresh_handle.clone()).await
+                {
+                    log::error!("Failed to start background world refresh: {}", e);
+                }
+                if let Err(e) =
+                    InstanceScheduler::start(&app_state, &mut tasks, refresh_handle.clone())
+                {
+                    log::error!("Failed to start instance scheduler: {}", e);
+                }
+                if let Err(e) =
+                    SessionWatcher::start(&app_state, &mut tasks, refresh_handle.clone()).await
+                {
+                    log::error!("Failed to start session watcher: {}", e);
+                }
+                if let Err(e) = OutboxService::start(&app_state, &mut tasks, refresh_handle).await {
+                    log::error!("Failed to start outbox replay: {}", e);
+                }
+            });
+
+            app.manage(Arc::new(Mutex::new(get_update_handler(
+                app.handle().clone(),
+                &update_channel,
+            ))));
 
             Ok(())
         })
-        .run(tauri::generate_context!())
-        .expect("error while running tauri application");
+        .build(tauri::generate_context!())
+        .expect("error while building tauri application")
+        .run(|app, event| {
+            if let tauri::RunEvent::Exit = event {
+                flush_caches(app);
+            }
+        });
     log::info!("Application started");
 }
 
-fn initialize_app() -> Result<(), String> {
-    match services::initialize_service::initialize_app() {
-        Ok((preferences, folders, worlds, cookies, init_state)) => {
-            let memo_path = BaseDirs::new()
-                .expect("Failed to get base directories")
-                .data_local_dir()
-                .join("VRC_Worlds_Manager_new")
-                .join("memo.json");
-            let memo_manager = MemoManager::load(memo_path)?;
+/// Writes the changes of the caches that are only written now and then
+fn flush_caches(app: &AppHandle) {
+    let Some(state) = app.try_state::<Arc<AppState>>() else {
+        return;
+    };
+    match state.world_cache.write() {
+        Ok(mut cache) => cache.flush(),
+        Err(_) => log::error!("Failed to acquire write lock for world cache"),
+    };
+}
+
+/// Loads the library from disk into a new `AppState`
+/// If loading fails, an empty state is returned, carrying the error in its init state
+fn initialize_app(
+    rate_limits: api::RateLimitStore,
+    world_cache: WorldCache,
+    thumbnail_cache: ThumbnailCache,
+    outbox: Outbox,
+) -> AppState {
+    let storage = services::storage::open_storage();
+    let encryption = EncryptionService::new(FileService::get_app_dir().join("secret_key.json"));
 
+    match services::initialize_service::initialize_app(&*storage, &encryption) {
+        Ok((preferences, folders, worlds, cookies, init_state)) => {
             log::info!("App initialized successfully");
-            PREFERENCES.set(RwLock::new(preferences));
-            FOLDERS.set(RwLock::new(folders));
-            WORLDS.set(RwLock::new(worlds));
-            INITSTATE.set(tokio::sync::RwLock::new(init_state));
-            let cookie_store = ApiService::initialize_with_cookies(cookies.clone());
-            AUTHENTICATOR.set(tokio::sync::RwLock::new(
-                VRChatAPIClientAuthenticator::from_cookie_store(cookie_store),
-            ));
-            MEMO_MANAGER.set(RwLock::new(memo_manager));
-            Ok(())
+            let cookie_store = ApiService::initialize_with_cookies(cookies);
+            AppState::new(
+                preferences,
+                folders,
+                worlds,
+                init_state,
+                cookie_store,
+                rate_limits,
+                world_cache,
+                thumbnail_cache,
+                outbox,
+                storage,
+                encryption,
+            )
         }
         Err(e) => {
-            log::info!("Error initializing app: {}", e);
-            PREFERENCES.set(RwLock::new(PreferenceModel::new()));
-            FOLDERS.set(RwLock::new(vec![]));
-            WORLDS.set(RwLock::new(vec![]));
-            INITSTATE.set(tokio::sync::RwLock::new(InitState::error(e.clone())));
-            AUTHENTICATOR.set(tokio::sync::RwLock::new(VRChatAPIClientAuthenticator::new(
-                String::new(),
-            )));
-            Err(e)
+            log::error!("Failed to initialize app: {}", e);
+            AppState::new(
+                PreferenceModel::new(),
+                vec![],
+                vec![],
+                InitState::error(e),
+                Arc::new(Jar::default()),
+                rate_limits,
+                world_cache,
+                thumbnail_cache,
+                outbox,
+                storage,
+                encryption,
+            )
+        }
+    }
+}
+
+/// Serves the thumbnail of a world to the webview, requested as
+/// `thumbnail://localhost/<world id>?source=<image url>`
+///
+/// The image URL of the world is passed in, so the library doesn't have to be searched.
+/// A thumbnail that is not cached, or was made from an older image, is downloaded first,
+/// unless offline mode is on. If that fails, the cached thumbnail is served even if it
+/// is outdated.
+async fn serve_thumbnail(app: &AppHandle, uri: &Uri) -> Response<Vec<u8>> {
+    let world_id = uri.path().trim_start_matches('/');
+    if !thumbnail_cache::is_valid_world_id(world_id) {
+        return empty_response(StatusCode::BAD_REQUEST);
+    }
+    let Some(state) = app.try_state::<Arc<AppState>>() else {
+        return empty_response(StatusCode::SERVICE_UNAVAILABLE);
+    };
+
+    let offline = state
+        .preferences
+        .read()
+        .map(|preferences| preferences.offline_mode)
+        .unwrap_or(false);
+    let source_url = uri
+        .query()
+        .into_iter()
+        .flat_map(|query| query.split('&'))
+        .find_map(|pair| pair.strip_prefix("source="))
+        .and_then(|url| urlencoding::decode(url).ok())
+        .map(|url| url.into_owned())
+        .filter(|url| thumbnail_cache::is_valid_source_url(url));
+    let cache = Arc::clone(&state.thumbnail_cache);
+    let current = source_url
+        .as_deref()
+        .and_then(|url| cache.get(world_id, Some(url)));
+    let thumbnail = match (current, source_url) {
+        (Some(thumbnail), _) => Some(thumbnail),
+        (None, Some(url)) if !offline => {
+            match cache.fetch(world_id, &url, &state.http_client()).await {
+                Ok(thumbnail) => Some(thumbnail),
+                Err(_) => cache.get(world_id, None),
+            }
         }
+        (None, _) => cache.get(world_id, None),
+    };
+
+    match thumbnail {
+        Some(thumbnail) => Response::builder()
+            .header(header::CONTENT_TYPE, "image/jpeg")
+            .body(thumbnail)
+            .unwrap_or_else(|_| empty_response(StatusCode::INTERNAL_SERVER_ERROR)),
+        None => empty_response(StatusCode::NOT_FOUND),
     }
 }
 
+fn empty_response(status: StatusCode) -> Response<Vec<u8>> {
+    let mut response = Response::new(vec![]);
+    *response.status_mut() = status;
+    response
+}
+
 fn get_update_handler(app: AppHandle, channel: &UpdateChannel) -> UpdateHandler {
     tauri::async_runtime::block_on(async move {
         let mut update_handler = UpdateHandler::new(app);
         let result = update_handler.check_for_update(channel).await;
 
-        if result.is_err() {
-            log::error!("Failed to check for update: {}", result.unwrap_err());
+        if let Err(e) = result {
+            log::error!("Failed to check for update: {}", e);
         }
 
         update_handler
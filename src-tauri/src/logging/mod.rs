@@ -1,4 +1,7 @@
+// The log buffer is not read by the frontend yet
+#[allow(dead_code)]
 mod definitions;
+#[allow(dead_code)]
 mod worker;
 
 pub use worker::purge_outdated_logs;
@@ -10,7 +10,7 @@
  * Further modifications by @Raifa21
  */
 
-use std::path::PathBuf;
+use std::path::Path;
 use std::sync::Mutex;
 
 use ringbuffer::{ConstGenericRingBuffer, RingBuffer};
@@ -27,7 +27,7 @@ pub fn get_logs() -> Vec<LogEntry> {
     buffer.iter().cloned().collect()
 }
 
-pub fn purge_outdated_logs(logs_dir: &PathBuf) -> Result<(), std::io::Error> {
+pub fn purge_outdated_logs(logs_dir: &Path) -> Result<(), std::io::Error> {
     log::info!("Checking logs in directory: {}", logs_dir.display());
     let readdir = logs_dir.read_dir()?;
 
@@ -41,8 +41,7 @@ pub fn purge_outdated_logs(logs_dir: &PathBuf) -> Result<(), std::io::Error> {
 
                 // Handle new format log files
                 if let Some(file_stem) = path.file_stem().and_then(|s| s.to_str()) {
-                    if file_stem.starts_with("vrc-worlds-manager-") {
-                        let timestamp = &file_stem[19..];
+                    if let Some(timestamp) = file_stem.strip_prefix("vrc-worlds-manager-") {
                         if is_outdated_timestamp(timestamp) {
                             log::info!("purging outdated log file: {}", path.display());
                             if let Err(e) = std::fs::remove_file(&path) {
@@ -62,7 +61,7 @@ pub fn purge_outdated_logs(logs_dir: &PathBuf) -> Result<(), std::io::Error> {
 }
 
 fn is_outdated_timestamp(timestamp: &str) -> bool {
-    let timestamp = match chrono::NaiveDateTime::parse_from_str(&timestamp, "%Y-%m-%d_%H-%M-%S.%6f")
+    let timestamp = match chrono::NaiveDateTime::parse_from_str(timestamp, "%Y-%m-%d_%H-%M-%S.%6f")
     {
         Ok(timestamp) => timestamp,
         Err(e) => {
@@ -73,7 +72,7 @@ fn is_outdated_timestamp(timestamp: &str) -> bool {
 
     let timestamp = chrono::DateTime::<chrono::Utc>::from_naive_utc_and_offset(
         timestamp,
-        chrono::Utc::now().offset().clone(),
+        *chrono::Utc::now().offset(),
     );
 
     let threshold = chrono::Utc::now() - chrono::Duration::days(LOG_RETENTION_DAYS);
@@ -16,7 +16,7 @@ where
     }
 }
 
-#[derive(Debug, Deserialize, Clone)]
+#[derive(Debug, Deserialize, Clone, Default)]
 pub struct PreviousWorldModel {
     #[serde(rename = "ThumbnailImageUrl")]
     pub thumbnail_image_url: String,
@@ -68,26 +68,6 @@ fn default_user_memo() -> Option<String> {
     Some("".to_string())
 }
 
-impl Default for PreviousWorldModel {
-    fn default() -> Self {
-        PreviousWorldModel {
-            thumbnail_image_url: String::default(),
-            world_name: String::default(),
-            world_id: String::default(),
-            author_name: String::default(),
-            author_id: String::default(),
-            capacity: 0,
-            last_update: String::default(),
-            description: String::default(),
-            visits: None,
-            favorites: 0,
-            date_added: None,
-            platform: None,
-            user_memo: None,
-        }
-    }
-}
-
 #[derive(Debug, Deserialize)]
 pub struct PreviousFolderCollection {
     #[serde(rename = "Name")]
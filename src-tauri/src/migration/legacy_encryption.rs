@@ -0,0 +1,95 @@
+use aes::Aes256;
+use base64::{engine::general_purpose::STANDARD, Engine};
+use cbc::{
+    cipher::{block_padding::Pkcs7, BlockDecryptMut, KeyIvInit},
+    Decryptor,
+};
+
+/// The key and IV the old VRC Worlds Manager, and secrets before version 2, were encrypted with
+const ENCRYPTION_KEY: Option<&str> = option_env!("ENCRYPTION_KEY");
+const ENCRYPTION_IV: Option<&str> = option_env!("ENCRYPTION_IV");
+
+fn get_encryption_keys() -> Result<(Vec<u8>, Vec<u8>), String> {
+    let key = ENCRYPTION_KEY
+        .ok_or_else(|| "ENCRYPTION_KEY environment variable not set at compile time".to_string())?;
+
+    let iv = ENCRYPTION_IV
+        .ok_or_else(|| "ENCRYPTION_IV environment variable not set at compile time".to_string())?;
+
+    // Convert from base64 to bytes for AES
+    let key = STANDARD
+        .decode(key)
+        .map_err(|e| format!("Failed to decode key: {}", e))?;
+
+    let iv = STANDARD
+        .decode(iv)
+        .map_err(|e| format!("Failed to decode iv: {}", e))?;
+
+    // Validate key and IV sizes
+    if key.len() != 32 {
+        return Err(format!(
+            "Invalid key length: {}. Expected 32 bytes",
+            key.len()
+        ));
+    }
+    if iv.len() != 16 {
+        return Err(format!(
+            "Invalid IV length: {}. Expected 16 bytes",
+            iv.len()
+        ));
+    }
+
+    Ok((key, iv))
+}
+
+/// Decrypts data encrypted with the static AES-CBC key
+/// Nothing is encrypted this way anymore, it is only read to import old data
+pub(super) fn decrypt(ciphertext: &str) -> Result<String, String> {
+    let (key, iv) = get_encryption_keys()?;
+    decrypt_with(ciphertext, &key, &iv)
+}
+
+fn decrypt_with(ciphertext: &str, key: &[u8], iv: &[u8]) -> Result<String, String> {
+    let encrypted = STANDARD
+        .decode(ciphertext)
+        .map_err(|e| format!("Failed to decode base64: {}", e))?;
+
+    type Aes256CbcDec = Decryptor<Aes256>;
+    let cipher = Aes256CbcDec::new(key.into(), iv.into());
+
+    let mut buffer = vec![0u8; encrypted.len()];
+    let decrypted_data_len = cipher
+        .decrypt_padded_b2b_mut::<Pkcs7>(&encrypted, &mut buffer)
+        .map_err(|e| format!("Decryption failed: {}", e))?
+        .len();
+
+    // Convert decrypted bytes to a UTF-8 string
+    let decrypted_str = String::from_utf8(buffer[..decrypted_data_len].to_vec())
+        .map_err(|e| format!("Invalid UTF-8: {}", e))?;
+    // Return the decrypted string
+    Ok(decrypted_str)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const KEY: &[u8; 32] = b"0123456789abcdef0123456789abcdef";
+    const IV: &[u8; 16] = b"fedcba9876543210";
+
+    #[test]
+    fn test_decryption() {
+        let ciphertext = "yf0hinqYMEtpcOh5nPfsugym59m51SVN/8dZ65nY8jebZOlvQ8hbI+p69tpiGZZw";
+        assert_eq!(
+            decrypt_with(ciphertext, KEY, IV).unwrap(),
+            r#"[{"WorldId":"wrld_test","WorldName":"Test"}]"#
+        );
+    }
+
+    #[test]
+    fn test_decryption_with_wrong_key_fails() {
+        let ciphertext = "yf0hinqYMEtpcOh5nPfsugym59m51SVN/8dZ65nY8jebZOlvQ8hbI+p69tpiGZZw";
+        assert!(decrypt_with(ciphertext, b"fedcba9876543210fedcba9876543210", IV).is_err());
+        assert!(decrypt_with("not base64!", KEY, IV).is_err());
+    }
+}
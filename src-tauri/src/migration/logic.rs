@@ -1,7 +1,9 @@
-use crate::definitions::{FolderModel, Platform, WorldApiData, WorldModel, WorldUserData};
+use crate::definitions::{
+    FolderModel, Platform, WorldApiData, WorldAvailability, WorldModel, WorldUserData,
+};
+use crate::migration::legacy_encryption;
 use crate::migration::{PreviousFolderCollection, PreviousMetadata, PreviousWorldModel};
-use crate::services::EncryptionService;
-use crate::services::FileService;
+use crate::services::{Changes, Storage};
 use chrono::{DateTime, Duration, Utc};
 use directories::BaseDirs;
 use std::collections::{HashMap, HashSet};
@@ -48,17 +50,33 @@ impl MigrationService {
         }
     }
 
+    /// Decrypts a secret stored before the per-install encryption, so it can be
+    /// encrypted again with the current format
+    ///
+    /// # Arguments
+    /// * `ciphertext` - The secret, encrypted with the static key
+    ///
+    /// # Returns
+    /// Returns the decrypted secret
+    ///
+    /// # Errors
+    /// Returns an error message if the secret could not be decrypted
+    pub fn decrypt_legacy_secret(ciphertext: &str) -> Result<String, String> {
+        legacy_encryption::decrypt(ciphertext)
+    }
+
     /// checks if worlds and folders data already exists, to avoid overwriting
     ///
+    /// # Arguments
+    /// * `storage` - The storage holding the library
+    ///
     /// # Returns
     /// Returns a boolean indicating if the worlds and folders data already exists
     ///
     /// # Errors
     /// Returns an error message if the worlds and folders data could not be checked
-    pub fn check_existing_data() -> Result<(bool, bool), String> {
-        let (_, folders_path, worlds_path, _) = FileService::get_paths();
-
-        Ok((folders_path.exists(), worlds_path.exists()))
+    pub fn check_existing_data(storage: &dyn Storage) -> Result<(bool, bool), String> {
+        storage.existing_data().map_err(|e| e.to_string())
     }
 
     async fn read_data_files(
@@ -73,13 +91,13 @@ impl MigrationService {
     }
 
     fn parse_world_data(worlds_json: &str) -> Result<Vec<PreviousWorldModel>, String> {
-        let decrypted = EncryptionService::decrypt_aes(worlds_json)
+        let decrypted = legacy_encryption::decrypt(worlds_json)
             .map_err(|e| format!("Failed to decrypt worlds: {}", e))?;
         serde_json::from_str(&decrypted).map_err(|e| format!("Failed to parse worlds: {}", e))
     }
 
     fn parse_folder_data(folders_json: &str) -> Result<Vec<PreviousFolderCollection>, String> {
-        let decrypted = EncryptionService::decrypt_aes(folders_json)
+        let decrypted = legacy_encryption::decrypt(folders_json)
             .map_err(|e| format!("Failed to decrypt folders: {}", e))?;
 
         // Parse the JSON into a Vec of serde_json::Value
@@ -111,7 +129,7 @@ impl MigrationService {
             .iter()
             .filter_map(|w| w.date_added)
             .min()
-            .unwrap_or_else(|| Utc::now());
+            .unwrap_or_else(Utc::now);
 
         let null_count = worlds.iter().filter(|w| w.date_added.is_none()).count();
 
@@ -184,20 +202,18 @@ impl MigrationService {
                         for p in old_platforms {
                             let lp = p.to_lowercase();
                             match lp.as_str() {
-                                "pc" | "standalonewindows" => {
-                                    if !new_platforms.contains(&Platform::StandaloneWindows) {
-                                        new_platforms.push(Platform::StandaloneWindows);
-                                    }
+                                "pc" | "standalonewindows"
+                                    if !new_platforms.contains(&Platform::StandaloneWindows) =>
+                                {
+                                    new_platforms.push(Platform::StandaloneWindows);
                                 }
-                                "quest" | "android" => {
-                                    if !new_platforms.contains(&Platform::Android) {
-                                        new_platforms.push(Platform::Android);
-                                    }
+                                "quest" | "android"
+                                    if !new_platforms.contains(&Platform::Android) =>
+                                {
+                                    new_platforms.push(Platform::Android);
                                 }
-                                "ios" => {
-                                    if !new_platforms.contains(&Platform::IOS) {
-                                        new_platforms.push(Platform::IOS);
-                                    }
+                                "ios" if !new_platforms.contains(&Platform::Ios) => {
+                                    new_platforms.push(Platform::Ios);
                                 }
                                 _ => {}
                             }
@@ -223,6 +239,9 @@ impl MigrationService {
                 folders: Vec::new(),
                 hidden,
                 custom_tags: vec![],
+                history: vec![],
+                availability: WorldAvailability::Unknown,
+                last_seen: None,
             },
         }
     }
@@ -233,7 +252,7 @@ impl MigrationService {
 
         // First pass - find duplicates and their positions
         for (idx, world) in old_worlds.iter().enumerate() {
-            if let Some((_, first_idx)) = unique_worlds.get(&world.world_id) {
+            if let Some((_, _first_idx)) = unique_worlds.get(&world.world_id) {
                 if first_duplicate_idx.is_none() {
                     first_duplicate_idx = Some(idx);
                     break;
@@ -267,6 +286,9 @@ impl MigrationService {
     /// # Arguments
     /// * `path_to_worlds` - The path to the old VRC Worlds Manager Worlds file
     /// * `path_to_folders` - The path to the old VRC Worlds Manager Folders file
+    /// * `worlds` - The list of worlds, as a RwLock
+    /// * `folders` - The list of folders, as a RwLock
+    /// * `storage` - The storage to write the migrated library to
     ///
     /// # Errors
     /// Returns an error message if the old VRC Worlds Manager Data could not be migrated
@@ -275,6 +297,7 @@ impl MigrationService {
         path_to_folders: String,
         worlds: &RwLock<Vec<WorldModel>>,
         folders: &RwLock<Vec<FolderModel>>,
+        storage: &dyn Storage,
     ) -> Result<(), String> {
         let (worlds_content, folders_content) =
             Self::read_data_files(&path_to_worlds, &path_to_folders).await?;
@@ -347,6 +370,9 @@ impl MigrationService {
                     folder_name: folder.name,
                     world_ids,
                     share: None,
+                    query: None,
+                    parent: None,
+                    favorite_sync: None,
                 });
             }
         }
@@ -360,7 +386,9 @@ impl MigrationService {
             worlds_lock.clear();
             log::info!("Cleared existing worlds data");
             worlds_lock.extend(new_worlds);
-            FileService::write_worlds(&*worlds_lock).map_err(|e| e.to_string())?;
+            storage
+                .write_worlds(&worlds_lock, Changes::All)
+                .map_err(|e| e.to_string())?;
             log::info!("Retrieved {} worlds", worlds_lock.len());
         }
         {
@@ -371,7 +399,9 @@ impl MigrationService {
             folders_lock.clear();
             log::info!("Cleared existing folders data");
             folders_lock.extend(new_folders);
-            FileService::write_folders(&*folders_lock).map_err(|e| e.to_string())?;
+            storage
+                .write_folders(&folders_lock, Changes::All)
+                .map_err(|e| e.to_string())?;
             log::info!("Retrieved {} folders", folders_lock.len());
         }
 
@@ -398,7 +428,7 @@ impl MigrationService {
                 .await
                 .map_err(|e| format!("Failed to read data files: {}", e))?;
 
-        let mut old_worlds = Self::parse_world_data(&worlds_content)
+        let old_worlds = Self::parse_world_data(&worlds_content)
             .map_err(|e| format!("Failed to parse worlds: {}", e))?;
         let old_folders = Self::parse_folder_data(&folders_content)
             .map_err(|e| format!("Failed to parse folders: {}", e))?;
@@ -465,7 +495,7 @@ mod tests {
             },
         ];
 
-        let (earliest, dates) = MigrationService::calculate_dates(&worlds);
+        let (_earliest, dates) = MigrationService::calculate_dates(&worlds);
 
         assert_eq!(dates.len(), 3);
         assert!(
@@ -492,7 +522,7 @@ mod tests {
             },
         ];
 
-        let (earliest, dates) = MigrationService::calculate_dates(&worlds);
+        let (_earliest, dates) = MigrationService::calculate_dates(&worlds);
 
         assert_eq!(dates.len(), 3);
         assert!(dates[0] < dates[1], "Sequential ordering");
@@ -503,7 +533,7 @@ mod tests {
     #[test]
     fn test_calculate_dates_empty() {
         let worlds: Vec<PreviousWorldModel> = vec![];
-        let (earliest, dates) = MigrationService::calculate_dates(&worlds);
+        let (_earliest, dates) = MigrationService::calculate_dates(&worlds);
         assert_eq!(dates.len(), 0);
     }
 
@@ -640,7 +670,7 @@ mod tests {
         "#;
 
         // Parse the JSON into a Vec of serde_json::Value
-        let mut folders: Vec<serde_json::Value> = serde_json::from_str(&decrypted)
+        let mut folders: Vec<serde_json::Value> = serde_json::from_str(decrypted)
             .map_err(|e| format!("Failed to parse decrypted folders JSON: {}", e))?;
 
         // Iterate through each folder and filter out invalid worlds
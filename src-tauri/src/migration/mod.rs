@@ -1,4 +1,5 @@
 mod definitions;
+mod legacy_encryption;
 mod logic;
 
 pub use definitions::{PreviousFolderCollection, PreviousMetadata, PreviousWorldModel};
@@ -0,0 +1,266 @@
+use std::sync::Arc;
+
+use reqwest::cookie::Jar;
+
+use crate::api::auth::{self, VRChatAPIClientAuthenticator};
+use crate::definitions::{AuthCookies, InitState, StoredAccount};
+use crate::services::{ApiService, EncryptionService, FileService};
+
+/// A stored VRChat account, as shown in the account switcher
+#[derive(Clone, Debug, serde::Serialize, specta::Type)]
+pub struct AccountInfo {
+    pub user_id: String,
+    pub display_name: String,
+    /// Whether this is the account the API requests are made with
+    pub active: bool,
+}
+
+/// Keeps the sessions of every account the user logged in with, so they can switch
+/// between them without logging in again
+///
+/// The active session stays in auth.json, and every session, including the active
+/// one, is kept in accounts.json keyed by the user ID.
+pub struct AccountService;
+
+impl AccountService {
+    /// Lists the stored accounts
+    ///
+    /// # Arguments
+    /// * `active_user_id` - The ID of the logged in user, or an empty string if not logged in
+    /// * `encryption` - The service the stored sessions are encrypted with
+    ///
+    /// # Returns
+    /// The stored accounts, in the order they were first logged in with
+    ///
+    /// # Errors
+    /// Returns an error if the accounts file could not be read
+    pub fn list(
+        active_user_id: &str,
+        encryption: &EncryptionService,
+    ) -> Result<Vec<AccountInfo>, String> {
+        let accounts = FileService::read_accounts(encryption).map_err(|e| e.to_string())?;
+        Ok(accounts
+            .into_iter()
+            .map(|account| AccountInfo {
+                active: account.user_id == active_user_id,
+                user_id: account.user_id,
+                display_name: account.display_name,
+            })
+            .collect())
+    }
+
+    /// Stores the session of a logged in user, replacing their previous session
+    /// Errors are logged, as the login itself succeeded
+    ///
+    /// # Arguments
+    /// * `user_id` - The ID of the logged in user
+    /// * `display_name` - The display name of the logged in user
+    /// * `cookies` - The session cookies of the user
+    /// * `encryption` - The service the stored sessions are encrypted with
+    pub fn remember(
+        user_id: &str,
+        display_name: &str,
+        cookies: &AuthCookies,
+        encryption: &EncryptionService,
+    ) {
+        let mut accounts = match FileService::read_accounts(encryption) {
+            Ok(accounts) => accounts,
+            Err(e) => {
+                log::error!("Failed to read stored accounts: {}", e);
+                return;
+            }
+        };
+        Self::upsert(
+            &mut accounts,
+            StoredAccount {
+                user_id: user_id.to_string(),
+                display_name: display_name.to_string(),
+                cookies: cookies.clone(),
+            },
+        );
+        if let Err(e) = FileService::write_accounts(&accounts, encryption) {
+            log::error!("Failed to write stored accounts: {}", e);
+        }
+    }
+
+    /// Drops the stored session of a user
+    ///
+    /// # Arguments
+    /// * `user_id` - The ID of the user to drop
+    /// * `encryption` - The service the stored sessions are encrypted with
+    ///
+    /// # Errors
+    /// Returns an error if the accounts file could not be read or written
+    pub fn forget(user_id: &str, encryption: &EncryptionService) -> Result<(), String> {
+        let mut accounts = FileService::read_accounts(encryption).map_err(|e| e.to_string())?;
+        let count = accounts.len();
+        accounts.retain(|account| account.user_id != user_id);
+        if accounts.len() == count {
+            return Ok(());
+        }
+        FileService::write_accounts(&accounts, encryption).map_err(|e| e.to_string())
+    }
+
+    /// Replaces the stored account with the same user ID, or appends a new one
+    ///
+    /// # Arguments
+    /// * `accounts` - The stored accounts
+    /// * `account` - The account to store
+    pub fn upsert(accounts: &mut Vec<StoredAccount>, account: StoredAccount) {
+        match accounts.iter_mut().find(|a| a.user_id == account.user_id) {
+            Some(existing) => *existing = account,
+            None => accounts.push(account),
+        }
+    }
+
+    /// Detaches the active session without logging it out, so another account can log in
+    /// The detached session stays stored and can be switched back to
+    ///
+    /// # Arguments
+    /// * `auth` - The authenticator of the active session
+    /// * `init` - The init state holding the ID of the logged in user
+    /// * `encryption` - The service the stored sessions are encrypted with
+    ///
+    /// # Errors
+    /// Returns an error if the active session could not be cleared from disk
+    pub async fn add(
+        auth: &tokio::sync::RwLock<VRChatAPIClientAuthenticator>,
+        init: &tokio::sync::RwLock<InitState>,
+        encryption: &EncryptionService,
+    ) -> Result<(), String> {
+        let mut auth_lock = auth.write().await;
+        FileService::write_auth(&AuthCookies::new(), encryption).map_err(|e| e.to_string())?;
+        init.write().await.user_id = String::new();
+
+        let context = auth_lock.get_context();
+        *auth_lock = VRChatAPIClientAuthenticator::from_context(
+            context.with_cookie(Arc::new(Jar::default())),
+        );
+        Ok(())
+    }
+
+    /// Makes a stored account the active one
+    /// The active session is only replaced once the stored session is verified
+    ///
+    /// # Arguments
+    /// * `user_id` - The ID of the account to switch to
+    /// * `auth` - The authenticator of the active session
+    /// * `init` - The init state holding the ID of the logged in user
+    /// * `encryption` - The service the stored sessions are encrypted with
+    ///
+    /// # Errors
+    /// Returns an error if the account is not stored, or its session has expired
+    pub async fn switch(
+        user_id: &str,
+        auth: &tokio::sync::RwLock<VRChatAPIClientAuthenticator>,
+        init: &tokio::sync::RwLock<InitState>,
+        encryption: &EncryptionService,
+    ) -> Result<(), String> {
+        let account = FileService::read_accounts(encryption)
+            .map_err(|e| e.to_string())?
+            .into_iter()
+            .find(|account| account.user_id == user_id)
+            .ok_or_else(|| format!("Account not found: {}", user_id))?;
+
+        let mut auth_lock = auth.write().await;
+        let context = auth_lock
+            .get_context()
+            .with_cookie(ApiService::initialize_with_cookies(account.cookies));
+        let mut authenticator = VRChatAPIClientAuthenticator::from_context(context);
+
+        match authenticator.verify_token().await {
+            Ok(auth::VRChatAuthStatus::Success(cookies, user)) => {
+                FileService::write_auth(&cookies, encryption).map_err(|e| e.to_string())?;
+                log::info!("Switched to account {}, ID: {}", user.username, user.id);
+                Self::remember(&user.id, &user.username, &cookies, encryption);
+                authenticator.update_user_info(user.username);
+                *auth_lock = authenticator;
+                init.write().await.user_id = user.id;
+                Ok(())
+            }
+            Ok(_) => Err(format!(
+                "The session of {} has expired, please log in again",
+                account.display_name
+            )),
+            Err(e) => Err(format!("Failed to switch account: {}", e)),
+        }
+    }
+
+    /// Unlocks the stored sessions with the user's passphrase, and restores the active session
+    /// The session still has to be verified by logging in with the token
+    ///
+    /// # Arguments
+    /// * `passphrase` - The passphrase the sessions are protected by
+    /// * `auth` - The authenticator to restore the active session into
+    /// * `encryption` - The service the stored sessions are encrypted with
+    ///
+    /// # Errors
+    /// Returns an error if the passphrase is wrong, or the session could not be read
+    pub async fn unlock(
+        passphrase: &str,
+        auth: &tokio::sync::RwLock<VRChatAPIClientAuthenticator>,
+        encryption: &EncryptionService,
+    ) -> Result<(), String> {
+        encryption.unlock(passphrase)?;
+        let cookies = FileService::read_auth(encryption).map_err(|e| e.to_string())?;
+
+        let mut auth_lock = auth.write().await;
+        let context = auth_lock
+            .get_context()
+            .with_cookie(ApiService::initialize_with_cookies(cookies));
+        *auth_lock = VRChatAPIClientAuthenticator::from_context(context);
+        Ok(())
+    }
+
+    /// Removes a stored account, logging it out first if it is the active one
+    ///
+    /// # Arguments
+    /// * `user_id` - The ID of the account to remove
+    /// * `auth` - The authenticator of the active session
+    /// * `init` - The init state holding the ID of the logged in user
+    /// * `encryption` - The service the stored sessions are encrypted with
+    ///
+    /// # Errors
+    /// Returns an error if the logout fails, or the accounts file could not be written
+    pub async fn remove(
+        user_id: &str,
+        auth: &tokio::sync::RwLock<VRChatAPIClientAuthenticator>,
+        init: &tokio::sync::RwLock<InitState>,
+        encryption: &EncryptionService,
+    ) -> Result<(), String> {
+        let active = init.read().await.user_id == user_id;
+        if active {
+            ApiService::logout(auth, init, encryption).await
+        } else {
+            Self::forget(user_id, encryption)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn account(user_id: &str, display_name: &str) -> StoredAccount {
+        StoredAccount {
+            user_id: user_id.to_string(),
+            display_name: display_name.to_string(),
+            cookies: AuthCookies::new(),
+        }
+    }
+
+    #[test]
+    fn test_upsert_replaces_session_of_same_user() {
+        let mut accounts = vec![account("usr_main", "Main"), account("usr_alt", "Alt")];
+
+        let mut relogged = account("usr_main", "Main renamed");
+        relogged.cookies.auth_token = Some("authcookie_new".to_string());
+        AccountService::upsert(&mut accounts, relogged.clone());
+        AccountService::upsert(&mut accounts, account("usr_group", "Group owner"));
+
+        assert_eq!(accounts.len(), 3);
+        assert_eq!(accounts[0], relogged);
+        assert_eq!(accounts[1].user_id, "usr_alt");
+        assert_eq!(accounts[2].user_id, "usr_group");
+    }
+}
@@ -1,22 +1,51 @@
 use crate::api::auth::VRChatAPIClientAuthenticator;
-use crate::api::world::{SearchWorldSort, VRChatWorld, WorldSearchParametersBuilder};
-use crate::api::{auth, group, instance, invite, world};
-use crate::definitions::{AuthCookies, WorldApiData, WorldDisplayData, WorldModel};
-use crate::services::api_service::world::WorldSearchParameters;
+use crate::api::world::{SearchWorldSort, WorldSearchParametersBuilder};
+use crate::api::{api_base_url, auth, group, instance, invite, world, ApiContext};
+use crate::definitions::{
+    AuthCookies, InstanceOptions, WorldApiData, WorldDisplayData, WorldModel,
+};
+use crate::errors::{ApiError, AppError, ConcurrencyError, NetworkError};
 use crate::services::file_service::FileService;
-use crate::services::FolderManager;
+use crate::services::AccountService;
+use crate::services::EncryptionService;
+use crate::services::WorldCache;
 use crate::InitState;
-use crate::INITSTATE;
+use chrono::Utc;
 use reqwest::cookie::CookieStore;
-use reqwest::{cookie::Jar, Client, Url};
+use reqwest::{cookie::Jar, Url};
 use std::sync::{Arc, RwLock};
 use tauri::http::HeaderValue;
 use tauri::AppHandle;
 use tauri_plugin_opener::OpenerExt;
-use world::ReleaseStatus;
+use world::{ConditionalWorld, ReleaseStatus};
 
 pub struct ApiService;
 
+/// The result of looking up a world in the library on VRChat
+#[derive(Debug)]
+pub enum WorldLookup {
+    Available(Box<WorldApiData>),
+    Private,
+    Deleted,
+}
+
+/// A world returned by `get_world_by_id`
+#[derive(Debug)]
+pub enum FetchedWorld {
+    /// The data sent by VRChat, or recent enough to need no request
+    Current(WorldApiData),
+    /// The last known data, from the library or the world cache, as VRChat could not be reached
+    Offline(WorldApiData),
+}
+
+impl FetchedWorld {
+    pub fn into_inner(self) -> WorldApiData {
+        match self {
+            FetchedWorld::Current(world) | FetchedWorld::Offline(world) => world,
+        }
+    }
+}
+
 #[derive(Clone, Debug, serde::Serialize, specta::Type)]
 pub struct InstanceInfo {
     pub world_id: String,
@@ -25,25 +54,50 @@ pub struct InstanceInfo {
     pub short_name: Option<String>,
 }
 
+/// The outcome of inviting one user to an instance
+#[derive(Clone, Debug, serde::Serialize, specta::Type)]
+pub struct InviteResult {
+    pub user_id: String,
+    /// Why the invite was not sent, or None if it was
+    pub error: Option<String>,
+}
+
+#[derive(Clone, Debug, serde::Serialize, specta::Type)]
+pub struct InstanceInvites {
+    pub instance: InstanceInfo,
+    pub invites: Vec<InviteResult>,
+}
+
 impl ApiService {
+    /// Returns the root of the configured API server, which the auth cookies are scoped to
+    fn api_root_url() -> Url {
+        Url::parse(&api_base_url())
+            .and_then(|url| url.join("/"))
+            .expect("Url not okay")
+    }
+
     /// Saves the cookie store to disk
     ///
     /// # Arguments
     /// * `cookie_store` - The cookie store to save
+    /// * `encryption` - The service the stored secrets are encrypted with
     ///
     /// # Returns
     /// Returns a Result containing an empty Ok if the cookies were saved successfully
     ///
     /// # Errors
     /// Returns a string error message if the cookies could not be saved
-    async fn save_cookie_store(cookie_store: Arc<Jar>) -> Result<(), String> {
+    async fn save_cookie_store(
+        cookie_store: Arc<Jar>,
+        encryption: &EncryptionService,
+    ) -> Result<(), String> {
         let cookie_str = cookie_store
-            .cookies(&Url::parse("https://api.vrchat.cloud").unwrap())
+            .cookies(&Self::api_root_url())
             .map(|cookies| cookies.to_str().unwrap_or_default().to_string())
             .unwrap_or_default();
         //convert to AuthCookies
         let auth = AuthCookies::from_cookie_str(&cookie_str);
-        FileService::write_auth(&auth).map_err(|e| e.to_string())
+        FileService::write_auth(&auth, encryption).map_err(|e| e.to_string())
     }
 
     /// Initializes the API service with the provided cookies
@@ -56,7 +110,7 @@ impl ApiService {
     #[must_use]
     pub fn initialize_with_cookies(cookies: AuthCookies) -> Arc<Jar> {
         let jar = Jar::default();
-        let vrchat_url = Url::parse("https://api.vrchat.cloud").expect("Url not okay");
+        let vrchat_url = Self::api_root_url();
 
         // Set auth cookie if present
         if let Some(auth) = cookies.auth_token {
@@ -87,6 +141,7 @@ impl ApiService {
     ///
     /// # Arguments
     /// * `auth` - The VRChatAPIClientAuthenticator to use for the login
+    /// * `encryption` - The service the stored session is encrypted with
     ///
     /// # Returns
     /// Returns a Result containing the VRChatAPIClientAuthenticator if the login was successful
@@ -96,14 +151,16 @@ impl ApiService {
     pub async fn login_with_token(
         auth: &tokio::sync::RwLock<VRChatAPIClientAuthenticator>,
         init: &tokio::sync::RwLock<InitState>,
+        encryption: &EncryptionService,
     ) -> Result<(), String> {
         let mut auth_lock = auth.write().await;
         let mut init_lock = init.write().await;
         match auth_lock.verify_token().await {
             Ok(auth::VRChatAuthStatus::Success(cookies, user)) => {
                 // Store cookies and update AUTHENTICATOR state
-                FileService::write_auth(&cookies).map_err(|e| e.to_string())?;
+                FileService::write_auth(&cookies, encryption).map_err(|e| e.to_string())?;
                 log::info!("Username: {}, ID: {}", user.username, user.id);
+                AccountService::remember(&user.id, &user.username, &cookies, encryption);
                 auth_lock.update_user_info(user.username);
                 init_lock.user_id = user.id.clone();
                 Ok(())
@@ -124,6 +181,7 @@ impl ApiService {
     /// * `username` - The username of the user
     /// * `password` - The password of the user
     /// * `auth` - The VRChatAPIClientAuthenticator to use for the login
+    /// * `encryption` - The service the stored session is encrypted with
     ///
     /// # Returns
     /// Returns a Result containing the VRChatAPIClientAuthenticator if the login was successful
@@ -134,6 +192,7 @@ impl ApiService {
         username: String,
         password: String,
         auth: &tokio::sync::RwLock<VRChatAPIClientAuthenticator>,
+        encryption: &EncryptionService,
     ) -> Result<(), String> {
         let mut auth_lock = auth.write().await;
         auth_lock.update_user_info(username);
@@ -146,13 +205,13 @@ impl ApiService {
         let status = result.unwrap();
 
         match status {
-            auth::VRChatAuthStatus::Success(cookies, user) => {
+            auth::VRChatAuthStatus::Success(cookies, _user) => {
                 // Store cookies and update AUTHENTICATOR state
-                FileService::write_auth(&cookies).map_err(|e| e.to_string())?;
+                FileService::write_auth(&cookies, encryption).map_err(|e| e.to_string())?;
 
                 // Save the cookie store to disk
                 let cookie_store = Self::initialize_with_cookies(cookies);
-                Self::save_cookie_store(cookie_store)
+                Self::save_cookie_store(cookie_store, encryption)
                     .await
                     .map_err(|e| e.to_string())?;
                 Ok(())
@@ -170,6 +229,7 @@ impl ApiService {
     /// # Arguments
     /// * `code` - The 2FA code to use for the login
     /// * `auth` - The VRChatAPIClientAuthenticator to use for the login
+    /// * `encryption` - The service the stored session is encrypted with
     ///
     /// # Returns
     /// Returns a Result containing the VRChatAPIClientAuthenticator if the login was successful
@@ -179,16 +239,17 @@ impl ApiService {
     pub async fn login_with_2fa(
         code: String,
         auth: &tokio::sync::RwLock<VRChatAPIClientAuthenticator>,
+        encryption: &EncryptionService,
     ) -> Result<(), String> {
         let mut auth_lock = auth.write().await;
         match auth_lock.login_with_2fa(&code).await {
-            Ok(auth::VRChatAuthStatus::Success(cookies, user)) => {
+            Ok(auth::VRChatAuthStatus::Success(cookies, _user)) => {
                 // Store cookies and update AUTHENTICATOR state
-                FileService::write_auth(&cookies).map_err(|e| e.to_string())?;
+                FileService::write_auth(&cookies, encryption).map_err(|e| e.to_string())?;
 
                 // Save the cookie store to disk
                 let cookie_store = Self::initialize_with_cookies(cookies);
-                Self::save_cookie_store(cookie_store)
+                Self::save_cookie_store(cookie_store, encryption)
                     .await
                     .map_err(|e| e.to_string())?;
                 Ok(())
@@ -213,6 +274,8 @@ impl ApiService {
     /// # Arguments
     /// * `code` - The email 2FA code to use for the login
     /// * `auth` - The VRChatAPIClientAuthenticator to use for the login
+    /// * `init` - The init state to store the logged in user's ID in
+    /// * `encryption` - The service the stored session is encrypted with
     ///
     /// # Returns
     /// Returns a Result containing the VRChatAPIClientAuthenticator if the login was successful
@@ -222,19 +285,21 @@ impl ApiService {
     pub async fn login_with_email_2fa(
         code: String,
         auth: &tokio::sync::RwLock<VRChatAPIClientAuthenticator>,
+        init: &tokio::sync::RwLock<InitState>,
+        encryption: &EncryptionService,
     ) -> Result<(), String> {
         let mut auth_lock = auth.write().await;
         match auth_lock.login_with_email_2fa(&code).await {
             Ok(auth::VRChatAuthStatus::Success(cookies, user)) => {
                 // Store cookies and update AUTHENTICATOR state
-                FileService::write_auth(&cookies).map_err(|e| e.to_string())?;
+                FileService::write_auth(&cookies, encryption).map_err(|e| e.to_string())?;
                 log::info!("Username: {}, ID: {}", user.username, user.id);
                 auth_lock.update_user_info(user.username);
-                INITSTATE.get().write().await.user_id = user.id.clone();
+                init.write().await.user_id = user.id.clone();
 
                 // Save the cookie store to disk
                 let cookie_store = Self::initialize_with_cookies(cookies);
-                Self::save_cookie_store(cookie_store)
+                Self::save_cookie_store(cookie_store, encryption)
                     .await
                     .map_err(|e| e.to_string())?;
                 Ok(())
@@ -259,6 +324,8 @@ impl ApiService {
     ///
     /// # Arguments
     /// * `auth` - The VRChatAPIClientAuthenticator to use for the logout
+    /// * `init` - The init state to clear the user's ID from
+    /// * `encryption` - The service the stored session is encrypted with
     ///
     /// # Returns
     /// Returns a Result containing an empty Ok if the logout was successful
@@ -267,56 +334,73 @@ impl ApiService {
     /// Returns a string error message if the logout fails
     pub async fn logout(
         auth: &tokio::sync::RwLock<VRChatAPIClientAuthenticator>,
+        init: &tokio::sync::RwLock<InitState>,
+        encryption: &EncryptionService,
     ) -> Result<(), String> {
         let authenticator = auth.read().await;
-        let cookie_store = authenticator.get_cookies();
+        let context = authenticator.get_context();
 
         // Call the API logout endpoint
-        auth::logout(&cookie_store).await.map_err(|e| {
+        auth::logout(&context).await.map_err(|e| {
             let err = format!("Failed to logout from VRChat: {}", e);
             log::info!("{}", err);
             err
         })?;
 
         // Clear cookies from disk
-        FileService::write_auth(&AuthCookies::new()).map_err(|e| e.to_string())?;
+        FileService::write_auth(&AuthCookies::new(), encryption).map_err(|e| e.to_string())?;
 
-        // Reset INITSTATE
-        INITSTATE.get().write().await.user_id = String::new();
+        // Reset the init state, and drop the logged out session from the stored accounts
+        let user_id = std::mem::take(&mut init.write().await.user_id);
+        if let Err(e) = AccountService::forget(&user_id, encryption) {
+            log::error!("Failed to remove logged out account: {}", e);
+        }
 
-        // Reset authenticator
+        // Reset authenticator with an empty cookie jar
         drop(authenticator);
         let mut auth_lock = auth.write().await;
-        *auth_lock = VRChatAPIClientAuthenticator::new(String::new());
+        *auth_lock = VRChatAPIClientAuthenticator::from_context(
+            context.with_cookie(Arc::new(Jar::default())),
+        );
 
         Ok(())
     }
 
-    #[must_use]
+    /// Fetch the favorite worlds of the logged in user
+    ///
+    /// # Arguments
+    /// * `context` - The API context (cookies, server, rate limits) to use
+    /// * `user_id` - The ID of the logged in user
+    ///
+    /// # Returns
+    /// The public favorite worlds, and the IDs of the favorites of other authors
+    /// that are no longer public
+    ///
+    /// # Errors
+    /// Returns a string error message if the request fails
     pub async fn get_favorite_worlds(
-        cookie_store: Arc<Jar>,
+        context: &ApiContext,
         user_id: String,
-    ) -> Result<Vec<WorldApiData>, String> {
+    ) -> Result<(Vec<WorldApiData>, Vec<String>), String> {
         let mut worlds = vec![];
 
-        let result = world::get_favorite_worlds(cookie_store).await;
+        let result = world::get_favorite_worlds(context).await;
 
-        let favorite_worlds = match result {
+        let (favorite_worlds, hidden_worlds) = match result {
             Ok(worlds) => worlds,
-            Err(e) => {
-                return Err(format!(
-                    "Failed to parse favorite worlds: {}",
-                    e.to_string()
-                ))
-            }
+            Err(e) => return Err(format!("Failed to parse favorite worlds: {}", e)),
         };
 
+        let mut unavailable: Vec<String> = hidden_worlds.into_iter().map(|w| w.id).collect();
         for world in favorite_worlds {
             // Only include public worlds
             // Worlds which belong to the user are not included, as only public worlds have the correct format when calling this endpoint.
             if world.release_status != ReleaseStatus::Public {
                 log::info!("Skipping non-public world: {}", world.id);
-
+                // The user's own worlds can still be visited, so they are not marked
+                if world.author_id != user_id {
+                    unavailable.push(world.id);
+                }
                 continue;
             }
 
@@ -326,48 +410,192 @@ impl ApiService {
             }
         }
 
-        Ok(worlds)
+        Ok((worlds, unavailable))
     }
 
-    #[must_use]
+    /// Fetch a world of the library, and find out whether it can still be visited
+    /// Worlds owned by the user count as available even when they are not public
+    ///
+    /// # Arguments
+    /// * `world_id` - The ID of the world to look up
+    /// * `context` - The API context (cookies, server, rate limits) to use
+    /// * `user_id` - The ID of the logged in user
+    ///
+    /// # Returns
+    /// The world data if it is available, or whether it was privated or deleted
+    ///
+    /// # Errors
+    /// Returns the API error if the request fails, e.g. `ApiError::AuthenticationFailed`
+    /// once the session expired, or `ApiError::RateLimitExceeded` while rate limited
+    pub async fn lookup_world(
+        world_id: String,
+        context: &ApiContext,
+        user_id: String,
+    ) -> Result<WorldLookup, AppError> {
+        let world = match world::find_world_by_id(context, &world_id).await? {
+            Some(world) => world,
+            None => return Ok(WorldLookup::Deleted),
+        };
+
+        if world.release_status != ReleaseStatus::Public && world.author_id != user_id {
+            log::info!("World {} is not public", world_id);
+            return Ok(WorldLookup::Private);
+        }
+
+        match world::WorldDetails::try_into(world) {
+            Ok(world_data) => Ok(WorldLookup::Available(Box::new(world_data))),
+            Err(e) => {
+                log::error!("Failed to convert world {}: {}", world_id, e);
+                Err(NetworkError::InvalidResponse.into())
+            }
+        }
+    }
+
+    /// Get a world, from the library if it was checked recently, otherwise from the world cache,
+    /// which asks VRChat whether the world changed once its response is no longer fresh
+    ///
+    /// While offline, or if VRChat could not be reached, the last known data of the world
+    /// is returned instead, from the library or the world cache.
+    ///
+    /// # Arguments
+    /// * `world_id` - The ID of the world
+    /// * `context` - The API context (cookies, server, rate limits) to use
+    /// * `worlds` - The worlds of the library
+    /// * `user_id` - The ID of the logged in user
+    /// * `cache` - The cache of world details responses
+    /// * `offline` - Whether the app is in offline mode, so VRChat is not asked
+    ///
+    /// # Returns
+    /// The world data, and whether it is current
+    ///
+    /// # Errors
+    /// Returns a string error message if the world is not public, or could not be fetched
     pub async fn get_world_by_id(
         world_id: String,
-        cookie_store: Arc<Jar>,
+        context: &ApiContext,
         worlds: Vec<WorldModel>,
         user_id: String,
-    ) -> Result<WorldApiData, String> {
-        // First check if we have a cached version
+        cache: &RwLock<WorldCache>,
+        offline: bool,
+    ) -> Result<FetchedWorld, String> {
+        // Worlds refreshed recently by the library cost no request
         if let Some(existing_world) = worlds.iter().find(|w| w.api_data.world_id == world_id) {
             if !existing_world.user_data.needs_update() {
                 log::info!("World already exists in cache");
-                return Ok(existing_world.api_data.clone());
+                return Ok(FetchedWorld::Current(existing_world.api_data.clone()));
             }
         }
 
-        // Fetch from API
-        match world::get_world_by_id(cookie_store, &world_id).await {
-            Ok(world) => {
-                // Check if world is public, or if the user is the owner
-                if world.release_status != ReleaseStatus::Public && world.author_id != user_id {
-                    log::info!("World {} is not public", world_id);
-                    return Err("World is not public".to_string());
-                }
+        let result = if offline {
+            Err(NetworkError::ConnectionFailed.into())
+        } else {
+            Self::fetch_world_cached(&world_id, context, cache).await
+        };
+        let world = match result {
+            Ok(world) => world,
+            Err(AppError::Api(ApiError::NotFound)) => {
+                return Err(format!("World not found: {}", world_id))
+            }
+            Err(e) if e.is_unreachable() => {
+                return match Self::local_world(&world_id, &worlds, cache) {
+                    Some(world) => {
+                        log::info!("World {} served from local data while offline", world_id);
+                        Ok(FetchedWorld::Offline(world))
+                    }
+                    None => Err(format!("Failed to fetch world: {}", e)),
+                };
+            }
+            Err(e) => return Err(format!("Failed to fetch world: {}", e)),
+        };
+
+        // Check if world is public, or if the user is the owner
+        if world.release_status != ReleaseStatus::Public && world.author_id != user_id {
+            log::info!("World {} is not public", world_id);
+            return Err("World is not public".to_string());
+        }
 
-                match world::WorldDetails::try_into(world) {
-                    Ok(world_data) => Ok(world_data),
-                    Err(e) => Err(e.to_string()),
+        match world::WorldDetails::try_into(world) {
+            Ok(world_data) => Ok(FetchedWorld::Current(world_data)),
+            Err(e) => Err(e.to_string()),
+        }
+    }
+
+    /// Get the last known data of a world, from the library, or else from the world cache
+    /// regardless of how old the cached response is
+    fn local_world(
+        world_id: &str,
+        worlds: &[WorldModel],
+        cache: &RwLock<WorldCache>,
+    ) -> Option<WorldApiData> {
+        if let Some(world) = worlds.iter().find(|w| w.api_data.world_id == world_id) {
+            return Some(world.api_data.clone());
+        }
+
+        let cached = cache.read().ok()?.get(world_id)?;
+        let world = world::parse_world(&cached.body).ok()?;
+        world::WorldDetails::try_into(world).ok()
+    }
+
+    async fn fetch_world_cached(
+        world_id: &str,
+        context: &ApiContext,
+        cache: &RwLock<WorldCache>,
+    ) -> Result<world::WorldDetails, AppError> {
+        let cached = cache
+            .read()
+            .map_err(|_| ConcurrencyError::PoisonedLock)?
+            .get(world_id);
+
+        if let Some(cached) = cached.as_ref().filter(|c| c.is_fresh(Utc::now())) {
+            if let Ok(world) = world::parse_world(&cached.body) {
+                log::info!("World {} served from cache", world_id);
+                cache
+                    .write()
+                    .map_err(|_| ConcurrencyError::PoisonedLock)?
+                    .record_hit();
+                return Ok(world);
+            }
+        }
+
+        let validators = cached.as_ref().map(|c| c.validators()).unwrap_or_default();
+        let result = world::get_world_if_modified(context, world_id, &validators).await;
+
+        let mut cache = cache.write().map_err(|_| ConcurrencyError::PoisonedLock)?;
+        match result {
+            Ok(ConditionalWorld::Modified {
+                world,
+                body,
+                validators,
+            }) => {
+                cache.insert(world_id, body, validators, Utc::now());
+                Ok(*world)
+            }
+            Ok(ConditionalWorld::NotModified) => {
+                let world = cached
+                    .ok_or(NetworkError::InvalidResponse)
+                    .map_err(AppError::from)
+                    .and_then(|c| world::parse_world(&c.body));
+                match world {
+                    Ok(_) => cache.mark_revalidated(world_id, Utc::now()),
+                    Err(_) => cache.remove(world_id),
+                }
+                world
+            }
+            Err(e) => {
+                if matches!(e, AppError::Api(ApiError::NotFound)) {
+                    cache.remove(world_id);
                 }
+                Err(e)
             }
-            Err(e) => Err(format!("Failed to fetch world: {}", e)),
         }
     }
 
     async fn invite_self_to_instance(
-        cookie_store: Arc<Jar>,
+        context: &ApiContext,
         world_id: String,
         instance_id: String,
     ) -> Result<(), String> {
-        match invite::invite_self_to_instance(cookie_store, &world_id, &instance_id).await {
+        match invite::invite_self_to_instance(context, &world_id, &instance_id).await {
             Ok(_) => Ok(()),
             Err(e) => Err(format!("Failed to invite self to instance: {}", e)),
         }
@@ -376,7 +604,7 @@ impl ApiService {
     /// Get the instance short name, and open the instance menu in the user's client
     ///
     /// # Arguments
-    /// * `cookie` - The cookie jar to use for the API
+    /// * `context` - The API context (cookies, server, rate limits) to use
     /// * `world_id` - The ID of the world to get the instance short name
     /// * `instance_id` - The ID of the instance to get the short name for
     /// * `app` - The AppHandle to use for opening the instance in the user's client
@@ -386,13 +614,13 @@ impl ApiService {
     ///
     /// # Errors
     /// Returns a string error message if the request fails
-    pub async fn get_instance_short_name_and_open_client<J: Into<Arc<Jar>>>(
-        cookie: J,
+    pub async fn get_instance_short_name_and_open_client(
+        context: &ApiContext,
         world_id: &str,
         instance_id: &str,
         app: AppHandle,
     ) -> Result<String, String> {
-        let short_name = instance::get_instance_short_name(cookie, world_id, instance_id).await?;
+        let short_name = instance::get_instance_short_name(context, world_id, instance_id).await?;
 
         // Open the instance in the user's client
         let url = format!(
@@ -410,18 +638,17 @@ impl ApiService {
     /// Get the user's recently visited worlds  
     ///
     /// # Arguments
-    /// * `cookie_store` - The cookie store to use for the API
+    /// * `context` - The API context (cookies, server, rate limits) to use
     ///
     /// # Returns
     /// Returns a Result containing a vector of WorldDisplayData if the request was successful
     ///
     /// # Errors
     /// Returns a string error message if the request fails
-    #[must_use]
     pub async fn get_recently_visited_worlds(
-        cookie_store: Arc<Jar>,
+        context: &ApiContext,
     ) -> Result<Vec<WorldDisplayData>, String> {
-        match world::get_recently_visited_worlds(cookie_store).await {
+        match world::get_recently_visited_worlds(context).await {
             Ok(worlds) => {
                 let converted_worlds = worlds
                     .into_iter()
@@ -443,7 +670,7 @@ impl ApiService {
     /// Searches for worlds within the server, using the provided query
     ///
     /// # Arguments
-    /// * `cookie_store` - The cookie store to use for the API
+    /// * `context` - The API context (cookies, server, rate limits) to use
     /// * `sort` - The sort priority for the search
     /// * `tag` - The tags that the worlds should have
     /// * `platform` - The platforms which the worlds should be available on
@@ -455,9 +682,8 @@ impl ApiService {
     ///
     /// # Errors
     /// Returns a string error message if the request fails
-    #[must_use]
     pub async fn search_worlds(
-        cookie_store: Arc<Jar>,
+        context: &ApiContext,
         sort: Option<String>,
         tags: Option<Vec<String>>,
         exclude_tags: Option<Vec<String>>,
@@ -507,7 +733,7 @@ impl ApiService {
             parameter_builder.search = Some(search);
         }
 
-        match world::search_worlds(cookie_store, &parameter_builder.build(), page).await {
+        match world::search_worlds(context, &parameter_builder.build(), page).await {
             Ok(worlds) => {
                 let converted_worlds = worlds
                     .into_iter()
@@ -532,7 +758,7 @@ impl ApiService {
     /// * `world_id` - The ID of the world to create an instance of
     /// * `instance_type_str` - The type of instance to create
     /// * `region_str` - The region to create the instance in
-    /// * `cookie_store` - The cookie store to use for the API
+    /// * `context` - The API context (cookies, server, rate limits) to use
     /// * `user_id` - The ID of the user to create the instance for
     ///
     /// # Returns
@@ -540,14 +766,13 @@ impl ApiService {
     ///
     /// # Errors
     /// Returns a string error message if the request fails
-    #[must_use]
     pub async fn create_world_instance(
         world_id: String,
         instance_type_str: String,
         region_str: String,
-        cookie_store: Arc<Jar>,
+        context: &ApiContext,
         user_id: String,
-        app: AppHandle,
+        _app: AppHandle,
     ) -> Result<InstanceInfo, String> {
         log::info!(
             "Creating instance: {} {} {}",
@@ -555,78 +780,288 @@ impl ApiService {
             instance_type_str,
             region_str
         );
+        let options = InstanceOptions::new(instance_type_str, Self::parse_region(&region_str)?);
+        Self::create_instance_with_options(world_id, options, context, user_id, true).await
+    }
+
+    /// Creates a new instance of a world with every creation option
+    ///
+    /// # Arguments
+    /// * `world_id` - The ID of the world to create an instance of
+    /// * `options` - The type, region and other settings of the instance
+    /// * `context` - The API context (cookies, server, rate limits) to use
+    /// * `user_id` - The ID of the user to create the instance for
+    /// * `invite_self` - Whether to invite the user to the new instance
+    ///
+    /// # Returns
+    /// Returns the created instance
+    ///
+    /// # Errors
+    /// Returns a string error message if the options are invalid, or the request fails
+    pub async fn create_instance_with_options(
+        world_id: String,
+        options: InstanceOptions,
+        context: &ApiContext,
+        user_id: String,
+        invite_self: bool,
+    ) -> Result<InstanceInfo, String> {
+        let instance_type = Self::instance_type(&options, user_id)?;
+
+        // Create request using builder
+        let mut builder = instance::CreateInstanceRequestBuilder::new(
+            instance_type,
+            world_id,
+            options.region,
+            options.queue_enabled,
+        )
+        .age_gate(options.age_gate)
+        .hard_close(options.hard_close)
+        .invite_only(options.invite_only);
+        if let Some(display_name) = &options.display_name {
+            builder = builder.display_name(display_name);
+        }
+        if let Some(minutes) = options.close_after_minutes {
+            builder =
+                builder.closed_at(chrono::Utc::now() + chrono::Duration::minutes(minutes.into()));
+        }
+        if let Some(can_request_invite) = options.can_request_invite {
+            builder = builder.can_request_invite(can_request_invite);
+        }
+
+        // Call API endpoint
+        match instance::create_instance(context, builder.build()).await {
+            Ok(_instance) => {
+                // Invite self to the instance
+                let instance_id = _instance.instance_id.clone();
+                let world_id = _instance.world_id.clone();
+                if invite_self {
+                    Self::invite_self_to_instance(context, world_id.clone(), instance_id.clone())
+                        .await?;
+                }
+
+                // Do NOT fetch the short name here. Frontend will request it when user chooses to open in client.
+                Ok(InstanceInfo {
+                    world_id,
+                    instance_id,
+                    short_name: None,
+                })
+            }
+            Err(e) if options.group_id.is_some() => {
+                Err(format!("Failed to create group instance: {}", e))
+            }
+            Err(e) => Err(format!("Failed to create world instance: {}", e)),
+        }
+    }
+
+    fn parse_region(region_str: &str) -> Result<instance::InstanceRegion, String> {
         // region_str is already in the correct format ("us", "use", "eu", "jp"), just map directly
-        let region = match region_str.as_str() {
-            "us" => instance::InstanceRegion::UsWest,
-            "use" => instance::InstanceRegion::UsEast,
-            "eu" => instance::InstanceRegion::EU,
-            "jp" => instance::InstanceRegion::JP,
-            _ => return Err("Invalid region".to_string()),
-        };
+        match region_str {
+            "us" => Ok(instance::InstanceRegion::UsWest),
+            "use" => Ok(instance::InstanceRegion::UsEast),
+            "eu" => Ok(instance::InstanceRegion::EU),
+            "jp" => Ok(instance::InstanceRegion::JP),
+            _ => Err("Invalid region".to_string()),
+        }
+    }
+
+    fn instance_type(
+        options: &InstanceOptions,
+        user_id: String,
+    ) -> Result<instance::InstanceType, String> {
+        if let Some(group_id) = &options.group_id {
+            return match options.instance_type.as_str() {
+                "public" => Ok(instance::InstanceType::group_public(group_id)),
+                "group+" => Ok(instance::InstanceType::group_plus(group_id)),
+                "group" => Ok(instance::InstanceType::GroupOnly(
+                    instance::GroupOnlyInstanceConfig {
+                        group_id: group_id.clone(),
+                        allowed_roles: Some(options.role_ids.clone())
+                            .filter(|roles| !roles.is_empty()),
+                    },
+                )),
+                _ => Err("Invalid instance type".to_string()),
+            };
+        }
+
         // Create instance type based on string and user_id
-        let instance_type = match instance_type_str.as_str() {
-            "public" => instance::InstanceType::Public,
+        match options.instance_type.as_str() {
+            "public" => Ok(instance::InstanceType::public()),
             // The following instance types require a valid user id. If we don't have one, fail early
             "friends+" => {
                 if user_id.is_empty() {
                     return Err("Not logged in: cannot create friends+ instance".to_string());
                 }
-                instance::InstanceType::friends_plus(user_id)
+                Ok(instance::InstanceType::friends_plus(user_id))
             }
             "friends" => {
                 if user_id.is_empty() {
                     return Err("Not logged in: cannot create friends instance".to_string());
                 }
-                instance::InstanceType::friends_only(user_id)
+                Ok(instance::InstanceType::friends_only(user_id))
             }
             "invite+" => {
                 if user_id.is_empty() {
                     return Err("Not logged in: cannot create invite+ instance".to_string());
                 }
-                instance::InstanceType::invite_plus(user_id)
+                Ok(instance::InstanceType::invite_plus(user_id))
             }
             "invite" => {
                 if user_id.is_empty() {
                     return Err("Not logged in: cannot create invite instance".to_string());
                 }
-                instance::InstanceType::invite_only(user_id)
+                Ok(instance::InstanceType::invite_only(user_id))
             }
-            _ => return Err("Invalid instance type".to_string()),
-        };
-
-        // Create request using builder
-        let request =
-            instance::CreateInstanceRequestBuilder::new(instance_type, world_id, region, false)
-                .build();
+            _ => Err("Invalid instance type".to_string()),
+        }
+    }
 
-        // Call API endpoint
-        match instance::create_instance(cookie_store.clone(), request).await {
-            Ok(_instance) => {
-                // Invite self to the instance
-                let instance_id = _instance.instance_id.clone();
-                let world_id = _instance.world_id.clone();
-                Self::invite_self_to_instance(
-                    cookie_store.clone(),
-                    world_id.clone(),
-                    instance_id.clone(),
-                )
-                .await?;
+    /// Creates a new instance of a world, and invites a list of users to it
+    /// The instance is kept even if some invites fail, so they can be sent again.
+    ///
+    /// # Arguments
+    /// * `world_id` - The ID of the world to create an instance of
+    /// * `instance_type_str` - The type of instance to create
+    /// * `region_str` - The region to create the instance in
+    /// * `user_ids` - The IDs of the users to invite
+    /// * `context` - The API context (cookies, server, rate limits) to use
+    /// * `user_id` - The ID of the user to create the instance for
+    ///
+    /// # Returns
+    /// Returns the created instance, and the result of each invite
+    ///
+    /// # Errors
+    /// Returns a string error message if the instance could not be created
+    pub async fn create_world_instance_and_invite(
+        world_id: String,
+        instance_type_str: String,
+        region_str: String,
+        user_ids: Vec<String>,
+        context: &ApiContext,
+        user_id: String,
+        app: AppHandle,
+    ) -> Result<InstanceInvites, String> {
+        let instance = Self::create_world_instance(
+            world_id,
+            instance_type_str,
+            region_str,
+            context,
+            user_id,
+            app,
+        )
+        .await?;
+        let invites = Self::invite_users_to_instance(
+            context,
+            &instance.world_id,
+            &instance.instance_id,
+            user_ids,
+        )
+        .await;
+        Ok(InstanceInvites { instance, invites })
+    }
 
-                // Do NOT fetch the short name here. Frontend will request it when user chooses to open in client.
-                Ok(InstanceInfo {
-                    world_id,
-                    instance_id,
-                    short_name: None,
-                })
+    /// Invites a list of users to an instance, one after the other
+    ///
+    /// # Arguments
+    /// * `context` - The API context (cookies, server, rate limits) to use
+    /// * `world_id` - The ID of the world of the instance
+    /// * `instance_id` - The ID of the instance
+    /// * `user_ids` - The IDs of the users to invite
+    ///
+    /// # Returns
+    /// Returns the result of each invite, in the order of `user_ids`
+    pub async fn invite_users_to_instance(
+        context: &ApiContext,
+        world_id: &str,
+        instance_id: &str,
+        user_ids: Vec<String>,
+    ) -> Vec<InviteResult> {
+        let mut results: Vec<InviteResult> = vec![];
+        for user_id in user_ids {
+            if results.iter().any(|r| r.user_id == user_id) {
+                continue;
             }
-            Err(e) => Err(format!("Failed to create world instance: {}", e)),
+            let error = invite::invite_user(context, &user_id, world_id, instance_id, None)
+                .await
+                .err()
+                .map(|e| e.to_string());
+            if let Some(e) = &error {
+                log::warn!("Failed to invite {}: {}", user_id, e);
+            }
+            results.push(InviteResult { user_id, error });
+        }
+        results
+    }
+
+    /// Asks another user to invite the logged in user to their instance
+    ///
+    /// # Arguments
+    /// * `context` - The API context (cookies, server, rate limits) to use
+    /// * `user_id` - The ID of the user to ask
+    /// * `request_slot` - The slot of the request message to send, if not the default
+    ///
+    /// # Returns
+    /// Returns an empty Ok if the request was sent
+    ///
+    /// # Errors
+    /// Returns a string error message if the request fails
+    pub async fn request_invite(
+        context: &ApiContext,
+        user_id: String,
+        request_slot: Option<u32>,
+    ) -> Result<(), String> {
+        match invite::request_invite(context, &user_id, request_slot).await {
+            Ok(_) => Ok(()),
+            Err(e) => Err(format!("Failed to request invite: {}", e)),
         }
     }
 
+    /// Answers a received invite or invite request with a response message
+    ///
+    /// # Arguments
+    /// * `context` - The API context (cookies, server, rate limits) to use
+    /// * `notification_id` - The ID of the received notification
+    /// * `response_slot` - The slot of the response message to send
+    ///
+    /// # Returns
+    /// Returns an empty Ok if the response was sent
+    ///
+    /// # Errors
+    /// Returns a string error message if the request fails
+    pub async fn respond_to_invite(
+        context: &ApiContext,
+        notification_id: String,
+        response_slot: u32,
+    ) -> Result<(), String> {
+        match invite::respond_to_invite(context, &notification_id, response_slot).await {
+            Ok(_) => Ok(()),
+            Err(e) => Err(format!("Failed to respond to invite: {}", e)),
+        }
+    }
+
+    /// Gets the instances of a world that anyone can join
+    ///
+    /// # Arguments
+    /// * `world_id` - The ID of the world
+    /// * `context` - The API context (cookies, server, rate limits) to use
+    ///
+    /// # Returns
+    /// Returns the public and public group instances, with their user counts
+    ///
+    /// # Errors
+    /// Returns a string error message if the request fails
+    pub async fn get_world_instances(
+        world_id: String,
+        context: &ApiContext,
+    ) -> Result<Vec<instance::Instance>, String> {
+        instance::get_world_instances(context, &world_id)
+            .await
+            .map_err(|e| format!("Failed to fetch world instances: {}", e))
+    }
+
     /// Gets the user's groups
     ///
     /// # Arguments
-    /// * `cookie_store` - The cookie store to use for the API
+    /// * `context` - The API context (cookies, server, rate limits) to use
     /// * `user_id` - The ID of the user to get the groups for
     ///
     /// # Returns
@@ -634,12 +1069,11 @@ impl ApiService {
     ///
     /// # Errors
     /// Returns a string error message if the request fails
-    #[must_use]
     pub async fn get_user_groups(
-        cookie_store: Arc<Jar>,
+        context: &ApiContext,
         user_id: String,
     ) -> Result<Vec<group::UserGroup>, String> {
-        match group::get_user_groups(cookie_store, &user_id).await {
+        match group::get_user_groups(context, &user_id).await {
             Ok(groups) => Ok(groups),
             Err(e) => Err(format!("Failed to fetch user groups: {}", e)),
         }
@@ -648,7 +1082,7 @@ impl ApiService {
     /// Gets the permission for creating a group instance
     ///
     /// # Arguments
-    /// * `cookie_store` - The cookie store to use for the API
+    /// * `context` - The API context (cookies, server, rate limits) to use
     /// * `group_id` - The ID of the group to get the permission for
     ///
     /// # Returns
@@ -656,12 +1090,11 @@ impl ApiService {
     ///
     /// # Errors
     /// Returns a string error message if the request fails
-    #[must_use]
     pub async fn get_permission_for_create_group_instance(
-        cookie_store: Arc<Jar>,
+        context: &ApiContext,
         group_id: String,
     ) -> Result<group::GroupInstancePermissionInfo, String> {
-        match group::get_permission_for_create_group_instance(cookie_store, &group_id).await {
+        match group::get_permission_for_create_group_instance(context, &group_id).await {
             Ok(permission) => Ok(permission),
             Err(e) => Err(format!("Failed to fetch group instance permission: {}", e)),
         }
@@ -676,14 +1109,13 @@ impl ApiService {
     /// * `allowed_roles` - The allowed roles for the instance
     /// * `region_str` - The region to create the instance in
     /// * `queue_enabled` - Whether the instance should have a queue
-    /// * `cookie_store` - The cookie store to use for the API
+    /// * `context` - The API context (cookies, server, rate limits) to use
     ///
     /// # Returns
     /// Returns an empty Ok if the request was successful
     ///
     /// # Errors
     /// Returns a string error message if the request fails
-    #[must_use]
     pub async fn create_group_instance(
         world_id: String,
         group_id: String,
@@ -691,8 +1123,8 @@ impl ApiService {
         allowed_roles: Option<Vec<String>>,
         region_str: String,
         queue_enabled: bool,
-        cookie_store: Arc<Jar>,
-        app: AppHandle,
+        context: &ApiContext,
+        _app: AppHandle,
     ) -> Result<InstanceInfo, String> {
         log::info!(
             "Creating group instance: {} {} {} {} {:?}",
@@ -702,77 +1134,20 @@ impl ApiService {
             region_str,
             allowed_roles
         );
-        // Convert region string to InstanceRegion enum
-        let region = match region_str.as_str() {
-            "us" => instance::InstanceRegion::UsWest,
-            "use" => instance::InstanceRegion::UsEast,
-            "eu" => instance::InstanceRegion::EU,
-            "jp" => instance::InstanceRegion::JP,
-            _ => return Err("Invalid region".to_string()),
-        };
-
-        // Create instance type based on string
-        let instance_type = match instance_type_str.as_str() {
-            "public" => instance::InstanceType::GroupPublic(group_id.clone()),
-            "group+" => instance::InstanceType::GroupPlus(group_id.clone()),
-            "group" => {
-                if let Some(roles) = allowed_roles {
-                    let config = instance::GroupOnlyInstanceConfig {
-                        group_id: group_id.clone(),
-                        allowed_roles: Some(roles),
-                    };
-                    instance::InstanceType::GroupOnly(config)
-                } else {
-                    let config = instance::GroupOnlyInstanceConfig {
-                        group_id: group_id.clone(),
-                        allowed_roles: None,
-                    };
-                    instance::InstanceType::GroupOnly(config)
-                }
-            }
-            _ => return Err("Invalid instance type".to_string()),
-        };
-
-        // Create request using builder
-        let request = instance::CreateInstanceRequestBuilder::new(
-            instance_type,
-            world_id,
-            region,
-            queue_enabled,
-        )
-        .build();
-
-        // Call API endpoint
-        match instance::create_instance(cookie_store.clone(), request).await {
-            Ok(_instance) => {
-                // Invite self to the instance
-                let instance_id = _instance.instance_id.clone();
-                let world_id = _instance.world_id.clone();
-                Self::invite_self_to_instance(
-                    cookie_store.clone(),
-                    world_id.clone(),
-                    instance_id.clone(),
-                )
-                .await?;
-
-                // Do NOT fetch the short name here. Frontend will request it when user chooses to open in client.
-                Ok(InstanceInfo {
-                    world_id,
-                    instance_id,
-                    short_name: None,
-                })
-            }
-            Err(e) => Err(format!("Failed to create group instance: {}", e)),
-        }
+        let mut options = InstanceOptions::new(instance_type_str, Self::parse_region(&region_str)?);
+        options.group_id = Some(group_id);
+        options.role_ids = allowed_roles.unwrap_or_default();
+        options.queue_enabled = queue_enabled;
+        Self::create_instance_with_options(world_id, options, context, String::new(), true).await
     }
 
     /// Opens the given instance in the user's client. Returns the short_name on success.
-    pub async fn open_instance_in_client<J: Into<Arc<Jar>>>(
-        cookie: J,
+    pub async fn open_instance_in_client(
+        context: &ApiContext,
         world_id: &str,
         instance_id: &str,
         app: AppHandle,
     ) -> Result<String, String> {
-        Self::get_instance_short_name_and_open_client(cookie, world_id, instance_id, app).await
+        Self::get_instance_short_name_and_open_client(context, world_id, instance_id, app).await
     }
 }
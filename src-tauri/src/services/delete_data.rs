@@ -1,9 +1,11 @@
-use crate::{services, FolderModel, WorldModel};
+use crate::definitions::{FolderModel, WorldModel};
+use crate::services::Storage;
 use std::sync::RwLock;
 
 pub async fn delete_data(
     worlds: &RwLock<Vec<WorldModel>>,
     folders: &RwLock<Vec<FolderModel>>,
+    storage: &dyn Storage,
 ) -> Result<(), String> {
     log::info!("Deleting data");
     let mut worlds_lock = worlds.write().map_err(|e| {
@@ -20,6 +22,6 @@ pub async fn delete_data(
     folders_lock.clear();
     log::info!("Cleared existing folders data");
 
-    services::FileService::delete_worlds_and_folders().map_err(|e| e.to_string())?;
+    storage.clear().map_err(|e| e.to_string())?;
     Ok(())
 }
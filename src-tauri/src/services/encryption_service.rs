@@ -1,144 +1,419 @@
-use aes::{
-    cipher::{BlockDecryptMut, BlockEncryptMut, KeyIvInit},
-    Aes256,
+use aes_gcm::{
+    aead::{Aead, KeyInit},
+    Aes256Gcm, Nonce,
 };
+use argon2::Argon2;
 use base64::{engine::general_purpose::STANDARD, Engine};
-use cbc::{cipher::block_padding::Pkcs7, Decryptor};
-use std::fs;
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+use std::sync::RwLock;
 
-pub struct EncryptionService;
+use crate::services::FileService;
 
-const ENCRYPTION_KEY: Option<&str> = option_env!("ENCRYPTION_KEY");
-const ENCRYPTION_IV: Option<&str> = option_env!("ENCRYPTION_IV");
+/// The format version of secrets encrypted by this service, as stored in `AuthCookies.version`
+pub const SECRET_VERSION: u8 = 2;
 
-impl EncryptionService {
-    fn get_encryption_keys() -> Result<(Vec<u8>, Vec<u8>), String> {
-        let key = ENCRYPTION_KEY.ok_or_else(|| {
-            "ENCRYPTION_KEY environment variable not set at compile time".to_string()
-        })?;
+const KEY_LEN: usize = 32;
+const NONCE_LEN: usize = 12;
+const SALT_LEN: usize = 16;
 
-        let iv = ENCRYPTION_IV.ok_or_else(|| {
-            "ENCRYPTION_IV environment variable not set at compile time".to_string()
-        })?;
+/// Encrypted with a passphrase derived key, to check the passphrase when unlocking
+const PASSPHRASE_CHECK: &str = "VRC Worlds Manager";
 
-        // Convert from base64 to bytes for AES
-        let key = STANDARD
-            .decode(key)
-            .map_err(|e| format!("Failed to decode key: {}", e))?;
-
-        let iv = STANDARD
-            .decode(iv)
-            .map_err(|e| format!("Failed to decode iv: {}", e))?;
-
-        // Validate key and IV sizes
-        if key.len() != 32 {
-            return Err(format!(
-                "Invalid key length: {}. Expected 32 bytes",
-                key.len()
-            ));
+/// The key file, holding either the random key of this install,
+/// or the salt of the passphrase it is derived from
+#[derive(Serialize, Deserialize)]
+struct KeyFile {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    key: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    salt: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    check: Option<String>,
+}
+
+/// Encrypts the secrets stored on disk, such as the session cookies, with AES-256-GCM
+///
+/// Every install has its own key, generated on first use and kept next to the data,
+/// unless the user sets a passphrase to derive it from. The key is then never stored,
+/// and secrets stay locked until the passphrase is entered.
+///
+/// The app state owns the single instance, and hands it to the services that read
+/// or write secrets.
+pub struct EncryptionService {
+    key_path: PathBuf,
+    /// The key of this install, once it is loaded or unlocked
+    key: RwLock<Option<[u8; KEY_LEN]>>,
+}
+
+impl EncryptionService {
+    /// Creates the service, without reading the key file yet
+    ///
+    /// # Arguments
+    /// * `key_path` - The file the key of this install, or its salt, is stored in
+    pub fn new(key_path: PathBuf) -> Self {
+        Self {
+            key_path,
+            key: RwLock::new(None),
         }
-        if iv.len() != 16 {
-            return Err(format!(
-                "Invalid IV length: {}. Expected 16 bytes",
-                iv.len()
-            ));
+    }
+
+    fn read_key_file(&self) -> Result<Option<KeyFile>, String> {
+        if !self.key_path.exists() {
+            return Ok(None);
         }
+        FileService::read_file(&self.key_path)
+            .map(Some)
+            .map_err(|e| format!("Failed to read key file: {}", e))
+    }
 
-        Ok((key, iv))
+    fn write_key_file(&self, key_file: &KeyFile) -> Result<(), String> {
+        let data = serde_json::to_string_pretty(key_file)
+            .map_err(|e| format!("Failed to serialize key file: {}", e))?;
+        FileService::atomic_write(&self.key_path, &data)
+            .map_err(|e| format!("Failed to write key file: {}", e))
     }
 
-    pub fn encrypt_aes(plaintext: &str) -> Result<String, String> {
-        let (key, iv) = Self::get_encryption_keys()?;
+    fn set_key(&self, key: [u8; KEY_LEN]) -> Result<(), String> {
+        *self
+            .key
+            .write()
+            .map_err(|_| "Failed to acquire write lock for secret key".to_string())? = Some(key);
+        Ok(())
+    }
 
-        type Aes256CbcEnc = cbc::Encryptor<Aes256>;
-        let cipher = Aes256CbcEnc::new(key.as_slice().into(), iv.as_slice().into());
+    /// Get the key of this install, generating it on first use
+    fn key(&self) -> Result<[u8; KEY_LEN], String> {
+        if let Some(key) = *self
+            .key
+            .read()
+            .map_err(|_| "Failed to acquire read lock for secret key".to_string())?
+        {
+            return Ok(key);
+        }
 
-        let mut buffer = vec![0u8; plaintext.len() + 16];
-        let encrypted_data_len = cipher
-            .encrypt_padded_b2b_mut::<Pkcs7>(plaintext.as_bytes(), &mut buffer)
-            .map_err(|e| format!("Encryption failed: {}", e))?
-            .len();
+        let mut cached = self
+            .key
+            .write()
+            .map_err(|_| "Failed to acquire write lock for secret key".to_string())?;
+        if let Some(key) = *cached {
+            return Ok(key);
+        }
 
-        let encrypted_slice = &buffer[..encrypted_data_len];
-        Ok(STANDARD.encode(encrypted_slice))
+        let key = match self.read_key_file()? {
+            Some(KeyFile { key: Some(key), .. }) => Self::decode_key(&key)?,
+            Some(_) => return Err("Stored secrets are locked by a passphrase".to_string()),
+            None => {
+                log::info!("Generating the secret key of this install");
+                let key: [u8; KEY_LEN] = rand::random();
+                self.write_key_file(&KeyFile {
+                    key: Some(STANDARD.encode(key)),
+                    salt: None,
+                    check: None,
+                })?;
+                key
+            }
+        };
+        *cached = Some(key);
+        Ok(key)
     }
 
-    pub fn decrypt_aes(ciphertext: &str) -> Result<String, String> {
-        let (key, iv) = Self::get_encryption_keys()?;
+    fn decode_key(key: &str) -> Result<[u8; KEY_LEN], String> {
+        STANDARD
+            .decode(key)
+            .map_err(|e| format!("Failed to decode key: {}", e))?
+            .try_into()
+            .map_err(|_| format!("Invalid key length. Expected {} bytes", KEY_LEN))
+    }
 
-        let encrypted = STANDARD
+    fn derive_key(passphrase: &str, salt: &[u8]) -> Result<[u8; KEY_LEN], String> {
+        let mut key = [0u8; KEY_LEN];
+        Argon2::default()
+            .hash_password_into(passphrase.as_bytes(), salt, &mut key)
+            .map_err(|e| format!("Failed to derive key: {}", e))?;
+        Ok(key)
+    }
+
+    fn encrypt_with(key: &[u8; KEY_LEN], plaintext: &str) -> Result<String, String> {
+        let cipher = Aes256Gcm::new_from_slice(key).map_err(|e| format!("Invalid key: {}", e))?;
+        let nonce: [u8; NONCE_LEN] = rand::random();
+
+        let encrypted = cipher
+            .encrypt(Nonce::from_slice(&nonce), plaintext.as_bytes())
+            .map_err(|e| format!("Encryption failed: {}", e))?;
+
+        let mut data = nonce.to_vec();
+        data.extend_from_slice(&encrypted);
+        Ok(STANDARD.encode(data))
+    }
+
+    fn decrypt_with(key: &[u8; KEY_LEN], ciphertext: &str) -> Result<String, String> {
+        let data = STANDARD
             .decode(ciphertext)
             .map_err(|e| format!("Failed to decode base64: {}", e))?;
+        if data.len() < NONCE_LEN {
+            return Err("Encrypted data is too short".to_string());
+        }
+        let (nonce, encrypted) = data.split_at(NONCE_LEN);
 
-        type Aes256CbcDec = Decryptor<Aes256>;
-        let cipher = Aes256CbcDec::new(key.as_slice().into(), iv.as_slice().into());
+        let cipher = Aes256Gcm::new_from_slice(key).map_err(|e| format!("Invalid key: {}", e))?;
+        let decrypted = cipher
+            .decrypt(Nonce::from_slice(nonce), encrypted)
+            .map_err(|e| format!("Decryption failed: {}", e))?;
 
-        let mut buffer = vec![0u8; encrypted.len()];
-        let decrypted_data_len = cipher
-            .decrypt_padded_b2b_mut::<Pkcs7>(&encrypted, &mut buffer)
-            .map_err(|e| format!("Decryption failed: {}", e))?
-            .len();
+        String::from_utf8(decrypted).map_err(|e| format!("Invalid UTF-8: {}", e))
+    }
 
-        // Convert decrypted bytes to a UTF-8 string
-        let decrypted_str = String::from_utf8(buffer[..decrypted_data_len].to_vec())
-            .map_err(|e| format!("Invalid UTF-8: {}", e))?;
-        // Return the decrypted string
-        Ok(decrypted_str)
+    /// Encrypts a secret with the key of this install, using a random nonce
+    ///
+    /// # Arguments
+    /// * `plaintext` - The secret to encrypt
+    ///
+    /// # Returns
+    /// Returns the nonce and the encrypted secret, base64 encoded
+    ///
+    /// # Errors
+    /// Returns an error message if the key could not be loaded, or is locked
+    pub fn encrypt(&self, plaintext: &str) -> Result<String, String> {
+        Self::encrypt_with(&self.key()?, plaintext)
+    }
+
+    /// Decrypts a secret encrypted with the key of this install
+    ///
+    /// # Arguments
+    /// * `ciphertext` - The secret, as returned by `encrypt`
+    ///
+    /// # Returns
+    /// Returns the decrypted secret
+    ///
+    /// # Errors
+    /// Returns an error message if the key is locked, or the secret was tampered with
+    pub fn decrypt(&self, ciphertext: &str) -> Result<String, String> {
+        Self::decrypt_with(&self.key()?, ciphertext)
+    }
+
+    /// Whether the stored secrets are protected by a passphrase that was not entered yet
+    /// Only reads the key file, the key of this install is still generated on first use
+    pub fn is_locked(&self) -> bool {
+        match self.key.read() {
+            Ok(key) if key.is_some() => return false,
+            Ok(_) => {}
+            Err(_) => {
+                log::error!("Failed to acquire read lock for secret key");
+                return true;
+            }
+        }
+        match self.read_key_file() {
+            Ok(Some(KeyFile { key: None, .. })) => true,
+            Ok(_) => false,
+            Err(e) => {
+                log::info!("Secret key unavailable: {}", e);
+                true
+            }
+        }
+    }
+
+    /// Unlocks the stored secrets with the passphrase they are protected by
+    ///
+    /// # Arguments
+    /// * `passphrase` - The passphrase set by the user
+    ///
+    /// # Errors
+    /// Returns an error message if no passphrase is set, or the passphrase is wrong
+    pub fn unlock(&self, passphrase: &str) -> Result<(), String> {
+        let Some(KeyFile {
+            salt: Some(salt),
+            check: Some(check),
+            ..
+        }) = self.read_key_file()?
+        else {
+            return Err("Stored secrets are not protected by a passphrase".to_string());
+        };
+        let salt = STANDARD
+            .decode(salt)
+            .map_err(|e| format!("Failed to decode salt: {}", e))?;
+
+        let key = Self::derive_key(passphrase, &salt)?;
+        if Self::decrypt_with(&key, &check).as_deref() != Ok(PASSPHRASE_CHECK) {
+            return Err("Wrong passphrase".to_string());
+        }
+        self.set_key(key)
+    }
+
+    /// Creates the key that replaces the key of this install, deriving it from a
+    /// passphrase or generating a random one, without using it yet
+    /// Secrets encrypted with the previous key can no longer be decrypted once the
+    /// new key is committed, so they are encrypted with the new key first
+    ///
+    /// # Arguments
+    /// * `passphrase` - The passphrase to derive the key from, or None to remove the passphrase
+    ///
+    /// # Returns
+    /// The new key, which takes effect when passed to `commit_key`
+    ///
+    /// # Errors
+    /// Returns an error message if the key could not be derived
+    pub fn prepare_passphrase(&self, passphrase: Option<&str>) -> Result<PendingKey, String> {
+        let (key, key_file) = match passphrase {
+            Some(passphrase) => {
+                let salt: [u8; SALT_LEN] = rand::random();
+                let key = Self::derive_key(passphrase, &salt)?;
+                let key_file = KeyFile {
+                    key: None,
+                    salt: Some(STANDARD.encode(salt)),
+                    check: Some(Self::encrypt_with(&key, PASSPHRASE_CHECK)?),
+                };
+                (key, key_file)
+            }
+            None => {
+                let key: [u8; KEY_LEN] = rand::random();
+                let key_file = KeyFile {
+                    key: Some(STANDARD.encode(key)),
+                    salt: None,
+                    check: None,
+                };
+                (key, key_file)
+            }
+        };
+
+        Ok(PendingKey {
+            encryption: Self {
+                key_path: self.key_path.clone(),
+                key: RwLock::new(Some(key)),
+            },
+            key,
+            key_file,
+        })
+    }
+
+    /// Makes a key created by `prepare_passphrase` the key of this install
+    /// The key file is replaced atomically, so the previous key stays in place on failure
+    ///
+    /// # Arguments
+    /// * `pending` - The new key
+    ///
+    /// # Errors
+    /// Returns an error message if the key file could not be written
+    pub fn commit_key(&self, pending: PendingKey) -> Result<(), String> {
+        // Take the lock first, so nothing can fail once the key file is replaced
+        let mut key = self
+            .key
+            .write()
+            .map_err(|_| "Failed to acquire write lock for secret key".to_string())?;
+        self.write_key_file(&pending.key_file)?;
+        *key = Some(pending.key);
+        Ok(())
+    }
+}
+
+/// A key replacing the key of this install, see `EncryptionService::prepare_passphrase`
+pub struct PendingKey {
+    /// Encrypts with the new key, without ever writing the key file
+    encryption: EncryptionService,
+    key: [u8; KEY_LEN],
+    key_file: KeyFile,
+}
+
+impl PendingKey {
+    /// The service encrypting secrets with the new key
+    pub fn encryption(&self) -> &EncryptionService {
+        &self.encryption
     }
 }
 
 #[cfg(test)]
 mod tests {
-    // use "decrypted.json" and encrypt, then write to "encrypted.json"
     use super::*;
-    use serde_json::Value;
-    use std::fs::File;
-    use std::io::{BufReader, Read};
-    use std::path::Path;
+    use tempfile::TempDir;
 
     #[test]
     fn test_encryption_decryption() {
-        // Read the JSON file
-        let path = Path::new("decrypted.json");
-        let file = File::open(path).expect("Failed to open the file");
-        let mut reader = BufReader::new(file);
-        let mut json_data = String::new();
-        reader
-            .read_to_string(&mut json_data)
-            .expect("Failed to read the file");
-        // Encrypt the JSON data
-        let encrypted_data =
-            EncryptionService::encrypt_aes(&json_data).expect("Failed to encrypt the data");
-        // Write the encrypted data to a file
-        fs::write("encrypted.json", &encrypted_data)
-            .expect("Failed to write encrypted data to file");
-        // Decrypt the data
-        let decrypted_data =
-            EncryptionService::decrypt_aes(&encrypted_data).expect("Failed to decrypt the data");
-        // Parse the decrypted data to ensure it's valid JSON
-        let parsed_data: Value =
-            serde_json::from_str(&decrypted_data).expect("Failed to parse decrypted data as JSON");
-        // Check if the parsed data is equal to the original JSON data
-        let original_data: Value =
-            serde_json::from_str(&json_data).expect("Failed to parse original data as JSON");
-        assert_eq!(original_data, parsed_data);
+        let key: [u8; KEY_LEN] = rand::random();
+        let secret = "authcookie_0123456789abcdef";
+
+        let first = EncryptionService::encrypt_with(&key, secret).unwrap();
+        let second = EncryptionService::encrypt_with(&key, secret).unwrap();
+        // Every secret gets its own nonce
+        assert_ne!(first, second);
+
+        assert_eq!(
+            EncryptionService::decrypt_with(&key, &first).unwrap(),
+            secret
+        );
+        assert_eq!(
+            EncryptionService::decrypt_with(&key, &second).unwrap(),
+            secret
+        );
+    }
+
+    #[test]
+    fn test_decryption_rejects_wrong_key_and_tampering() {
+        let key: [u8; KEY_LEN] = rand::random();
+        let other_key: [u8; KEY_LEN] = rand::random();
+        let encrypted = EncryptionService::encrypt_with(&key, "authcookie_test").unwrap();
+
+        assert!(EncryptionService::decrypt_with(&other_key, &encrypted).is_err());
+
+        let mut data = STANDARD.decode(&encrypted).unwrap();
+        let last = data.len() - 1;
+        data[last] ^= 1;
+        assert!(EncryptionService::decrypt_with(&key, &STANDARD.encode(data)).is_err());
+    }
+
+    #[test]
+    fn test_derived_key_depends_on_passphrase_and_salt() {
+        let salt: [u8; SALT_LEN] = rand::random();
+        let other_salt: [u8; SALT_LEN] = rand::random();
+
+        let key = EncryptionService::derive_key("correct horse", &salt).unwrap();
+        assert_eq!(
+            key,
+            EncryptionService::derive_key("correct horse", &salt).unwrap()
+        );
+        assert_ne!(
+            key,
+            EncryptionService::derive_key("battery staple", &salt).unwrap()
+        );
+        assert_ne!(
+            key,
+            EncryptionService::derive_key("correct horse", &other_salt).unwrap()
+        );
     }
 
     #[test]
-    fn test_decryption() {
-        // Read the encrypted JSON file
-        let path = Path::new("encrypted.json");
-        let file = File::open(path).expect("Failed to open the file");
-        let mut reader = BufReader::new(file);
-        let mut encrypted_data = String::new();
-        reader
-            .read_to_string(&mut encrypted_data)
-            .expect("Failed to read the file");
-        // Decrypt the data
-        let decrypted_data =
-            EncryptionService::decrypt_aes(&encrypted_data).expect("Failed to decrypt the data");
-        // write the decrypted data to a file
-        fs::write("decrypted.json", &decrypted_data)
-            .expect("Failed to write decrypted data to file");
+    fn test_lock_check_does_not_generate_a_key() {
+        let dir = TempDir::new().unwrap();
+        let key_path = dir.path().join("secret_key.json");
+        let service = EncryptionService::new(key_path.clone());
+
+        assert!(!service.is_locked());
+        assert!(!key_path.exists());
+
+        let encrypted = service.encrypt("authcookie_test").unwrap();
+        assert!(key_path.exists());
+        // Another run of the app reads the same key
+        let reloaded = EncryptionService::new(key_path);
+        assert_eq!(reloaded.decrypt(&encrypted).unwrap(), "authcookie_test");
+    }
+
+    #[test]
+    fn test_passphrase_locks_until_unlocked() {
+        let dir = TempDir::new().unwrap();
+        let key_path = dir.path().join("secret_key.json");
+        let service = EncryptionService::new(key_path.clone());
+        let pending = service.prepare_passphrase(Some("correct horse")).unwrap();
+        service.commit_key(pending).unwrap();
+        let encrypted = service.encrypt("authcookie_test").unwrap();
+        assert!(!service.is_locked());
+
+        let reloaded = EncryptionService::new(key_path);
+        assert!(reloaded.is_locked());
+        assert!(reloaded.decrypt(&encrypted).is_err());
+        assert!(reloaded.unlock("battery staple").is_err());
+        assert!(reloaded.is_locked());
+
+        reloaded.unlock("correct horse").unwrap();
+        assert!(!reloaded.is_locked());
+        assert_eq!(reloaded.decrypt(&encrypted).unwrap(), "authcookie_test");
     }
 }
@@ -1,9 +1,10 @@
 use serde::Serialize;
+use std::collections::HashSet;
 use std::sync::RwLock;
 
 use crate::{
     definitions::{FolderModel, Platform, WorldModel},
-    services::{FileService, SortingService},
+    services::{FileService, FolderManager, SortingService},
 };
 
 #[derive(Serialize)]
@@ -30,6 +31,8 @@ struct PLSWorlds {
     description: String,
     #[serde(rename = "Platform")]
     platform: PLSPlatform,
+    #[serde(rename = "Memo", skip_serializing_if = "String::is_empty")]
+    memo: String,
 }
 
 #[derive(Serialize)]
@@ -81,13 +84,32 @@ impl ExportService {
             sort_direction
         );
 
+        // PortalLibrarySystem has no nested categories, so subfolders are exported
+        // as categories of their own, named by their path ("Parent/Child")
+        let mut names_to_export: Vec<String> = Vec::new();
         for folder_name in folder_names {
+            let subfolders = FolderManager::descendant_names(&folder_name, &folders_lock);
+            for name in std::iter::once(folder_name).chain(subfolders) {
+                if !names_to_export.contains(&name) {
+                    names_to_export.push(name);
+                }
+            }
+        }
+
+        for folder_name in names_to_export {
             log::info!("Processing folder: {}", folder_name);
 
-            // Get all worlds in this folder
+            // Get all worlds in this folder, evaluating the query of smart folders
+            let world_ids: HashSet<String> = folders_lock
+                .iter()
+                .find(|folder| folder.folder_name == folder_name)
+                .map(|folder| FolderManager::resolve_world_ids(folder, &worlds_lock))
+                .unwrap_or_default()
+                .into_iter()
+                .collect();
             let mut folder_worlds: Vec<WorldModel> = worlds_lock
                 .iter()
-                .filter(|world| world.user_data.folders.contains(&folder_name))
+                .filter(|world| world_ids.contains(&world.api_data.world_id))
                 .cloned()
                 .collect();
 
@@ -96,7 +118,7 @@ impl ExportService {
                 SortingService::sort_world_models(folder_worlds, &sort_field, &sort_direction);
 
             folders_to_export.push(FolderExport {
-                folder_name: folder_name.clone(),
+                folder_name,
                 worlds: folder_worlds,
             });
         }
@@ -132,7 +154,7 @@ impl ExportService {
                         .platform
                         .contains(&Platform::StandaloneWindows),
                     android: world.api_data.platform.contains(&Platform::Android),
-                    ios: world.api_data.platform.contains(&Platform::IOS),
+                    ios: world.api_data.platform.contains(&Platform::Ios),
                 };
 
                 worlds_list.push(PLSWorlds {
@@ -145,6 +167,7 @@ impl ExportService {
                     capacity: world.api_data.capacity,
                     description: world.api_data.description.clone(),
                     platform,
+                    memo: world.user_data.memo.clone(),
                 });
             }
             categories.push(PLSCategory {
@@ -0,0 +1,789 @@
+use std::collections::{HashMap, HashSet};
+use std::sync::RwLock;
+
+use serde::Serialize;
+
+use crate::api::world::{self, FavoriteWorld, HiddenWorld, ReleaseStatus};
+use crate::api::{favorite, ApiContext};
+use crate::definitions::{FolderModel, WorldApiData, WorldAvailability, WorldModel};
+use crate::errors::{AppError, ConcurrencyError, EntityError};
+use crate::services::{FolderManager, Storage};
+
+/// The number of worlds VRChat allows in one favorite group
+pub const FAVORITE_GROUP_LIMIT: usize = 100;
+
+/// Why a change to a world can't be synced
+#[derive(Debug, Clone, PartialEq, Serialize, specta::Type)]
+pub enum FavoriteSyncConflictReason {
+    /// The favorite group already holds `FAVORITE_GROUP_LIMIT` worlds
+    GroupFull,
+    /// The world is favorited in another group, and VRChat allows only one
+    InOtherGroup(String),
+    /// The world was privated or deleted, so it can't be favorited or imported
+    Unavailable,
+}
+
+#[derive(Debug, Clone, Serialize, specta::Type)]
+pub struct FavoriteSyncConflict {
+    pub world_id: String,
+    pub reason: FavoriteSyncConflictReason,
+}
+
+/// The changes that bring a folder and its favorite group back in sync
+#[derive(Debug, Clone, Serialize, specta::Type)]
+pub struct FavoriteSyncPlan {
+    pub folder_name: String,
+    pub group: String,
+    /// Worlds added to the group in game, to add to the folder
+    pub import: Vec<String>,
+    /// Worlds removed from the group in game, to remove from the folder
+    pub remove_local: Vec<String>,
+    /// Worlds added to the folder, to favorite in the group
+    pub push_add: Vec<String>,
+    /// Worlds removed from the folder, to unfavorite
+    pub push_remove: Vec<String>,
+    /// Changes that are left out, and why
+    pub conflicts: Vec<FavoriteSyncConflict>,
+    /// Worlds whose change could not be sent to VRChat, once the plan is applied
+    pub failed: Vec<String>,
+}
+
+impl FavoriteSyncPlan {
+    fn new(folder_name: String, group: String) -> Self {
+        Self {
+            folder_name,
+            group,
+            import: vec![],
+            remove_local: vec![],
+            push_add: vec![],
+            push_remove: vec![],
+            conflicts: vec![],
+            failed: vec![],
+        }
+    }
+
+    fn conflict(&mut self, world_id: &str, reason: FavoriteSyncConflictReason) {
+        self.conflicts.push(FavoriteSyncConflict {
+            world_id: world_id.to_string(),
+            reason,
+        });
+    }
+}
+
+/// What importing a favorite group changed in the folder it is filed into
+#[derive(Debug, Clone, Serialize, specta::Type)]
+pub struct FavoriteGroupImport {
+    pub group: String,
+    pub folder_name: String,
+    /// Worlds filed into the folder
+    pub added: u32,
+    /// Worlds taken out of the folder, as they left the group since the last sync
+    pub removed: u32,
+    /// Worlds of the group that were already in the folder
+    pub unchanged: u32,
+}
+
+/// The world favorites of the user, as listed by VRChat
+pub struct RemoteFavorites {
+    /// The favorite group of each favorited world
+    groups: HashMap<String, String>,
+    /// The worlds of each favorite group, in the order VRChat lists them
+    members: HashMap<String, Vec<String>>,
+    /// The data of the favorited worlds that are public
+    worlds: HashMap<String, WorldApiData>,
+}
+
+impl RemoteFavorites {
+    pub fn new(favorites: Vec<FavoriteWorld>, hidden: Vec<HiddenWorld>) -> Self {
+        let mut remote = RemoteFavorites {
+            groups: HashMap::new(),
+            members: HashMap::new(),
+            worlds: HashMap::new(),
+        };
+
+        for favorite in favorites {
+            let (id, group) = (favorite.id.clone(), favorite.favorite_group.clone());
+            if favorite.release_status == ReleaseStatus::Public {
+                match favorite.try_into() {
+                    Ok(world_data) => {
+                        remote.worlds.insert(id.clone(), world_data);
+                    }
+                    Err(e) => log::warn!("Failed to parse favorite world {}: {}", id, e),
+                }
+            }
+            remote.add(id, group);
+        }
+        for world in hidden {
+            remote.add(world.id, world.favorite_group);
+        }
+        remote
+    }
+
+    fn add(&mut self, world_id: String, group: String) {
+        self.members
+            .entry(group.clone())
+            .or_default()
+            .push(world_id.clone());
+        self.groups.insert(world_id, group);
+    }
+}
+
+/// Keeps folders in sync with the VRChat world favorite groups they are linked to
+///
+/// Each linked folder remembers the worlds it had in common with its group
+/// after the last sync. A world missing on one side since then was removed
+/// there, and a world that is new on one side was added there, so changes
+/// made in game and in the app can both be carried over.
+pub struct FavoriteSyncService;
+
+impl FavoriteSyncService {
+    /// Fetch the world favorites of the logged in user
+    ///
+    /// # Arguments
+    /// * `context` - The API context (cookies, server, rate limits) to use
+    ///
+    /// # Returns
+    /// The favorites, grouped by favorite group
+    ///
+    /// # Errors
+    /// Returns an error if the request fails
+    pub async fn fetch_favorites(context: &ApiContext) -> Result<RemoteFavorites, AppError> {
+        let (favorites, hidden) = world::get_favorite_worlds(context).await.map_err(|e| {
+            log::error!("Failed to fetch favorite worlds: {}", e);
+            e
+        })?;
+        Ok(RemoteFavorites::new(favorites, hidden))
+    }
+
+    /// Work out the changes needed to sync every linked folder, without applying them
+    ///
+    /// # Arguments
+    /// * `folders` - The folders of the library
+    /// * `worlds` - The worlds of the library
+    /// * `remote` - The favorites of the user
+    ///
+    /// # Returns
+    /// A plan for each linked folder, including the changes that conflict
+    pub fn plan(
+        folders: &[FolderModel],
+        worlds: &[WorldModel],
+        remote: &RemoteFavorites,
+    ) -> Vec<FavoriteSyncPlan> {
+        let library: HashMap<&str, &WorldModel> = worlds
+            .iter()
+            .map(|w| (w.api_data.world_id.as_str(), w))
+            .collect();
+        // Worlds favorited in a group earlier in this sync, which no other group can take
+        let mut claimed: HashMap<String, String> = HashMap::new();
+        let mut plans = vec![];
+
+        for folder in folders.iter().filter(|f| !f.is_smart()) {
+            let Some(sync) = &folder.favorite_sync else {
+                continue;
+            };
+            let mut plan = FavoriteSyncPlan::new(folder.folder_name.clone(), sync.group.clone());
+            let local: HashSet<&String> = folder.world_ids.iter().collect();
+            let members = remote.members.get(&sync.group).cloned().unwrap_or_default();
+            let in_group: HashSet<&String> = members.iter().collect();
+            let synced: HashSet<&String> = sync.synced_world_ids.iter().collect();
+
+            for world_id in &members {
+                if synced.contains(world_id) {
+                    if !local.contains(world_id) {
+                        plan.push_remove.push(world_id.clone());
+                    }
+                } else if !local.contains(world_id) {
+                    if library.contains_key(world_id.as_str())
+                        || remote.worlds.contains_key(world_id)
+                    {
+                        plan.import.push(world_id.clone());
+                    } else {
+                        plan.conflict(world_id, FavoriteSyncConflictReason::Unavailable);
+                    }
+                }
+            }
+
+            let mut group_size = members.len() - plan.push_remove.len();
+            for world_id in &folder.world_ids {
+                if synced.contains(world_id) {
+                    if !in_group.contains(world_id) {
+                        plan.remove_local.push(world_id.clone());
+                    }
+                    continue;
+                }
+                if in_group.contains(world_id) {
+                    continue;
+                }
+
+                let other_group = remote.groups.get(world_id).or(claimed.get(world_id));
+                if let Some(other_group) = other_group {
+                    plan.conflict(
+                        world_id,
+                        FavoriteSyncConflictReason::InOtherGroup(other_group.clone()),
+                    );
+                } else if library
+                    .get(world_id.as_str())
+                    .is_some_and(|w| w.user_data.availability.is_unavailable())
+                {
+                    plan.conflict(world_id, FavoriteSyncConflictReason::Unavailable);
+                } else if group_size >= FAVORITE_GROUP_LIMIT {
+                    plan.conflict(world_id, FavoriteSyncConflictReason::GroupFull);
+                } else {
+                    plan.push_add.push(world_id.clone());
+                    claimed.insert(world_id.clone(), sync.group.clone());
+                    group_size += 1;
+                }
+            }
+
+            plans.push(plan);
+        }
+        plans
+    }
+
+    /// Sync every linked folder with its favorite group
+    /// Changes listed as conflicts are left out, and are reported again next time.
+    ///
+    /// # Arguments
+    /// * `context` - The API context (cookies, server, rate limits) to use
+    /// * `folders` - The list of folders, as a RwLock
+    /// * `worlds` - The list of worlds, as a RwLock
+    /// * `storage` - The storage to persist the changes to
+    ///
+    /// # Returns
+    /// The applied plans, with the worlds that could not be sent to VRChat
+    ///
+    /// # Errors
+    /// Returns an error if the favorites could not be fetched
+    /// Returns an error if a lock is poisoned, or the folders could not be saved
+    pub async fn apply(
+        context: &ApiContext,
+        folders: &RwLock<Vec<FolderModel>>,
+        worlds: &RwLock<Vec<WorldModel>>,
+        storage: &dyn Storage,
+    ) -> Result<Vec<FavoriteSyncPlan>, AppError> {
+        let remote = Self::fetch_favorites(context).await?;
+        let mut plans = {
+            let folders_lock = folders.read().map_err(|_| ConcurrencyError::PoisonedLock)?;
+            let worlds_lock = worlds.read().map_err(|_| ConcurrencyError::PoisonedLock)?;
+            Self::plan(&folders_lock, &worlds_lock, &remote)
+        };
+
+        for plan in plans.iter_mut() {
+            log::info!(
+                "Syncing folder {} with favorite group {}",
+                plan.folder_name,
+                plan.group
+            );
+            Self::apply_locally(plan, &remote, folders, worlds, storage)?;
+
+            let mut in_group: HashSet<String> = remote
+                .members
+                .get(&plan.group)
+                .map(|members| members.iter().cloned().collect())
+                .unwrap_or_default();
+            for world_id in plan.push_remove.clone() {
+                match favorite::remove_favorite(context, &world_id).await {
+                    Ok(_) => {
+                        in_group.remove(&world_id);
+                    }
+                    Err(e) => {
+                        log::error!("Failed to unfavorite world {}: {}", world_id, e);
+                        plan.failed.push(world_id);
+                    }
+                }
+            }
+            for world_id in plan.push_add.clone() {
+                match favorite::add_world_favorite(context, &world_id, &plan.group).await {
+                    Ok(_) => {
+                        in_group.insert(world_id);
+                    }
+                    Err(e) => {
+                        log::error!("Failed to favorite world {}: {}", world_id, e);
+                        plan.failed.push(world_id);
+                    }
+                }
+            }
+
+            // Worlds that failed are not recorded, so the next sync tries them again
+            let synced: Vec<String> = {
+                let folders_lock = folders.read().map_err(|_| ConcurrencyError::PoisonedLock)?;
+                folders_lock
+                    .iter()
+                    .find(|f| f.folder_name == plan.folder_name)
+                    .map(|f| {
+                        f.world_ids
+                            .iter()
+                            .filter(|id| in_group.contains(*id))
+                            .cloned()
+                            .collect()
+                    })
+                    .unwrap_or_default()
+            };
+            FolderManager::set_favorite_sync_base(
+                plan.folder_name.clone(),
+                synced,
+                folders,
+                storage,
+            )?;
+        }
+
+        Ok(plans)
+    }
+
+    /// Import the favorites of the user into one folder per favorite group
+    /// Each folder is made to hold every world of its group, so running the
+    /// import again only reports unchanged worlds.
+    ///
+    /// # Arguments
+    /// * `context` - The API context (cookies, server, rate limits) to use
+    /// * `folders` - The list of folders, as a RwLock
+    /// * `worlds` - The list of worlds, as a RwLock
+    /// * `storage` - The storage to persist the changes to
+    ///
+    /// # Returns
+    /// The changes made to the folder of each group
+    ///
+    /// # Errors
+    /// Returns an error if the favorites or favorite groups could not be fetched
+    /// Returns an error if a group can't be filed into a folder, or the library
+    /// could not be saved
+    pub async fn import_groups(
+        context: &ApiContext,
+        folders: &RwLock<Vec<FolderModel>>,
+        worlds: &RwLock<Vec<WorldModel>>,
+        storage: &dyn Storage,
+    ) -> Result<Vec<FavoriteGroupImport>, AppError> {
+        let remote = Self::fetch_favorites(context).await?;
+        let groups = favorite::get_world_favorite_groups(context)
+            .await
+            .map_err(|e| {
+                log::error!("Failed to fetch favorite groups: {}", e);
+                e
+            })?
+            .into_iter()
+            .map(|group| {
+                let folder_name = FolderManager::escape_folder_name(&group.display_name);
+                (group.name, folder_name)
+            })
+            .collect::<Vec<_>>();
+
+        Self::import_remote(&remote, &groups, folders, worlds, storage)
+    }
+
+    /// Import fetched favorites into one folder per favorite group
+    ///
+    /// The folder of a group is the folder linked to it for syncing, or else
+    /// the folder named after its display name, which is created if the group
+    /// has worlds.
+    /// Only worlds known to have come from the group are taken out of the folder,
+    /// which are the worlds of a linked folder that left the group since the
+    /// last sync. The worlds the user filed into a folder of the same name stay.
+    ///
+    /// # Arguments
+    /// * `remote` - The favorites of the user
+    /// * `groups` - The name and display name of each world favorite group
+    /// * `folders` - The list of folders, as a RwLock
+    /// * `worlds` - The list of worlds, as a RwLock
+    /// * `storage` - The storage to persist the changes to
+    ///
+    /// # Returns
+    /// The changes made to the folder of each group
+    ///
+    /// # Errors
+    /// Returns an error if the folder named after a group is a smart folder, or
+    /// is linked to another group
+    /// Returns an error if a lock is poisoned, or the library could not be saved
+    pub fn import_remote(
+        remote: &RemoteFavorites,
+        groups: &[(String, String)],
+        folders: &RwLock<Vec<FolderModel>>,
+        worlds: &RwLock<Vec<WorldModel>>,
+        storage: &dyn Storage,
+    ) -> Result<Vec<FavoriteGroupImport>, AppError> {
+        // Groups missing from the list of groups keep their name
+        let mut groups = groups.to_vec();
+        let mut unlisted: Vec<&String> = remote
+            .members
+            .keys()
+            .filter(|group| !groups.iter().any(|(name, _)| name == *group))
+            .collect();
+        unlisted.sort();
+        groups.extend(
+            unlisted
+                .into_iter()
+                .map(|group| (group.clone(), group.clone())),
+        );
+
+        // Reverse the order to preserve the original date added order
+        let favorites: Vec<WorldApiData> = groups
+            .iter()
+            .flat_map(|(group, _)| remote.members.get(group).into_iter().flatten())
+            .filter_map(|id| remote.worlds.get(id).cloned())
+            .rev()
+            .collect();
+        let available = favorites.iter().map(|w| w.world_id.clone()).collect();
+        let unavailable = remote
+            .groups
+            .keys()
+            .filter(|id| !remote.worlds.contains_key(*id))
+            .cloned()
+            .collect();
+        FolderManager::add_worlds(worlds, favorites, storage)?;
+        FolderManager::set_world_availability(
+            available,
+            WorldAvailability::Available,
+            worlds,
+            storage,
+        )?;
+        FolderManager::set_world_availability(
+            unavailable,
+            WorldAvailability::Private,
+            worlds,
+            storage,
+        )?;
+
+        let mut imports = vec![];
+        for (group, display_name) in groups {
+            let members = remote.members.get(&group).cloned().unwrap_or_default();
+            // Worlds that are not in the library, or are hidden, are not filed
+            let to_file: Vec<String> = {
+                let worlds_lock = worlds.read().map_err(|_| ConcurrencyError::PoisonedLock)?;
+                members
+                    .iter()
+                    .filter(|id| {
+                        worlds_lock
+                            .iter()
+                            .any(|w| &w.api_data.world_id == *id && !w.user_data.hidden)
+                    })
+                    .cloned()
+                    .collect()
+            };
+
+            let folder_name = match Self::import_folder(&group, &display_name, folders)? {
+                Some(folder_name) => folder_name,
+                None if to_file.is_empty() => continue,
+                None => FolderManager::create_folder(display_name, folders, storage)?,
+            };
+            let (current, synced) = {
+                let folders_lock = folders.read().map_err(|_| ConcurrencyError::PoisonedLock)?;
+                let folder = folders_lock
+                    .iter()
+                    .find(|f| f.folder_name == folder_name)
+                    .ok_or_else(|| EntityError::FolderNotFound(folder_name.clone()))?;
+                (
+                    folder.world_ids.clone(),
+                    folder
+                        .favorite_sync
+                        .as_ref()
+                        .map(|sync| sync.synced_world_ids.clone()),
+                )
+            };
+
+            let mut import = FavoriteGroupImport {
+                group,
+                folder_name,
+                added: 0,
+                removed: 0,
+                unchanged: 0,
+            };
+            for world_id in &to_file {
+                if current.contains(world_id) {
+                    import.unchanged += 1;
+                    continue;
+                }
+                FolderManager::add_world_to_folder(
+                    import.folder_name.clone(),
+                    world_id.clone(),
+                    folders,
+                    worlds,
+                    storage,
+                )?;
+                import.added += 1;
+            }
+            let left_group = current.iter().filter(|id| {
+                !members.contains(id) && synced.as_ref().is_some_and(|synced| synced.contains(id))
+            });
+            for world_id in left_group {
+                FolderManager::remove_world_from_folder(
+                    import.folder_name.clone(),
+                    world_id.clone(),
+                    folders,
+                    worlds,
+                    storage,
+                )?;
+                import.removed += 1;
+            }
+
+            // The folder now holds its group, which is what a sync starts from
+            if synced.is_some() {
+                let synced = {
+                    let folders_lock =
+                        folders.read().map_err(|_| ConcurrencyError::PoisonedLock)?;
+                    folders_lock
+                        .iter()
+                        .find(|f| f.folder_name == import.folder_name)
+                        .map(|f| {
+                            f.world_ids
+                                .iter()
+                                .filter(|id| members.contains(id))
+                                .cloned()
+                                .collect()
+                        })
+                        .unwrap_or_default()
+                };
+                FolderManager::set_favorite_sync_base(
+                    import.folder_name.clone(),
+                    synced,
+                    folders,
+                    storage,
+                )?;
+            }
+            imports.push(import);
+        }
+        Ok(imports)
+    }
+
+    /// Find the folder a favorite group is imported into
+    ///
+    /// # Returns
+    /// The name of the folder, or None if it has to be created
+    ///
+    /// # Errors
+    /// Returns an error if the folder named after the group can't hold it
+    fn import_folder(
+        group: &str,
+        display_name: &str,
+        folders: &RwLock<Vec<FolderModel>>,
+    ) -> Result<Option<String>, AppError> {
+        let folders_lock = folders.read().map_err(|_| ConcurrencyError::PoisonedLock)?;
+
+        if let Some(folder) = folders_lock
+            .iter()
+            .find(|f| f.favorite_sync.as_ref().is_some_and(|s| s.group == group))
+        {
+            return Ok(Some(folder.folder_name.clone()));
+        }
+
+        let Some(folder) = folders_lock.iter().find(|f| f.folder_name == display_name) else {
+            return Ok(None);
+        };
+        if folder.is_smart() {
+            return Err(EntityError::InvalidOperation(format!(
+                "favorite group {} can't be imported into smart folder {}",
+                group, display_name
+            ))
+            .into());
+        }
+        if let Some(sync) = &folder.favorite_sync {
+            return Err(EntityError::InvalidOperation(format!(
+                "favorite group {} can't be imported into folder {}, which is linked to {}",
+                group, display_name, sync.group
+            ))
+            .into());
+        }
+        Ok(Some(folder.folder_name.clone()))
+    }
+
+    /// Applies the changes of a plan that only touch the library
+    fn apply_locally(
+        plan: &FavoriteSyncPlan,
+        remote: &RemoteFavorites,
+        folders: &RwLock<Vec<FolderModel>>,
+        worlds: &RwLock<Vec<WorldModel>>,
+        storage: &dyn Storage,
+    ) -> Result<(), AppError> {
+        let new_worlds: Vec<WorldApiData> = {
+            let worlds_lock = worlds.read().map_err(|_| ConcurrencyError::PoisonedLock)?;
+            plan.import
+                .iter()
+                .filter(|id| !worlds_lock.iter().any(|w| &w.api_data.world_id == *id))
+                .filter_map(|id| remote.worlds.get(id).cloned())
+                .collect()
+        };
+        if !new_worlds.is_empty() {
+            FolderManager::add_worlds(worlds, new_worlds, storage)?;
+        }
+
+        for world_id in &plan.import {
+            FolderManager::add_world_to_folder(
+                plan.folder_name.clone(),
+                world_id.clone(),
+                folders,
+                worlds,
+                storage,
+            )?;
+        }
+        for world_id in &plan.remove_local {
+            FolderManager::remove_world_from_folder(
+                plan.folder_name.clone(),
+                world_id.clone(),
+                folders,
+                worlds,
+                storage,
+            )?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::definitions::{FavoriteGroupSync, WorldAvailability};
+    use crate::services::storage::SqliteStorage;
+    use crate::services::test_support::test_world;
+
+    fn linked_folder(name: &str, group: &str, world_ids: &[&str], synced: &[&str]) -> FolderModel {
+        let mut folder = FolderModel::new(name.to_string());
+        folder.world_ids = world_ids.iter().map(|id| id.to_string()).collect();
+        folder.favorite_sync = Some(FavoriteGroupSync {
+            group: group.to_string(),
+            synced_world_ids: synced.iter().map(|id| id.to_string()).collect(),
+        });
+        folder
+    }
+
+    fn remote(groups: &[(&str, &[&str])]) -> RemoteFavorites {
+        let mut remote = RemoteFavorites {
+            groups: HashMap::new(),
+            members: HashMap::new(),
+            worlds: HashMap::new(),
+        };
+        for (group, world_ids) in groups {
+            for world_id in world_ids.iter() {
+                remote.add(world_id.to_string(), group.to_string());
+                remote
+                    .worlds
+                    .insert(world_id.to_string(), test_world(world_id).api_data);
+            }
+        }
+        remote
+    }
+
+    #[test]
+    fn test_plan_carries_changes_both_ways() {
+        // wrld_kept was synced before, wrld_gone was removed in game, wrld_dropped
+        // was removed in the app, wrld_new was added in game, wrld_local in the app
+        let folders = vec![linked_folder(
+            "Events",
+            "worlds1",
+            &["wrld_kept", "wrld_gone", "wrld_local"],
+            &["wrld_kept", "wrld_gone", "wrld_dropped"],
+        )];
+        let remote = remote(&[("worlds1", &["wrld_kept", "wrld_dropped", "wrld_new"])]);
+
+        let plans = FavoriteSyncService::plan(&folders, &[], &remote);
+        assert_eq!(plans.len(), 1);
+        let plan = &plans[0];
+        assert_eq!(plan.import, vec!["wrld_new"]);
+        assert_eq!(plan.remove_local, vec!["wrld_gone"]);
+        assert_eq!(plan.push_add, vec!["wrld_local"]);
+        assert_eq!(plan.push_remove, vec!["wrld_dropped"]);
+        assert!(plan.conflicts.is_empty());
+    }
+
+    #[test]
+    fn test_plan_reports_conflicts() {
+        let full: Vec<String> = (0..FAVORITE_GROUP_LIMIT)
+            .map(|i| format!("wrld_{}", i))
+            .collect();
+        let full: Vec<&str> = full.iter().map(String::as_str).collect();
+        let folders = vec![
+            linked_folder("Full", "worlds1", &["wrld_extra"], &[]),
+            linked_folder("Other", "worlds2", &["wrld_0", "wrld_private"], &[]),
+        ];
+        let mut private = test_world("wrld_private");
+        private.user_data.availability = WorldAvailability::Private;
+        let remote = remote(&[("worlds1", &full)]);
+
+        let plans = FavoriteSyncService::plan(&folders, &[private], &remote);
+        assert!(plans[0].push_add.is_empty());
+        assert_eq!(
+            plans[0].conflicts[0].reason,
+            FavoriteSyncConflictReason::GroupFull
+        );
+        assert_eq!(
+            plans[1].conflicts[0].reason,
+            FavoriteSyncConflictReason::InOtherGroup("worlds1".to_string())
+        );
+        assert_eq!(
+            plans[1].conflicts[1].reason,
+            FavoriteSyncConflictReason::Unavailable
+        );
+        // The first sync of the full group imports its worlds into the folder
+        assert_eq!(plans[0].import.len(), FAVORITE_GROUP_LIMIT);
+    }
+
+    #[test]
+    fn test_import_groups_is_idempotent() {
+        let storage = SqliteStorage::open_in_memory().unwrap();
+        let folders = RwLock::new(vec![]);
+        let worlds = RwLock::new(vec![]);
+        FolderManager::add_worlds(&worlds, vec![test_world("wrld_old").api_data], &storage)
+            .unwrap();
+        FolderManager::create_folder("Games".to_string(), &folders, &storage).unwrap();
+        FolderManager::add_world_to_folder(
+            "Games".to_string(),
+            "wrld_old".to_string(),
+            &folders,
+            &worlds,
+            &storage,
+        )
+        .unwrap();
+
+        let remote = remote(&[
+            ("worlds1", &["wrld_a", "wrld_b"]),
+            ("worlds2", &["wrld_c"]),
+            ("worlds3", &[]),
+        ]);
+        let groups = vec![
+            ("worlds1".to_string(), "Chill".to_string()),
+            ("worlds2".to_string(), "Games".to_string()),
+            ("worlds3".to_string(), "Empty".to_string()),
+        ];
+
+        let imports =
+            FavoriteSyncService::import_remote(&remote, &groups, &folders, &worlds, &storage)
+                .unwrap();
+        assert_eq!(imports.len(), 2);
+        assert_eq!(imports[0].folder_name, "Chill");
+        assert_eq!((imports[0].added, imports[0].removed), (2, 0));
+        assert_eq!(imports[1].folder_name, "Games");
+        assert_eq!((imports[1].added, imports[1].removed), (1, 0));
+
+        let imports =
+            FavoriteSyncService::import_remote(&remote, &groups, &folders, &worlds, &storage)
+                .unwrap();
+        assert_eq!(folders.read().unwrap().len(), 2);
+        assert_eq!(imports[0].unchanged, 2);
+        assert_eq!(imports[1].unchanged, 1);
+        assert!(imports.iter().all(|i| i.added == 0 && i.removed == 0));
+
+        // The world the user filed into the folder of the same name stays
+        let games = FolderManager::get_worlds("Games".to_string(), &folders, &worlds).unwrap();
+        let mut ids: Vec<&str> = games.iter().map(|w| w.world_id.as_str()).collect();
+        ids.sort();
+        assert_eq!(ids, vec!["wrld_c", "wrld_old"]);
+
+        // Worlds that left the group since the last sync are taken out of a linked folder
+        FolderManager::set_folder_favorite_group(
+            "Chill".to_string(),
+            Some("worlds1".to_string()),
+            &folders,
+            &storage,
+        )
+        .unwrap();
+        FolderManager::set_favorite_sync_base(
+            "Chill".to_string(),
+            vec!["wrld_a".to_string(), "wrld_b".to_string()],
+            &folders,
+            &storage,
+        )
+        .unwrap();
+        let shrunk = self::remote(&[("worlds1", &["wrld_a"]), ("worlds2", &["wrld_c"])]);
+        let imports =
+            FavoriteSyncService::import_remote(&shrunk, &groups, &folders, &worlds, &storage)
+                .unwrap();
+        assert_eq!((imports[0].unchanged, imports[0].removed), (1, 1));
+    }
+}
@@ -1,11 +1,11 @@
 use crate::definitions::AuthCookies;
-use crate::definitions::{FolderModel, PreferenceModel, WorldModel};
+use crate::definitions::{PreferenceModel, ScheduledInstance, StoredAccount};
 use crate::errors::FileError;
+use crate::migration::MigrationService;
+use crate::services::encryption_service::SECRET_VERSION;
 use crate::services::EncryptionService;
 use directories::BaseDirs;
-use log::debug;
 use serde_json;
-use std::ffi::OsString;
 use std::fs;
 use std::io::Write;
 use std::path::{Path, PathBuf};
@@ -20,7 +20,7 @@ impl FileService {
     /// # Returns
     /// Returns the path to the application directory
     #[must_use]
-    fn get_app_dir() -> PathBuf {
+    pub(crate) fn get_app_dir() -> PathBuf {
         BaseDirs::new()
             .expect("Failed to get base directories")
             .data_local_dir()
@@ -66,7 +66,7 @@ impl FileService {
     ///
     /// # Returns
     /// Returns the backup file path with .bak appended
-    fn get_backup_path(path: &PathBuf) -> PathBuf {
+    fn get_backup_path(path: &Path) -> PathBuf {
         // Use OsString to handle non-UTF-8 paths correctly
         let mut os_string = path.as_os_str().to_os_string();
         os_string.push(".bak");
@@ -121,7 +121,7 @@ impl FileService {
     ///
     /// # Errors
     /// Returns a FileError if the data could not be written
-    fn atomic_write(path: &PathBuf, data: &str) -> Result<(), FileError> {
+    pub(crate) fn atomic_write(path: &PathBuf, data: &str) -> Result<(), FileError> {
         // If the file exists, create a backup first
         if path.exists() {
             let backup_path = Self::get_backup_path(path);
@@ -177,8 +177,9 @@ impl FileService {
     ///
     /// # Errors
     /// Returns a FileError if access is denied, the file is not found, or the file is invalid
-    #[must_use]
-    fn read_file<T: serde::de::DeserializeOwned>(path: &PathBuf) -> Result<T, FileError> {
+    pub(crate) fn read_file<T: serde::de::DeserializeOwned>(
+        path: &PathBuf,
+    ) -> Result<T, FileError> {
         // Try to read the primary file
         let result = fs::read_to_string(path)
             .map_err(|e| match e.kind() {
@@ -233,7 +234,62 @@ impl FileService {
         result
     }
 
-    fn read_auth_file(path: &PathBuf) -> Result<AuthCookies, FileError> {
+    /// Reads a JSON file the app keeps for itself, such as a cache or a queue,
+    /// starting over with the default value if it is missing or could not be read
+    ///
+    /// # Arguments
+    /// * `path` - Path to the data file
+    /// * `what` - What the file holds, for the logs
+    pub(crate) fn read_or_default<T: serde::de::DeserializeOwned + Default>(
+        path: &PathBuf,
+        what: &str,
+    ) -> T {
+        if !path.exists() {
+            return T::default();
+        }
+        Self::read_file(path).unwrap_or_else(|e| {
+            log::error!("Failed to read {}: {}", what, e);
+            T::default()
+        })
+    }
+
+    /// Writes a value as JSON atomically, creating its directory if needed
+    /// Errors are only logged, as the app keeps working with the value in memory
+    ///
+    /// # Arguments
+    /// * `path` - Path to the data file
+    /// * `value` - The value to write
+    /// * `what` - What the file holds, for the logs
+    ///
+    /// # Returns
+    /// Whether the value was written
+    pub(crate) fn write_json<T: serde::Serialize>(path: &PathBuf, value: &T, what: &str) -> bool {
+        let data = match serde_json::to_string(value) {
+            Ok(data) => data,
+            Err(e) => {
+                log::error!("Failed to serialize {}: {}", what, e);
+                return false;
+            }
+        };
+        if let Some(parent) = path.parent() {
+            if let Err(e) = fs::create_dir_all(parent) {
+                log::error!("Failed to create directory for {}: {}", what, e);
+                return false;
+            }
+        }
+        match Self::atomic_write(path, &data) {
+            Ok(()) => true,
+            Err(e) => {
+                log::error!("Failed to save {}: {}", what, e);
+                false
+            }
+        }
+    }
+
+    fn read_auth_file(
+        path: &PathBuf,
+        encryption: &EncryptionService,
+    ) -> Result<AuthCookies, FileError> {
         let content_result = fs::read_to_string(path).map_err(|e| match e.kind() {
             std::io::ErrorKind::PermissionDenied => FileError::AccessDenied,
             _ => FileError::FileNotFound,
@@ -291,41 +347,7 @@ impl FileService {
         };
 
         match serde_json::from_str::<AuthCookies>(&content) {
-            Ok(mut cookies) => {
-                if let Some(auth) = &cookies.auth_token {
-                    if !auth.is_empty() {
-                        cookies.auth_token =
-                            Some(EncryptionService::decrypt_aes(auth).map_err(|e| {
-                                log::error!("Failed to decrypt auth token: {}", e);
-                                FileError::InvalidFile {
-                                    line: None,
-                                    column: None,
-                                    file_name: Some(path.to_string_lossy().to_string()),
-                                    error_message: e.to_string(),
-                                }
-                            })?);
-                    }
-                } else {
-                    cookies.auth_token = None;
-                }
-                if let Some(tfa) = &cookies.two_factor_auth {
-                    if !tfa.is_empty() {
-                        cookies.two_factor_auth =
-                            Some(EncryptionService::decrypt_aes(tfa).map_err(|e| {
-                                log::error!("Failed to decrypt two-factor auth token: {}", e);
-                                FileError::InvalidFile {
-                                    line: None,
-                                    column: None,
-                                    file_name: Some(path.to_string_lossy().to_string()),
-                                    error_message: e.to_string(),
-                                }
-                            })?);
-                    }
-                } else {
-                    cookies.two_factor_auth = None;
-                }
-                Ok(cookies)
-            }
+            Ok(cookies) => Self::decrypt_cookies(cookies, path, encryption),
             Err(e) => Err(FileError::InvalidFile {
                 line: Some(e.line()),
                 column: Some(e.column()),
@@ -335,58 +357,145 @@ impl FileService {
         }
     }
 
-    /// Loads data from disk
-    /// Calls read_config and read_file to load data from disk
+    /// Decrypts the tokens of authentication data read from disk
+    /// Tokens from before the per-install encryption are decrypted with the legacy key,
+    /// and the version of the file is kept, so they can be written again in the current format
+    fn decrypt_cookies(
+        mut cookies: AuthCookies,
+        path: &Path,
+        encryption: &EncryptionService,
+    ) -> Result<AuthCookies, FileError> {
+        let version = cookies.version;
+        let decrypt = |secret: &str| {
+            if version >= SECRET_VERSION {
+                encryption.decrypt(secret)
+            } else {
+                MigrationService::decrypt_legacy_secret(secret)
+            }
+        };
+
+        if let Some(auth) = &cookies.auth_token {
+            if !auth.is_empty() {
+                cookies.auth_token = Some(decrypt(auth).map_err(|e| {
+                    log::error!("Failed to decrypt auth token: {}", e);
+                    FileError::InvalidFile {
+                        line: None,
+                        column: None,
+                        file_name: Some(path.to_string_lossy().to_string()),
+                        error_message: e.to_string(),
+                    }
+                })?);
+            }
+        } else {
+            cookies.auth_token = None;
+        }
+        if let Some(tfa) = &cookies.two_factor_auth {
+            if !tfa.is_empty() {
+                cookies.two_factor_auth = Some(decrypt(tfa).map_err(|e| {
+                    log::error!("Failed to decrypt two-factor auth token: {}", e);
+                    FileError::InvalidFile {
+                        line: None,
+                        column: None,
+                        file_name: Some(path.to_string_lossy().to_string()),
+                        error_message: e.to_string(),
+                    }
+                })?);
+            }
+        } else {
+            cookies.two_factor_auth = None;
+        }
+        Ok(cookies)
+    }
+
+    /// Whether decrypted authentication data was stored in an older format
+    fn is_legacy(cookies: &AuthCookies) -> bool {
+        cookies.version < SECRET_VERSION
+            && (cookies.auth_token.is_some() || cookies.two_factor_auth.is_some())
+    }
+
+    /// Encrypts the tokens of authentication data before it is written to disk
+    fn encrypt_cookies(
+        cookies: &AuthCookies,
+        encryption: &EncryptionService,
+    ) -> Result<AuthCookies, FileError> {
+        let mut encrypted_cookies = cookies.clone();
+        let encrypt = |secret: &str, name: &str| {
+            encryption.encrypt(secret).map_err(|e| {
+                log::error!("Failed to encrypt {}: {}", name, e);
+                FileError::FileWriteError
+            })
+        };
+
+        if let Some(auth) = &cookies.auth_token {
+            encrypted_cookies.auth_token = Some(encrypt(auth, "auth token")?);
+        }
+        if let Some(tfa) = &cookies.two_factor_auth {
+            encrypted_cookies.two_factor_auth = Some(encrypt(tfa, "two-factor auth token")?);
+        }
+        encrypted_cookies.version = SECRET_VERSION;
+        Ok(encrypted_cookies)
+    }
+
+    /// Loads the preferences and authentication data from disk
+    /// The folders and worlds are loaded through the library storage
+    ///
+    /// # Arguments
+    /// * `encryption` - The service the stored secrets are encrypted with
     ///
     /// # Returns
-    /// Returns the preferences, folders, and worlds
+    /// Returns the preferences and authentication cookies
     ///
     /// # Errors
     /// Returns a FileError if any file is not found, cannot be decrypted, or is invalid
-    #[must_use]
-    pub fn load_data() -> Result<
-        (
-            PreferenceModel,
-            Vec<FolderModel>,
-            Vec<WorldModel>,
-            AuthCookies,
-        ),
-        FileError,
-    > {
-        let (config_path, folders_path, worlds_path, cookies_path) = Self::get_paths();
+    pub fn load_data(
+        encryption: &EncryptionService,
+    ) -> Result<(PreferenceModel, AuthCookies), FileError> {
+        let (config_path, _, _, cookies_path) = Self::get_paths();
 
         log::info!("Reading files");
         let preferences = Self::read_file(&config_path)?;
-        let folders: Vec<FolderModel> = Self::read_file(&folders_path)?;
-        let mut worlds: Vec<WorldModel> = Self::read_file(&worlds_path)?;
-        let cookies = Self::read_auth_file(&cookies_path)?;
-
-        // populate per-world folder list
-        for world in worlds.iter_mut() {
-            world.user_data.folders = folders
-                .iter()
-                .filter(|folder| folder.world_ids.contains(&world.api_data.world_id))
-                .map(|folder| folder.folder_name.clone())
-                .collect();
-        }
+        let cookies = if encryption.is_locked() {
+            // The session is read again once the user unlocks it
+            log::info!("Stored secrets are locked, starting logged out");
+            AuthCookies::new()
+        } else {
+            Self::read_auth_at(&cookies_path, encryption)?
+        };
 
-        // Backwards-compat: dedupe any duplicate platform entries in worlds.json
-        {
-            use std::collections::HashSet;
-            for world in worlds.iter_mut() {
-                let mut seen = HashSet::new();
-                world
-                    .api_data
-                    .platform
-                    .retain(|plat| seen.insert(plat.clone()));
-            }
-            // write back deduplicated worlds
-            if let Err(e) = Self::write_worlds(&worlds) {
-                log::error!("Failed to persist deduplicated worlds.json: {}", e);
+        Ok((preferences, cookies))
+    }
+
+    /// Reads the authentication data of the active session from disk
+    ///
+    /// # Arguments
+    /// * `encryption` - The service the stored secrets are encrypted with
+    ///
+    /// # Returns
+    /// Returns the authentication cookies
+    ///
+    /// # Errors
+    /// Returns a FileError if the file is not found, cannot be decrypted, or is invalid
+    pub fn read_auth(encryption: &EncryptionService) -> Result<AuthCookies, FileError> {
+        let (_, _, _, auth_path) = Self::get_paths();
+        Self::read_auth_at(&auth_path, encryption)
+    }
+
+    /// Reads the authentication data, writing it in the current format if it is older
+    fn read_auth_at(
+        path: &PathBuf,
+        encryption: &EncryptionService,
+    ) -> Result<AuthCookies, FileError> {
+        let cookies = Self::read_auth_file(path, encryption)?;
+        if Self::is_legacy(&cookies) {
+            log::info!("Upgrading the encryption of the stored session");
+            if let Err(e) = Self::write_auth(&cookies, encryption) {
+                log::error!(
+                    "Failed to upgrade the encryption of the stored session: {:?}",
+                    e
+                );
             }
         }
-
-        Ok((preferences, folders, worlds, cookies))
+        Ok(cookies)
     }
 
     /// Writes preference data to disk
@@ -413,49 +522,41 @@ impl FileService {
         Self::atomic_write(&config_path, &data)
     }
 
-    /// Writes folder data to disk
-    /// Serializes and writes the data to disk
-    ///
-    /// # Arguments
-    /// * `folders` - The folder data to write
+    /// Reads the scheduled instances from disk
     ///
     /// # Returns
-    /// Ok(()) if the data was written successfully
+    /// Returns the scheduled instances, or an empty list if none were saved yet
     ///
     /// # Errors
-    /// Returns a FileError if the data could not be written    
-    pub fn write_folders(folders: &Vec<FolderModel>) -> Result<(), FileError> {
-        let (_, folders_path, _, _) = Self::get_paths();
-        let data = serde_json::to_string_pretty(folders).map_err(|e| FileError::InvalidFile {
-            line: None,
-            column: None,
-            file_name: Some(folders_path.to_string_lossy().to_string()),
-            error_message: e.to_string(),
-        })?;
-        Self::atomic_write(&folders_path, &data)
+    /// Returns a FileError if the file cannot be read or is invalid
+    pub fn read_schedules() -> Result<Vec<ScheduledInstance>, FileError> {
+        let path = Self::get_app_dir().join("schedules.json");
+        if !path.exists() {
+            return Ok(vec![]);
+        }
+        Self::read_file(&path)
     }
 
-    /// Writes world data to disk
-    /// Serializes and writes the data to disk
+    /// Writes the scheduled instances to disk
     ///
     /// # Arguments
-    /// * `worlds` - The world data to write
+    /// * `schedules` - The scheduled instances to write
     ///
     /// # Returns
     /// Ok(()) if the data was written successfully
     ///
     /// # Errors
     /// Returns a FileError if the data could not be written
-    pub fn write_worlds(worlds: &Vec<WorldModel>) -> Result<(), FileError> {
-        let (_, _, worlds_path, _) = Self::get_paths();
+    pub fn write_schedules(schedules: &[ScheduledInstance]) -> Result<(), FileError> {
+        let path = Self::get_app_dir().join("schedules.json");
 
-        let data = serde_json::to_string_pretty(&worlds).map_err(|e| FileError::InvalidFile {
+        let data = serde_json::to_string_pretty(schedules).map_err(|e| FileError::InvalidFile {
             line: None,
             column: None,
-            file_name: Some(worlds_path.to_string_lossy().to_string()),
+            file_name: Some(path.to_string_lossy().to_string()),
             error_message: e.to_string(),
         })?;
-        Self::atomic_write(&worlds_path, &data)
+        Self::atomic_write(&path, &data)
     }
 
     /// Writes authentication data to disk
@@ -463,118 +564,232 @@ impl FileService {
     ///
     /// # Arguments
     /// * `cookies` - The authentication data to write
+    /// * `encryption` - The service the stored secrets are encrypted with
     ///
     /// # Returns
     /// Ok(()) if the data was written successfully
     ///
     /// # Errors
     /// Returns a FileError if the data could not be written
-    pub fn write_auth(cookies: &AuthCookies) -> Result<(), FileError> {
+    pub fn write_auth(
+        cookies: &AuthCookies,
+        encryption: &EncryptionService,
+    ) -> Result<(), FileError> {
         let (_, _, _, auth_path) = Self::get_paths();
-        let mut encrypted_cookies = cookies.clone();
+        let data = Self::auth_data(&auth_path, cookies, encryption)?;
+        Self::atomic_write(&auth_path, &data)
+    }
 
+    /// Serializes the authentication data, with its tokens encrypted
+    fn auth_data(
+        path: &Path,
+        cookies: &AuthCookies,
+        encryption: &EncryptionService,
+    ) -> Result<String, FileError> {
         // Ensure tokens are encrypted when writing
-        if let Some(auth) = &cookies.auth_token {
-            encrypted_cookies.auth_token = match EncryptionService::encrypt_aes(auth) {
-                Ok(encrypted) => Some(encrypted),
-                Err(e) => {
-                    log::error!("Failed to encrypt auth token: {}", e);
-                    None
-                }
-            };
-        }
-        if let Some(tfa) = &cookies.two_factor_auth {
-            encrypted_cookies.two_factor_auth = match EncryptionService::encrypt_aes(tfa) {
-                Ok(encrypted) => Some(encrypted),
-                Err(e) => {
-                    log::error!("Failed to encrypt two-factor auth token: {}", e);
-                    None
-                }
-            };
-        }
-        encrypted_cookies.version = 1;
+        let encrypted_cookies = Self::encrypt_cookies(cookies, encryption)?;
 
-        let data = serde_json::to_string_pretty(&encrypted_cookies).map_err(|e| {
-            FileError::InvalidFile {
-                line: None,
-                column: None,
-                file_name: Some(auth_path.to_string_lossy().to_string()),
-                error_message: e.to_string(),
-            }
-        })?;
-        Self::atomic_write(&auth_path, &data)
+        serde_json::to_string_pretty(&encrypted_cookies).map_err(|e| FileError::InvalidFile {
+            line: None,
+            column: None,
+            file_name: Some(path.to_string_lossy().to_string()),
+            error_message: e.to_string(),
+        })
     }
 
-    /// Creates an empty authentication file if it doesn't exist
+    /// Reads the stored accounts from disk
     ///
-    /// Note: This uses fs::write instead of atomic_write because it's only called
-    /// during initialization when there's no existing data to protect.
+    /// # Arguments
+    /// * `encryption` - The service the stored secrets are encrypted with
     ///
     /// # Returns
-    /// Ok(()) if the file was created successfully or already exists
+    /// Returns the stored accounts with decrypted tokens, or an empty list if none were saved yet
     ///
     /// # Errors
-    /// Returns a FileError if the file could not be created
-    pub fn create_empty_auth_file() -> Result<(), FileError> {
-        let (_, _, _, auth_path) = Self::get_paths();
-        if !auth_path.exists() {
-            fs::write(auth_path, "{}").map_err(|_| FileError::FileWriteError)?;
+    /// Returns a FileError if the file cannot be read, decrypted, or is invalid
+    pub fn read_accounts(encryption: &EncryptionService) -> Result<Vec<StoredAccount>, FileError> {
+        let path = Self::get_app_dir().join("accounts.json");
+        if !path.exists() {
+            return Ok(vec![]);
         }
-        Ok(())
+        Self::read_accounts_file(&path, encryption)
     }
 
-    /// Creates an empty worlds file if it doesn't exist
+    fn read_accounts_file(
+        path: &PathBuf,
+        encryption: &EncryptionService,
+    ) -> Result<Vec<StoredAccount>, FileError> {
+        let accounts: Vec<StoredAccount> = Self::read_file(path)?;
+        let accounts = accounts
+            .into_iter()
+            .map(|mut account| {
+                account.cookies = Self::decrypt_cookies(account.cookies, path, encryption)?;
+                Ok(account)
+            })
+            .collect::<Result<Vec<_>, FileError>>()?;
+
+        if accounts
+            .iter()
+            .any(|account| Self::is_legacy(&account.cookies))
+        {
+            log::info!("Upgrading the encryption of the stored accounts");
+            if let Err(e) = Self::write_accounts_file(path, &accounts, encryption) {
+                log::error!(
+                    "Failed to upgrade the encryption of the stored accounts: {:?}",
+                    e
+                );
+            }
+        }
+        Ok(accounts)
+    }
+
+    /// Writes the stored accounts to disk, encrypting their tokens
     ///
-    /// Note: This uses fs::write instead of atomic_write because it's only called
-    /// during initialization when there's no existing data to protect.
+    /// # Arguments
+    /// * `accounts` - The accounts to write
+    /// * `encryption` - The service the stored secrets are encrypted with
     ///
     /// # Returns
-    /// Ok(()) if the file was created successfully or already exists
+    /// Ok(()) if the data was written successfully
     ///
     /// # Errors
-    /// Returns a FileError if the file could not be created
-    pub fn create_empty_worlds_file() -> Result<(), FileError> {
-        let (_, _, worlds_path, _) = Self::get_paths();
-        if !worlds_path.exists() {
-            fs::write(worlds_path, "[]").map_err(|_| FileError::FileWriteError)?;
-        }
-        Ok(())
+    /// Returns a FileError if the data could not be written
+    pub fn write_accounts(
+        accounts: &[StoredAccount],
+        encryption: &EncryptionService,
+    ) -> Result<(), FileError> {
+        Self::write_accounts_file(
+            &Self::get_app_dir().join("accounts.json"),
+            accounts,
+            encryption,
+        )
     }
 
-    /// Creates an empty folders file if it doesn't exist
+    fn write_accounts_file(
+        path: &PathBuf,
+        accounts: &[StoredAccount],
+        encryption: &EncryptionService,
+    ) -> Result<(), FileError> {
+        let data = Self::accounts_data(path, accounts, encryption)?;
+        Self::atomic_write(path, &data)
+    }
+
+    /// Serializes the stored accounts, with their tokens encrypted
+    fn accounts_data(
+        path: &Path,
+        accounts: &[StoredAccount],
+        encryption: &EncryptionService,
+    ) -> Result<String, FileError> {
+        let encrypted_accounts: Vec<StoredAccount> = accounts
+            .iter()
+            .map(|account| {
+                Ok(StoredAccount {
+                    cookies: Self::encrypt_cookies(&account.cookies, encryption)?,
+                    ..account.clone()
+                })
+            })
+            .collect::<Result<_, FileError>>()?;
+
+        serde_json::to_string_pretty(&encrypted_accounts).map_err(|e| FileError::InvalidFile {
+            line: None,
+            column: None,
+            file_name: Some(path.to_string_lossy().to_string()),
+            error_message: e.to_string(),
+        })
+    }
+
+    /// Protects the stored sessions with a passphrase, or removes the passphrase,
+    /// encrypting them again with the new key
     ///
-    /// Note: This uses fs::write instead of atomic_write because it's only called
-    /// during initialization when there's no existing data to protect.
+    /// # Arguments
+    /// * `passphrase` - The passphrase to derive the key from, or None to use a random key
+    /// * `encryption` - The service the stored secrets are encrypted with
     ///
     /// # Returns
-    /// Ok(()) if the file was created successfully or already exists
+    /// Ok(()) if the sessions were encrypted with the new key
     ///
     /// # Errors
-    /// Returns a FileError if the file could not be created
-    pub fn create_empty_folders_file() -> Result<(), FileError> {
-        let (_, folders_path, _, _) = Self::get_paths();
-        if !folders_path.exists() {
-            fs::write(folders_path, "[]").map_err(|_| FileError::FileWriteError)?;
+    /// Returns a FileError if the sessions are locked, or could not be read or written
+    pub fn set_secret_passphrase(
+        passphrase: Option<&str>,
+        encryption: &EncryptionService,
+    ) -> Result<(), FileError> {
+        let (_, _, _, auth_path) = Self::get_paths();
+        let accounts_path = Self::get_app_dir().join("accounts.json");
+        Self::set_secret_passphrase_at(&auth_path, &accounts_path, passphrase, encryption)
+    }
+
+    /// Encrypts the sessions in the given files with a new key
+    /// Both files are encrypted before either is replaced, and the key file is
+    /// only replaced once both are saved. If a step fails, the files written so far
+    /// are put back, so the secrets stay readable with the previous key.
+    fn set_secret_passphrase_at(
+        auth_path: &PathBuf,
+        accounts_path: &PathBuf,
+        passphrase: Option<&str>,
+        encryption: &EncryptionService,
+    ) -> Result<(), FileError> {
+        if encryption.is_locked() {
+            return Err(FileError::DecryptionError);
         }
-        Ok(())
+        let cookies = Self::read_auth_file(auth_path, encryption)?;
+        let accounts = if accounts_path.exists() {
+            Self::read_accounts_file(accounts_path, encryption)?
+        } else {
+            vec![]
+        };
+
+        let pending = encryption.prepare_passphrase(passphrase).map_err(|e| {
+            log::error!("Failed to create the secret key: {}", e);
+            FileError::FileWriteError
+        })?;
+        let auth_data = Self::auth_data(auth_path, &cookies, pending.encryption())?;
+        let accounts_data = Self::accounts_data(accounts_path, &accounts, pending.encryption())?;
+
+        let previous = [
+            (auth_path, fs::read_to_string(auth_path).ok()),
+            (accounts_path, fs::read_to_string(accounts_path).ok()),
+        ];
+        let result = Self::atomic_write(auth_path, &auth_data)
+            .and_then(|_| Self::atomic_write(accounts_path, &accounts_data))
+            .and_then(|_| {
+                encryption.commit_key(pending).map_err(|e| {
+                    log::error!("Failed to change the secret key: {}", e);
+                    FileError::FileWriteError
+                })
+            });
+        if result.is_err() {
+            for (path, data) in previous {
+                let restored = match data {
+                    Some(data) => Self::atomic_write(path, &data),
+                    None if path.exists() => {
+                        fs::remove_file(path).map_err(|_| FileError::FileWriteError)
+                    }
+                    None => Ok(()),
+                };
+                if let Err(e) = restored {
+                    log::error!("Failed to restore {:?}: {:?}", path, e);
+                }
+            }
+        }
+        result
     }
 
-    /// Deletes data from the worlds and folders files
-    /// Overwrites the files with empty data
+    /// Creates an empty authentication file if it doesn't exist
     ///
-    /// Note: This uses fs::write instead of atomic_write because it's intentionally
-    /// clearing/deleting data, so there's no existing data to protect.
+    /// Note: This uses fs::write instead of atomic_write because it's only called
+    /// during initialization when there's no existing data to protect.
     ///
     /// # Returns
-    /// Ok(()) if the data was deleted successfully
+    /// Ok(()) if the file was created successfully or already exists
     ///
     /// # Errors
-    /// Returns a FileError if the data could not be deleted
-    pub fn delete_worlds_and_folders() -> Result<(), FileError> {
-        let (_, folders_path, worlds_path, _) = Self::get_paths();
-        fs::write(folders_path, "[]").map_err(|_| FileError::FileWriteError)?;
-        fs::write(worlds_path, "[]").map_err(|_| FileError::FileWriteError)?;
-
+    /// Returns a FileError if the file could not be created
+    pub fn create_empty_auth_file() -> Result<(), FileError> {
+        let (_, _, _, auth_path) = Self::get_paths();
+        if !auth_path.exists() {
+            fs::write(auth_path, "{}").map_err(|_| FileError::FileWriteError)?;
+        }
         Ok(())
     }
 
@@ -780,6 +995,25 @@ mod tests {
         assert!(result.is_err());
     }
 
+    #[test]
+    fn test_json_round_trip_and_default() {
+        let temp = setup_test_dir();
+        let path = temp.path().join("cache").join("entries.json");
+
+        let missing: Vec<String> = FileService::read_or_default(&path, "entries");
+        assert!(missing.is_empty());
+
+        let entries = vec!["wrld_1".to_string(), "wrld_2".to_string()];
+        assert!(FileService::write_json(&path, &entries, "entries"));
+        let read: Vec<String> = FileService::read_or_default(&path, "entries");
+        assert_eq!(read, entries);
+
+        fs::write(&path, "not json").unwrap();
+        fs::remove_file(FileService::get_backup_path(&path)).ok();
+        let invalid: Vec<String> = FileService::read_or_default(&path, "entries");
+        assert!(invalid.is_empty());
+    }
+
     #[test]
     fn test_read_auth_file_recovers_from_backup_on_null_bytes() {
         let temp = setup_test_dir();
@@ -795,10 +1029,53 @@ mod tests {
         fs::write(&test_path, null_data).unwrap();
 
         // read_auth_file should recover from backup
-        let result = FileService::read_auth_file(&test_path);
+        let encryption = EncryptionService::new(temp.path().join("secret_key.json"));
+        let result = FileService::read_auth_file(&test_path, &encryption);
         assert!(result.is_ok());
     }
 
+    #[test]
+    fn test_set_secret_passphrase_keeps_secrets_readable_on_failure() {
+        let temp = setup_test_dir();
+        let auth_path = temp.path().join("auth.json");
+        let accounts_path = temp.path().join("accounts.json");
+        let key_path = temp.path().join("secret_key.json");
+        let encryption = EncryptionService::new(key_path.clone());
+        let mut cookies = AuthCookies::new();
+        cookies.auth_token = Some("authcookie_123".to_string());
+        let data = FileService::auth_data(&auth_path, &cookies, &encryption).unwrap();
+        FileService::atomic_write(&auth_path, &data).unwrap();
+
+        // The key file can't be replaced, so the secrets are put back
+        fs::remove_file(&key_path).unwrap();
+        fs::create_dir(&key_path).unwrap();
+        let result = FileService::set_secret_passphrase_at(
+            &auth_path,
+            &accounts_path,
+            Some("correct horse"),
+            &encryption,
+        );
+        assert!(result.is_err());
+        assert!(!accounts_path.exists());
+        let read = FileService::read_auth_file(&auth_path, &encryption).unwrap();
+        assert_eq!(read.auth_token.as_deref(), Some("authcookie_123"));
+
+        // Once the key file can be written, the secrets move to the new key
+        fs::remove_dir(&key_path).unwrap();
+        FileService::set_secret_passphrase_at(
+            &auth_path,
+            &accounts_path,
+            Some("correct horse"),
+            &encryption,
+        )
+        .unwrap();
+        let reopened = EncryptionService::new(key_path);
+        assert!(reopened.is_locked());
+        reopened.unlock("correct horse").unwrap();
+        let read = FileService::read_auth_file(&auth_path, &reopened).unwrap();
+        assert_eq!(read.auth_token.as_deref(), Some("authcookie_123"));
+    }
+
     #[test]
     fn test_atomic_write_is_durable() {
         let temp = setup_test_dir();
@@ -1,27 +1,54 @@
 use log::info;
 
 use crate::definitions::{
-    FolderModel, PreferenceModel, WorldApiData, WorldDetails, WorldDisplayData, WorldModel,
+    FavoriteGroupSync, FolderModel, PreferenceModel, WorldApiData, WorldAvailability, WorldChange,
+    WorldDetails, WorldDisplayData, WorldModel,
 };
 use crate::errors::{AppError, ConcurrencyError, EntityError};
 use serde::{Deserialize, Serialize};
+use std::cmp::Reverse;
 use std::collections::{HashMap, HashSet};
 use std::sync::RwLock;
 
-use super::FileService;
+use super::{Changes, Storage, WorldQuery};
 
 #[derive(Debug, Clone, Serialize, Deserialize, specta::Type)]
 pub struct FolderData {
     pub name: String,
+    /// The names of the folder and its ancestors, joined with "/"
+    /// This identifies the folder in commands taking a folder name
+    pub path: String,
     pub world_count: u16,
+    /// The number of distinct worlds in the folder and all of its subfolders
+    pub total_count: u16,
+    /// The saved query, if this is a smart folder
+    pub query: Option<String>,
+    /// The VRChat favorite group the folder is synced with, if any
+    pub favorite_group: Option<String>,
+    pub children: Vec<FolderData>,
 }
 
 impl FolderData {
-    pub fn new(name: String, world_count: u16) -> Self {
-        Self { name, world_count }
+    pub fn new(name: String, path: String, world_count: u16, query: Option<String>) -> Self {
+        Self {
+            name,
+            path,
+            world_count,
+            total_count: world_count,
+            query,
+            favorite_group: None,
+            children: vec![],
+        }
     }
 }
 
+/// Separator between folder names in a folder path
+pub const PATH_SEPARATOR: char = '/';
+
+/// Stands in for PATH_SEPARATOR in folder names that come from elsewhere,
+/// such as favorite groups and shared folders
+pub const PATH_SEPARATOR_ESCAPE: char = '\u{2215}';
+
 /// Service for managing world/folder operations
 #[derive(Debug)]
 pub struct FolderManager;
@@ -34,12 +61,14 @@ impl FolderManager {
     /// * `world_id` - The ID of the world to add
     /// * `folders` - The list of folders, as a RwLock
     /// * `worlds` - The list of worlds, as a RwLock
+    /// * `storage` - The storage to persist the change to
     ///
     /// # Returns
     /// Ok if the world was added successfully
     ///
     /// # Errors
     /// Returns an error if the folder is not found
+    /// Returns an error if the folder is a smart folder
     /// Returns an error if the world is not found
     /// Returns an error if the folders lock is poisoned
     pub fn add_world_to_folder(
@@ -47,6 +76,7 @@ impl FolderManager {
         world_id: String,
         folders: &RwLock<Vec<FolderModel>>,
         worlds: &RwLock<Vec<WorldModel>>,
+        storage: &dyn Storage,
     ) -> Result<(), AppError> {
         let mut folders_lock = folders
             .write()
@@ -68,12 +98,15 @@ impl FolderManager {
         }
         let folder = folder.unwrap();
         let world = world.unwrap();
+        if folder.is_smart() {
+            return Err(Self::smart_folder_error(&folder_name).into());
+        }
 
         if !world.user_data.folders.iter().any(|f| f == &folder_name) {
             folder.world_ids.push(world_id.clone());
             world.user_data.folders.push(folder_name.clone());
         }
-        FileService::write_folders(&*folders_lock)?;
+        storage.write_folders(&folders_lock, Changes::Upserted(vec![folder_name]))?;
         Ok(())
     }
 
@@ -85,18 +118,21 @@ impl FolderManager {
     /// * `world_id` - The ID of the world to remove
     /// * `folders` - The list of folders, as a RwLock
     /// * `worlds` - The list of worlds, as a RwLock
+    /// * `storage` - The storage to persist the change to
     ///
     /// # Returns
     /// Ok if the world was removed successfully
     ///
     /// # Errors
     /// Returns an error if the folder is not found
+    /// Returns an error if the folder is a smart folder
     /// Returns an error if the folders lock is poisoned
     pub fn remove_world_from_folder(
         folder_name: String,
         world_id: String,
         folders: &RwLock<Vec<FolderModel>>,
         worlds: &RwLock<Vec<WorldModel>>,
+        storage: &dyn Storage,
     ) -> Result<(), AppError> {
         let mut folders_lock = folders
             .write()
@@ -117,6 +153,9 @@ impl FolderManager {
         }
         let folder = folder.unwrap();
         let world = world.unwrap();
+        if folder.is_smart() {
+            return Err(Self::smart_folder_error(&folder_name).into());
+        }
 
         if world.user_data.folders.contains(&folder_name) {
             // Remove folder from world's folders
@@ -135,7 +174,7 @@ impl FolderManager {
         } else {
             return Err(EntityError::FolderNotFound(folder.folder_name.clone()).into());
         }
-        FileService::write_folders(&*folders_lock)?;
+        storage.write_folders(&folders_lock, Changes::Upserted(vec![folder_name]))?;
         Ok(())
     }
 
@@ -147,6 +186,7 @@ impl FolderManager {
     /// * `world_id` - The ID of the world to hide
     /// * `folders` - The list of folders, as a RwLock
     /// * `worlds` - The list of worlds, as a RwLock
+    /// * `storage` - The storage to persist the change to
     ///
     /// # Returns
     /// Ok if the world was hidden successfully
@@ -158,6 +198,7 @@ impl FolderManager {
         world_id: String,
         folders: &RwLock<Vec<FolderModel>>,
         worlds: &RwLock<Vec<WorldModel>>,
+        storage: &dyn Storage,
     ) -> Result<(), AppError> {
         let mut worlds_lock = worlds.write().map_err(|_| ConcurrencyError::PoisonedLock)?;
         let world = worlds_lock
@@ -178,7 +219,7 @@ impl FolderManager {
             .map(|folder| folder.folder_name.clone())
             .collect();
         drop(folders_lock);
-        FileService::write_worlds(&*worlds_lock)?;
+        storage.write_worlds(&worlds_lock, Changes::Upserted(vec![world_id.clone()]))?;
         drop(worlds_lock);
 
         for folder_name in folders_to_remove {
@@ -187,6 +228,7 @@ impl FolderManager {
                 world_id.clone(),
                 folders,
                 worlds,
+                storage,
             )?;
         }
 
@@ -202,6 +244,7 @@ impl FolderManager {
     /// * `world_id` - The ID of the world to unhide
     /// * `folders` - The list of folders, as a RwLock
     /// * `worlds` - The list of worlds, as a RwLock
+    /// * `storage` - The storage to persist the change to
     ///
     /// # Returns
     /// Ok if the world was unhidden successfully
@@ -213,6 +256,7 @@ impl FolderManager {
         world_id: String,
         folders: &RwLock<Vec<FolderModel>>,
         worlds: &RwLock<Vec<WorldModel>>,
+        storage: &dyn Storage,
     ) -> Result<(), AppError> {
         let mut worlds_lock = worlds.write().map_err(|_| ConcurrencyError::PoisonedLock)?;
         let world = worlds_lock
@@ -233,44 +277,307 @@ impl FolderManager {
             .map(|folder| folder.folder_name.clone())
             .collect();
         drop(folders_lock);
-        FileService::write_worlds(&*worlds_lock)?;
+        storage.write_worlds(&worlds_lock, Changes::Upserted(vec![world_id.clone()]))?;
         drop(worlds_lock);
 
         for folder_name in folders_to_add {
-            FolderManager::add_world_to_folder(folder_name, world_id.clone(), folders, worlds)?;
+            FolderManager::add_world_to_folder(
+                folder_name,
+                world_id.clone(),
+                folders,
+                worlds,
+                storage,
+            )?;
         }
 
         Ok(())
     }
 
-    /// Get the names of all folders, and the number of worlds in each folder
+    /// Get the folder tree, with the number of worlds in each folder
+    /// The worlds of smart folders are counted from the current worlds
+    /// Folders whose parent no longer exists are returned as top-level folders
     ///
     /// # Arguments
     /// * `folders` - The list of folders, as a RwLock
+    /// * `worlds` - The list of worlds, as a RwLock
     ///
     /// # Returns
-    /// A vector of folder names, each paired with the number of worlds in that folder
+    /// The top-level folders in display order, each with its subfolders as children
     ///
     /// # Errors
-    /// Returns an error if the folders lock is poisoned
-    #[must_use]
-    pub fn get_folders(folders: &RwLock<Vec<FolderModel>>) -> Result<Vec<FolderData>, AppError> {
+    /// Returns an error if the folders or worlds lock is poisoned
+    pub fn get_folders(
+        folders: &RwLock<Vec<FolderModel>>,
+        worlds: &RwLock<Vec<WorldModel>>,
+    ) -> Result<Vec<FolderData>, AppError> {
         let folders_lock = folders.read().map_err(|_| ConcurrencyError::PoisonedLock)?;
-        let mut folder_data: Vec<FolderData> = Vec::new();
+        let worlds_lock = worlds.read().map_err(|_| ConcurrencyError::PoisonedLock)?;
+
+        let is_top_level = |folder: &FolderModel| match &folder.parent {
+            Some(parent) => !folders_lock.iter().any(|f| &f.folder_name == parent),
+            None => true,
+        };
+        let mut visited = HashSet::new();
+        let mut folder_data = Vec::new();
+        for folder in folders_lock.iter().filter(|f| is_top_level(f)) {
+            if let Some((data, _)) =
+                Self::build_folder_data(folder, &folders_lock, &worlds_lock, &mut visited)
+            {
+                folder_data.push(data);
+            }
+        }
+        // Folders caught in a parent cycle can't be reached from the top level
         for folder in folders_lock.iter() {
-            let world_count = folder.world_ids.len() as u16;
-            folder_data.push(FolderData::new(folder.folder_name.clone(), world_count));
+            if let Some((data, _)) =
+                Self::build_folder_data(folder, &folders_lock, &worlds_lock, &mut visited)
+            {
+                folder_data.push(data);
+            }
         }
         Ok(folder_data)
     }
+
+    /// Builds the FolderData of a folder and its subfolders
+    ///
+    /// # Returns
+    /// The folder data, and the IDs of the worlds in the folder and its subfolders
+    /// None if the folder was already visited
+    fn build_folder_data(
+        folder: &FolderModel,
+        folders: &[FolderModel],
+        worlds: &[WorldModel],
+        visited: &mut HashSet<String>,
+    ) -> Option<(FolderData, HashSet<String>)> {
+        if !visited.insert(folder.folder_name.clone()) {
+            return None;
+        }
+
+        let world_ids = Self::resolve_world_ids(folder, worlds);
+        let mut data = FolderData::new(
+            Self::name_in_path(&folder.folder_name).to_string(),
+            folder.folder_name.clone(),
+            world_ids.len() as u16,
+            folder.query.clone(),
+        );
+        data.favorite_group = folder.favorite_sync.as_ref().map(|s| s.group.clone());
+        let mut subtree_ids: HashSet<String> = world_ids.into_iter().collect();
+        for child in folders
+            .iter()
+            .filter(|f| f.parent.as_ref() == Some(&folder.folder_name))
+        {
+            if let Some((child_data, child_ids)) =
+                Self::build_folder_data(child, folders, worlds, visited)
+            {
+                subtree_ids.extend(child_ids);
+                data.children.push(child_data);
+            }
+        }
+        data.total_count = subtree_ids.len() as u16;
+        Some((data, subtree_ids))
+    }
+
+    /// Get the name of a folder from its path, which is the last name in the path
+    pub fn name_in_path(path: &str) -> &str {
+        path.rsplit(PATH_SEPARATOR).next().unwrap_or(path)
+    }
+
+    /// Get the path of a folder from the path of its parent and its name
+    ///
+    /// # Arguments
+    /// * `parent` - The path of the parent folder, or None for a top-level folder
+    /// * `name` - The name of the folder
+    ///
+    /// # Returns
+    /// The path of the folder, with names separated by PATH_SEPARATOR
+    pub fn folder_path(parent: Option<&str>, name: &str) -> String {
+        match parent {
+            Some(parent) => format!("{}{}{}", parent, PATH_SEPARATOR, name),
+            None => name.to_string(),
+        }
+    }
+
+    /// Get the names of all subfolders of a folder, at any depth
+    ///
+    /// # Arguments
+    /// * `folder_name` - The name of the folder
+    /// * `folders` - All folders
+    ///
+    /// # Returns
+    /// The names of the subfolders, each followed by its own subfolders, in display order
+    pub fn descendant_names(folder_name: &str, folders: &[FolderModel]) -> Vec<String> {
+        let mut names = Vec::new();
+        let mut pending = vec![folder_name.to_string()];
+        while let Some(name) = pending.pop() {
+            let children: Vec<String> = folders
+                .iter()
+                .filter(|f| f.parent.as_ref() == Some(&name))
+                .map(|f| f.folder_name.clone())
+                .filter(|child| child != folder_name && !names.contains(child))
+                .collect();
+            for child in children.into_iter().rev() {
+                pending.push(child);
+            }
+            if name != folder_name {
+                names.push(name);
+            }
+        }
+        names
+    }
+
+    /// Get the IDs of the worlds in a folder
+    /// For smart folders, the saved query is evaluated against the given worlds,
+    /// so the result always reflects the current library
+    ///
+    /// # Arguments
+    /// * `folder` - The folder
+    /// * `worlds` - All worlds of the library
+    ///
+    /// # Returns
+    /// The IDs of the worlds in the folder
+    pub fn resolve_world_ids(folder: &FolderModel, worlds: &[WorldModel]) -> Vec<String> {
+        let Some(query) = &folder.query else {
+            return folder.world_ids.clone();
+        };
+        match WorldQuery::parse(query) {
+            Ok(query) => Self::matching_worlds(&query, worlds)
+                .map(|w| w.api_data.world_id.clone())
+                .collect(),
+            Err(e) => {
+                log::warn!(
+                    "Invalid query in smart folder {}: {}",
+                    folder.folder_name,
+                    e
+                );
+                vec![]
+            }
+        }
+    }
+
+    /// Filters worlds by a query, leaving out hidden worlds unless the query asks for them
+    fn matching_worlds<'a>(
+        query: &'a WorldQuery,
+        worlds: &'a [WorldModel],
+    ) -> impl Iterator<Item = &'a WorldModel> + 'a {
+        let include_hidden = query.mentions_hidden();
+        worlds
+            .iter()
+            .filter(move |w| include_hidden || !w.user_data.hidden)
+            .filter(move |w| query.matches(w))
+    }
+
+    fn smart_folder_error(folder_name: &str) -> EntityError {
+        EntityError::InvalidOperation(format!(
+            "worlds can't be added to or removed from smart folder {}",
+            folder_name
+        ))
+    }
+
+    /// Replaces PATH_SEPARATOR in a folder name with a look-alike character,
+    /// so the name can be used in a folder path
+    pub fn escape_folder_name(name: &str) -> String {
+        name.replace(PATH_SEPARATOR, &PATH_SEPARATOR_ESCAPE.to_string())
+    }
+
+    /// Checks that a folder name entered by the user can be used in a folder path
+    ///
+    /// # Errors
+    /// Returns an error if the name contains PATH_SEPARATOR
+    fn validate_folder_name(name: &str) -> Result<(), AppError> {
+        if name.contains(PATH_SEPARATOR) {
+            return Err(EntityError::InvalidOperation(format!(
+                "folder name {} can't contain '{}'",
+                name, PATH_SEPARATOR
+            ))
+            .into());
+        }
+        Ok(())
+    }
+
+    /// Renames the top-level folders whose name contains PATH_SEPARATOR, which
+    /// older versions allowed, so they aren't mistaken for subfolders
+    /// The separator is escaped, and a number is appended if the escaped name is taken.
+    /// The new names are applied to the subfolders, worlds and starred folders.
+    ///
+    /// # Arguments
+    /// * `folders` - All folders
+    /// * `worlds` - All worlds
+    /// * `preferences` - The preferences holding the starred folders
+    ///
+    /// # Returns
+    /// The IDs of the worlds whose folder list changed, or None if no folder was renamed
+    pub fn escape_folder_names(
+        folders: &mut [FolderModel],
+        worlds: &mut [WorldModel],
+        preferences: &mut PreferenceModel,
+    ) -> Option<Vec<String>> {
+        let mut renamed: Vec<(String, String)> = Vec::new();
+        for index in 0..folders.len() {
+            if folders[index].parent.is_some()
+                || !folders[index].folder_name.contains(PATH_SEPARATOR)
+            {
+                continue;
+            }
+            let old_name = folders[index].folder_name.clone();
+            let escaped = Self::escape_folder_name(&old_name);
+            let mut new_name = escaped.clone();
+            let mut count = 1;
+            while folders.iter().any(|f| f.folder_name == new_name) {
+                new_name = format!("{} ({})", escaped, count);
+                count += 1;
+            }
+            folders[index].folder_name = new_name.clone();
+            log::info!("Renamed folder {} to {}", old_name, new_name);
+            renamed.push((old_name, new_name));
+        }
+        if renamed.is_empty() {
+            return None;
+        }
+
+        let new_name_of = |name: &str| {
+            renamed
+                .iter()
+                .find(|(old_name, _)| old_name == name)
+                .map(|(_, new_name)| new_name.clone())
+        };
+        for folder in folders.iter_mut() {
+            if let Some(new_name) = folder.parent.as_deref().and_then(new_name_of) {
+                folder.parent = Some(new_name);
+            }
+        }
+        let mut changed = Vec::new();
+        for world in worlds.iter_mut() {
+            let mut world_changed = false;
+            for folder in world.user_data.folders.iter_mut() {
+                if let Some(new_name) = new_name_of(folder) {
+                    *folder = new_name;
+                    world_changed = true;
+                }
+            }
+            if world_changed {
+                changed.push(world.api_data.world_id.clone());
+            }
+        }
+        if let Some(starred_selector) = &mut preferences.filter_item_selector_starred {
+            for folder in starred_selector.folder.iter_mut() {
+                if let Some(new_name) = new_name_of(folder) {
+                    *folder = new_name;
+                }
+            }
+        }
+        Some(changed)
+    }
+
     /// Returns a unique name for a folder, as a string
     /// If the passed name is "", the default name "New Folder" is used
     /// If the folder already exists, we append a number to the name
     /// When appending, we first check if it is already a numbered folder
     /// If it is, we increment the number
+    /// Only the folders sharing the parent are checked, as folders are told
+    /// apart by their path
     ///
     /// # Arguments
     /// * `name` - The name of the new folder
+    /// * `parent` - The path of the parent folder, or None for a top-level folder
     /// * `folders` - The list of folders, as a RwLock
     ///
     /// # Returns
@@ -278,9 +585,9 @@ impl FolderManager {
     ///
     /// # Errors
     /// Returns an error if the folders lock is poisoned
-    #[must_use]
     fn increment_folder_name(
         name: String,
+        parent: Option<&str>,
         folders: &RwLock<Vec<FolderModel>>,
     ) -> Result<String, AppError> {
         let folders_lock = folders.read().map_err(|_| ConcurrencyError::PoisonedLock)?;
@@ -301,7 +608,10 @@ impl FolderManager {
             }
         }
         // if not, check if the name already exists
-        while folders_lock.iter().any(|f| f.folder_name == new_name) {
+        while folders_lock
+            .iter()
+            .any(|f| f.folder_name == Self::folder_path(parent, &new_name))
+        {
             log::info!("Folder name exists: {}", new_name);
             new_name = format!("{} ({})", base_name, count);
             count += 1;
@@ -315,206 +625,665 @@ impl FolderManager {
     /// # Arguments
     /// * `name` - The name of the new folder
     /// * `folders` - The list of folders, as a RwLock
+    /// * `storage` - The storage to persist the change to
     ///
     /// # Returns
     /// The new folder
     ///
     /// # Errors
+    /// Returns an error if the name contains PATH_SEPARATOR
     /// Returns an error if the folders lock is poisoned
-    #[must_use]
     pub fn create_folder(
         name: String,
         folders: &RwLock<Vec<FolderModel>>,
+        storage: &dyn Storage,
+    ) -> Result<String, AppError> {
+        Self::create_subfolder(name, None, folders, storage)
+    }
+
+    /// Create a new folder inside another folder, after its existing subfolders
+    /// Use the increment_folder_name function to get a name unique among its siblings
+    ///
+    /// # Arguments
+    /// * `name` - The name of the new folder
+    /// * `parent` - The path of the parent folder, or None for a top-level folder
+    /// * `folders` - The list of folders, as a RwLock
+    /// * `storage` - The storage to persist the change to
+    ///
+    /// # Returns
+    /// The path of the new folder
+    ///
+    /// # Errors
+    /// Returns an error if the name contains PATH_SEPARATOR
+    /// Returns an error if the parent folder is not found
+    /// Returns an error if the folders lock is poisoned
+    pub fn create_subfolder(
+        name: String,
+        parent: Option<String>,
+        folders: &RwLock<Vec<FolderModel>>,
+        storage: &dyn Storage,
     ) -> Result<String, AppError> {
-        let new_name = FolderManager::increment_folder_name(name, folders)?;
+        Self::validate_folder_name(&name)?;
+        let new_name = FolderManager::increment_folder_name(name, parent.as_deref(), folders)?;
 
         let mut folders_lock = folders
             .write()
             .map_err(|_| ConcurrencyError::PoisonedLock)?;
+        if let Some(parent) = &parent {
+            if !folders_lock.iter().any(|f| &f.folder_name == parent) {
+                return Err(EntityError::FolderNotFound(parent.clone()).into());
+            }
+        }
 
-        let new_folder = FolderModel::new(new_name);
+        let mut new_folder = FolderModel::new(Self::folder_path(parent.as_deref(), &new_name));
+        new_folder.parent = parent;
         folders_lock.push(new_folder.clone());
-        FileService::write_folders(&*folders_lock)?;
+        storage.write_folders(
+            &folders_lock,
+            Changes::Upserted(vec![new_folder.folder_name.clone()]),
+        )?;
         Ok(new_folder.folder_name)
     }
 
-    /// Delete a folder by name
-    /// For each world in the folder, pass to remove_world_from_folder
+    /// Create a new folder at a path
+    /// The last name in the path is the name of the new folder, and the rest is
+    /// the path of its parent
+    ///
+    /// # Arguments
+    /// * `path` - The path of the new folder
+    /// * `folders` - The list of folders, as a RwLock
+    /// * `storage` - The storage to persist the change to
+    ///
+    /// # Returns
+    /// The path of the new folder, which differs from `path` if the name was taken
     ///
+    /// # Errors
+    /// Returns an error if the parent folder is not found
+    /// Returns an error if the folders lock is poisoned
+    pub fn create_folder_at_path(
+        path: String,
+        folders: &RwLock<Vec<FolderModel>>,
+        storage: &dyn Storage,
+    ) -> Result<String, AppError> {
+        let (parent, name) = match path.rsplit_once(PATH_SEPARATOR) {
+            Some((parent, name)) => (Some(parent.to_string()), name.to_string()),
+            None => (None, path),
+        };
+        Self::create_subfolder(name, parent, folders, storage)
+    }
+
+    /// Create a new smart folder, whose worlds are the ones matching a saved query
+    /// Use the increment_folder_name function to get a unique name
     ///
     /// # Arguments
-    /// * `name` - The name of the folder to delete
+    /// * `name` - The name of the new folder
+    /// * `query` - The query selecting the worlds of the folder
     /// * `folders` - The list of folders, as a RwLock
-    /// * `worlds` - The list of worlds, as a RwLock
+    /// * `storage` - The storage to persist the change to
     ///
     /// # Returns
-    /// Ok if the folder was deleted successfully
+    /// The name of the new folder
     ///
     /// # Errors
-    /// Returns an error if the folder is not found
-    pub fn delete_folder(
+    /// Returns an error if the name contains PATH_SEPARATOR
+    /// Returns an error if the query can't be parsed
+    /// Returns an error if the folders lock is poisoned
+    pub fn create_smart_folder(
         name: String,
+        query: String,
         folders: &RwLock<Vec<FolderModel>>,
-        worlds: &RwLock<Vec<WorldModel>>,
-    ) -> Result<(), AppError> {
+        storage: &dyn Storage,
+    ) -> Result<String, AppError> {
+        Self::validate_folder_name(&name)?;
+        WorldQuery::parse(&query)?;
+        let new_name = FolderManager::increment_folder_name(name, None, folders)?;
+
         let mut folders_lock = folders
             .write()
             .map_err(|_| ConcurrencyError::PoisonedLock)?;
 
-        let folder_index = folders_lock.iter().position(|f| f.folder_name == name);
-        match folder_index {
-            Some(index) => {
-                let world_ids = folders_lock[index].world_ids.clone();
-                folders_lock.remove(index);
-                FileService::write_folders(&*folders_lock)?;
-                drop(folders_lock);
-                for world_id in world_ids {
-                    FolderManager::remove_world_from_folder(
-                        name.clone(),
-                        world_id,
-                        folders,
-                        worlds,
-                    )?;
-                }
-                Ok(())
-            }
-            None => Err(EntityError::FolderNotFound(name).into()),
-        }
+        let mut new_folder = FolderModel::new(new_name);
+        new_folder.query = Some(query);
+        folders_lock.push(new_folder.clone());
+        storage.write_folders(
+            &folders_lock,
+            Changes::Upserted(vec![new_folder.folder_name.clone()]),
+        )?;
+        Ok(new_folder.folder_name)
     }
 
-    /// Move a folder to a new position in the list
+    /// Replace the saved query of a smart folder
     ///
     /// # Arguments
-    /// * `folder_name` - The name of the folder to move
-    /// * `new_index` - The new index for the folder
+    /// * `folder_name` - The name of the smart folder
+    /// * `query` - The new query
     /// * `folders` - The list of folders, as a RwLock
+    /// * `storage` - The storage to persist the change to
     ///
     /// # Returns
-    /// Ok if the folder was moved successfully
+    /// Ok if the query was updated successfully
     ///
     /// # Errors
-    /// Returns an error if the folder is not found
-    pub fn move_folder(
+    /// Returns an error if the query can't be parsed
+    /// Returns an error if the folder is not found, or is not a smart folder
+    /// Returns an error if the folders lock is poisoned
+    pub fn update_smart_folder(
         folder_name: String,
-        new_index: usize,
+        query: String,
         folders: &RwLock<Vec<FolderModel>>,
+        storage: &dyn Storage,
     ) -> Result<(), AppError> {
+        WorldQuery::parse(&query)?;
         let mut folders_lock = folders
             .write()
             .map_err(|_| ConcurrencyError::PoisonedLock)?;
 
-        let current_index = folders_lock
-            .iter()
-            .position(|f| f.folder_name == folder_name)
-            .ok_or_else(|| EntityError::FolderNotFound(folder_name))?;
-        // Remove from current position and insert at new position
-        let folder = folders_lock.remove(current_index);
-        folders_lock.insert(new_index, folder);
+        let folder = folders_lock
+            .iter_mut()
+            .find(|f| f.folder_name == folder_name)
+            .ok_or_else(|| EntityError::FolderNotFound(folder_name.clone()))?;
+        if !folder.is_smart() {
+            return Err(EntityError::InvalidOperation(format!(
+                "{} is not a smart folder",
+                folder_name
+            ))
+            .into());
+        }
+        folder.query = Some(query);
 
-        FileService::write_folders(&*folders_lock)?;
+        storage.write_folders(&folders_lock, Changes::Upserted(vec![folder_name]))?;
         Ok(())
     }
 
-    /// Rename a folder
-    /// This is done by removing the folder from the list, and adding it back with the new name
-    /// We also need to update the world user_data.folders list
+    /// Delete a folder by name
+    /// The worlds in the folder are removed from it, and its subfolders are moved
+    /// up to the parent of the deleted folder
+    /// A subfolder gets a number appended if its name is taken in the parent
     ///
     /// # Arguments
-    /// * `old_name` - The old name of the folder
-    /// * `new_name` - The new name of the folder
+    /// * `name` - The name of the folder to delete
     /// * `folders` - The list of folders, as a RwLock
     /// * `worlds` - The list of worlds, as a RwLock
-    /// * `preferences` - The preferences, as a RwLock. Used to store user-specific settings
-    ///   and configurations that may influence folder renaming behavior, such as naming conventions
-    ///   or restrictions.
+    /// * `preferences` - The preferences, as a RwLock
+    /// * `storage` - The storage to persist the change to
     ///
     /// # Returns
-    /// Ok if the folder was renamed successfully
+    /// Ok if the folder was deleted successfully
     ///
     /// # Errors
     /// Returns an error if the folder is not found
-    /// Returns an error if the worlds lock is poisoned
-    /// Returns an error if the folders lock is poisoned
-    pub fn rename_folder(
-        old_name: String,
-        new_name: String,
+    /// Returns an error if any lock is poisoned
+    pub fn delete_folder(
+        name: String,
         folders: &RwLock<Vec<FolderModel>>,
         worlds: &RwLock<Vec<WorldModel>>,
         preferences: &RwLock<PreferenceModel>,
+        storage: &dyn Storage,
     ) -> Result<(), AppError> {
         let mut preferences_lock = preferences
             .write()
             .map_err(|_| ConcurrencyError::PoisonedLock)?;
-
-        if let Some(starred_selector) = &mut preferences_lock.filter_item_selector_starred {
-            if let Some(folder_index) = starred_selector.folder.iter().position(|f| f == &old_name)
-            {
-                starred_selector.folder[folder_index] = new_name.clone();
-            }
-        }
-
         let mut folders_lock = folders
             .write()
             .map_err(|_| ConcurrencyError::PoisonedLock)?;
         let mut worlds_lock = worlds.write().map_err(|_| ConcurrencyError::PoisonedLock)?;
 
-        let folder_index = folders_lock.iter().position(|f| f.folder_name == old_name);
-        match folder_index {
-            Some(index) => {
-                let world_ids = folders_lock[index].world_ids.clone();
-                folders_lock[index].folder_name = new_name.clone();
-                FileService::write_folders(&*folders_lock)?;
-                drop(folders_lock);
-                for world_id in world_ids {
-                    if let Some(world) = worlds_lock
-                        .iter_mut()
-                        .find(|w| w.api_data.world_id == world_id)
-                    {
-                        world.user_data.folders.retain(|folder| folder != &old_name);
-                        if !world.user_data.folders.contains(&new_name) {
-                            world.user_data.folders.push(new_name.clone());
-                        }
-                    }
-                }
-                FileService::write_worlds(&*worlds_lock)?;
-                Ok(())
+        let index = folders_lock
+            .iter()
+            .position(|f| f.folder_name == name)
+            .ok_or_else(|| EntityError::FolderNotFound(name.clone()))?;
+        let removed = folders_lock.remove(index);
+        let mut changed: Vec<String> = Vec::new();
+        for world in worlds_lock
+            .iter_mut()
+            .filter(|w| removed.world_ids.contains(&w.api_data.world_id))
+        {
+            world.user_data.folders.retain(|folder| folder != &name);
+            changed.push(world.api_data.world_id.clone());
+        }
+
+        let children: Vec<String> = folders_lock
+            .iter()
+            .filter(|f| f.parent.as_ref() == Some(&name))
+            .map(|f| f.folder_name.clone())
+            .collect();
+        for child in &children {
+            let child_name = Self::name_in_path(child);
+            let mut new_name = child_name.to_string();
+            let mut count = 1;
+            while folders_lock
+                .iter()
+                .any(|f| f.folder_name == Self::folder_path(removed.parent.as_deref(), &new_name))
+            {
+                new_name = format!("{} ({})", child_name, count);
+                count += 1;
+            }
+            let new_path = Self::folder_path(removed.parent.as_deref(), &new_name);
+            changed.extend(Self::repath_folder(
+                child,
+                &new_path,
+                &mut folders_lock,
+                &mut worlds_lock,
+                &mut preferences_lock,
+            ));
+            if let Some(folder) = folders_lock.iter_mut().find(|f| f.folder_name == new_path) {
+                folder.parent = removed.parent.clone();
             }
-            None => Err(EntityError::FolderNotFound(old_name).into()),
         }
+
+        let changes = if children.is_empty() {
+            Changes::Removed(vec![name])
+        } else {
+            Changes::All
+        };
+        storage.write_folders(&folders_lock, changes)?;
+        storage.write_worlds(&worlds_lock, Changes::Upserted(changed))?;
+        Ok(())
     }
 
-    /// Get a world by its ID
+    /// Delete the folder at a path
+    /// Subfolders are moved up, as with delete_folder
     ///
     /// # Arguments
-    /// * world_id - The ID of the world
-    /// * worlds - The list of worlds, as a RwLock
+    /// * `path` - The path of the folder to delete
+    /// * `folders` - The list of folders, as a RwLock
+    /// * `worlds` - The list of worlds, as a RwLock
+    /// * `preferences` - The preferences, as a RwLock
+    /// * `storage` - The storage to persist the change to
     ///
     /// # Returns
-    /// Returns the world with the specified ID if found
+    /// Ok if the folder was deleted successfully
     ///
     /// # Errors
-    /// Returns an error if the world is not found
-    #[must_use]
-    fn get_world(
-        world_id: String,
+    /// Returns an error if no folder has the path
+    pub fn delete_folder_at_path(
+        path: String,
+        folders: &RwLock<Vec<FolderModel>>,
         worlds: &RwLock<Vec<WorldModel>>,
-    ) -> Result<WorldModel, AppError> {
-        let worlds_lock = worlds.read().map_err(|_| ConcurrencyError::PoisonedLock)?;
-        match worlds_lock.iter().find(|w| w.api_data.world_id == world_id) {
-            Some(world) => Ok(world.clone()),
-            None => Err(EntityError::WorldNotFound(world_id).into()),
-        }
+        preferences: &RwLock<PreferenceModel>,
+        storage: &dyn Storage,
+    ) -> Result<(), AppError> {
+        Self::delete_folder(path, folders, worlds, preferences, storage)
     }
 
-    fn normalize_custom_tag(tag: &str) -> Option<String> {
-        let trimmed = tag.trim();
-        if trimmed.is_empty() {
-            return None;
-        }
-
-        let without_prefix = trimmed.strip_prefix("custom:").unwrap_or(trimmed).trim();
+    /// Move a folder to a new position among the folders sharing its parent
+    ///
+    /// # Arguments
+    /// * `folder_name` - The name of the folder to move
+    /// * `new_index` - The new index for the folder, among its siblings
+    /// * `folders` - The list of folders, as a RwLock
+    /// * `worlds` - The list of worlds, as a RwLock
+    /// * `preferences` - The preferences, as a RwLock
+    /// * `storage` - The storage to persist the change to
+    ///
+    /// # Returns
+    /// Ok if the folder was moved successfully
+    ///
+    /// # Errors
+    /// Returns an error if the folder is not found
+    pub fn move_folder(
+        folder_name: String,
+        new_index: usize,
+        folders: &RwLock<Vec<FolderModel>>,
+        worlds: &RwLock<Vec<WorldModel>>,
+        preferences: &RwLock<PreferenceModel>,
+        storage: &dyn Storage,
+    ) -> Result<(), AppError> {
+        let parent = {
+            let folders_lock = folders.read().map_err(|_| ConcurrencyError::PoisonedLock)?;
+            folders_lock
+                .iter()
+                .find(|f| f.folder_name == folder_name)
+                .ok_or_else(|| EntityError::FolderNotFound(folder_name.clone()))?
+                .parent
+                .clone()
+        };
+        Self::move_folder_to(
+            folder_name,
+            parent,
+            new_index,
+            folders,
+            worlds,
+            preferences,
+            storage,
+        )
+    }
 
-        if without_prefix.is_empty() {
+    /// Move a folder under a new parent, at a position among its new siblings
+    /// The paths of the folder and its subfolders change with the parent
+    ///
+    /// # Arguments
+    /// * `folder_name` - The name of the folder to move
+    /// * `new_parent` - The path of the new parent, or None to make it a top-level folder
+    /// * `new_index` - The new index for the folder, among its new siblings
+    /// * `folders` - The list of folders, as a RwLock
+    /// * `worlds` - The list of worlds, as a RwLock
+    /// * `preferences` - The preferences, as a RwLock
+    /// * `storage` - The storage to persist the change to
+    ///
+    /// # Returns
+    /// Ok if the folder was moved successfully
+    ///
+    /// # Errors
+    /// Returns an error if the folder or new parent is not found
+    /// Returns an error if the new parent is the folder itself, or one of its subfolders
+    /// Returns an error if the new parent already has a folder with the same name
+    pub fn move_folder_to(
+        folder_name: String,
+        new_parent: Option<String>,
+        new_index: usize,
+        folders: &RwLock<Vec<FolderModel>>,
+        worlds: &RwLock<Vec<WorldModel>>,
+        preferences: &RwLock<PreferenceModel>,
+        storage: &dyn Storage,
+    ) -> Result<(), AppError> {
+        let mut preferences_lock = preferences
+            .write()
+            .map_err(|_| ConcurrencyError::PoisonedLock)?;
+        let mut folders_lock = folders
+            .write()
+            .map_err(|_| ConcurrencyError::PoisonedLock)?;
+        let mut worlds_lock = worlds.write().map_err(|_| ConcurrencyError::PoisonedLock)?;
+
+        if !folders_lock.iter().any(|f| f.folder_name == folder_name) {
+            return Err(EntityError::FolderNotFound(folder_name).into());
+        }
+        if let Some(parent) = &new_parent {
+            if !folders_lock.iter().any(|f| &f.folder_name == parent) {
+                return Err(EntityError::FolderNotFound(parent.clone()).into());
+            }
+            if parent == &folder_name
+                || Self::descendant_names(&folder_name, &folders_lock).contains(parent)
+            {
+                return Err(EntityError::InvalidOperation(format!(
+                    "can't move folder {} into itself",
+                    folder_name
+                ))
+                .into());
+            }
+        }
+
+        let new_path = Self::folder_path(new_parent.as_deref(), Self::name_in_path(&folder_name));
+        let mut changed = Vec::new();
+        if new_path != folder_name {
+            if folders_lock.iter().any(|f| f.folder_name == new_path) {
+                return Err(EntityError::DuplicateFolder(new_path).into());
+            }
+            changed = Self::repath_folder(
+                &folder_name,
+                &new_path,
+                &mut folders_lock,
+                &mut worlds_lock,
+                &mut preferences_lock,
+            );
+        }
+
+        // Remove from current position and insert at the new position among the siblings
+        let current_index = folders_lock
+            .iter()
+            .position(|f| f.folder_name == new_path)
+            .ok_or_else(|| EntityError::FolderNotFound(new_path.clone()))?;
+        let mut folder = folders_lock.remove(current_index);
+        folder.parent = new_parent;
+        let siblings: Vec<usize> = folders_lock
+            .iter()
+            .enumerate()
+            .filter(|(_, f)| f.parent == folder.parent)
+            .map(|(index, _)| index)
+            .collect();
+        let insert_at = match siblings.get(new_index) {
+            Some(&index) => index,
+            None => siblings
+                .last()
+                .map_or(folders_lock.len(), |&index| index + 1),
+        };
+        folders_lock.insert(insert_at, folder);
+
+        storage.write_folders(&folders_lock, Changes::All)?;
+        if !changed.is_empty() {
+            storage.write_worlds(&worlds_lock, Changes::Upserted(changed))?;
+        }
+        Ok(())
+    }
+
+    /// Move the folder at a path under a new parent
+    ///
+    /// # Arguments
+    /// * `path` - The path of the folder to move
+    /// * `new_parent_path` - The path of the new parent, or None to make it a top-level folder
+    /// * `new_index` - The new index for the folder, among its new siblings
+    /// * `folders` - The list of folders, as a RwLock
+    /// * `worlds` - The list of worlds, as a RwLock
+    /// * `preferences` - The preferences, as a RwLock
+    /// * `storage` - The storage to persist the change to
+    ///
+    /// # Returns
+    /// Ok if the folder was moved successfully
+    ///
+    /// # Errors
+    /// Returns an error if no folder has the path, or the new parent path
+    /// Returns an error if the new parent is the folder itself, or one of its subfolders
+    pub fn move_folder_at_path(
+        path: String,
+        new_parent_path: Option<String>,
+        new_index: usize,
+        folders: &RwLock<Vec<FolderModel>>,
+        worlds: &RwLock<Vec<WorldModel>>,
+        preferences: &RwLock<PreferenceModel>,
+        storage: &dyn Storage,
+    ) -> Result<(), AppError> {
+        Self::move_folder_to(
+            path,
+            new_parent_path,
+            new_index,
+            folders,
+            worlds,
+            preferences,
+            storage,
+        )
+    }
+
+    /// Rename a folder
+    /// The folder keeps its parent, so only the last name in its path changes
+    /// The paths of its subfolders change along with it, in the worlds, the
+    /// starred folders and the queries of smart folders
+    ///
+    /// # Arguments
+    /// * `old_name` - The old name of the folder
+    /// * `new_name` - The new name of the folder
+    /// * `folders` - The list of folders, as a RwLock
+    /// * `worlds` - The list of worlds, as a RwLock
+    /// * `preferences` - The preferences, as a RwLock. Used to store user-specific settings
+    ///   and configurations that may influence folder renaming behavior, such as naming conventions
+    ///   or restrictions.
+    /// * `storage` - The storage to persist the change to
+    ///
+    /// # Returns
+    /// Ok if the folder was renamed successfully
+    ///
+    /// # Errors
+    /// Returns an error if the name contains PATH_SEPARATOR
+    /// Returns an error if another folder in the same parent already has the new name
+    /// Returns an error if the folder is not found
+    /// Returns an error if the worlds lock is poisoned
+    /// Returns an error if the folders lock is poisoned
+    pub fn rename_folder(
+        old_name: String,
+        new_name: String,
+        folders: &RwLock<Vec<FolderModel>>,
+        worlds: &RwLock<Vec<WorldModel>>,
+        preferences: &RwLock<PreferenceModel>,
+        storage: &dyn Storage,
+    ) -> Result<(), AppError> {
+        Self::validate_folder_name(&new_name)?;
+        let mut preferences_lock = preferences
+            .write()
+            .map_err(|_| ConcurrencyError::PoisonedLock)?;
+        let mut folders_lock = folders
+            .write()
+            .map_err(|_| ConcurrencyError::PoisonedLock)?;
+        let mut worlds_lock = worlds.write().map_err(|_| ConcurrencyError::PoisonedLock)?;
+
+        let parent = folders_lock
+            .iter()
+            .find(|f| f.folder_name == old_name)
+            .ok_or_else(|| EntityError::FolderNotFound(old_name.clone()))?
+            .parent
+            .clone();
+        let new_path = Self::folder_path(parent.as_deref(), &new_name);
+        if new_path == old_name {
+            return Ok(());
+        }
+        if folders_lock.iter().any(|f| f.folder_name == new_path) {
+            return Err(EntityError::DuplicateFolder(new_path).into());
+        }
+
+        let changed = Self::repath_folder(
+            &old_name,
+            &new_path,
+            &mut folders_lock,
+            &mut worlds_lock,
+            &mut preferences_lock,
+        );
+        storage.write_folders(&folders_lock, Changes::All)?;
+        storage.write_worlds(&worlds_lock, Changes::Upserted(changed))?;
+        Ok(())
+    }
+
+    /// Rename the folder at a path
+    /// Only the last name in the path changes; the folder keeps its parent
+    ///
+    /// # Arguments
+    /// * `path` - The path of the folder to rename
+    /// * `new_name` - The new name of the folder
+    /// * `folders` - The list of folders, as a RwLock
+    /// * `worlds` - The list of worlds, as a RwLock
+    /// * `preferences` - The preferences, as a RwLock
+    /// * `storage` - The storage to persist the change to
+    ///
+    /// # Returns
+    /// Ok if the folder was renamed successfully
+    ///
+    /// # Errors
+    /// Returns an error if no folder has the path
+    /// Returns an error if any lock is poisoned
+    pub fn rename_folder_at_path(
+        path: String,
+        new_name: String,
+        folders: &RwLock<Vec<FolderModel>>,
+        worlds: &RwLock<Vec<WorldModel>>,
+        preferences: &RwLock<PreferenceModel>,
+        storage: &dyn Storage,
+    ) -> Result<(), AppError> {
+        Self::rename_folder(path, new_name, folders, worlds, preferences, storage)
+    }
+
+    /// Changes the path of a folder and of its subfolders
+    /// The worlds, starred folders and smart folder queries referring to the old
+    /// paths are updated to the new ones. The parent of the folder itself is left
+    /// to the caller.
+    ///
+    /// # Arguments
+    /// * `old_path` - The current path of the folder
+    /// * `new_path` - The new path of the folder
+    /// * `folders` - All folders
+    /// * `worlds` - All worlds
+    /// * `preferences` - The preferences holding the starred folders
+    ///
+    /// # Returns
+    /// The IDs of the worlds whose folder list changed
+    fn repath_folder(
+        old_path: &str,
+        new_path: &str,
+        folders: &mut [FolderModel],
+        worlds: &mut [WorldModel],
+        preferences: &mut PreferenceModel,
+    ) -> Vec<String> {
+        let prefix = format!("{}{}", old_path, PATH_SEPARATOR);
+        let repath = |path: &str| {
+            if path == old_path {
+                Some(new_path.to_string())
+            } else {
+                path.strip_prefix(&prefix)
+                    .map(|rest| Self::folder_path(Some(new_path), rest))
+            }
+        };
+
+        let mut renamed: Vec<(String, String)> = Vec::new();
+        for folder in folders.iter_mut() {
+            if let Some(path) = repath(&folder.folder_name) {
+                renamed.push((folder.folder_name.clone(), path.clone()));
+                folder.folder_name = path;
+            }
+            if let Some(parent) = folder.parent.as_deref().and_then(repath) {
+                folder.parent = Some(parent);
+            }
+        }
+        for folder in folders.iter_mut() {
+            let Some(query) = &mut folder.query else {
+                continue;
+            };
+            for (old, new) in &renamed {
+                if let Some(rewritten) = WorldQuery::rename_folder(query, old, new) {
+                    *query = rewritten;
+                }
+            }
+        }
+
+        let mut changed = Vec::new();
+        for world in worlds.iter_mut() {
+            let mut world_changed = false;
+            for folder in world.user_data.folders.iter_mut() {
+                if let Some(path) = repath(folder) {
+                    *folder = path;
+                    world_changed = true;
+                }
+            }
+            if world_changed {
+                changed.push(world.api_data.world_id.clone());
+            }
+        }
+        if let Some(starred_selector) = &mut preferences.filter_item_selector_starred {
+            for folder in starred_selector.folder.iter_mut() {
+                if let Some(path) = repath(folder) {
+                    *folder = path;
+                }
+            }
+        }
+        changed
+    }
+
+    /// Get a world by its ID
+    ///
+    /// # Arguments
+    /// * world_id - The ID of the world
+    /// * worlds - The list of worlds, as a RwLock
+    ///
+    /// # Returns
+    /// Returns the world with the specified ID if found
+    ///
+    /// # Errors
+    /// Returns an error if the world is not found
+    fn get_world(
+        world_id: String,
+        worlds: &RwLock<Vec<WorldModel>>,
+    ) -> Result<WorldModel, AppError> {
+        let worlds_lock = worlds.read().map_err(|_| ConcurrencyError::PoisonedLock)?;
+        match worlds_lock.iter().find(|w| w.api_data.world_id == world_id) {
+            Some(world) => Ok(world.clone()),
+            None => Err(EntityError::WorldNotFound(world_id).into()),
+        }
+    }
+
+    fn normalize_custom_tag(tag: &str) -> Option<String> {
+        let trimmed = tag.trim();
+        if trimmed.is_empty() {
+            return None;
+        }
+
+        let without_prefix = trimmed.strip_prefix("custom:").unwrap_or(trimmed).trim();
+
+        if without_prefix.is_empty() {
             return None;
         }
 
@@ -541,6 +1310,7 @@ impl FolderManager {
         world_id: String,
         tags: Vec<String>,
         worlds: &RwLock<Vec<WorldModel>>,
+        storage: &dyn Storage,
     ) -> Result<Vec<String>, AppError> {
         let mut worlds_lock = worlds.write().map_err(|_| ConcurrencyError::PoisonedLock)?;
 
@@ -561,14 +1331,91 @@ impl FolderManager {
             }
         }
 
-        normalized.sort_by(|a, b| a.to_lowercase().cmp(&b.to_lowercase()));
+        normalized.sort_by_key(|a| a.to_lowercase());
 
         world.user_data.custom_tags = normalized.clone();
-        FileService::write_worlds(&*worlds_lock)?;
+        storage.write_worlds(&worlds_lock, Changes::Upserted(vec![world_id]))?;
 
         Ok(normalized)
     }
 
+    /// Get the memo of a world
+    ///
+    /// # Arguments
+    /// * `world_id` - The ID of the world
+    /// * `worlds` - The list of worlds, as a RwLock
+    ///
+    /// # Returns
+    /// The memo of the world, which is empty if none was written
+    ///
+    /// # Errors
+    /// Returns an error if the world is not found
+    /// Returns an error if the worlds lock is poisoned
+    pub fn get_memo(
+        world_id: String,
+        worlds: &RwLock<Vec<WorldModel>>,
+    ) -> Result<String, AppError> {
+        let world = Self::get_world(world_id, worlds)?;
+        Ok(world.user_data.memo)
+    }
+
+    /// Set the memo of a world
+    ///
+    /// # Arguments
+    /// * `world_id` - The ID of the world
+    /// * `memo` - The new memo
+    /// * `worlds` - The list of worlds, as a RwLock
+    /// * `storage` - The storage to persist the change to
+    ///
+    /// # Returns
+    /// Ok if the memo was saved successfully
+    ///
+    /// # Errors
+    /// Returns an error if the world is not found
+    /// Returns an error if the worlds lock is poisoned
+    pub fn set_memo(
+        world_id: String,
+        memo: String,
+        worlds: &RwLock<Vec<WorldModel>>,
+        storage: &dyn Storage,
+    ) -> Result<(), AppError> {
+        let mut worlds_lock = worlds.write().map_err(|_| ConcurrencyError::PoisonedLock)?;
+
+        let world = worlds_lock
+            .iter_mut()
+            .find(|w| w.api_data.world_id == world_id)
+            .ok_or_else(|| EntityError::WorldNotFound(world_id.clone()))?;
+        world.user_data.memo = memo;
+
+        storage.write_worlds(&worlds_lock, Changes::Upserted(vec![world_id]))?;
+        Ok(())
+    }
+
+    /// Find the worlds whose memo contains the given text, ignoring case
+    ///
+    /// # Arguments
+    /// * `search_text` - The text to search for
+    /// * `worlds` - The list of worlds, as a RwLock
+    ///
+    /// # Returns
+    /// The IDs of the matching worlds
+    ///
+    /// # Errors
+    /// Returns an error if the worlds lock is poisoned
+    pub fn search_memo_text(
+        search_text: String,
+        worlds: &RwLock<Vec<WorldModel>>,
+    ) -> Result<Vec<String>, AppError> {
+        let worlds_lock = worlds.read().map_err(|_| ConcurrencyError::PoisonedLock)?;
+        let search_text = search_text.to_lowercase();
+
+        Ok(worlds_lock
+            .iter()
+            .filter(|w| w.user_data.memo.to_lowercase().contains(&search_text))
+            .map(|w| w.api_data.world_id.clone())
+            .collect())
+    }
+
     /// Get the worlds in a folder by name
     /// Calls get_world for each world ID in the folder
     ///
@@ -583,7 +1430,6 @@ impl FolderManager {
     /// # Errors
     /// Returns an error if the folder is not found
     /// Returns an error if the folders lock is poisoned
-    #[must_use]
     pub fn get_worlds(
         folder_name: String,
         folders: &RwLock<Vec<FolderModel>>,
@@ -594,7 +1440,10 @@ impl FolderManager {
         let folder = folders_lock.iter().find(|f| f.folder_name == folder_name);
         match folder {
             Some(folder) => {
-                let world_ids = folder.world_ids.clone();
+                let world_ids = {
+                    let worlds_lock = worlds.read().map_err(|_| ConcurrencyError::PoisonedLock)?;
+                    Self::resolve_world_ids(folder, &worlds_lock)
+                };
                 let mut folder_worlds = vec![];
                 drop(folders_lock);
                 for world_id in world_ids {
@@ -618,20 +1467,101 @@ impl FolderManager {
     ///
     /// # Errors
     /// Returns an error if the worlds lock is poisoned
-    #[must_use]
     pub fn get_all_worlds(
         worlds: &RwLock<Vec<WorldModel>>,
     ) -> Result<Vec<WorldDisplayData>, AppError> {
         let worlds_lock = worlds.read().map_err(|_| ConcurrencyError::PoisonedLock)?;
         let worlds_lock = worlds_lock
             .iter()
-            .filter(|w| w.user_data.hidden == false)
+            .filter(|w| !w.user_data.hidden)
             .cloned()
             .collect::<Vec<WorldModel>>();
         let all_worlds = worlds_lock.iter().map(|w| w.to_display_data()).collect();
         Ok(all_worlds)
     }
 
+    /// Get the worlds matching a query
+    /// Hidden worlds are excluded, unless the query filters on `hidden`.
+    ///
+    /// # Arguments
+    /// * `query` - The query, in the syntax described by WorldQuery
+    /// * `worlds` - The list of worlds, as a RwLock
+    ///
+    /// # Returns
+    /// The matching worlds, in library order
+    ///
+    /// # Errors
+    /// Returns an error if the query can't be parsed
+    /// Returns an error if the worlds lock is poisoned
+    pub fn query_worlds(
+        query: String,
+        worlds: &RwLock<Vec<WorldModel>>,
+    ) -> Result<Vec<WorldDisplayData>, AppError> {
+        let query = WorldQuery::parse(&query)?;
+        let worlds_lock = worlds.read().map_err(|_| ConcurrencyError::PoisonedLock)?;
+        Ok(Self::matching_worlds(&query, &worlds_lock)
+            .map(|w| w.to_display_data())
+            .collect())
+    }
+
+    /// Get the recorded changes of a world, oldest first
+    ///
+    /// # Arguments
+    /// * `world_id` - The ID of the world
+    /// * `worlds` - The list of worlds, as a RwLock
+    ///
+    /// # Returns
+    /// The changes seen each time the world was refreshed
+    ///
+    /// # Errors
+    /// Returns an error if the world is not found
+    /// Returns an error if the worlds lock is poisoned
+    pub fn get_world_history(
+        world_id: String,
+        worlds: &RwLock<Vec<WorldModel>>,
+    ) -> Result<Vec<WorldChange>, AppError> {
+        let world = Self::get_world(world_id, worlds)?;
+        Ok(world.user_data.history)
+    }
+
+    /// Get the worlds whose creator changed them since a point in time
+    /// Changes to the visit and favorite counts alone are not counted.
+    /// Hidden worlds are excluded.
+    ///
+    /// # Arguments
+    /// * `since` - Only changes recorded at or after this time are considered
+    /// * `worlds` - The list of worlds, as a RwLock
+    ///
+    /// # Returns
+    /// The updated worlds, most recently changed first
+    ///
+    /// # Errors
+    /// Returns an error if the worlds lock is poisoned
+    pub fn get_worlds_updated_since(
+        since: chrono::DateTime<chrono::Utc>,
+        worlds: &RwLock<Vec<WorldModel>>,
+    ) -> Result<Vec<WorldDisplayData>, AppError> {
+        let worlds_lock = worlds.read().map_err(|_| ConcurrencyError::PoisonedLock)?;
+        let mut updated: Vec<(chrono::DateTime<chrono::Utc>, &WorldModel)> = worlds_lock
+            .iter()
+            .filter(|w| !w.user_data.hidden)
+            .filter_map(|w| {
+                w.user_data
+                    .history
+                    .iter()
+                    .filter(|change| change.recorded_at >= since && change.is_creator_update())
+                    .map(|change| change.recorded_at)
+                    .max()
+                    .map(|latest| (latest, w))
+            })
+            .collect();
+        updated.sort_by_key(|(latest, _)| Reverse(*latest));
+        Ok(updated
+            .into_iter()
+            .map(|(_, w)| w.to_display_data())
+            .collect())
+    }
+
     /// Get all worlds that are Unclassified
     /// Check all worlds, and return those that are not in any folder
     /// This is done by checking if the world's folders list is empty, and the hidden flag is false
@@ -644,15 +1574,13 @@ impl FolderManager {
     ///
     /// # Errors
     /// Returns an error if the worlds lock is poisoned
-    #[must_use]
     pub fn get_unclassified_worlds(
         worlds: &RwLock<Vec<WorldModel>>,
     ) -> Result<Vec<WorldDisplayData>, AppError> {
         let worlds_lock = worlds.read().map_err(|_| ConcurrencyError::PoisonedLock)?;
         let unclassified_worlds = worlds_lock
             .iter()
-            .filter(|w| w.user_data.folders.is_empty() && w.user_data.hidden == false)
-            .cloned()
+            .filter(|w| w.user_data.folders.is_empty() && !w.user_data.hidden)
             .map(|w| w.to_display_data())
             .collect();
         Ok(unclassified_worlds)
@@ -669,15 +1597,13 @@ impl FolderManager {
     ///
     /// # Errors
     /// Returns an error if the worlds lock is poisoned
-    #[must_use]
     pub fn get_hidden_worlds(
         worlds: &RwLock<Vec<WorldModel>>,
     ) -> Result<Vec<WorldDisplayData>, AppError> {
         let worlds_lock = worlds.read().map_err(|_| ConcurrencyError::PoisonedLock)?;
         let hidden_worlds = worlds_lock
             .iter()
-            .filter(|w| w.user_data.hidden == true)
-            .cloned()
+            .filter(|w| w.user_data.hidden)
             .map(|w| w.to_display_data())
             .collect();
         Ok(hidden_worlds)
@@ -693,6 +1619,7 @@ impl FolderManager {
     /// # Arguments
     /// * `worlds` - The list of worlds, as a RwLock
     /// * `new_worlds` - The list of new worlds to add
+    /// * `storage` - The storage to persist the change to
     ///
     /// # Returns
     /// Ok if the worlds were added successfully
@@ -702,7 +1629,9 @@ impl FolderManager {
     pub fn add_worlds(
         worlds: &RwLock<Vec<WorldModel>>,
         new_worlds: Vec<WorldApiData>,
+        storage: &dyn Storage,
     ) -> Result<(), AppError> {
+        let world_ids: Vec<String> = new_worlds.iter().map(|w| w.world_id.clone()).collect();
         let mut worlds_lock = worlds.write().map_err(|_| ConcurrencyError::PoisonedLock)?;
         for new_world in new_worlds {
             let world_id = new_world.world_id.clone();
@@ -714,15 +1643,15 @@ impl FolderManager {
                 Some(world) => {
                     log::info!("World already exists, updating world data: {}", world_id);
                     // Only update if new_world has a more recent last_update
-                    if new_world.last_update > world.api_data.last_update {
-                        world.api_data = new_world;
-                    } else if new_world.last_update == world.api_data.last_update {
-                        // If updatedAt is equal, use the one with greater visits
-                        let existing_visits = world.api_data.visits.unwrap_or(0);
-                        let new_visits = new_world.visits.unwrap_or(0);
-                        if new_visits > existing_visits {
-                            world.api_data = new_world;
+                    // If updatedAt is equal, use the one with greater visits
+                    let is_newer = new_world.last_update > world.api_data.last_update
+                        || (new_world.last_update == world.api_data.last_update
+                            && new_world.visits.unwrap_or(0) > world.api_data.visits.unwrap_or(0));
+                    if is_newer {
+                        if let Some(change) = WorldChange::between(&world.api_data, &new_world) {
+                            world.user_data.record_change(change);
                         }
+                        world.api_data = new_world;
                     }
                     world.user_data.last_checked = chrono::Utc::now();
                 }
@@ -731,10 +1660,111 @@ impl FolderManager {
                 }
             }
         }
-        FileService::write_worlds(&*worlds_lock)?;
+        storage.write_worlds(&worlds_lock, Changes::Upserted(world_ids))?;
+        Ok(())
+    }
+
+    /// Record that worlds were checked, without changing their data
+    /// Used when a world could not be fetched, so it is not retried before the others
+    ///
+    /// # Arguments
+    /// * `world_ids` - The IDs of the worlds that were checked
+    /// * `worlds` - The list of worlds, as a RwLock
+    /// * `storage` - The storage to persist the change to
+    ///
+    /// # Returns
+    /// Ok if the worlds were updated successfully
+    ///
+    /// # Errors
+    /// Returns an error if the worlds lock is poisoned
+    pub fn mark_worlds_checked(
+        world_ids: Vec<String>,
+        worlds: &RwLock<Vec<WorldModel>>,
+        storage: &dyn Storage,
+    ) -> Result<(), AppError> {
+        let mut worlds_lock = worlds.write().map_err(|_| ConcurrencyError::PoisonedLock)?;
+        let now = chrono::Utc::now();
+        for world in worlds_lock
+            .iter_mut()
+            .filter(|w| world_ids.contains(&w.api_data.world_id))
+        {
+            world.user_data.last_checked = now;
+        }
+        storage.write_worlds(&worlds_lock, Changes::Upserted(world_ids))?;
+        Ok(())
+    }
+
+    /// Record whether worlds can still be visited
+    /// The worlds also count as checked, and as seen if they are available.
+    /// IDs of worlds that are not in the library are ignored.
+    ///
+    /// # Arguments
+    /// * `world_ids` - The IDs of the worlds that were looked up
+    /// * `availability` - What the lookup found
+    /// * `worlds` - The list of worlds, as a RwLock
+    /// * `storage` - The storage to persist the change to
+    ///
+    /// # Returns
+    /// Ok if the worlds were updated successfully
+    ///
+    /// # Errors
+    /// Returns an error if the worlds lock is poisoned
+    pub fn set_world_availability(
+        world_ids: Vec<String>,
+        availability: WorldAvailability,
+        worlds: &RwLock<Vec<WorldModel>>,
+        storage: &dyn Storage,
+    ) -> Result<(), AppError> {
+        let mut worlds_lock = worlds.write().map_err(|_| ConcurrencyError::PoisonedLock)?;
+        let now = chrono::Utc::now();
+        let mut changed = vec![];
+        for world in worlds_lock
+            .iter_mut()
+            .filter(|w| world_ids.contains(&w.api_data.world_id))
+        {
+            if availability != world.user_data.availability {
+                log::info!(
+                    "World {} is now {:?}",
+                    world.api_data.world_id,
+                    availability
+                );
+            }
+            world.user_data.availability = availability;
+            world.user_data.last_checked = now;
+            if availability == WorldAvailability::Available {
+                world.user_data.last_seen = Some(now);
+            }
+            changed.push(world.api_data.world_id.clone());
+        }
+        if changed.is_empty() {
+            return Ok(());
+        }
+        storage.write_worlds(&worlds_lock, Changes::Upserted(changed))?;
         Ok(())
     }
 
+    /// Get all worlds that were found to be privated or deleted
+    /// Hidden worlds are excluded
+    ///
+    /// # Arguments
+    /// * `worlds` - The list of worlds, as a RwLock
+    ///
+    /// # Returns
+    /// A vector of world display data
+    ///
+    /// # Errors
+    /// Returns an error if the worlds lock is poisoned
+    pub fn get_unavailable_worlds(
+        worlds: &RwLock<Vec<WorldModel>>,
+    ) -> Result<Vec<WorldDisplayData>, AppError> {
+        let worlds_lock = worlds.read().map_err(|_| ConcurrencyError::PoisonedLock)?;
+        Ok(worlds_lock
+            .iter()
+            .filter(|w| !w.user_data.hidden && w.user_data.availability.is_unavailable())
+            .map(|w| w.to_display_data())
+            .collect())
+    }
+
     /// return a list of tags, sorted by the number of worlds in each tag
     ///
     /// # Arguments
@@ -745,7 +1775,6 @@ impl FolderManager {
     ///
     /// # Errors
     /// Returns an error if the worlds lock is poisoned
-    #[must_use]
     pub fn get_tags_by_count(worlds: &RwLock<Vec<WorldModel>>) -> Result<Vec<String>, AppError> {
         let worlds_lock = worlds.read().map_err(|_| ConcurrencyError::PoisonedLock)?;
         // create a map which contains the tag and the number of worlds in that tag
@@ -760,7 +1789,7 @@ impl FolderManager {
         }
         // sort the map by the number of worlds in each tag
         let mut tags: Vec<(String, usize)> = tag_map.into_iter().collect();
-        tags.sort_by(|a, b| b.1.cmp(&a.1));
+        tags.sort_by_key(|(_, count)| Reverse(*count));
 
         let tags: Vec<String> = tags.into_iter().map(|(tag, _)| tag).collect();
 
@@ -777,7 +1806,6 @@ impl FolderManager {
     ///
     /// # Errors
     /// Returns an error if the worlds lock is poisoned
-    #[must_use]
     pub fn get_authors_by_count(worlds: &RwLock<Vec<WorldModel>>) -> Result<Vec<String>, AppError> {
         let worlds_lock = worlds.read().map_err(|_| ConcurrencyError::PoisonedLock)?;
         // create a map which contains the author name and the number of worlds by that author
@@ -789,7 +1817,7 @@ impl FolderManager {
         }
         // sort the map by the number of worlds by each author
         let mut authors: Vec<(String, usize)> = author_map.into_iter().collect();
-        authors.sort_by(|a, b| b.1.cmp(&a.1));
+        authors.sort_by_key(|(_, count)| Reverse(*count));
 
         let authors: Vec<String> = authors.into_iter().map(|(author, _)| author).collect();
 
@@ -803,6 +1831,7 @@ impl FolderManager {
     /// * `world_id` - The ID of the world to delete
     /// * `folders` - The list of folders, as a RwLock
     /// * `worlds` - The list of worlds, as a RwLock
+    /// * `storage` - The storage to persist the change to
     ///
     /// # Returns
     /// Ok if the world was deleted successfully
@@ -815,6 +1844,7 @@ impl FolderManager {
         world_id: String,
         folders: &RwLock<Vec<FolderModel>>,
         worlds: &RwLock<Vec<WorldModel>>,
+        storage: &dyn Storage,
     ) -> Result<(), AppError> {
         let mut worlds_lock = worlds.write().map_err(|_| ConcurrencyError::PoisonedLock)?;
         let world = worlds_lock
@@ -826,7 +1856,7 @@ impl FolderManager {
         let world_index = world.unwrap();
         let world = worlds_lock.remove(world_index);
         info!("Deleting world: {}", world.api_data.world_id);
-        FileService::write_worlds(&*worlds_lock)?;
+        storage.write_worlds(&worlds_lock, Changes::Removed(vec![world_id.clone()]))?;
         drop(worlds_lock);
 
         // First, collect the folder names that contain the world
@@ -843,18 +1873,18 @@ impl FolderManager {
             let mut folders_lock = folders
                 .write()
                 .map_err(|_| ConcurrencyError::PoisonedLock)?;
-            for folder_name in folders_to_update {
+            for folder_name in &folders_to_update {
                 log::info!("Removing world from folder: {}", folder_name);
                 if let Some(folder) = folders_lock
                     .iter_mut()
-                    .find(|f| f.folder_name == folder_name)
+                    .find(|f| &f.folder_name == folder_name)
                 {
                     if let Some(index) = folder.world_ids.iter().position(|id| id == &world_id) {
                         folder.world_ids.remove(index);
                     }
                 }
             }
-            FileService::write_folders(&*folders_lock)?;
+            storage.write_folders(&folders_lock, Changes::Upserted(folders_to_update))?;
         }
         Ok(())
     }
@@ -872,7 +1902,6 @@ impl FolderManager {
     /// # Errors
     /// Returns an error if the world is not found
     /// Returns an error if the worlds lock is poisoned
-    #[must_use]
     pub fn get_folders_for_world(
         world_id: String,
         worlds: &RwLock<Vec<WorldModel>>,
@@ -894,6 +1923,7 @@ impl FolderManager {
     /// * `folder_name` - The name of the folder to set the share
     /// * `folders` - The list of folders, as a RwLock
     /// * `share_id` - The ID of the share to set
+    /// * `storage` - The storage to persist the change to
     ///
     /// # Returns
     /// Ok if the share was set successfully
@@ -906,6 +1936,7 @@ impl FolderManager {
         folders: &RwLock<Vec<FolderModel>>,
         share_id: String,
         ts: String,
+        storage: &dyn Storage,
     ) -> Result<(), AppError> {
         let mut folders_lock = folders
             .write()
@@ -928,7 +1959,7 @@ impl FolderManager {
             expiry_time: time + chrono::Duration::days(30), // Set expiry time to 30 days from now
         });
 
-        FileService::write_folders(&*folders_lock)?;
+        storage.write_folders(&folders_lock, Changes::Upserted(vec![folder_name]))?;
         Ok(())
     }
 
@@ -941,6 +1972,7 @@ impl FolderManager {
     /// # Arguments
     /// * `folder_name` - The name of the folder to update share
     /// * `folders` - The list of folders, as a RwLock
+    /// * `storage` - The storage to persist the change to
     ///
     /// # Returns
     /// Ok with the share ID if it is still valid, or None if it has expired
@@ -951,34 +1983,137 @@ impl FolderManager {
     pub fn update_folder_share(
         folder_name: String,
         folders: &RwLock<Vec<FolderModel>>,
+        storage: &dyn Storage,
     ) -> Result<Option<String>, AppError> {
         let mut folders_lock = folders
             .write()
             .map_err(|_| ConcurrencyError::PoisonedLock)?;
 
-        let folder = match folders_lock
+        let folder = match folders_lock
+            .iter_mut()
+            .find(|f| f.folder_name == folder_name)
+        {
+            Some(f) => f,
+            None => return Err(EntityError::FolderNotFound(folder_name).into()),
+        };
+
+        if let Some(ref share_info) = folder.share {
+            if share_info.expiry_time <= chrono::Utc::now() {
+                folder.share = None;
+                log::info!(
+                    "Share ID for folder '{}' has expired, setting share to None",
+                    folder_name
+                );
+                storage
+                    .write_folders(&folders_lock, Changes::Upserted(vec![folder_name.clone()]))?;
+                Ok(None)
+            } else {
+                Ok(Some(share_info.id.clone()))
+            }
+        } else {
+            Ok(None)
+        }
+    }
+
+    /// Link a folder to a VRChat world favorite group, or unlink it
+    /// Linking starts over, so the first sync merges the folder and the group.
+    ///
+    /// # Arguments
+    /// * `folder_name` - The name of the folder
+    /// * `group` - The name of the favorite group, or None to unlink the folder
+    /// * `folders` - The list of folders, as a RwLock
+    /// * `storage` - The storage to persist the change to
+    ///
+    /// # Returns
+    /// Ok if the link was changed successfully
+    ///
+    /// # Errors
+    /// Returns an error if the folder is not found, or is a smart folder
+    /// Returns an error if another folder is already linked to the group
+    /// Returns an error if the folders lock is poisoned
+    pub fn set_folder_favorite_group(
+        folder_name: String,
+        group: Option<String>,
+        folders: &RwLock<Vec<FolderModel>>,
+        storage: &dyn Storage,
+    ) -> Result<(), AppError> {
+        let mut folders_lock = folders
+            .write()
+            .map_err(|_| ConcurrencyError::PoisonedLock)?;
+
+        if let Some(group) = &group {
+            if group.trim().is_empty() {
+                return Err(EntityError::InvalidOperation(
+                    "favorite group name can't be empty".to_string(),
+                )
+                .into());
+            }
+            if let Some(linked) = folders_lock.iter().find(|f| {
+                f.folder_name != folder_name
+                    && f.favorite_sync.as_ref().is_some_and(|s| &s.group == group)
+            }) {
+                return Err(EntityError::InvalidOperation(format!(
+                    "favorite group {} is already linked to folder {}",
+                    group, linked.folder_name
+                ))
+                .into());
+            }
+        }
+
+        let folder = folders_lock
+            .iter_mut()
+            .find(|f| f.folder_name == folder_name)
+            .ok_or_else(|| EntityError::FolderNotFound(folder_name.clone()))?;
+        if folder.is_smart() && group.is_some() {
+            return Err(Self::smart_folder_error(&folder_name).into());
+        }
+        folder.favorite_sync = group.map(|group| FavoriteGroupSync {
+            group,
+            synced_world_ids: vec![],
+        });
+
+        storage.write_folders(&folders_lock, Changes::Upserted(vec![folder_name]))?;
+        Ok(())
+    }
+
+    /// Record the worlds a folder and its favorite group had in common after a sync
+    ///
+    /// # Arguments
+    /// * `folder_name` - The name of the linked folder
+    /// * `world_ids` - The worlds in both the folder and the group
+    /// * `folders` - The list of folders, as a RwLock
+    /// * `storage` - The storage to persist the change to
+    ///
+    /// # Returns
+    /// Ok if the synced worlds were saved successfully
+    ///
+    /// # Errors
+    /// Returns an error if the folder is not found, or is not linked to a group
+    /// Returns an error if the folders lock is poisoned
+    pub fn set_favorite_sync_base(
+        folder_name: String,
+        world_ids: Vec<String>,
+        folders: &RwLock<Vec<FolderModel>>,
+        storage: &dyn Storage,
+    ) -> Result<(), AppError> {
+        let mut folders_lock = folders
+            .write()
+            .map_err(|_| ConcurrencyError::PoisonedLock)?;
+
+        let folder = folders_lock
             .iter_mut()
             .find(|f| f.folder_name == folder_name)
-        {
-            Some(f) => f,
-            None => return Err(EntityError::FolderNotFound(folder_name).into()),
-        };
-
-        if let Some(ref share_info) = folder.share {
-            if share_info.expiry_time <= chrono::Utc::now() {
-                folder.share = None;
-                log::info!(
-                    "Share ID for folder '{}' has expired, setting share to None",
-                    folder_name
-                );
-                FileService::write_folders(&*folders_lock)?;
-                Ok(None)
-            } else {
-                Ok(Some(share_info.id.clone()))
-            }
-        } else {
-            Ok(None)
-        }
+            .ok_or_else(|| EntityError::FolderNotFound(folder_name.clone()))?;
+        let sync = folder.favorite_sync.as_mut().ok_or_else(|| {
+            EntityError::InvalidOperation(format!(
+                "folder {} is not linked to a favorite group",
+                folder_name
+            ))
+        })?;
+        sync.synced_world_ids = world_ids;
+
+        storage.write_folders(&folders_lock, Changes::Upserted(vec![folder_name]))?;
+        Ok(())
     }
 }
 
@@ -986,23 +2121,16 @@ impl FolderManager {
 mod tests {
     use super::*;
     use crate::definitions::Platform;
-    use crate::definitions::{AuthCookies, FolderModel, PreferenceModel, WorldModel};
+    use crate::definitions::{FilterItemSelectorStarred, FolderModel, PreferenceModel, WorldModel};
+    use crate::services::storage::SqliteStorage;
     use chrono::{NaiveDate, NaiveDateTime, NaiveTime};
-    use std::sync::LazyLock;
     use std::sync::RwLock;
 
-    static TEST_STATE: LazyLock<TestState> = LazyLock::new(|| TestState {
-        preferences: RwLock::new(PreferenceModel::new()),
-        folders: RwLock::new(vec![]),
-        worlds: RwLock::new(vec![]),
-        auth: RwLock::new(AuthCookies::new()),
-    });
-
     struct TestState {
         preferences: RwLock<PreferenceModel>,
         folders: RwLock<Vec<FolderModel>>,
         worlds: RwLock<Vec<WorldModel>>,
-        auth: RwLock<AuthCookies>,
+        storage: SqliteStorage,
     }
 
     fn add_test_world_to_state(
@@ -1047,7 +2175,7 @@ mod tests {
             preferences: RwLock::new(PreferenceModel::new()),
             folders: RwLock::new(vec![]),
             worlds: RwLock::new(vec![]),
-            auth: RwLock::new(AuthCookies::new()),
+            storage: SqliteStorage::open_in_memory().unwrap(),
         }
     }
 
@@ -1057,31 +2185,42 @@ mod tests {
         let name = "Test Folder".to_string();
 
         // Test basic increment
-        let result = FolderManager::increment_folder_name(name.clone(), &state.folders).unwrap();
+        let result =
+            FolderManager::increment_folder_name(name.clone(), None, &state.folders).unwrap();
         assert_eq!(result, "Test Folder");
 
         // Test increment with existing folder
-        let _ = FolderManager::create_folder(name.clone(), &state.folders).unwrap();
-        let result = FolderManager::increment_folder_name(name.clone(), &state.folders).unwrap();
+        let _ = FolderManager::create_folder(name.clone(), &state.folders, &state.storage).unwrap();
+        let result =
+            FolderManager::increment_folder_name(name.clone(), None, &state.folders).unwrap();
         assert_eq!(result, "Test Folder (1)");
     }
 
     #[test]
     fn test_increment_folder_name_numbered() {
         let state = setup_test_state();
-        let _ = FolderManager::create_folder("Test Folder".to_string(), &state.folders).unwrap();
+        let _ =
+            FolderManager::create_folder("Test Folder".to_string(), &state.folders, &state.storage)
+                .unwrap();
         let name = "Test Folder (1)".to_string();
 
         // Test increment of already numbered folder
-        let result = FolderManager::increment_folder_name(name, &state.folders).unwrap();
+        let result = FolderManager::increment_folder_name(name, None, &state.folders).unwrap();
         assert_eq!(result, "Test Folder (1)");
 
         // Test increment with existing numbered folder
-        let _ =
-            FolderManager::create_folder("Test Folder (1)".to_string(), &state.folders).unwrap();
-        let result =
-            FolderManager::increment_folder_name("Test Folder (1)".to_string(), &state.folders)
-                .unwrap();
+        let _ = FolderManager::create_folder(
+            "Test Folder (1)".to_string(),
+            &state.folders,
+            &state.storage,
+        )
+        .unwrap();
+        let result = FolderManager::increment_folder_name(
+            "Test Folder (1)".to_string(),
+            None,
+            &state.folders,
+        )
+        .unwrap();
         assert_eq!(result, "Test Folder (2)");
     }
 
@@ -1090,11 +2229,12 @@ mod tests {
         let state = setup_test_state();
         let name = "Test Folder".to_string();
 
-        let result = FolderManager::create_folder(name.clone(), &state.folders).unwrap();
+        let result =
+            FolderManager::create_folder(name.clone(), &state.folders, &state.storage).unwrap();
         assert_eq!(result, name);
 
         // Test creating duplicate folder
-        let result = FolderManager::create_folder(name, &state.folders).unwrap();
+        let result = FolderManager::create_folder(name, &state.folders, &state.storage).unwrap();
         assert_eq!(result, "Test Folder (1)");
     }
 
@@ -1104,16 +2244,27 @@ mod tests {
         let name = "Test Folder".to_string();
 
         // Test delete existing folder
-        let _ = FolderManager::create_folder(name.clone(), &state.folders).unwrap();
-        let result = FolderManager::delete_folder(name, &state.folders, &state.worlds);
+        let _ = FolderManager::create_folder(name.clone(), &state.folders, &state.storage).unwrap();
+        let result = FolderManager::delete_folder(
+            name,
+            &state.folders,
+            &state.worlds,
+            &state.preferences,
+            &state.storage,
+        );
         if let Err(e) = result.clone() {
             log::error!("Error deleting folder: {}", e);
         }
         assert!(result.is_ok());
 
         // Test delete non-existent folder
-        let result =
-            FolderManager::delete_folder("NonExistent".to_string(), &state.folders, &state.worlds);
+        let result = FolderManager::delete_folder(
+            "NonExistent".to_string(),
+            &state.folders,
+            &state.worlds,
+            &state.preferences,
+            &state.storage,
+        );
         assert!(result.is_err());
     }
 
@@ -1124,12 +2275,14 @@ mod tests {
         let world_id = "test_world".to_string();
         add_test_world_to_state(world_id.clone(), &state.worlds).unwrap();
 
-        let _ = FolderManager::create_folder(folder_name.clone(), &state.folders).unwrap();
+        let _ = FolderManager::create_folder(folder_name.clone(), &state.folders, &state.storage)
+            .unwrap();
         let result = FolderManager::add_world_to_folder(
             folder_name,
             world_id,
             &state.folders,
             &state.worlds,
+            &state.storage,
         );
         if let Err(e) = result.clone() {
             log::error!("Error adding world to folder: {}", e);
@@ -1144,13 +2297,15 @@ mod tests {
         let world_id = "test_world".to_string();
         add_test_world_to_state(world_id.clone(), &state.worlds).unwrap();
 
-        let _ = FolderManager::create_folder(folder_name.clone(), &state.folders).unwrap();
+        let _ = FolderManager::create_folder(folder_name.clone(), &state.folders, &state.storage)
+            .unwrap();
 
-        let _ = FolderManager::add_world_to_folder(
+        FolderManager::add_world_to_folder(
             folder_name.clone(),
             world_id.clone(),
             &state.folders,
             &state.worlds,
+            &state.storage,
         )
         .unwrap();
 
@@ -1159,6 +2314,7 @@ mod tests {
             world_id,
             &state.folders,
             &state.worlds,
+            &state.storage,
         );
         if let Err(e) = result.clone() {
             log::error!("Error removing world from folder: {}", e);
@@ -1170,7 +2326,7 @@ mod tests {
     fn test_get_worlds() {
         let state = setup_test_state();
         let name = "Test Folder".to_string();
-        let _ = FolderManager::create_folder(name.clone(), &state.folders).unwrap();
+        let _ = FolderManager::create_folder(name.clone(), &state.folders, &state.storage).unwrap();
         let result = FolderManager::get_worlds(name, &state.folders, &state.worlds);
         if let Err(e) = result.clone() {
             log::error!("Error getting worlds: {}", e);
@@ -1208,12 +2364,14 @@ mod tests {
         add_test_world_to_state(world_id.clone(), &state.worlds).unwrap();
 
         // Create a folder and add the world to it
-        let _ = FolderManager::create_folder(folder_name.clone(), &state.folders).unwrap();
-        let _ = FolderManager::add_world_to_folder(
+        let _ = FolderManager::create_folder(folder_name.clone(), &state.folders, &state.storage)
+            .unwrap();
+        FolderManager::add_world_to_folder(
             folder_name.clone(),
             world_id.clone(),
             &state.folders,
             &state.worlds,
+            &state.storage,
         )
         .unwrap();
 
@@ -1224,7 +2382,12 @@ mod tests {
         assert_eq!(worlds_in_folder[0].world_id, world_id);
 
         // Delete the world
-        let result = FolderManager::delete_world(world_id.clone(), &state.folders, &state.worlds);
+        let result = FolderManager::delete_world(
+            world_id.clone(),
+            &state.folders,
+            &state.worlds,
+            &state.storage,
+        );
         assert!(result.is_ok());
 
         // Verify world is removed from the folder
@@ -1238,7 +2401,12 @@ mod tests {
 
         // Test deleting a non-existent world
         let non_existent_id = "non_existent_world".to_string();
-        let result = FolderManager::delete_world(non_existent_id, &state.folders, &state.worlds);
+        let result = FolderManager::delete_world(
+            non_existent_id,
+            &state.folders,
+            &state.worlds,
+            &state.storage,
+        );
         assert!(result.is_err());
     }
 
@@ -1253,22 +2421,26 @@ mod tests {
         add_test_world_to_state(world_id.clone(), &state.worlds).unwrap();
 
         // Create two folders and add the world to both
-        let _ = FolderManager::create_folder(folder1.clone(), &state.folders).unwrap();
-        let _ = FolderManager::create_folder(folder2.clone(), &state.folders).unwrap();
+        let _ =
+            FolderManager::create_folder(folder1.clone(), &state.folders, &state.storage).unwrap();
+        let _ =
+            FolderManager::create_folder(folder2.clone(), &state.folders, &state.storage).unwrap();
 
-        let _ = FolderManager::add_world_to_folder(
+        FolderManager::add_world_to_folder(
             folder1.clone(),
             world_id.clone(),
             &state.folders,
             &state.worlds,
+            &state.storage,
         )
         .unwrap();
 
-        let _ = FolderManager::add_world_to_folder(
+        FolderManager::add_world_to_folder(
             folder2.clone(),
             world_id.clone(),
             &state.folders,
             &state.worlds,
+            &state.storage,
         )
         .unwrap();
 
@@ -1282,7 +2454,12 @@ mod tests {
         assert_eq!(worlds_in_folder2.len(), 1);
 
         // Delete the world
-        let result = FolderManager::delete_world(world_id.clone(), &state.folders, &state.worlds);
+        let result = FolderManager::delete_world(
+            world_id.clone(),
+            &state.folders,
+            &state.worlds,
+            &state.storage,
+        );
         assert!(result.is_ok());
 
         // Verify world is removed from both folders
@@ -1302,7 +2479,13 @@ mod tests {
 
         // Add a test world and hide it
         add_test_world_to_state(world_id.clone(), &state.worlds).unwrap();
-        let _ = FolderManager::hide_world(world_id.clone(), &state.folders, &state.worlds).unwrap();
+        FolderManager::hide_world(
+            world_id.clone(),
+            &state.folders,
+            &state.worlds,
+            &state.storage,
+        )
+        .unwrap();
 
         // Verify the world is in hidden worlds
         let hidden_worlds = FolderManager::get_hidden_worlds(&state.worlds).unwrap();
@@ -1310,11 +2493,593 @@ mod tests {
         assert_eq!(hidden_worlds[0].world_id, world_id);
 
         // Delete the hidden world
-        let result = FolderManager::delete_world(world_id.clone(), &state.folders, &state.worlds);
+        let result = FolderManager::delete_world(
+            world_id.clone(),
+            &state.folders,
+            &state.worlds,
+            &state.storage,
+        );
         assert!(result.is_ok());
 
         // Verify the world is no longer in hidden worlds
         let hidden_worlds = FolderManager::get_hidden_worlds(&state.worlds).unwrap();
         assert_eq!(hidden_worlds.len(), 0);
     }
+
+    #[test]
+    fn test_set_memo_is_persisted_with_the_world() {
+        let state = setup_test_state();
+        let world_id = "test_world_123".to_string();
+        add_test_world_to_state(world_id.clone(), &state.worlds).unwrap();
+        state
+            .storage
+            .write_worlds(&state.worlds.read().unwrap(), Changes::All)
+            .unwrap();
+
+        FolderManager::set_memo(
+            world_id.clone(),
+            "Great Mirror".to_string(),
+            &state.worlds,
+            &state.storage,
+        )
+        .unwrap();
+
+        let memo = FolderManager::get_memo(world_id.clone(), &state.worlds).unwrap();
+        assert_eq!(memo, "Great Mirror");
+        let stored = state.storage.load_worlds().unwrap();
+        assert_eq!(stored[0].user_data.memo, "Great Mirror");
+
+        let found = FolderManager::search_memo_text("mirror".to_string(), &state.worlds).unwrap();
+        assert_eq!(found, vec![world_id]);
+        let found = FolderManager::search_memo_text("pool".to_string(), &state.worlds).unwrap();
+        assert!(found.is_empty());
+    }
+
+    #[test]
+    fn test_set_memo_for_unknown_world() {
+        let state = setup_test_state();
+        let result = FolderManager::set_memo(
+            "test_world_123".to_string(),
+            "memo".to_string(),
+            &state.worlds,
+            &state.storage,
+        );
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn test_query_worlds() {
+        let state = setup_test_state();
+        add_test_world_to_state("wrld_visible".to_string(), &state.worlds).unwrap();
+        add_test_world_to_state("wrld_hidden".to_string(), &state.worlds).unwrap();
+        state.worlds.write().unwrap()[1].user_data.hidden = true;
+
+        let found = FolderManager::query_worlds("capacity>=0".to_string(), &state.worlds).unwrap();
+        assert_eq!(found.len(), 1);
+        assert_eq!(found[0].world_id, "wrld_visible");
+
+        let found = FolderManager::query_worlds("hidden:true".to_string(), &state.worlds).unwrap();
+        assert_eq!(found.len(), 1);
+        assert_eq!(found[0].world_id, "wrld_hidden");
+
+        let result = FolderManager::query_worlds("(capacity>=0".to_string(), &state.worlds);
+        assert!(matches!(result, Err(AppError::Query(_))));
+    }
+
+    #[test]
+    fn test_refresh_records_world_history() {
+        let state = setup_test_state();
+        add_test_world_to_state("wrld_changed".to_string(), &state.worlds).unwrap();
+        add_test_world_to_state("wrld_popular".to_string(), &state.worlds).unwrap();
+        let before = chrono::Utc::now();
+
+        let mut changed = state.worlds.read().unwrap()[0].api_data.clone();
+        changed.world_name = "Renamed World".to_string();
+        changed.capacity = 32;
+        changed.tags = vec!["New Tag".to_string()];
+        changed.visits = Some(10);
+        changed.last_update += chrono::Duration::days(1);
+        let mut popular = state.worlds.read().unwrap()[1].api_data.clone();
+        popular.visits = Some(5);
+        popular.favorites = 2;
+        FolderManager::add_worlds(&state.worlds, vec![changed, popular], &state.storage).unwrap();
+
+        let history =
+            FolderManager::get_world_history("wrld_changed".to_string(), &state.worlds).unwrap();
+        assert_eq!(history.len(), 1);
+        let change = &history[0];
+        assert_eq!(change.name.as_ref().unwrap().old, "Test World");
+        assert_eq!(change.name.as_ref().unwrap().new, "Renamed World");
+        assert_eq!(change.capacity.as_ref().unwrap().new, 32);
+        assert!(change.description.is_none());
+        assert_eq!(change.tags_added, vec!["New Tag"]);
+        assert_eq!(change.tags_removed, vec!["Test Tag"]);
+        assert_eq!(change.visits_delta, 10);
+
+        // Only the statistics of the second world moved, so it is not an update
+        let history =
+            FolderManager::get_world_history("wrld_popular".to_string(), &state.worlds).unwrap();
+        assert_eq!(history.len(), 1);
+        assert_eq!(history[0].favorites_delta, 2);
+        assert!(!history[0].is_creator_update());
+
+        let updated = FolderManager::get_worlds_updated_since(before, &state.worlds).unwrap();
+        assert_eq!(updated.len(), 1);
+        assert_eq!(updated[0].world_id, "wrld_changed");
+        let later = chrono::Utc::now() + chrono::Duration::hours(1);
+        let updated = FolderManager::get_worlds_updated_since(later, &state.worlds).unwrap();
+        assert!(updated.is_empty());
+
+        // The history is persisted with the world
+        let stored = state.storage.load_worlds().unwrap();
+        assert_eq!(stored[0].user_data.history.len(), 1);
+    }
+
+    #[test]
+    fn test_world_availability() {
+        let state = setup_test_state();
+        add_test_world_to_state("wrld_deleted".to_string(), &state.worlds).unwrap();
+        add_test_world_to_state("wrld_available".to_string(), &state.worlds).unwrap();
+        add_test_world_to_state("wrld_hidden".to_string(), &state.worlds).unwrap();
+        state.worlds.write().unwrap()[2].user_data.hidden = true;
+
+        FolderManager::set_world_availability(
+            vec!["wrld_deleted".to_string(), "wrld_hidden".to_string()],
+            WorldAvailability::Deleted,
+            &state.worlds,
+            &state.storage,
+        )
+        .unwrap();
+        FolderManager::set_world_availability(
+            vec!["wrld_available".to_string(), "wrld_unknown".to_string()],
+            WorldAvailability::Available,
+            &state.worlds,
+            &state.storage,
+        )
+        .unwrap();
+
+        let unavailable = FolderManager::get_unavailable_worlds(&state.worlds).unwrap();
+        assert_eq!(unavailable.len(), 1);
+        assert_eq!(unavailable[0].world_id, "wrld_deleted");
+        assert_eq!(unavailable[0].availability, WorldAvailability::Deleted);
+
+        let worlds = state.worlds.read().unwrap();
+        assert!(worlds[0].user_data.last_seen.is_none());
+        assert!(worlds[1].user_data.last_seen.is_some());
+        drop(worlds);
+
+        let found =
+            FolderManager::query_worlds("status:unavailable".to_string(), &state.worlds).unwrap();
+        assert_eq!(found.len(), 1);
+        let stored = state.storage.load_worlds().unwrap();
+        assert_eq!(stored[0].user_data.availability, WorldAvailability::Deleted);
+    }
+
+    #[test]
+    fn test_smart_folder() {
+        let state = setup_test_state();
+        add_test_world_to_state("wrld_1".to_string(), &state.worlds).unwrap();
+        add_test_world_to_state("wrld_2".to_string(), &state.worlds).unwrap();
+        state.worlds.write().unwrap()[1].api_data.capacity = 20;
+
+        let name = FolderManager::create_smart_folder(
+            "Large".to_string(),
+            "capacity>=20".to_string(),
+            &state.folders,
+            &state.storage,
+        )
+        .unwrap();
+
+        let folders = FolderManager::get_folders(&state.folders, &state.worlds).unwrap();
+        assert_eq!(folders[0].world_count, 1);
+        assert_eq!(folders[0].query.as_deref(), Some("capacity>=20"));
+        let worlds =
+            FolderManager::get_worlds(name.clone(), &state.folders, &state.worlds).unwrap();
+        assert_eq!(worlds[0].world_id, "wrld_2");
+
+        // Membership follows changes to the worlds
+        state.worlds.write().unwrap()[0].api_data.capacity = 40;
+        let folders = FolderManager::get_folders(&state.folders, &state.worlds).unwrap();
+        assert_eq!(folders[0].world_count, 2);
+
+        FolderManager::update_smart_folder(
+            name.clone(),
+            "capacity>=30".to_string(),
+            &state.folders,
+            &state.storage,
+        )
+        .unwrap();
+        let folders = FolderManager::get_folders(&state.folders, &state.worlds).unwrap();
+        assert_eq!(folders[0].world_count, 1);
+        assert_eq!(
+            state.storage.load_folders().unwrap()[0].query.as_deref(),
+            Some("capacity>=30")
+        );
+
+        let result = FolderManager::add_world_to_folder(
+            name,
+            "wrld_2".to_string(),
+            &state.folders,
+            &state.worlds,
+            &state.storage,
+        );
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn test_create_smart_folder_with_invalid_query() {
+        let state = setup_test_state();
+        let result = FolderManager::create_smart_folder(
+            "Broken".to_string(),
+            "capacity>=".to_string(),
+            &state.folders,
+            &state.storage,
+        );
+        assert!(matches!(result, Err(AppError::Query(_))));
+        assert!(state.folders.read().unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_folder_tree() {
+        let state = setup_test_state();
+        add_test_world_to_state("wrld_1".to_string(), &state.worlds).unwrap();
+        add_test_world_to_state("wrld_2".to_string(), &state.worlds).unwrap();
+        for path in ["Events", "Events/Music", "Events/Music/Live", "Chill"] {
+            FolderManager::create_folder_at_path(path.to_string(), &state.folders, &state.storage)
+                .unwrap();
+        }
+        for (folder, world) in [
+            ("Events", "wrld_1"),
+            ("Events/Music/Live", "wrld_1"),
+            ("Events/Music", "wrld_2"),
+        ] {
+            FolderManager::add_world_to_folder(
+                folder.to_string(),
+                world.to_string(),
+                &state.folders,
+                &state.worlds,
+                &state.storage,
+            )
+            .unwrap();
+        }
+
+        let tree = FolderManager::get_folders(&state.folders, &state.worlds).unwrap();
+        assert_eq!(tree.len(), 2);
+        let events = &tree[0];
+        assert_eq!(events.world_count, 1);
+        assert_eq!(events.total_count, 2);
+        let live = &events.children[0].children[0];
+        assert_eq!(live.path, "Events/Music/Live");
+        assert_eq!(tree[1].name, "Chill");
+
+        // Parents are persisted with the folders
+        let stored = state.storage.load_folders().unwrap();
+        assert_eq!(stored[2].folder_name, "Events/Music/Live");
+        assert_eq!(stored[2].parent.as_deref(), Some("Events/Music"));
+    }
+
+    #[test]
+    fn test_create_folder_at_missing_path() {
+        let state = setup_test_state();
+        let result = FolderManager::create_folder_at_path(
+            "Missing/Child".to_string(),
+            &state.folders,
+            &state.storage,
+        );
+        assert!(result.is_err());
+        assert!(state.folders.read().unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_move_folder_at_path() {
+        let state = setup_test_state();
+        for path in ["A", "B", "B/C", "B/D"] {
+            FolderManager::create_folder_at_path(path.to_string(), &state.folders, &state.storage)
+                .unwrap();
+        }
+
+        // Reorder among siblings
+        FolderManager::move_folder_at_path(
+            "B/D".to_string(),
+            Some("B".to_string()),
+            0,
+            &state.folders,
+            &state.worlds,
+            &state.preferences,
+            &state.storage,
+        )
+        .unwrap();
+        let tree = FolderManager::get_folders(&state.folders, &state.worlds).unwrap();
+        assert_eq!(tree[1].children[0].name, "D");
+        assert_eq!(tree[1].children[1].name, "C");
+
+        // Move under another parent, and back to the top level
+        FolderManager::move_folder_at_path(
+            "B".to_string(),
+            Some("A".to_string()),
+            0,
+            &state.folders,
+            &state.worlds,
+            &state.preferences,
+            &state.storage,
+        )
+        .unwrap();
+        FolderManager::move_folder_at_path(
+            "A/B/C".to_string(),
+            None,
+            0,
+            &state.folders,
+            &state.worlds,
+            &state.preferences,
+            &state.storage,
+        )
+        .unwrap();
+        let tree = FolderManager::get_folders(&state.folders, &state.worlds).unwrap();
+        let names: Vec<&str> = tree.iter().map(|f| f.name.as_str()).collect();
+        assert_eq!(names, vec!["C", "A"]);
+        assert_eq!(tree[1].children[0].children[0].path, "A/B/D");
+
+        // A folder can't be moved into its own subfolder
+        let result = FolderManager::move_folder_at_path(
+            "A".to_string(),
+            Some("A/B/D".to_string()),
+            0,
+            &state.folders,
+            &state.worlds,
+            &state.preferences,
+            &state.storage,
+        );
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn test_rename_and_delete_folder_at_path() {
+        let state = setup_test_state();
+        add_test_world_to_state("wrld_1".to_string(), &state.worlds).unwrap();
+        for path in ["A", "A/B", "A/B/C"] {
+            FolderManager::create_folder_at_path(path.to_string(), &state.folders, &state.storage)
+                .unwrap();
+        }
+        FolderManager::add_world_to_folder(
+            "A/B".to_string(),
+            "wrld_1".to_string(),
+            &state.folders,
+            &state.worlds,
+            &state.storage,
+        )
+        .unwrap();
+
+        FolderManager::rename_folder_at_path(
+            "A/B".to_string(),
+            "Renamed".to_string(),
+            &state.folders,
+            &state.worlds,
+            &state.preferences,
+            &state.storage,
+        )
+        .unwrap();
+        let tree = FolderManager::get_folders(&state.folders, &state.worlds).unwrap();
+        assert_eq!(tree[0].children[0].children[0].path, "A/Renamed/C");
+        let folders =
+            FolderManager::get_folders_for_world("wrld_1".to_string(), &state.worlds).unwrap();
+        assert_eq!(folders, vec!["A/Renamed"]);
+
+        // Subfolders of a deleted folder move up to its parent
+        FolderManager::delete_folder_at_path(
+            "A/Renamed".to_string(),
+            &state.folders,
+            &state.worlds,
+            &state.preferences,
+            &state.storage,
+        )
+        .unwrap();
+        let tree = FolderManager::get_folders(&state.folders, &state.worlds).unwrap();
+        assert_eq!(tree[0].children[0].path, "A/C");
+        let folders =
+            FolderManager::get_folders_for_world("wrld_1".to_string(), &state.worlds).unwrap();
+        assert!(folders.is_empty());
+    }
+
+    #[test]
+    fn test_folder_names_with_path_separator() {
+        let state = setup_test_state();
+        add_test_world_to_state("wrld_1".to_string(), &state.worlds).unwrap();
+        FolderManager::create_folder("A".to_string(), &state.folders, &state.storage).unwrap();
+
+        // New and renamed folders can't contain the separator
+        let result =
+            FolderManager::create_folder("A/B".to_string(), &state.folders, &state.storage);
+        assert!(result.is_err());
+        let result = FolderManager::rename_folder(
+            "A".to_string(),
+            "A/B".to_string(),
+            &state.folders,
+            &state.worlds,
+            &state.preferences,
+            &state.storage,
+        );
+        assert!(result.is_err());
+
+        // Folders left behind by older versions are renamed with the separator escaped
+        let mut folders = vec![
+            FolderModel::new("Games/Fun".to_string()),
+            FolderModel::new("Games\u{2215}Fun".to_string()),
+            FolderModel::new("Child".to_string()),
+        ];
+        folders[0].world_ids.push("wrld_1".to_string());
+        folders[2].parent = Some("Games/Fun".to_string());
+        let mut worlds = state.worlds.read().unwrap().clone();
+        worlds[0].user_data.folders = vec!["Games/Fun".to_string()];
+        let mut preferences = PreferenceModel::new();
+        preferences.filter_item_selector_starred = Some(FilterItemSelectorStarred {
+            author: vec![],
+            tag: vec![],
+            exclude_tag: vec![],
+            folder: vec!["Games/Fun".to_string()],
+        });
+
+        let changed =
+            FolderManager::escape_folder_names(&mut folders, &mut worlds, &mut preferences);
+        assert_eq!(changed, Some(vec!["wrld_1".to_string()]));
+        assert_eq!(folders[0].folder_name, "Games\u{2215}Fun (1)");
+        assert_eq!(folders[2].parent.as_deref(), Some("Games\u{2215}Fun (1)"));
+        assert_eq!(worlds[0].user_data.folders, vec!["Games\u{2215}Fun (1)"]);
+        assert_eq!(
+            preferences.filter_item_selector_starred.unwrap().folder,
+            vec!["Games\u{2215}Fun (1)"]
+        );
+        assert_eq!(
+            FolderManager::escape_folder_names(
+                &mut folders,
+                &mut worlds,
+                &mut PreferenceModel::new()
+            ),
+            None
+        );
+    }
+
+    #[test]
+    fn test_rename_folder_onto_existing_name() {
+        let state = setup_test_state();
+        add_test_world_to_state("wrld_1".to_string(), &state.worlds).unwrap();
+        add_test_world_to_state("wrld_2".to_string(), &state.worlds).unwrap();
+        for (folder, world) in [("A", "wrld_1"), ("B", "wrld_2")] {
+            FolderManager::create_folder(folder.to_string(), &state.folders, &state.storage)
+                .unwrap();
+            FolderManager::add_world_to_folder(
+                folder.to_string(),
+                world.to_string(),
+                &state.folders,
+                &state.worlds,
+                &state.storage,
+            )
+            .unwrap();
+        }
+
+        let result = FolderManager::rename_folder(
+            "A".to_string(),
+            "B".to_string(),
+            &state.folders,
+            &state.worlds,
+            &state.preferences,
+            &state.storage,
+        );
+        assert!(matches!(
+            result,
+            Err(AppError::Entity(EntityError::DuplicateFolder(_)))
+        ));
+
+        // Both folders keep their rows and worlds
+        let stored = state.storage.load_folders().unwrap();
+        assert_eq!(stored.len(), 2);
+        let world_ids = |name: &str| {
+            stored
+                .iter()
+                .find(|f| f.folder_name == name)
+                .map(|f| f.world_ids.clone())
+        };
+        assert_eq!(world_ids("A"), Some(vec!["wrld_1".to_string()]));
+        assert_eq!(world_ids("B"), Some(vec!["wrld_2".to_string()]));
+    }
+
+    #[test]
+    fn test_folder_names_are_unique_among_siblings() {
+        let state = setup_test_state();
+        add_test_world_to_state("wrld_1".to_string(), &state.worlds).unwrap();
+        for path in ["Events", "Events/Misc", "Chill", "Chill/Misc"] {
+            let created = FolderManager::create_folder_at_path(
+                path.to_string(),
+                &state.folders,
+                &state.storage,
+            )
+            .unwrap();
+            assert_eq!(created, path);
+        }
+        let created = FolderManager::create_folder_at_path(
+            "Chill/Misc".to_string(),
+            &state.folders,
+            &state.storage,
+        )
+        .unwrap();
+        assert_eq!(created, "Chill/Misc (1)");
+
+        FolderManager::add_world_to_folder(
+            "Chill/Misc".to_string(),
+            "wrld_1".to_string(),
+            &state.folders,
+            &state.worlds,
+            &state.storage,
+        )
+        .unwrap();
+        FolderManager::create_smart_folder(
+            "Smart".to_string(),
+            "folder:Chill/Misc OR folder:Chill".to_string(),
+            &state.folders,
+            &state.storage,
+        )
+        .unwrap();
+
+        // Renaming updates the subfolders, worlds and queries in the same save
+        FolderManager::rename_folder(
+            "Chill".to_string(),
+            "Quiet spots".to_string(),
+            &state.folders,
+            &state.worlds,
+            &state.preferences,
+            &state.storage,
+        )
+        .unwrap();
+        let query = |folders: &[FolderModel]| {
+            folders
+                .iter()
+                .find(|f| f.folder_name == "Smart")
+                .and_then(|f| f.query.clone())
+        };
+        let stored = state.storage.load_folders().unwrap();
+        assert_eq!(
+            query(&stored).as_deref(),
+            Some("folder:\"Quiet spots/Misc\" OR folder:\"Quiet spots\"")
+        );
+        let misc = stored
+            .iter()
+            .find(|f| f.folder_name == "Quiet spots/Misc")
+            .unwrap();
+        assert_eq!(misc.parent.as_deref(), Some("Quiet spots"));
+        let smart_worlds =
+            FolderManager::get_worlds("Smart".to_string(), &state.folders, &state.worlds).unwrap();
+        assert_eq!(smart_worlds.len(), 1);
+
+        // Moving the folder next to a sibling of the same name is rejected
+        let result = FolderManager::move_folder_to(
+            "Quiet spots/Misc".to_string(),
+            Some("Events".to_string()),
+            0,
+            &state.folders,
+            &state.worlds,
+            &state.preferences,
+            &state.storage,
+        );
+        assert!(matches!(
+            result,
+            Err(AppError::Entity(EntityError::DuplicateFolder(_)))
+        ));
+        FolderManager::move_folder_to(
+            "Quiet spots/Misc".to_string(),
+            None,
+            0,
+            &state.folders,
+            &state.worlds,
+            &state.preferences,
+            &state.storage,
+        )
+        .unwrap();
+        let folders =
+            FolderManager::get_folders_for_world("wrld_1".to_string(), &state.worlds).unwrap();
+        assert_eq!(folders, vec!["Misc"]);
+        let stored = state.storage.load_folders().unwrap();
+        assert_eq!(
+            query(&stored).as_deref(),
+            Some("folder:Misc OR folder:\"Quiet spots\"")
+        );
+    }
 }
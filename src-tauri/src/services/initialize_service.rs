@@ -1,13 +1,23 @@
-use crate::definitions;
 use crate::definitions::{AuthCookies, FolderModel, InitState, PreferenceModel, WorldModel};
 use crate::services::file_service::FileService;
-use crate::PREFERENCES;
+use crate::services::storage::{self, Changes, Storage};
+use crate::services::{EncryptionService, FolderManager};
+
+/// The data loaded on startup: preferences, folders, worlds, cookies and the init state
+type AppData = (
+    PreferenceModel,
+    Vec<FolderModel>,
+    Vec<WorldModel>,
+    AuthCookies,
+    InitState,
+);
 
 /// Runs startup tasks for the application
 /// Checks if the app is being run for the first time, and loads the data
 ///
 /// # Arguments
-/// * `app` - A handle to the Tauri application
+/// * `storage` - The storage to load the folders and worlds from
+/// * `encryption` - The service the stored session is encrypted with
 ///
 /// # Returns
 /// Returns a tuple containing the authentication cookies, folders, and worlds
@@ -15,16 +25,10 @@ use crate::PREFERENCES;
 ///
 /// # Errors
 /// Returns a string error message if the app is being run for the first time, or if there was an error loading the data
-pub fn initialize_app() -> Result<
-    (
-        PreferenceModel,
-        Vec<FolderModel>,
-        Vec<WorldModel>,
-        AuthCookies,
-        InitState,
-    ),
-    String,
-> {
+pub fn initialize_app(
+    storage: &dyn Storage,
+    encryption: &EncryptionService,
+) -> Result<AppData, String> {
     // Check for first time run
     let first_time = FileService::check_first_time();
     if first_time {
@@ -32,40 +36,24 @@ pub fn initialize_app() -> Result<
     }
 
     // Load data from disk
-    match FileService::load_data() {
-        Ok((preferences, folders, worlds, cookies)) => {
-            Ok((preferences, folders, worlds, cookies, InitState::success()))
+    let (mut preferences, cookies) =
+        FileService::load_data(encryption).map_err(|e| e.to_string())?;
+    let (mut folders, mut worlds) = storage::load_library(storage).map_err(|e| e.to_string())?;
+
+    // Folder names containing the path separator break the folder paths
+    if let Some(changed) =
+        FolderManager::escape_folder_names(&mut folders, &mut worlds, &mut preferences)
+    {
+        if let Err(e) = storage.write_folders(&folders, Changes::All) {
+            log::error!("Failed to persist renamed folders: {}", e);
+        }
+        if let Err(e) = storage.write_worlds(&worlds, Changes::Upserted(changed)) {
+            log::error!("Failed to persist renamed folders of worlds: {}", e);
+        }
+        if let Err(e) = FileService::write_preferences(&preferences) {
+            log::error!("Failed to persist renamed starred folders: {}", e);
         }
-        Err(e) => Err(e.to_string()),
     }
-}
 
-/// /// Set the user's preference for first time run
-/// This is called when the user has completed the initial setup
-///
-/// # Arguments
-/// * `theme` - A string indicating the theme the user has selected
-/// * `language` - A string indicating the language the user has selected
-/// * `card_size` - A string indicating the size of the cards the user has selected
-///
-/// # Returns
-/// Returns a boolean indicating if the app is being run for the first time
-///
-/// # Errors
-/// Returns a string error message if there was an error saving the preferences
-pub fn set_preferences(
-    theme: String,
-    language: String,
-    card_size: definitions::CardSize,
-) -> Result<bool, String> {
-    let mut preferences_lock = PREFERENCES.get().write();
-    let preference = preferences_lock.as_mut().unwrap();
-    preference.theme = theme;
-    preference.language = language;
-    preference.card_size = card_size;
-    preference.first_time = false;
-    match FileService::write_preferences(preference) {
-        Ok(_) => Ok(true),
-        Err(e) => Err(e.to_string()),
-    }
+    Ok((preferences, folders, worlds, cookies, InitState::success()))
 }
@@ -0,0 +1,276 @@
+use std::sync::{Arc, RwLock};
+use std::time::Duration;
+
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use tauri::AppHandle;
+use tauri_specta::Event;
+use uuid::Uuid;
+
+use crate::definitions::{ScheduleStatus, ScheduledInstance};
+use crate::services::{ApiService, FileService};
+use crate::task::cancellable_task::TaskContainer;
+use crate::AppState;
+
+/// Pause between two checks for due schedules
+const CHECK_INTERVAL: Duration = Duration::from_secs(15);
+
+/// How late a schedule that came due while the app was running is still run,
+/// e.g. while waiting for the login to finish. Later than that, it is reported as missed instead.
+/// Schedules that came due while the app was closed are always missed.
+const RUN_LATE_LIMIT_MINUTES: i64 = 5;
+
+/// Emitted when a scheduled instance was created, failed, or was missed
+#[derive(Serialize, Clone, specta::Type, tauri_specta::Event)]
+pub struct ScheduledInstanceResult {
+    schedule: ScheduledInstance,
+}
+
+/// Creates scheduled instances when they are due
+///
+/// Schedules are persisted with their status, so the result of a schedule,
+/// including one missed while the app was closed, is kept until it is deleted.
+pub struct InstanceScheduler;
+
+impl InstanceScheduler {
+    /// Loads the schedules from disk and starts checking them
+    ///
+    /// # Arguments
+    /// * `state` - The app state to keep the schedules in
+    /// * `tasks` - The task container to run the scheduler in
+    /// * `app_handle` - The app handle used to emit result events
+    ///
+    /// # Returns
+    /// The ID of the scheduler task
+    ///
+    /// # Errors
+    /// Returns an error if the task could not be started
+    pub fn start(
+        state: &Arc<AppState>,
+        tasks: &mut TaskContainer,
+        app_handle: AppHandle,
+    ) -> Result<Uuid, String> {
+        let schedules = FileService::read_schedules().unwrap_or_else(|e| {
+            log::error!("Failed to read scheduled instances: {}", e);
+            vec![]
+        });
+        match state.schedules.write() {
+            Ok(mut lock) => *lock = schedules,
+            Err(_) => return Err("Failed to acquire write lock for schedules".to_string()),
+        }
+
+        let id = tasks.run(Self::run(Arc::clone(state), app_handle))?;
+        log::info!("Started instance scheduler: {}", id);
+        Ok(id)
+    }
+
+    async fn run(state: Arc<AppState>, app_handle: AppHandle) -> Result<(), String> {
+        let started_at = Utc::now();
+        loop {
+            Self::check(&state, &app_handle, started_at).await;
+            tokio::time::sleep(CHECK_INTERVAL).await;
+        }
+    }
+
+    /// Reports missed schedules, and runs the due ones
+    async fn check(state: &AppState, app_handle: &AppHandle, started_at: DateTime<Utc>) {
+        let due = match Self::due_schedules(&state.schedules, started_at, Utc::now()) {
+            Ok(due) => due,
+            Err(e) => {
+                log::error!("Failed to check scheduled instances: {}", e);
+                return;
+            }
+        };
+
+        let user_id = state.user_id().await;
+        for schedule in due {
+            if schedule.status == ScheduleStatus::Missed {
+                log::warn!("Scheduled instance {} was missed", schedule.id);
+            } else if user_id.is_empty() {
+                // Wait for the login, until the schedule is missed
+                continue;
+            } else {
+                let status = Self::create(state, &schedule, user_id.clone()).await;
+                Self::set_status(&state.schedules, schedule.id, status);
+            }
+
+            Self::save(&state.schedules);
+            let schedule = state
+                .schedules
+                .read()
+                .ok()
+                .and_then(|schedules| schedules.iter().find(|s| s.id == schedule.id).cloned());
+            if let Some(schedule) = schedule {
+                if let Err(e) = (ScheduledInstanceResult { schedule }).emit(app_handle) {
+                    log::error!("Failed to emit ScheduledInstanceResult event: {}", e);
+                }
+            }
+        }
+    }
+
+    async fn create(
+        state: &AppState,
+        schedule: &ScheduledInstance,
+        user_id: String,
+    ) -> ScheduleStatus {
+        let preset = state.preferences.read().ok().and_then(|preferences| {
+            preferences
+                .instance_presets
+                .iter()
+                .find(|p| p.name == schedule.preset_name)
+                .cloned()
+        });
+        let Some(preset) = preset else {
+            return ScheduleStatus::Failed(format!(
+                "Instance preset not found: {}",
+                schedule.preset_name
+            ));
+        };
+
+        let mut options = preset.options;
+        if let Some(region) = schedule.region {
+            options.region = region;
+        }
+        if schedule.group_id.is_some() {
+            options.group_id = schedule.group_id.clone();
+        }
+
+        log::info!("Creating scheduled instance {}", schedule.id);
+        let context = state.api_context().await;
+        match ApiService::create_instance_with_options(
+            schedule.world_id.clone(),
+            options,
+            &context,
+            user_id,
+            schedule.invite_self,
+        )
+        .await
+        {
+            Ok(instance) => ScheduleStatus::Created(instance.instance_id),
+            Err(e) => {
+                log::error!("Failed to create scheduled instance {}: {}", schedule.id, e);
+                ScheduleStatus::Failed(e)
+            }
+        }
+    }
+
+    /// Get the pending schedules that are due, marking those too late to run as missed
+    /// A schedule is too late if it came due before the scheduler started,
+    /// or more than RUN_LATE_LIMIT_MINUTES ago
+    ///
+    /// # Arguments
+    /// * `schedules` - The scheduled instances, as a RwLock
+    /// * `started_at` - When the scheduler started
+    /// * `now` - The current time
+    ///
+    /// # Returns
+    /// The due schedules, the missed ones with their new status
+    ///
+    /// # Errors
+    /// Returns an error if the schedules lock is poisoned
+    pub fn due_schedules(
+        schedules: &RwLock<Vec<ScheduledInstance>>,
+        started_at: DateTime<Utc>,
+        now: DateTime<Utc>,
+    ) -> Result<Vec<ScheduledInstance>, String> {
+        let mut schedules = schedules
+            .write()
+            .map_err(|_| "Failed to acquire write lock for schedules".to_string())?;
+        let late_limit = now - chrono::Duration::minutes(RUN_LATE_LIMIT_MINUTES);
+
+        let mut due = vec![];
+        for schedule in schedules.iter_mut() {
+            if schedule.status != ScheduleStatus::Pending || schedule.scheduled_at > now {
+                continue;
+            }
+            if schedule.scheduled_at < started_at || schedule.scheduled_at < late_limit {
+                schedule.status = ScheduleStatus::Missed;
+            }
+            due.push(schedule.clone());
+        }
+        Ok(due)
+    }
+
+    fn set_status(schedules: &RwLock<Vec<ScheduledInstance>>, id: Uuid, status: ScheduleStatus) {
+        match schedules.write() {
+            Ok(mut schedules) => {
+                if let Some(schedule) = schedules.iter_mut().find(|s| s.id == id) {
+                    schedule.status = status;
+                }
+            }
+            Err(_) => log::error!("Failed to acquire write lock for schedules"),
+        }
+    }
+
+    /// Writes the schedules to disk, logging any error
+    pub fn save(schedules: &RwLock<Vec<ScheduledInstance>>) {
+        match schedules.read() {
+            Ok(schedules) => {
+                if let Err(e) = FileService::write_schedules(&schedules) {
+                    log::error!("Failed to write scheduled instances: {}", e);
+                }
+            }
+            Err(_) => log::error!("Failed to acquire read lock for schedules"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn schedule(minutes_from_now: i64, now: DateTime<Utc>) -> ScheduledInstance {
+        ScheduledInstance {
+            id: Uuid::new_v4(),
+            world_id: "wrld_test".to_string(),
+            preset_name: "Friday meetup".to_string(),
+            region: None,
+            group_id: None,
+            scheduled_at: now + chrono::Duration::minutes(minutes_from_now),
+            invite_self: true,
+            status: ScheduleStatus::Pending,
+        }
+    }
+
+    #[test]
+    fn test_late_schedules_are_missed_instead_of_run() {
+        let now = Utc::now();
+        let started_at = now - chrono::Duration::hours(1);
+        let mut done = schedule(-1, now);
+        done.status = ScheduleStatus::Created("12345".to_string());
+        let schedules = RwLock::new(vec![
+            schedule(10, now),
+            schedule(-1, now),
+            schedule(-30, now),
+            done,
+        ]);
+
+        let due = InstanceScheduler::due_schedules(&schedules, started_at, now).unwrap();
+        assert_eq!(due.len(), 2);
+        assert_eq!(due[0].status, ScheduleStatus::Pending);
+        assert_eq!(due[1].status, ScheduleStatus::Missed);
+        assert_eq!(schedules.read().unwrap()[2].status, ScheduleStatus::Missed);
+
+        // A missed schedule is only reported once
+        let due = InstanceScheduler::due_schedules(&schedules, started_at, now).unwrap();
+        assert_eq!(due.len(), 1);
+        assert_eq!(due[0].status, ScheduleStatus::Pending);
+    }
+
+    #[test]
+    fn test_schedules_due_before_start_are_missed() {
+        let now = Utc::now();
+        let schedules = RwLock::new(vec![schedule(-1, now), schedule(1, now)]);
+
+        // Even within the grace period, a schedule that came due while the app was closed is missed
+        let due = InstanceScheduler::due_schedules(&schedules, now, now).unwrap();
+        assert_eq!(due.len(), 1);
+        assert_eq!(due[0].status, ScheduleStatus::Missed);
+
+        // One that comes due while the app is running is still run
+        let later = now + chrono::Duration::minutes(2);
+        let due = InstanceScheduler::due_schedules(&schedules, now, later).unwrap();
+        assert_eq!(due.len(), 1);
+        assert_eq!(due[0].status, ScheduleStatus::Pending);
+    }
+}
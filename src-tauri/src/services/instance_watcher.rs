@@ -0,0 +1,153 @@
+use std::collections::HashSet;
+use std::sync::Arc;
+use std::time::Duration;
+
+use serde::Serialize;
+use tauri::AppHandle;
+use tauri_specta::Event;
+use uuid::Uuid;
+
+use crate::api::instance::{self, Instance};
+use crate::api::should_backoff;
+use crate::task::cancellable_task::TaskContainer;
+use crate::AppState;
+
+/// The rate limited operation used to list the instances of a world
+const INSTANCES_OPERATION: &str = "get_world_instances";
+
+/// Pause between two polls of the instances of a world
+const POLL_INTERVAL: Duration = Duration::from_secs(60);
+
+/// Emitted when a watched world gets an instance that can be joined
+#[derive(Serialize, Clone, specta::Type, tauri_specta::Event)]
+pub struct InstanceAvailable {
+    instance: Instance,
+}
+
+/// Polls the instances of a world, to find populated ones worth joining
+pub struct InstanceWatcher;
+
+impl InstanceWatcher {
+    /// Starts watching a world until the task is cancelled
+    /// An event is emitted whenever an instance becomes joinable with at least
+    /// `min_users` users in it.
+    ///
+    /// # Arguments
+    /// * `world_id` - The ID of the world to watch
+    /// * `min_users` - The number of users an instance needs to be reported
+    /// * `state` - The app state, used for the API context
+    /// * `tasks` - The task container to run the watcher in
+    /// * `app_handle` - The app handle used to emit events
+    ///
+    /// # Returns
+    /// The ID of the watcher task
+    ///
+    /// # Errors
+    /// Returns an error if the task could not be started
+    pub fn start(
+        world_id: String,
+        min_users: u32,
+        state: &Arc<AppState>,
+        tasks: &mut TaskContainer,
+        app_handle: AppHandle,
+    ) -> Result<Uuid, String> {
+        let id = tasks.run(Self::run(
+            world_id,
+            min_users,
+            Arc::clone(state),
+            app_handle,
+        ))?;
+        log::info!("Started watching instances: {}", id);
+        Ok(id)
+    }
+
+    async fn run(
+        world_id: String,
+        min_users: u32,
+        state: Arc<AppState>,
+        app_handle: AppHandle,
+    ) -> Result<(), String> {
+        let mut announced = HashSet::new();
+        loop {
+            let context = state.api_context().await;
+            if context.session().is_expired() {
+                // Wait for the session to be verified again, or for the next login
+                context.session().valid().await;
+                continue;
+            }
+            if let Some(backoff_ms) = should_backoff(context.rate_limits(), INSTANCES_OPERATION) {
+                tokio::time::sleep(Duration::from_millis(backoff_ms)).await;
+                continue;
+            }
+
+            match instance::get_world_instances(&context, &world_id).await {
+                Ok(instances) => {
+                    for instance in Self::new_joinable(instances, min_users, &mut announced) {
+                        log::info!("Instance available: {}", instance.id);
+                        if let Err(e) = (InstanceAvailable { instance }).emit(&app_handle) {
+                            log::error!("Failed to emit InstanceAvailable event: {}", e);
+                        }
+                    }
+                }
+                Err(e) => log::warn!("Failed to poll instances of {}: {}", world_id, e),
+            }
+            tokio::time::sleep(POLL_INTERVAL).await;
+        }
+    }
+
+    /// Get the instances that became joinable since the last poll
+    /// An instance that fills up and then frees a slot is reported again.
+    ///
+    /// # Arguments
+    /// * `instances` - The instances of the world
+    /// * `min_users` - The number of users an instance needs to be reported
+    /// * `announced` - The IDs of the joinable instances of the last poll, updated in place
+    ///
+    /// # Returns
+    /// The instances to report
+    pub fn new_joinable(
+        instances: Vec<Instance>,
+        min_users: u32,
+        announced: &mut HashSet<String>,
+    ) -> Vec<Instance> {
+        let joinable: Vec<Instance> = instances
+            .into_iter()
+            .filter(|i| i.is_joinable() && i.user_count >= min_users)
+            .collect();
+        let new = joinable
+            .iter()
+            .filter(|i| !announced.contains(&i.id))
+            .cloned()
+            .collect();
+        *announced = joinable.into_iter().map(|i| i.id).collect();
+        new
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_new_joinable_reports_each_opening_once() {
+        let mut announced = HashSet::new();
+        let poll = |users: u32| {
+            vec![
+                Instance::from_listing("wrld_test", "1~region(jp)", users, 16),
+                Instance::from_listing("wrld_test", "2~private(usr_test)", 2, 16),
+                Instance::from_listing("wrld_test", "3", 0, 16),
+            ]
+        };
+
+        let found = InstanceWatcher::new_joinable(poll(4), 1, &mut announced);
+        assert_eq!(found.len(), 1);
+        assert_eq!(found[0].instance_id, "1~region(jp)");
+
+        assert!(InstanceWatcher::new_joinable(poll(5), 1, &mut announced).is_empty());
+        assert!(InstanceWatcher::new_joinable(poll(16), 1, &mut announced).is_empty());
+        assert_eq!(
+            InstanceWatcher::new_joinable(poll(15), 1, &mut announced).len(),
+            1
+        );
+    }
+}
@@ -1,20 +1,43 @@
+pub mod account_service;
 pub mod api_service;
 pub mod delete_data;
 pub mod encryption_service;
 pub mod export_service;
+pub mod favorite_sync_service;
 pub mod file_service;
 pub mod folder_manager;
 pub mod initialize_service;
-pub mod memo_manager;
+pub mod instance_scheduler;
+pub mod instance_watcher;
+pub mod outbox;
+pub mod outbox_service;
+pub mod refresh_service;
+pub mod session_watcher;
 pub mod share_service;
 pub mod sorting_service;
+pub mod storage;
+#[cfg(test)]
+pub mod test_support;
+pub mod thumbnail_cache;
+pub mod world_cache;
+pub mod world_query;
 
-pub use api_service::ApiService;
+pub use account_service::AccountService;
+pub use api_service::{ApiService, FetchedWorld, WorldLookup};
 pub use delete_data::delete_data;
 pub use encryption_service::EncryptionService;
 pub use export_service::ExportService;
+pub use favorite_sync_service::FavoriteSyncService;
 pub use file_service::FileService;
 pub use folder_manager::FolderManager;
-pub use initialize_service::{initialize_app, set_preferences};
-pub use share_service::{download_folder, share_folder};
+pub use instance_scheduler::InstanceScheduler;
+pub use instance_watcher::InstanceWatcher;
+pub use outbox::Outbox;
+pub use outbox_service::OutboxService;
+pub use refresh_service::RefreshService;
+pub use session_watcher::SessionWatcher;
 pub use sorting_service::SortingService;
+pub use storage::{Changes, Storage};
+pub use thumbnail_cache::ThumbnailCache;
+pub use world_cache::WorldCache;
+pub use world_query::WorldQuery;
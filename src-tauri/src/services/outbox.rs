@@ -0,0 +1,268 @@
+use std::path::PathBuf;
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::services::FileService;
+
+/// A network-bound action, queued while VRChat or the share worker could not be reached
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, specta::Type)]
+#[serde(tag = "type")]
+pub enum OutboxOperation {
+    /// Favorite a world in one of the user's favorite groups
+    AddFavorite {
+        #[serde(rename = "worldId")]
+        world_id: String,
+        group: String,
+    },
+    /// Fetch the latest data of a world in the library
+    RefreshWorld {
+        #[serde(rename = "worldId")]
+        world_id: String,
+    },
+    /// Upload a folder to the share worker
+    ShareFolder {
+        #[serde(rename = "folderName")]
+        folder_name: String,
+    },
+}
+
+impl OutboxOperation {
+    /// Whether the action is sent to VRChat, so it waits for the user to be logged in
+    pub fn requires_login(&self) -> bool {
+        !matches!(self, OutboxOperation::ShareFolder { .. })
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, specta::Type)]
+pub enum OutboxStatus {
+    /// Waiting for the connection to come back
+    Pending,
+    /// Being sent
+    Running,
+    /// Sent successfully
+    Completed,
+    /// Rejected by the server, and not retried unless the user asks to
+    Failed,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, specta::Type)]
+pub struct OutboxItem {
+    pub id: Uuid,
+    pub operation: OutboxOperation,
+    pub status: OutboxStatus,
+    /// How many times the action was sent
+    pub attempts: u32,
+    #[serde(rename = "lastError")]
+    pub last_error: Option<String>,
+    #[serde(rename = "queuedAt")]
+    pub queued_at: DateTime<Utc>,
+}
+
+/// The queued network-bound actions, kept across restarts and replayed in order
+/// once the connection comes back
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct Outbox {
+    items: Vec<OutboxItem>,
+    #[serde(skip)]
+    data_path: Option<PathBuf>,
+}
+
+impl Outbox {
+    /// Loads the outbox, starting empty if it could not be read
+    /// Completed actions are dropped, and the ones interrupted while running are pending again
+    pub fn load(path: PathBuf) -> Self {
+        let mut outbox: Self = FileService::read_or_default(&path, "outbox");
+        outbox
+            .items
+            .retain(|item| item.status != OutboxStatus::Completed);
+        for item in &mut outbox.items {
+            if item.status == OutboxStatus::Running {
+                item.status = OutboxStatus::Pending;
+            }
+        }
+        outbox.data_path = Some(path);
+        outbox
+    }
+
+    pub fn save(&self) {
+        if let Some(path) = &self.data_path {
+            FileService::write_json(path, self, "outbox");
+        }
+    }
+
+    pub fn items(&self) -> Vec<OutboxItem> {
+        self.items.clone()
+    }
+
+    /// Queues an action, unless the same action is already waiting to be sent
+    ///
+    /// # Returns
+    /// The queued item, or the one already waiting
+    pub fn enqueue(&mut self, operation: OutboxOperation, now: DateTime<Utc>) -> OutboxItem {
+        if let Some(item) = self.items.iter().find(|item| {
+            item.operation == operation
+                && matches!(item.status, OutboxStatus::Pending | OutboxStatus::Running)
+        }) {
+            return item.clone();
+        }
+
+        let item = OutboxItem {
+            id: Uuid::new_v4(),
+            operation,
+            status: OutboxStatus::Pending,
+            attempts: 0,
+            last_error: None,
+            queued_at: now,
+        };
+        self.items.push(item.clone());
+        self.save();
+        item
+    }
+
+    /// Get the oldest action waiting to be sent
+    ///
+    /// # Arguments
+    /// * `logged_in` - Whether the user is logged in to VRChat, otherwise
+    ///   only the actions that do not need VRChat are returned
+    pub fn next_pending(&self, logged_in: bool) -> Option<OutboxItem> {
+        self.items
+            .iter()
+            .filter(|item| logged_in || !item.operation.requires_login())
+            .find(|item| item.status == OutboxStatus::Pending)
+            .cloned()
+    }
+
+    /// Marks an action as being sent
+    pub fn start(&mut self, id: Uuid) -> Option<OutboxItem> {
+        self.update(id, |item| {
+            item.status = OutboxStatus::Running;
+            item.attempts += 1;
+        })
+    }
+
+    /// Records how sending an action ended
+    ///
+    /// # Arguments
+    /// * `id` - The ID of the item
+    /// * `status` - The new status, `Pending` if it is to be sent again later
+    /// * `error` - Why the action could not be sent, if it was not
+    pub fn finish(
+        &mut self,
+        id: Uuid,
+        status: OutboxStatus,
+        error: Option<String>,
+    ) -> Option<OutboxItem> {
+        self.update(id, |item| {
+            item.status = status;
+            item.last_error = error;
+        })
+    }
+
+    /// Queues a failed action again
+    pub fn retry(&mut self, id: Uuid) -> Option<OutboxItem> {
+        self.update(id, |item| {
+            if item.status == OutboxStatus::Failed {
+                item.status = OutboxStatus::Pending;
+            }
+        })
+    }
+
+    fn update(&mut self, id: Uuid, change: impl FnOnce(&mut OutboxItem)) -> Option<OutboxItem> {
+        let item = self.items.iter_mut().find(|item| item.id == id)?;
+        change(item);
+        let item = item.clone();
+        self.save();
+        Some(item)
+    }
+
+    /// Drops an action that is not being sent
+    ///
+    /// # Returns
+    /// Whether the action was dropped
+    pub fn remove(&mut self, id: Uuid) -> bool {
+        let before = self.items.len();
+        self.items
+            .retain(|item| item.id != id || item.status == OutboxStatus::Running);
+        let removed = self.items.len() != before;
+        if removed {
+            self.save();
+        }
+        removed
+    }
+
+    /// Drops the actions that were sent
+    pub fn clear_completed(&mut self) {
+        self.items
+            .retain(|item| item.status != OutboxStatus::Completed);
+        self.save();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn refresh(world_id: &str) -> OutboxOperation {
+        OutboxOperation::RefreshWorld {
+            world_id: world_id.to_string(),
+        }
+    }
+
+    #[test]
+    fn test_actions_are_queued_once_and_sent_in_order() {
+        let mut outbox = Outbox::default();
+        let first = outbox.enqueue(refresh("wrld_1"), Utc::now());
+        let second = outbox.enqueue(refresh("wrld_2"), Utc::now());
+        assert_eq!(outbox.enqueue(refresh("wrld_1"), Utc::now()).id, first.id);
+        assert_eq!(outbox.items().len(), 2);
+
+        assert_eq!(outbox.next_pending(true).unwrap().id, first.id);
+        outbox.start(first.id);
+        assert_eq!(outbox.next_pending(true).unwrap().id, second.id);
+        assert!(outbox.next_pending(false).is_none());
+
+        let failed = outbox
+            .finish(
+                first.id,
+                OutboxStatus::Failed,
+                Some("not found".to_string()),
+            )
+            .unwrap();
+        assert_eq!(failed.attempts, 1);
+        assert_eq!(failed.last_error.as_deref(), Some("not found"));
+
+        // A failed action can be queued again, by the user or by queueing it anew
+        assert_ne!(outbox.enqueue(refresh("wrld_1"), Utc::now()).id, first.id);
+        assert_eq!(
+            outbox.retry(first.id).unwrap().status,
+            OutboxStatus::Pending
+        );
+    }
+
+    #[test]
+    fn test_outbox_is_kept_across_restarts() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join("outbox.json");
+        let mut outbox = Outbox::load(path.clone());
+        let running = outbox.enqueue(refresh("wrld_1"), Utc::now());
+        let completed = outbox.enqueue(
+            OutboxOperation::ShareFolder {
+                folder_name: "Chill".to_string(),
+            },
+            Utc::now(),
+        );
+        outbox.start(running.id);
+        outbox.start(completed.id);
+        outbox.finish(completed.id, OutboxStatus::Completed, None);
+        assert!(!outbox.remove(running.id));
+
+        let outbox = Outbox::load(path);
+        let items = outbox.items();
+        assert_eq!(items.len(), 1);
+        assert_eq!(items[0].id, running.id);
+        assert_eq!(items[0].status, OutboxStatus::Pending);
+    }
+}
@@ -0,0 +1,286 @@
+use std::future::Future;
+use std::sync::Arc;
+use std::time::Duration;
+
+use chrono::Utc;
+use serde::Serialize;
+use tauri::AppHandle;
+use tauri_specta::Event;
+use uuid::Uuid;
+
+use crate::api::favorite;
+use crate::errors::{ApiError, AppError, ConcurrencyError};
+use crate::services::outbox::{Outbox, OutboxItem, OutboxOperation, OutboxStatus};
+use crate::services::{share_service, ApiService, FolderManager, RefreshService};
+use crate::task::cancellable_task::TaskContainer;
+use crate::task::definitions::TaskStatus;
+use crate::AppState;
+
+/// How long to wait before trying the outbox again, while offline or nobody is logged in
+const POLL_INTERVAL: Duration = Duration::from_secs(60);
+
+/// Pause between two queued actions, to spread them out
+const REQUEST_INTERVAL: Duration = Duration::from_secs(2);
+
+/// Emitted whenever a queued action is added, sent, or its status changes
+#[derive(Serialize, Clone, specta::Type, tauri_specta::Event)]
+pub struct OutboxItemChanged {
+    item: OutboxItem,
+}
+
+/// Performs the network-bound actions, or queues them in the outbox while offline
+///
+/// The outbox is replayed in order by a background task once VRChat and the share
+/// worker can be reached again, or right away when the user leaves offline mode.
+/// Actions the server rejects are marked as failed, and kept until the user retries
+/// or removes them.
+pub struct OutboxService;
+
+impl OutboxService {
+    /// Whether the user switched the app to offline mode
+    pub fn is_offline(state: &AppState) -> bool {
+        match state.preferences.read() {
+            Ok(preferences) => preferences.offline_mode,
+            Err(_) => {
+                log::error!("Failed to read offline mode preference");
+                false
+            }
+        }
+    }
+
+    /// Performs an action now, or queues it if the app is offline or the server
+    /// could not be reached
+    ///
+    /// # Arguments
+    /// * `state` - The app state holding the outbox
+    /// * `app_handle` - The app handle used to emit the outbox events
+    /// * `operation` - The action, as it is queued
+    /// * `action` - Performs the action now
+    ///
+    /// # Returns
+    /// The result of the action, or None if it was queued
+    ///
+    /// # Errors
+    /// Returns the error of the action if the server rejected it,
+    /// or an error if it could not be queued
+    pub async fn perform<T, F, Fut>(
+        state: &AppState,
+        app_handle: &AppHandle,
+        operation: OutboxOperation,
+        action: F,
+    ) -> Result<Option<T>, AppError>
+    where
+        F: FnOnce() -> Fut,
+        Fut: Future<Output = Result<T, AppError>>,
+    {
+        if !Self::is_offline(state) {
+            match action().await {
+                Ok(value) => return Ok(Some(value)),
+                Err(e) if e.is_unreachable() => {
+                    log::warn!("Server unreachable, queueing {:?}: {}", operation, e);
+                }
+                Err(e) => return Err(e),
+            }
+        }
+
+        Self::enqueue(state, app_handle, operation)?;
+        Ok(None)
+    }
+
+    /// Queues an action, and wakes the replay task
+    ///
+    /// # Errors
+    /// Returns an error if the outbox lock is poisoned
+    pub fn enqueue(
+        state: &AppState,
+        app_handle: &AppHandle,
+        operation: OutboxOperation,
+    ) -> Result<OutboxItem, AppError> {
+        let item = state
+            .outbox
+            .write()
+            .map_err(|_| ConcurrencyError::PoisonedLock)?
+            .enqueue(operation, Utc::now());
+        log::info!("Queued {:?} in the outbox", item.operation);
+        Self::emit(&item, app_handle);
+        state.outbox_wake.notify_one();
+        Ok(item)
+    }
+
+    /// Starts replaying the outbox in the background, unless it is already running,
+    /// in which case it is woken up to try the queued actions now
+    ///
+    /// # Arguments
+    /// * `state` - The app state holding the outbox
+    /// * `tasks` - The task container to run the replay in
+    /// * `app_handle` - The app handle used to emit the outbox events
+    ///
+    /// # Returns
+    /// The ID of the replay task
+    ///
+    /// # Errors
+    /// Returns an error if the task could not be started
+    pub async fn start(
+        state: &Arc<AppState>,
+        tasks: &mut TaskContainer,
+        app_handle: AppHandle,
+    ) -> Result<Uuid, String> {
+        let mut current = state.outbox_task.lock().await;
+        if let Some(id) = *current {
+            if let Some(task) = tasks.get(&id).await {
+                if task.lock().await.get_status().await == TaskStatus::Running {
+                    state.outbox_wake.notify_one();
+                    return Ok(id);
+                }
+            }
+        }
+
+        let id = tasks.run(Self::run(Arc::clone(state), app_handle))?;
+        log::info!("Started outbox replay: {}", id);
+        *current = Some(id);
+        Ok(id)
+    }
+
+    /// Replays the outbox whenever an action is queued, or the poll interval passes
+    async fn run(state: Arc<AppState>, app_handle: AppHandle) -> Result<(), String> {
+        loop {
+            Self::replay(&state, &app_handle).await;
+            // Either way, the outbox is tried again
+            let _ = tokio::time::timeout(POLL_INTERVAL, state.outbox_wake.notified()).await;
+        }
+    }
+
+    /// Sends the queued actions in order, until the outbox is empty
+    /// or the server can not be reached
+    async fn replay(state: &AppState, app_handle: &AppHandle) {
+        let mut sent = 0;
+        loop {
+            if Self::is_offline(state) {
+                return;
+            }
+            let logged_in = !state.user_id().await.is_empty()
+                && !state.api_context().await.session().is_expired();
+            let item = match state.outbox.read() {
+                Ok(outbox) => outbox.next_pending(logged_in),
+                Err(_) => {
+                    log::error!("Failed to acquire read lock for outbox");
+                    return;
+                }
+            };
+            let Some(item) = item else {
+                return;
+            };
+
+            if sent > 0 {
+                tokio::time::sleep(REQUEST_INTERVAL).await;
+            }
+            sent += 1;
+
+            Self::update(state, app_handle, |outbox| outbox.start(item.id));
+            let result = Self::execute(state, &item.operation).await;
+            let (status, error, stop) = match result {
+                Ok(()) => {
+                    log::info!("Sent queued {:?}", item.operation);
+                    (OutboxStatus::Completed, None, false)
+                }
+                Err(e) if e.is_unreachable() => (OutboxStatus::Pending, Some(e), true),
+                Err(
+                    e @ AppError::Api(
+                        ApiError::AuthenticationFailed | ApiError::RateLimitExceeded { .. },
+                    ),
+                ) => (OutboxStatus::Pending, Some(e), true),
+                Err(e) => {
+                    log::warn!("Queued {:?} was rejected: {}", item.operation, e);
+                    (OutboxStatus::Failed, Some(e), false)
+                }
+            };
+            Self::update(state, app_handle, |outbox| {
+                outbox.finish(item.id, status, error.map(|e| e.to_string()))
+            });
+            if stop {
+                log::info!("Outbox replay paused until the server can be reached");
+                return;
+            }
+        }
+    }
+
+    async fn execute(state: &AppState, operation: &OutboxOperation) -> Result<(), AppError> {
+        match operation {
+            OutboxOperation::AddFavorite { world_id, group } => {
+                Self::add_favorite(state, world_id, group).await
+            }
+            OutboxOperation::RefreshWorld { world_id } => {
+                Self::refresh_world(state, world_id).await
+            }
+            OutboxOperation::ShareFolder { folder_name } => {
+                Self::share_folder(state, folder_name).await.map(|_| ())
+            }
+        }
+    }
+
+    /// Favorites a world in one of the user's favorite groups
+    pub async fn add_favorite(
+        state: &AppState,
+        world_id: &str,
+        group: &str,
+    ) -> Result<(), AppError> {
+        let context = state.api_context().await;
+        favorite::add_world_favorite(&context, world_id, group).await?;
+        Ok(())
+    }
+
+    /// Fetches the latest data of a world, and saves it to the library
+    pub async fn refresh_world(state: &AppState, world_id: &str) -> Result<(), AppError> {
+        let context = state.api_context().await;
+        let lookup =
+            ApiService::lookup_world(world_id.to_string(), &context, state.user_id().await).await?;
+        RefreshService::save_lookup(state, world_id, lookup);
+        Ok(())
+    }
+
+    /// Uploads a folder to the share worker, and saves its share ID
+    ///
+    /// # Returns
+    /// The share ID of the folder
+    pub async fn share_folder(state: &AppState, folder_name: &str) -> Result<String, AppError> {
+        let (share_id, ts) = share_service::share_folder(
+            folder_name,
+            &state.folders,
+            &state.worlds,
+            &state.http_client(),
+        )
+        .await?;
+        FolderManager::set_folder_share(
+            folder_name.to_string(),
+            &state.folders,
+            share_id.clone(),
+            ts,
+            &*state.storage,
+        )?;
+        Ok(share_id)
+    }
+
+    /// Changes an item of the outbox, and emits the change
+    fn update(
+        state: &AppState,
+        app_handle: &AppHandle,
+        change: impl FnOnce(&mut Outbox) -> Option<OutboxItem>,
+    ) {
+        let item = match state.outbox.write() {
+            Ok(mut outbox) => change(&mut outbox),
+            Err(_) => {
+                log::error!("Failed to acquire write lock for outbox");
+                return;
+            }
+        };
+        if let Some(item) = item {
+            Self::emit(&item, app_handle);
+        }
+    }
+
+    pub fn emit(item: &OutboxItem, app_handle: &AppHandle) {
+        if let Err(e) = (OutboxItemChanged { item: item.clone() }).emit(app_handle) {
+            log::error!("Failed to emit OutboxItemChanged event: {}", e);
+        }
+    }
+}
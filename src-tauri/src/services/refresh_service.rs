@@ -0,0 +1,283 @@
+use std::sync::{Arc, RwLock};
+use std::time::Duration;
+
+use serde::Serialize;
+use tauri::AppHandle;
+use tauri_specta::Event;
+use uuid::Uuid;
+
+use crate::api::should_backoff;
+use crate::definitions::{WorldAvailability, WorldModel};
+use crate::errors::{ApiError, AppError, ConcurrencyError};
+use crate::services::{ApiService, FolderManager, WorldLookup};
+use crate::task::cancellable_task::TaskContainer;
+use crate::task::definitions::TaskStatus;
+use crate::AppState;
+
+/// The rate limited operation used to fetch a single world
+const WORLD_OPERATION: &str = "get_world_by_id";
+
+/// How long to wait before checking again while nobody is logged in, or while offline
+const LOGIN_POLL_INTERVAL: Duration = Duration::from_secs(30);
+
+/// Pause between two requests of a batch, to spread them out
+const REQUEST_INTERVAL: Duration = Duration::from_secs(2);
+
+/// Progress of the background world refresh, emitted after every world
+#[derive(Serialize, Clone, specta::Type, tauri_specta::Event)]
+pub struct WorldRefreshProgress {
+    /// Worlds refreshed in the current batch
+    refreshed: u32,
+    /// Worlds which could not be fetched in the current batch
+    failed: u32,
+    /// Stale worlds left, including the rest of the current batch
+    remaining: u32,
+    /// Set while the refresh is waiting for a rate limit backoff to end
+    waiting_seconds: Option<u32>,
+}
+
+/// Keeps the metadata of the library up to date in the background
+///
+/// Every batch refreshes the worlds whose `last_checked` is the oldest, and the
+/// new `last_checked` is persisted with each world. A refresh interrupted by a
+/// restart therefore continues with the worlds it had not reached yet.
+pub struct RefreshService;
+
+impl RefreshService {
+    /// Starts the background refresh, unless it is already running
+    ///
+    /// # Arguments
+    /// * `state` - The app state holding the library to refresh
+    /// * `tasks` - The task container to run the refresh in
+    /// * `app_handle` - The app handle used to emit progress events
+    ///
+    /// # Returns
+    /// The ID of the refresh task
+    ///
+    /// # Errors
+    /// Returns an error if the task could not be started
+    pub async fn start(
+        state: &Arc<AppState>,
+        tasks: &mut TaskContainer,
+        app_handle: AppHandle,
+    ) -> Result<Uuid, String> {
+        let mut current = state.refresh_task.lock().await;
+        if let Some(id) = *current {
+            if let Some(task) = tasks.get(&id).await {
+                if task.lock().await.get_status().await == TaskStatus::Running {
+                    return Ok(id);
+                }
+            }
+        }
+
+        let id = tasks.run(Self::run(Arc::clone(state), Some(app_handle)))?;
+        log::info!("Started background world refresh: {}", id);
+        *current = Some(id);
+        Ok(id)
+    }
+
+    /// Refreshes stale worlds in batches until the task is cancelled
+    async fn run(state: Arc<AppState>, app_handle: Option<AppHandle>) -> Result<(), String> {
+        loop {
+            let wait = Self::run_batch(&state, app_handle.as_ref()).await;
+            tokio::time::sleep(wait).await;
+        }
+    }
+
+    /// Refreshes one batch of stale worlds
+    ///
+    /// # Returns
+    /// How long to wait before the next batch
+    async fn run_batch(state: &AppState, app_handle: Option<&AppHandle>) -> Duration {
+        let user_id = state.user_id().await;
+        if user_id.is_empty() {
+            return LOGIN_POLL_INTERVAL;
+        }
+
+        let (interval, batch_size) = match state.preferences.read() {
+            Ok(preferences) if preferences.offline_mode => return LOGIN_POLL_INTERVAL,
+            Ok(preferences) => (
+                Duration::from_secs(preferences.refresh_interval_minutes.max(1) as u64 * 60),
+                preferences.refresh_batch_size.max(1) as usize,
+            ),
+            Err(_) => {
+                log::error!("Failed to read refresh preferences");
+                return LOGIN_POLL_INTERVAL;
+            }
+        };
+
+        let (batch, total) = match Self::stale_world_ids(&state.worlds, batch_size) {
+            Ok(stale) => stale,
+            Err(e) => {
+                log::error!("Failed to find stale worlds: {}", e);
+                return interval;
+            }
+        };
+        if batch.is_empty() {
+            return interval;
+        }
+        log::info!("Refreshing {} of {} stale worlds", batch.len(), total);
+
+        let context = state.api_context().await.bulk();
+        let mut progress = WorldRefreshProgress {
+            refreshed: 0,
+            failed: 0,
+            remaining: total as u32,
+            waiting_seconds: None,
+        };
+
+        for (index, world_id) in batch.into_iter().enumerate() {
+            if index > 0 {
+                tokio::time::sleep(REQUEST_INTERVAL).await;
+            }
+            if context.session().is_expired() {
+                // Wait for the session to be verified again, or for the next login
+                return LOGIN_POLL_INTERVAL;
+            }
+            if let Some(backoff_ms) = should_backoff(context.rate_limits(), WORLD_OPERATION) {
+                return Self::wait_for_backoff(progress, backoff_ms, app_handle);
+            }
+
+            match ApiService::lookup_world(world_id.clone(), &context, user_id.clone()).await {
+                Ok(lookup) => {
+                    Self::save_lookup(state, &world_id, lookup);
+                    progress.refreshed += 1;
+                }
+                Err(AppError::Api(ApiError::AuthenticationFailed)) => {
+                    // Every other world would fail the same way until the user logs in again
+                    log::warn!("World refresh paused, the session is no longer valid");
+                    return LOGIN_POLL_INTERVAL;
+                }
+                Err(e) => {
+                    // A rate limited world stays stale, and is the first to be retried
+                    if let Some(backoff_ms) = should_backoff(context.rate_limits(), WORLD_OPERATION)
+                    {
+                        return Self::wait_for_backoff(progress, backoff_ms, app_handle);
+                    }
+                    log::warn!("Failed to refresh world {}: {}", world_id, e);
+                    if let Err(e) = FolderManager::mark_worlds_checked(
+                        vec![world_id],
+                        &state.worlds,
+                        &*state.storage,
+                    ) {
+                        log::error!("Failed to mark world as checked: {}", e);
+                    }
+                    progress.failed += 1;
+                }
+            }
+            progress.remaining -= 1;
+            Self::emit(&progress, app_handle);
+        }
+
+        interval
+    }
+
+    /// Saves the result of looking a world up: its new data, and whether it is still available
+    ///
+    /// # Arguments
+    /// * `state` - The app state holding the library
+    /// * `world_id` - The ID of the world that was looked up
+    /// * `lookup` - The result of the lookup
+    pub fn save_lookup(state: &AppState, world_id: &str, lookup: WorldLookup) {
+        let availability = match lookup {
+            WorldLookup::Available(world) => {
+                if let Err(e) =
+                    FolderManager::add_worlds(&state.worlds, vec![*world], &*state.storage)
+                {
+                    log::error!("Failed to save refreshed world {}: {}", world_id, e);
+                }
+                WorldAvailability::Available
+            }
+            WorldLookup::Private => WorldAvailability::Private,
+            WorldLookup::Deleted => WorldAvailability::Deleted,
+        };
+        if let Err(e) = FolderManager::set_world_availability(
+            vec![world_id.to_string()],
+            availability,
+            &state.worlds,
+            &*state.storage,
+        ) {
+            log::error!("Failed to save availability of {}: {}", world_id, e);
+        }
+    }
+
+    fn wait_for_backoff(
+        mut progress: WorldRefreshProgress,
+        backoff_ms: u64,
+        app_handle: Option<&AppHandle>,
+    ) -> Duration {
+        log::info!("World refresh paused for {}ms by rate limit", backoff_ms);
+        progress.waiting_seconds = Some((backoff_ms / 1000) as u32 + 1);
+        Self::emit(&progress, app_handle);
+        Duration::from_millis(backoff_ms)
+    }
+
+    fn emit(progress: &WorldRefreshProgress, app_handle: Option<&AppHandle>) {
+        if let Some(app_handle) = app_handle {
+            if let Err(e) = progress.emit(app_handle) {
+                log::error!("Failed to emit WorldRefreshProgress event: {}", e);
+            }
+        }
+    }
+
+    /// Get the stale worlds to refresh next, least recently checked first
+    ///
+    /// # Arguments
+    /// * `worlds` - The list of worlds, as a RwLock
+    /// * `limit` - The maximum number of worlds to return
+    ///
+    /// # Returns
+    /// The IDs of up to `limit` stale worlds, and the total number of stale worlds
+    ///
+    /// # Errors
+    /// Returns an error if the worlds lock is poisoned
+    pub fn stale_world_ids(
+        worlds: &RwLock<Vec<WorldModel>>,
+        limit: usize,
+    ) -> Result<(Vec<String>, usize), AppError> {
+        let worlds_lock = worlds.read().map_err(|_| ConcurrencyError::PoisonedLock)?;
+        let mut stale: Vec<&WorldModel> = worlds_lock
+            .iter()
+            .filter(|w| w.user_data.needs_update())
+            .collect();
+        stale.sort_by_key(|w| w.user_data.last_checked);
+
+        let total = stale.len();
+        let ids = stale
+            .into_iter()
+            .take(limit)
+            .map(|w| w.api_data.world_id.clone())
+            .collect();
+        Ok((ids, total))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::services::test_support;
+    use chrono::Utc;
+
+    fn test_world(world_id: &str, hours_since_check: i64) -> WorldModel {
+        let mut world = test_support::test_world(world_id);
+        world.user_data.last_checked = Utc::now() - chrono::Duration::hours(hours_since_check);
+        world
+    }
+
+    #[test]
+    fn test_stale_worlds_are_oldest_first() {
+        let worlds = RwLock::new(vec![
+            test_world("wrld_fresh", 1),
+            test_world("wrld_old", 10),
+            test_world("wrld_oldest", 48),
+            test_world("wrld_stale", 5),
+        ]);
+
+        let (ids, total) = RefreshService::stale_world_ids(&worlds, 2).unwrap();
+        assert_eq!(ids, vec!["wrld_oldest", "wrld_old"]);
+        assert_eq!(total, 3);
+
+        let (ids, _) = RefreshService::stale_world_ids(&worlds, 10).unwrap();
+        assert_eq!(ids, vec!["wrld_oldest", "wrld_old", "wrld_stale"]);
+    }
+}
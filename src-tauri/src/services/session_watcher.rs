@@ -0,0 +1,93 @@
+use std::sync::Arc;
+
+use serde::Serialize;
+use tauri::AppHandle;
+use tauri_specta::Event;
+use uuid::Uuid;
+
+use crate::api::auth::VRChatAuthStatus;
+use crate::api::SessionMonitor;
+use crate::task::cancellable_task::TaskContainer;
+use crate::AppState;
+
+/// Emitted when VRChat rejected the session and it could not be verified again,
+/// so the user has to log in again
+#[derive(Serialize, Clone, specta::Type, tauri_specta::Event)]
+pub struct SessionExpired {
+    user_id: String,
+}
+
+/// Verifies the session again once VRChat rejects it on any endpoint
+///
+/// A rejected request can be a hiccup of VRChat, so the token is verified once before
+/// the user is logged out. The background jobs pause while nobody is logged in.
+pub struct SessionWatcher;
+
+impl SessionWatcher {
+    /// Starts waiting for the session to be rejected
+    ///
+    /// # Arguments
+    /// * `state` - The app state holding the authenticator and the init state
+    /// * `tasks` - The task container to run the watcher in
+    /// * `app_handle` - The app handle used to emit the expiry event
+    ///
+    /// # Returns
+    /// The ID of the watcher task
+    ///
+    /// # Errors
+    /// Returns an error if the task could not be started
+    pub async fn start(
+        state: &Arc<AppState>,
+        tasks: &mut TaskContainer,
+        app_handle: AppHandle,
+    ) -> Result<Uuid, String> {
+        let context = state.api_context().await;
+        let id = tasks.run(Self::run(
+            Arc::clone(state),
+            Arc::clone(context.session()),
+            app_handle,
+        ))?;
+        log::info!("Started session watcher: {}", id);
+        Ok(id)
+    }
+
+    async fn run(
+        state: Arc<AppState>,
+        session: Arc<SessionMonitor>,
+        app_handle: AppHandle,
+    ) -> Result<(), String> {
+        loop {
+            session.expired().await;
+            Self::verify(&state, &session, &app_handle).await;
+        }
+    }
+
+    async fn verify(state: &AppState, session: &SessionMonitor, app_handle: &AppHandle) {
+        let user_id = state.user_id().await;
+        if user_id.is_empty() {
+            // Nobody is logged in, the next login marks the session as valid
+            return;
+        }
+
+        log::info!("Verifying the session of {} again", user_id);
+        let status = state.authenticator.write().await.verify_token().await;
+        match status {
+            Ok(VRChatAuthStatus::Success(_, _)) => {
+                log::info!("The session is still valid");
+                return;
+            }
+            Ok(status) => log::warn!("The session has expired: {:?}", status),
+            Err(e) => {
+                // VRChat could not be reached, so the session is checked again on the next 401
+                log::error!("Failed to verify the session: {}", e);
+                session.mark_valid();
+                return;
+            }
+        }
+
+        state.init_state.write().await.user_id = String::new();
+        if let Err(e) = (SessionExpired { user_id }).emit(app_handle) {
+            log::error!("Failed to emit SessionExpired event: {}", e);
+        }
+    }
+}
@@ -1,11 +1,11 @@
 use crate::definitions::{FolderModel, WorldApiData, WorldModel};
+use crate::errors::{ApiError, AppError, ConcurrencyError, EntityError, NetworkError, StateError};
+use crate::services::FolderManager;
 use chrono::Utc;
 use hex;
 use hmac::{Hmac, Mac};
-use reqwest::Client;
 use serde::{Deserialize, Serialize};
 use sha2::Sha256;
-use std::env;
 use std::sync::RwLock;
 
 /// The shape of the share response
@@ -67,13 +67,22 @@ fn get_worlds(
         .read()
         .map_err(|_| "Failed to read worlds".to_string())?;
 
-    let mut world_data = Vec::new();
-    for folder in folders.iter() {
-        if folder.folder_name == name {
-            for world_id in &folder.world_ids {
-                if let Some(world) = worlds.iter().find(|w| w.api_data.world_id == *world_id) {
-                    world_data.push(world.api_data.clone());
-                }
+    // A shared folder is received as a single flat folder, so the worlds of its
+    // subfolders are included after its own
+    let folder_names: Vec<String> = std::iter::once(name.to_string())
+        .chain(FolderManager::descendant_names(name, &folders))
+        .collect();
+    let mut world_data: Vec<WorldApiData> = Vec::new();
+    for folder in folder_names
+        .iter()
+        .filter_map(|folder_name| folders.iter().find(|f| &f.folder_name == folder_name))
+    {
+        for world_id in FolderManager::resolve_world_ids(folder, &worlds) {
+            if world_data.iter().any(|w| w.world_id == world_id) {
+                continue;
+            }
+            if let Some(world) = worlds.iter().find(|w| w.api_data.world_id == world_id) {
+                world_data.push(world.api_data.clone());
             }
         }
     }
@@ -99,16 +108,25 @@ fn get_worlds(
 }
 
 // returns id and the ts for setting the expires_at field
-async fn post_folder(name: &str, worlds: &[WorldApiData]) -> Result<(String, String), String> {
+async fn post_folder(
+    name: &str,
+    worlds: &[WorldApiData],
+    client: &reqwest::Client,
+) -> Result<(String, String), AppError> {
     let api_url = "https://folder-sharing-worker.raifaworks.workers.dev";
 
     let ts: String = Utc::now().to_rfc3339();
     let signing = SigningPayload { name, worlds };
-    let data_str = serde_json::to_string(&signing).map_err(|e| e.to_string())?;
+    let data_str = serde_json::to_string(&signing).map_err(|e| {
+        log::error!("Failed to serialize shared folder: {}", e);
+        ApiError::InvalidRequest("Failed to serialize shared folder")
+    })?;
 
-    let hmac = compute_hmac(&data_str).map_err(|e| format!("Failed to compute HMAC: {}", e))?;
+    let hmac = compute_hmac(&data_str).map_err(|e| {
+        log::error!("Failed to compute HMAC: {}", e);
+        StateError::InvalidOperation("Failed to sign shared folder")
+    })?;
 
-    let client = Client::new();
     let full_url = format!("{}/api/share/folder", api_url);
 
     let req = ShareRequestPayload {
@@ -122,69 +140,120 @@ async fn post_folder(name: &str, worlds: &[WorldApiData]) -> Result<(String, Str
         .json(&req)
         .send()
         .await
-        .map_err(|e| e.to_string())?;
+        .map_err(|e| {
+            log::error!("Failed to reach share worker: {}", e);
+            if e.is_timeout() {
+                NetworkError::Timeout
+            } else {
+                NetworkError::ConnectionFailed
+            }
+        })?;
 
     let status = res.status();
+    if status.is_server_error() {
+        return Err(NetworkError::HttpError(status.as_u16()).into());
+    }
     if !status.is_success() {
         let txt = res.text().await.unwrap_or_default();
-        return Err(format!("Share failed: {} – {}", status, txt));
+        return Err(ApiError::ResponseError(format!("Share failed: {} – {}", status, txt)).into());
     }
 
-    let body: ShareResponse = res.json().await.map_err(|e| e.to_string())?;
+    let body: ShareResponse = res.json().await.map_err(|e| {
+        log::error!("Failed to parse share response: {}", e);
+        NetworkError::InvalidResponse
+    })?;
     Ok((body.id, ts))
 }
 
 /// Share the folder with the remote Worker
+///
+/// # Errors
+/// Returns `AppError::Network` if the worker could not be reached,
+/// so the share can be queued and retried later
 pub async fn share_folder(
     name: &str,
     folders_lock: &RwLock<Vec<FolderModel>>,
     worlds_lock: &RwLock<Vec<WorldModel>>,
-) -> Result<(String, String), String> {
+    client: &reqwest::Client,
+) -> Result<(String, String), AppError> {
     // 1) Load worlds from the specified folder
-    let worlds = get_worlds(name, folders_lock, worlds_lock)
-        .map_err(|e| format!("Failed to get worlds: {}", e))?;
+    let worlds = get_worlds(name, folders_lock, worlds_lock).map_err(|e| {
+        log::error!("Failed to get worlds: {}", e);
+        ConcurrencyError::PoisonedLock
+    })?;
 
     if worlds.is_empty() {
-        return Err("No worlds found in the specified folder".to_string());
+        return Err(EntityError::InvalidOperation(
+            "No worlds found in the specified folder".to_string(),
+        )
+        .into());
     }
 
-    // 2) Post the folder
-    post_folder(name, &worlds)
-        .await
-        .map_err(|e| format!("Failed to post folder: {}", e))
+    // 2) Post the folder, under its own name rather than its path
+    post_folder(FolderManager::name_in_path(name), &worlds, client).await
 }
 
-pub async fn download_folder(share_id: &str) -> Result<(String, Vec<WorldApiData>), String> {
+/// Download a shared folder from the remote Worker
+///
+/// # Errors
+/// Returns `AppError::Network` if the worker could not be reached
+/// Returns `AppError::Api` if the share is not found, or fails verification
+pub async fn download_folder(
+    share_id: &str,
+    client: &reqwest::Client,
+) -> Result<(String, Vec<WorldApiData>), AppError> {
     let api_url = "https://folder-sharing-worker.raifaworks.workers.dev";
     let full_url = format!("{}/api/share/folder/{}", api_url, share_id);
 
-    let client = Client::new();
-    let res = client
-        .get(&full_url)
-        .send()
-        .await
-        .map_err(|e| format!("Failed to download folder: {}", e))?;
+    let res = client.get(&full_url).send().await.map_err(|e| {
+        log::error!("Failed to reach share worker: {}", e);
+        if e.is_timeout() {
+            NetworkError::Timeout
+        } else {
+            NetworkError::ConnectionFailed
+        }
+    })?;
 
     let status = res.status();
+    if status.is_server_error() {
+        return Err(NetworkError::HttpError(status.as_u16()).into());
+    }
+    if status.as_u16() == 404 {
+        return Err(ApiError::NotFound.into());
+    }
     if !status.is_success() {
         let txt = res.text().await.unwrap_or_default();
-        return Err(format!("Download failed: {} – {}", status, txt));
+        return Err(
+            ApiError::ResponseError(format!("Download failed: {} – {}", status, txt)).into(),
+        );
     }
 
-    let folder: ShareRequest = res.json().await.map_err(|e| e.to_string())?;
+    let folder: ShareRequest = res.json().await.map_err(|e| {
+        log::error!("Failed to parse shared folder: {}", e);
+        NetworkError::InvalidResponse
+    })?;
     // Validate the HMAC
     let signing = SigningPayload {
         name: &folder.name,
         worlds: &folder.worlds,
     };
-    let data_str = serde_json::to_string(&signing).map_err(|e| e.to_string())?;
-    let expected_hmac =
-        compute_hmac(&data_str).map_err(|e| format!("Failed to compute HMAC: {}", e))?;
+    let data_str = serde_json::to_string(&signing).map_err(|e| {
+        log::error!("Failed to serialize shared folder: {}", e);
+        NetworkError::InvalidResponse
+    })?;
+    let expected_hmac = compute_hmac(&data_str).map_err(|e| {
+        log::error!("Failed to compute HMAC: {}", e);
+        StateError::InvalidOperation("Failed to verify shared folder")
+    })?;
     if expected_hmac != folder.hmac {
-        return Err(format!(
+        log::error!(
             "HMAC mismatch: expected {}, got {}",
-            expected_hmac, folder.hmac
-        ));
+            expected_hmac,
+            folder.hmac
+        );
+        return Err(
+            ApiError::ResponseError("Shared folder failed verification".to_string()).into(),
+        );
     }
 
     // Return the folder name and worlds
@@ -195,10 +264,10 @@ pub async fn download_folder(share_id: &str) -> Result<(String, Vec<WorldApiData
 #[cfg(test)]
 mod integration_tests {
     use super::post_folder;
+    use crate::api::HttpClientFactory;
     use crate::definitions::{Platform, WorldApiData};
-    use serde_json::Value;
+
     use std::env;
-    use std::fs;
 
     /// Build a minimal WorldApiData for testing
     fn dummy_world() -> WorldApiData {
@@ -235,7 +304,7 @@ mod integration_tests {
         // 1) POST the folder
         let worlds = vec![dummy_world()];
         let folder_name = "IntegrationTestFolder";
-        let (id, _ts) = post_folder(folder_name, &worlds)
+        let (id, _ts) = post_folder(folder_name, &worlds, &HttpClientFactory::default().client())
             .await
             .expect("post_folder failed");
         assert!(!id.is_empty(), "received empty share ID");
@@ -246,9 +315,9 @@ mod integration_tests {
     async fn integration_no_worlds_error() {
         let _ = env::var("HMAC_KEY").expect("export HMAC_KEY for integration test");
         // posting with empty worlds should error early
-        let err = post_folder("EmptyFolder", &[])
+        let err = post_folder("EmptyFolder", &[], &HttpClientFactory::default().client())
             .await
             .expect_err("expected error for no worlds");
-        assert!(err.contains("Failed to post folder"), "got: {}", err);
+        assert!(err.to_string().contains("Share failed"), "got: {}", err);
     }
 }
@@ -120,8 +120,8 @@ impl SortingService {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::definitions::{Platform, WorldApiData, WorldUserData};
-    use chrono::{NaiveDate, NaiveDateTime, NaiveTime, Utc};
+    use crate::definitions::{Platform, WorldApiData, WorldAvailability, WorldUserData};
+    use chrono::Utc;
 
     fn create_test_world_model(
         id: &str,
@@ -161,6 +161,9 @@ mod tests {
                 folders: vec![],
                 hidden: false,
                 custom_tags: vec![],
+                history: vec![],
+                availability: WorldAvailability::Unknown,
+                last_seen: None,
             },
         }
     }
@@ -188,6 +191,7 @@ mod tests {
             folders: vec![],
             tags: vec![],
             capacity,
+            availability: WorldAvailability::Available,
         }
     }
 
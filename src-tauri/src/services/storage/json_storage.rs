@@ -0,0 +1,118 @@
+use super::{Changes, Storage};
+use crate::definitions::{FolderModel, WorldModel};
+use crate::errors::FileError;
+use crate::services::FileService;
+use serde::Serialize;
+use std::collections::HashMap;
+use std::ffi::OsString;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Stores the library as pretty-printed JSON files
+///
+/// Every write rewrites the whole file, so `Changes` are ignored.
+pub struct JsonStorage {
+    folders_path: PathBuf,
+    worlds_path: PathBuf,
+    memo_path: PathBuf,
+}
+
+impl JsonStorage {
+    /// Creates a storage for the JSON files in the given directory
+    ///
+    /// # Arguments
+    /// * `dir` - The directory containing folders.json, worlds.json and memo.json
+    pub fn new(dir: &Path) -> Self {
+        Self {
+            folders_path: dir.join("folders.json"),
+            worlds_path: dir.join("worlds.json"),
+            memo_path: dir.join("memo.json"),
+        }
+    }
+
+    /// Checks whether there is a library to import
+    pub fn has_library(&self) -> bool {
+        self.folders_path.exists() && self.worlds_path.exists()
+    }
+
+    /// Renames the JSON files once their contents have been imported elsewhere,
+    /// so they are not picked up again
+    pub fn mark_migrated(&self) {
+        for path in [&self.folders_path, &self.worlds_path, &self.memo_path] {
+            if let Err(e) = Self::rename_migrated(path) {
+                log::warn!("Failed to rename migrated file {:?}: {}", path, e);
+            }
+        }
+    }
+
+    /// Moves a file out of the way by appending .migrated to its name
+    fn rename_migrated(path: &Path) -> std::io::Result<()> {
+        if !path.exists() {
+            return Ok(());
+        }
+        let mut migrated = OsString::from(path.as_os_str());
+        migrated.push(".migrated");
+        fs::rename(path, PathBuf::from(migrated))
+    }
+
+    fn write_json<T: Serialize + ?Sized>(path: &PathBuf, value: &T) -> Result<(), FileError> {
+        let data = serde_json::to_string_pretty(value).map_err(|e| FileError::InvalidFile {
+            line: None,
+            column: None,
+            file_name: Some(path.to_string_lossy().to_string()),
+            error_message: e.to_string(),
+        })?;
+        FileService::atomic_write(path, &data)
+    }
+}
+
+impl Storage for JsonStorage {
+    fn load_folders(&self) -> Result<Vec<FolderModel>, FileError> {
+        FileService::read_file(&self.folders_path)
+    }
+
+    fn load_worlds(&self) -> Result<Vec<WorldModel>, FileError> {
+        FileService::read_file(&self.worlds_path)
+    }
+
+    fn load_memos(&self) -> Result<HashMap<String, String>, FileError> {
+        if !self.memo_path.exists() {
+            return Ok(HashMap::new());
+        }
+        FileService::read_file(&self.memo_path)
+    }
+
+    fn write_folders(&self, folders: &[FolderModel], _changes: Changes) -> Result<(), FileError> {
+        Self::write_json(&self.folders_path, folders)
+    }
+
+    fn write_worlds(&self, worlds: &[WorldModel], _changes: Changes) -> Result<(), FileError> {
+        Self::write_json(&self.worlds_path, worlds)
+    }
+
+    /// Keeps memo.json around as memo.json.migrated instead of deleting it
+    fn clear_memos(&self) -> Result<(), FileError> {
+        Self::rename_migrated(&self.memo_path).map_err(|_| FileError::FileWriteError)
+    }
+
+    /// Uses fs::write instead of atomic_write, as there is no existing data to protect
+    fn create_empty(&self) -> Result<(), FileError> {
+        for path in [&self.folders_path, &self.worlds_path] {
+            if !path.exists() {
+                fs::write(path, "[]").map_err(|_| FileError::FileWriteError)?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Uses fs::write instead of atomic_write, as the data is being deleted on purpose
+    fn clear(&self) -> Result<(), FileError> {
+        fs::write(&self.folders_path, "[]").map_err(|_| FileError::FileWriteError)?;
+        fs::write(&self.worlds_path, "[]").map_err(|_| FileError::FileWriteError)?;
+        Ok(())
+    }
+
+    fn existing_data(&self) -> Result<(bool, bool), FileError> {
+        Ok((self.folders_path.exists(), self.worlds_path.exists()))
+    }
+}
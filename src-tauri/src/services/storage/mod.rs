@@ -0,0 +1,245 @@
+mod json_storage;
+mod sqlite_storage;
+
+pub use json_storage::JsonStorage;
+pub use sqlite_storage::SqliteStorage;
+
+use crate::definitions::{FolderModel, WorldModel};
+use crate::errors::FileError;
+use crate::services::FileService;
+use std::collections::{HashMap, HashSet};
+
+/// File name of the library database inside the application directory
+const DATABASE_FILE_NAME: &str = "library.db";
+
+/// Describes which entries of a collection were touched by a mutation
+///
+/// Backends that can write single entries use this to avoid rewriting the
+/// whole collection. Backends that can't simply persist the full collection.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Changes {
+    /// The collection was replaced or reordered as a whole
+    All,
+    /// The entries with these keys were added or modified
+    Upserted(Vec<String>),
+    /// The entries with these keys were removed
+    Removed(Vec<String>),
+}
+
+/// Persistent storage for the library: folders and worlds
+///
+/// Worlds are keyed by their world ID and folders by their name. Every write
+/// receives the full in-memory collection along with the `Changes` that were
+/// made to it.
+///
+/// Memos are part of `WorldUserData`. Older versions kept them in a separate
+/// store keyed by world ID, which is only read to merge it into the worlds.
+pub trait Storage: Send + Sync {
+    /// Loads all folders, in their display order
+    fn load_folders(&self) -> Result<Vec<FolderModel>, FileError>;
+
+    /// Loads all worlds, in the order they were added
+    fn load_worlds(&self) -> Result<Vec<WorldModel>, FileError>;
+
+    /// Loads the memos kept apart from the worlds by older versions, keyed by world ID
+    fn load_memos(&self) -> Result<HashMap<String, String>, FileError>;
+
+    /// Persists changes made to the folders
+    ///
+    /// # Arguments
+    /// * `folders` - The full list of folders after the change
+    /// * `changes` - The folders that were changed
+    fn write_folders(&self, folders: &[FolderModel], changes: Changes) -> Result<(), FileError>;
+
+    /// Persists changes made to the worlds
+    ///
+    /// # Arguments
+    /// * `worlds` - The full list of worlds after the change
+    /// * `changes` - The worlds that were changed
+    fn write_worlds(&self, worlds: &[WorldModel], changes: Changes) -> Result<(), FileError>;
+
+    /// Removes the memos kept apart from the worlds, once they have been merged
+    fn clear_memos(&self) -> Result<(), FileError>;
+
+    /// Creates an empty library if none exists yet
+    fn create_empty(&self) -> Result<(), FileError>;
+
+    /// Deletes all folders and worlds
+    fn clear(&self) -> Result<(), FileError>;
+
+    /// Checks whether any library data has been stored
+    ///
+    /// # Returns
+    /// Returns whether folders and worlds exist, in that order
+    fn existing_data(&self) -> Result<(bool, bool), FileError>;
+}
+
+/// Opens the library storage in the application directory
+///
+/// The SQLite database is used by default. The first time it is opened, the
+/// existing JSON library is imported into it. If the database can't be opened
+/// or the import fails, the JSON files are used as before.
+///
+/// # Returns
+/// Returns the storage to use for the rest of the session
+pub fn open_storage() -> Box<dyn Storage> {
+    let app_dir = FileService::get_app_dir();
+    let json = JsonStorage::new(&app_dir);
+
+    let sqlite = match SqliteStorage::open(&app_dir.join(DATABASE_FILE_NAME)) {
+        Ok(sqlite) => sqlite,
+        Err(e) => {
+            log::error!("Failed to open library database, using JSON files: {}", e);
+            return Box::new(json);
+        }
+    };
+
+    match sqlite.migrate_from_json(&json) {
+        Ok(true) => {
+            log::info!("Migrated JSON library into the library database");
+            json.mark_migrated();
+        }
+        Ok(false) => {}
+        Err(e) => {
+            log::error!("Failed to migrate JSON library, using JSON files: {}", e);
+            return Box::new(json);
+        }
+    }
+
+    Box::new(sqlite)
+}
+
+/// Loads the folders and worlds of the library
+/// Populates the folder list of each world, removes duplicate platforms left
+/// behind by older versions, and merges separately stored memos into the worlds
+///
+/// # Arguments
+/// * `storage` - The storage to load from
+///
+/// # Returns
+/// Returns the folders and worlds
+///
+/// # Errors
+/// Returns a FileError if either collection could not be read
+pub fn load_library(
+    storage: &dyn Storage,
+) -> Result<(Vec<FolderModel>, Vec<WorldModel>), FileError> {
+    let folders = storage.load_folders()?;
+    let mut worlds = storage.load_worlds()?;
+
+    let mut changed = Vec::new();
+    for world in worlds.iter_mut() {
+        world.user_data.folders = folders
+            .iter()
+            .filter(|folder| folder.world_ids.contains(&world.api_data.world_id))
+            .map(|folder| folder.folder_name.clone())
+            .collect();
+
+        let mut seen = HashSet::new();
+        let platform_count = world.api_data.platform.len();
+        world
+            .api_data
+            .platform
+            .retain(|plat| seen.insert(plat.clone()));
+        if world.api_data.platform.len() != platform_count {
+            changed.push(world.api_data.world_id.clone());
+        }
+    }
+
+    let memos = match storage.load_memos() {
+        Ok(memos) => memos,
+        Err(e) => {
+            log::error!("Failed to load separately stored memos: {}", e);
+            HashMap::new()
+        }
+    };
+    let merged = merge_memos(&mut worlds, &memos);
+    for world_id in merged {
+        if !changed.contains(&world_id) {
+            changed.push(world_id);
+        }
+    }
+
+    if !changed.is_empty() {
+        if let Err(e) = storage.write_worlds(&worlds, Changes::Upserted(changed)) {
+            log::error!("Failed to persist updated worlds: {}", e);
+            return Ok((folders, worlds));
+        }
+    }
+    if !memos.is_empty() {
+        if let Err(e) = storage.clear_memos() {
+            log::error!("Failed to remove merged memos: {}", e);
+        }
+    }
+
+    Ok((folders, worlds))
+}
+
+/// Copies separately stored memos into the worlds they belong to
+/// These memos are the ones shown and edited by older versions, so they replace
+/// the memo stored in the world
+///
+/// # Arguments
+/// * `worlds` - The worlds to merge the memos into
+/// * `memos` - The memos, keyed by world ID
+///
+/// # Returns
+/// Returns the IDs of the worlds whose memo changed
+fn merge_memos(worlds: &mut [WorldModel], memos: &HashMap<String, String>) -> Vec<String> {
+    let mut merged = Vec::new();
+    for world in worlds.iter_mut() {
+        if let Some(memo) = memos.get(&world.api_data.world_id) {
+            if !memo.is_empty() && memo != &world.user_data.memo {
+                world.user_data.memo = memo.clone();
+                merged.push(world.api_data.world_id.clone());
+            }
+        }
+    }
+
+    let orphaned = memos
+        .keys()
+        .filter(|id| !worlds.iter().any(|w| &w.api_data.world_id == *id))
+        .count();
+    if orphaned > 0 {
+        log::warn!("Dropping {} memos of worlds not in the library", orphaned);
+    }
+    merged
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::services::test_support;
+    use std::fs;
+    use tempfile::TempDir;
+
+    fn test_world(world_id: &str, memo: &str) -> WorldModel {
+        let mut world = test_support::test_world(world_id);
+        world.user_data.memo = memo.to_string();
+        world
+    }
+
+    #[test]
+    fn test_load_library_merges_separate_memos() {
+        let temp = TempDir::new().unwrap();
+        let storage = JsonStorage::new(temp.path());
+        let worlds = vec![test_world("wrld_1", ""), test_world("wrld_2", "kept")];
+        storage.write_folders(&[], Changes::All).unwrap();
+        storage.write_worlds(&worlds, Changes::All).unwrap();
+        fs::write(
+            temp.path().join("memo.json"),
+            r#"{"wrld_1": "from memo.json", "wrld_3": "orphaned"}"#,
+        )
+        .unwrap();
+
+        let (_, worlds) = load_library(&storage).unwrap();
+        assert_eq!(worlds[0].user_data.memo, "from memo.json");
+        assert_eq!(worlds[1].user_data.memo, "kept");
+
+        // The merged memos are persisted with the worlds, and the old store is moved aside
+        assert!(!temp.path().join("memo.json").exists());
+        assert!(temp.path().join("memo.json.migrated").exists());
+        let (_, reloaded) = load_library(&storage).unwrap();
+        assert_eq!(reloaded[0].user_data.memo, "from memo.json");
+    }
+}
@@ -0,0 +1,490 @@
+use super::{Changes, JsonStorage, Storage};
+use crate::definitions::{FolderModel, WorldModel};
+use crate::errors::FileError;
+use rusqlite::{params, Connection, OptionalExtension};
+use serde::{de::DeserializeOwned, Serialize};
+use std::collections::HashMap;
+use std::path::Path;
+use std::sync::{Mutex, MutexGuard};
+
+/// Meta key recording that the JSON library has already been imported
+const JSON_MIGRATED_KEY: &str = "json_migrated";
+
+const SCHEMA: &str = "
+    CREATE TABLE IF NOT EXISTS folders (
+        key TEXT PRIMARY KEY,
+        position INTEGER NOT NULL,
+        data TEXT NOT NULL
+    );
+    CREATE TABLE IF NOT EXISTS worlds (
+        key TEXT PRIMARY KEY,
+        position INTEGER NOT NULL,
+        data TEXT NOT NULL
+    );
+    CREATE TABLE IF NOT EXISTS memos (
+        key TEXT PRIMARY KEY,
+        position INTEGER NOT NULL,
+        data TEXT NOT NULL
+    );
+    CREATE TABLE IF NOT EXISTS meta (
+        key TEXT PRIMARY KEY,
+        value TEXT NOT NULL
+    );
+";
+
+/// The tables holding library entries
+///
+/// Every table has the same layout: the key of the entry, its position in the
+/// collection, and the entry itself serialized as JSON.
+#[derive(Debug, Clone, Copy)]
+enum Table {
+    Folders,
+    Worlds,
+    Memos,
+}
+
+impl Table {
+    fn name(self) -> &'static str {
+        match self {
+            Table::Folders => "folders",
+            Table::Worlds => "worlds",
+            Table::Memos => "memos",
+        }
+    }
+}
+
+fn db_error(e: rusqlite::Error) -> FileError {
+    FileError::DatabaseError(e.to_string())
+}
+
+fn encode<T: Serialize>(value: &T) -> Result<String, FileError> {
+    serde_json::to_string(value).map_err(|e| FileError::DatabaseError(e.to_string()))
+}
+
+fn decode<T: DeserializeOwned>(table: Table, data: &str) -> Result<T, FileError> {
+    serde_json::from_str(data).map_err(|e| FileError::InvalidFile {
+        line: Some(e.line()),
+        column: Some(e.column()),
+        file_name: Some(format!("library database ({})", table.name())),
+        error_message: e.to_string(),
+    })
+}
+
+/// Stores the library in an embedded SQLite database
+///
+/// Each world, folder and memo is a row of its own, so a mutation only writes
+/// the entries named in its `Changes`. New entries are appended after the
+/// existing ones; only `Changes::All` reorders a collection.
+pub struct SqliteStorage {
+    connection: Mutex<Connection>,
+}
+
+impl SqliteStorage {
+    /// Opens the database at the given path, creating it if needed
+    ///
+    /// # Arguments
+    /// * `path` - The path to the database file
+    ///
+    /// # Errors
+    /// Returns a FileError if the database could not be opened or initialized
+    pub fn open(path: &Path) -> Result<Self, FileError> {
+        let connection = Connection::open(path).map_err(db_error)?;
+        Self::from_connection(connection)
+    }
+
+    #[cfg(test)]
+    pub fn open_in_memory() -> Result<Self, FileError> {
+        let connection = Connection::open_in_memory().map_err(db_error)?;
+        Self::from_connection(connection)
+    }
+
+    fn from_connection(connection: Connection) -> Result<Self, FileError> {
+        connection
+            .pragma_update_and_check(None, "journal_mode", "WAL", |_| Ok(()))
+            .map_err(db_error)?;
+        connection
+            .pragma_update(None, "synchronous", "NORMAL")
+            .map_err(db_error)?;
+        connection.execute_batch(SCHEMA).map_err(db_error)?;
+
+        Ok(Self {
+            connection: Mutex::new(connection),
+        })
+    }
+
+    fn lock(&self) -> Result<MutexGuard<'_, Connection>, FileError> {
+        self.connection
+            .lock()
+            .map_err(|_| FileError::DatabaseError("database lock was poisoned".to_string()))
+    }
+
+    /// Imports the JSON library, unless it has been imported before
+    /// Folders, worlds and memos are imported in a single transaction
+    ///
+    /// # Arguments
+    /// * `json` - The JSON library to import
+    ///
+    /// # Returns
+    /// Returns true if a library was imported
+    ///
+    /// # Errors
+    /// Returns a FileError if the JSON files could not be read, or the database could not be written
+    pub fn migrate_from_json(&self, json: &JsonStorage) -> Result<bool, FileError> {
+        let mut connection = self.lock()?;
+        let migrated: Option<String> = connection
+            .query_row(
+                "SELECT value FROM meta WHERE key = ?1",
+                params![JSON_MIGRATED_KEY],
+                |row| row.get(0),
+            )
+            .optional()
+            .map_err(db_error)?;
+        if migrated.is_some() {
+            return Ok(false);
+        }
+
+        let imported = json.has_library();
+        let tx = connection.transaction().map_err(db_error)?;
+        if imported {
+            let folders = json.load_folders()?;
+            let worlds = json.load_worlds()?;
+            let memos = json.load_memos()?;
+            log::info!(
+                "Importing {} folders, {} worlds and {} memos from JSON",
+                folders.len(),
+                worlds.len(),
+                memos.len()
+            );
+
+            Self::replace_rows(&tx, Table::Folders, &folders, |f| &f.folder_name)?;
+            Self::replace_rows(&tx, Table::Worlds, &worlds, |w| &w.api_data.world_id)?;
+            Self::replace_memos(&tx, &memos)?;
+        }
+        tx.execute(
+            "INSERT OR REPLACE INTO meta (key, value) VALUES (?1, ?2)",
+            params![JSON_MIGRATED_KEY, chrono::Utc::now().to_rfc3339()],
+        )
+        .map_err(db_error)?;
+        tx.commit().map_err(db_error)?;
+
+        Ok(imported)
+    }
+
+    fn load_rows<T: DeserializeOwned>(&self, table: Table) -> Result<Vec<T>, FileError> {
+        let connection = self.lock()?;
+        let mut statement = connection
+            .prepare(&format!(
+                "SELECT data FROM {} ORDER BY position, rowid",
+                table.name()
+            ))
+            .map_err(db_error)?;
+        let rows = statement
+            .query_map([], |row| row.get::<_, String>(0))
+            .map_err(db_error)?;
+
+        let mut items = Vec::new();
+        for data in rows {
+            items.push(decode(table, &data.map_err(db_error)?)?);
+        }
+        Ok(items)
+    }
+
+    fn replace_rows<T: Serialize>(
+        connection: &Connection,
+        table: Table,
+        items: &[T],
+        key: impl Fn(&T) -> &str,
+    ) -> Result<(), FileError> {
+        connection
+            .execute(&format!("DELETE FROM {}", table.name()), [])
+            .map_err(db_error)?;
+        let mut insert = connection
+            .prepare(&format!(
+                "INSERT OR REPLACE INTO {} (key, position, data) VALUES (?1, ?2, ?3)",
+                table.name()
+            ))
+            .map_err(db_error)?;
+        for (position, item) in items.iter().enumerate() {
+            insert
+                .execute(params![key(item), position as i64, encode(item)?])
+                .map_err(db_error)?;
+        }
+        Ok(())
+    }
+
+    fn replace_memos(
+        connection: &Connection,
+        memos: &HashMap<String, String>,
+    ) -> Result<(), FileError> {
+        let entries: Vec<(&String, &String)> = memos.iter().collect();
+        Self::replace_rows(connection, Table::Memos, &entries, |(id, _)| id.as_str())
+    }
+
+    /// Writes the given entries, appending new ones after the existing entries
+    /// Keys which are no longer present in `items` are deleted
+    fn upsert_rows<T: Serialize>(
+        connection: &Connection,
+        table: Table,
+        items: &[T],
+        key: impl Fn(&T) -> &str,
+        keys: &[String],
+    ) -> Result<(), FileError> {
+        let mut upsert = connection
+            .prepare(&format!(
+                "INSERT INTO {0} (key, position, data)
+                 VALUES (?1, (SELECT COALESCE(MAX(position) + 1, 0) FROM {0}), ?2)
+                 ON CONFLICT(key) DO UPDATE SET data = excluded.data",
+                table.name()
+            ))
+            .map_err(db_error)?;
+        let by_key: HashMap<&str, &T> = items.iter().map(|item| (key(item), item)).collect();
+        let mut removed = Vec::new();
+        for changed in keys {
+            match by_key.get(changed.as_str()) {
+                Some(item) => {
+                    upsert
+                        .execute(params![changed, encode(item)?])
+                        .map_err(db_error)?;
+                }
+                None => removed.push(changed.clone()),
+            }
+        }
+        Self::delete_rows(connection, table, &removed)
+    }
+
+    fn delete_rows(
+        connection: &Connection,
+        table: Table,
+        keys: &[String],
+    ) -> Result<(), FileError> {
+        let mut delete = connection
+            .prepare(&format!("DELETE FROM {} WHERE key = ?1", table.name()))
+            .map_err(db_error)?;
+        for key in keys {
+            delete.execute(params![key]).map_err(db_error)?;
+        }
+        Ok(())
+    }
+
+    fn write_rows<T: Serialize>(
+        &self,
+        table: Table,
+        items: &[T],
+        key: impl Fn(&T) -> &str,
+        changes: Changes,
+    ) -> Result<(), FileError> {
+        let mut connection = self.lock()?;
+        let tx = connection.transaction().map_err(db_error)?;
+        match changes {
+            Changes::All => Self::replace_rows(&tx, table, items, key)?,
+            Changes::Upserted(keys) => Self::upsert_rows(&tx, table, items, key, &keys)?,
+            Changes::Removed(keys) => Self::delete_rows(&tx, table, &keys)?,
+        }
+        tx.commit().map_err(db_error)
+    }
+
+    fn has_rows(&self, table: Table) -> Result<bool, FileError> {
+        let connection = self.lock()?;
+        connection
+            .query_row(
+                &format!("SELECT EXISTS (SELECT 1 FROM {})", table.name()),
+                [],
+                |row| row.get(0),
+            )
+            .map_err(db_error)
+    }
+}
+
+impl Storage for SqliteStorage {
+    fn load_folders(&self) -> Result<Vec<FolderModel>, FileError> {
+        self.load_rows(Table::Folders)
+    }
+
+    fn load_worlds(&self) -> Result<Vec<WorldModel>, FileError> {
+        self.load_rows(Table::Worlds)
+    }
+
+    fn load_memos(&self) -> Result<HashMap<String, String>, FileError> {
+        let entries: Vec<(String, String)> = self.load_rows(Table::Memos)?;
+        Ok(entries.into_iter().collect())
+    }
+
+    fn write_folders(&self, folders: &[FolderModel], changes: Changes) -> Result<(), FileError> {
+        self.write_rows(Table::Folders, folders, |f| &f.folder_name, changes)
+    }
+
+    fn write_worlds(&self, worlds: &[WorldModel], changes: Changes) -> Result<(), FileError> {
+        self.write_rows(Table::Worlds, worlds, |w| &w.api_data.world_id, changes)
+    }
+
+    fn clear_memos(&self) -> Result<(), FileError> {
+        let connection = self.lock()?;
+        connection
+            .execute("DELETE FROM memos", [])
+            .map(|_| ())
+            .map_err(db_error)
+    }
+
+    /// The schema is created when the database is opened, so there is nothing to do
+    fn create_empty(&self) -> Result<(), FileError> {
+        Ok(())
+    }
+
+    fn clear(&self) -> Result<(), FileError> {
+        let connection = self.lock()?;
+        connection
+            .execute_batch("DELETE FROM folders; DELETE FROM worlds;")
+            .map_err(db_error)
+    }
+
+    fn existing_data(&self) -> Result<(bool, bool), FileError> {
+        Ok((
+            self.has_rows(Table::Folders)?,
+            self.has_rows(Table::Worlds)?,
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::services::test_support::test_world;
+    use std::fs;
+    use tempfile::TempDir;
+
+    fn folder_names(storage: &SqliteStorage) -> Vec<String> {
+        storage
+            .load_folders()
+            .unwrap()
+            .into_iter()
+            .map(|f| f.folder_name)
+            .collect()
+    }
+
+    #[test]
+    fn test_write_all_preserves_order() {
+        let storage = SqliteStorage::open_in_memory().unwrap();
+        let folders = vec![
+            FolderModel::new("B".to_string()),
+            FolderModel::new("A".to_string()),
+            FolderModel::new("C".to_string()),
+        ];
+
+        storage.write_folders(&folders, Changes::All).unwrap();
+        assert_eq!(folder_names(&storage), vec!["B", "A", "C"]);
+
+        let reordered = vec![folders[2].clone(), folders[0].clone()];
+        storage.write_folders(&reordered, Changes::All).unwrap();
+        assert_eq!(folder_names(&storage), vec!["C", "B"]);
+    }
+
+    #[test]
+    fn test_upsert_updates_in_place_and_appends_new_entries() {
+        let storage = SqliteStorage::open_in_memory().unwrap();
+        let mut worlds = vec![test_world("wrld_1"), test_world("wrld_2")];
+        storage.write_worlds(&worlds, Changes::All).unwrap();
+
+        worlds[0].user_data.hidden = true;
+        worlds.push(test_world("wrld_3"));
+        storage
+            .write_worlds(
+                &worlds,
+                Changes::Upserted(vec!["wrld_3".to_string(), "wrld_1".to_string()]),
+            )
+            .unwrap();
+
+        let loaded = storage.load_worlds().unwrap();
+        let ids: Vec<&str> = loaded
+            .iter()
+            .map(|w| w.api_data.world_id.as_str())
+            .collect();
+        assert_eq!(ids, vec!["wrld_1", "wrld_2", "wrld_3"]);
+        assert!(loaded[0].user_data.hidden);
+    }
+
+    #[test]
+    fn test_removed_and_missing_upserts_are_deleted() {
+        let storage = SqliteStorage::open_in_memory().unwrap();
+        let worlds = vec![
+            test_world("wrld_1"),
+            test_world("wrld_2"),
+            test_world("wrld_3"),
+        ];
+        storage.write_worlds(&worlds, Changes::All).unwrap();
+
+        storage
+            .write_worlds(&worlds, Changes::Removed(vec!["wrld_2".to_string()]))
+            .unwrap();
+        storage
+            .write_worlds(&worlds[..2], Changes::Upserted(vec!["wrld_3".to_string()]))
+            .unwrap();
+
+        let loaded = storage.load_worlds().unwrap();
+        assert_eq!(loaded.len(), 1);
+        assert_eq!(loaded[0].api_data.world_id, "wrld_1");
+    }
+
+    #[test]
+    fn test_clear_and_existing_data() {
+        let storage = SqliteStorage::open_in_memory().unwrap();
+        assert_eq!(storage.existing_data().unwrap(), (false, false));
+
+        storage
+            .write_folders(&[FolderModel::new("A".to_string())], Changes::All)
+            .unwrap();
+        storage
+            .write_worlds(&[test_world("wrld_1")], Changes::All)
+            .unwrap();
+        assert_eq!(storage.existing_data().unwrap(), (true, true));
+
+        storage.clear().unwrap();
+        assert_eq!(storage.existing_data().unwrap(), (false, false));
+    }
+
+    #[test]
+    fn test_migrate_from_json_runs_once() {
+        let temp = TempDir::new().unwrap();
+        let json = JsonStorage::new(temp.path());
+        let mut folder = FolderModel::new("Favorites".to_string());
+        folder.world_ids.push("wrld_1".to_string());
+        let mut memos = HashMap::new();
+        memos.insert("wrld_1".to_string(), "memo".to_string());
+        json.write_folders(&[folder], Changes::All).unwrap();
+        json.write_worlds(&[test_world("wrld_1")], Changes::All)
+            .unwrap();
+        fs::write(
+            temp.path().join("memo.json"),
+            serde_json::to_string(&memos).unwrap(),
+        )
+        .unwrap();
+
+        let storage = SqliteStorage::open(&temp.path().join("library.db")).unwrap();
+        assert!(storage.migrate_from_json(&json).unwrap());
+
+        assert_eq!(folder_names(&storage), vec!["Favorites"]);
+        assert_eq!(storage.load_worlds().unwrap().len(), 1);
+        assert_eq!(storage.load_memos().unwrap(), memos);
+        storage.clear_memos().unwrap();
+        assert!(storage.load_memos().unwrap().is_empty());
+
+        // Later changes to the JSON files are not imported again
+        json.write_worlds(&[], Changes::All).unwrap();
+        assert!(!storage.migrate_from_json(&json).unwrap());
+        assert_eq!(storage.load_worlds().unwrap().len(), 1);
+
+        json.mark_migrated();
+        assert!(!json.has_library());
+        assert!(temp.path().join("worlds.json.migrated").exists());
+        assert!(fs::read_to_string(temp.path().join("memo.json.migrated")).is_ok());
+    }
+
+    #[test]
+    fn test_migrate_without_json_library() {
+        let temp = TempDir::new().unwrap();
+        let json = JsonStorage::new(temp.path());
+        let storage = SqliteStorage::open_in_memory().unwrap();
+
+        assert!(!storage.migrate_from_json(&json).unwrap());
+        assert_eq!(storage.existing_data().unwrap(), (false, false));
+    }
+}
@@ -0,0 +1,27 @@
+use crate::definitions::{Platform, WorldApiData, WorldModel};
+
+/// Creates a world for tests, with placeholder data that tests change as needed
+///
+/// # Arguments
+/// * `world_id` - The ID of the world
+///
+/// # Returns
+/// A PC world, last updated now, that is in no folder
+pub fn test_world(world_id: &str) -> WorldModel {
+    WorldModel::new(WorldApiData {
+        world_id: world_id.to_string(),
+        world_name: "Test World".to_string(),
+        description: "Test Description".to_string(),
+        author_name: "Test Author".to_string(),
+        author_id: "test_author".to_string(),
+        tags: vec![],
+        publication_date: None,
+        last_update: chrono::Utc::now(),
+        image_url: "".to_string(),
+        capacity: 0,
+        recommended_capacity: None,
+        visits: None,
+        favorites: 0,
+        platform: vec![Platform::StandaloneWindows],
+    })
+}
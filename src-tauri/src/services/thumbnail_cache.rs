@@ -0,0 +1,434 @@
+use std::collections::HashMap;
+use std::fs;
+use std::io::Cursor;
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use chrono::{DateTime, Utc};
+use image::codecs::jpeg::JpegEncoder;
+use image::DynamicImage;
+use serde::{Deserialize, Serialize};
+
+use crate::definitions::WorldApiData;
+use crate::errors::{ApiError, AppError, ConcurrencyError, FileError, NetworkError, StateError};
+use crate::services::FileService;
+
+/// The URI scheme the webview loads thumbnails from, e.g. `thumbnail://localhost/wrld_...`
+pub const THUMBNAIL_SCHEME: &str = "thumbnail";
+
+/// The most disk space the thumbnails take, the least recently shown ones are dropped first
+pub const BUDGET_BYTES: u64 = 200 * 1024 * 1024;
+
+/// The largest size a thumbnail is stored at, enough for the world cards
+const MAX_WIDTH: u32 = 640;
+const MAX_HEIGHT: u32 = 480;
+const JPEG_QUALITY: u8 = 85;
+
+/// How long to wait between the downloads of a prefetch
+const PREFETCH_DELAY_MS: u64 = 500;
+
+const INDEX_FILE: &str = "index.json";
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct ThumbnailEntry {
+    /// The image URL the thumbnail was made from, a world with a new image is downloaded again
+    #[serde(rename = "sourceUrl")]
+    source_url: String,
+    #[serde(rename = "sizeBytes")]
+    size_bytes: u64,
+    #[serde(rename = "lastUsed")]
+    last_used: DateTime<Utc>,
+}
+
+/// How much the thumbnail cache holds
+#[derive(Debug, Clone, Serialize, specta::Type)]
+pub struct ThumbnailCacheStats {
+    entries: u32,
+    #[serde(rename = "sizeBytes")]
+    size_bytes: u32,
+    #[serde(rename = "budgetBytes")]
+    budget_bytes: u32,
+}
+
+/// Keeps resized world thumbnails in the app data directory, keyed by world ID,
+/// so the world cards render without downloading the images again, even offline
+///
+/// The thumbnails are served to the webview through the `thumbnail` URI scheme.
+/// When they grow past the budget, the least recently shown ones are dropped.
+#[derive(Debug)]
+pub struct ThumbnailCache {
+    dir: PathBuf,
+    budget_bytes: u64,
+    entries: Mutex<HashMap<String, ThumbnailEntry>>,
+}
+
+impl ThumbnailCache {
+    /// Loads the index of the thumbnails in a directory, starting empty if it could not be read
+    ///
+    /// # Arguments
+    /// * `dir` - The directory the thumbnails are stored in
+    /// * `budget_bytes` - The most disk space the thumbnails take
+    ///
+    /// # Returns
+    /// The cache, without the entries whose file is missing
+    pub fn load(dir: PathBuf, budget_bytes: u64) -> Self {
+        let mut entries: HashMap<String, ThumbnailEntry> =
+            FileService::read_or_default(&dir.join(INDEX_FILE), "thumbnail index");
+        entries.retain(|world_id, _| dir.join(file_name(world_id)).exists());
+
+        Self {
+            dir,
+            budget_bytes,
+            entries: Mutex::new(entries),
+        }
+    }
+
+    fn save(&self, entries: &HashMap<String, ThumbnailEntry>) {
+        FileService::write_json(&self.dir.join(INDEX_FILE), entries, "thumbnail index");
+    }
+
+    /// Get a stored thumbnail, marking it as recently shown
+    ///
+    /// # Arguments
+    /// * `world_id` - The ID of the world
+    /// * `source_url` - The current image URL of the world, if known;
+    ///   a thumbnail made from another image is not returned
+    ///
+    /// # Returns
+    /// The JPEG bytes of the thumbnail, or None if it is not stored
+    pub fn get(&self, world_id: &str, source_url: Option<&str>) -> Option<Vec<u8>> {
+        let mut entries = self.entries.lock().unwrap_or_else(|e| e.into_inner());
+        let entry = entries.get_mut(world_id)?;
+        if source_url.is_some_and(|url| url != entry.source_url) {
+            return None;
+        }
+
+        match fs::read(self.dir.join(file_name(world_id))) {
+            Ok(bytes) => {
+                // Only kept in memory until the index is saved with the next change
+                entry.last_used = Utc::now();
+                Some(bytes)
+            }
+            Err(e) => {
+                log::warn!("Failed to read thumbnail of {}: {}", world_id, e);
+                entries.remove(world_id);
+                None
+            }
+        }
+    }
+
+    /// Stores a resized image as the thumbnail of a world,
+    /// dropping the least recently shown thumbnails beyond the budget
+    ///
+    /// # Returns
+    /// The JPEG bytes of the thumbnail
+    ///
+    /// # Errors
+    /// Returns an error if the thumbnail could not be written
+    fn insert(
+        &self,
+        world_id: &str,
+        source_url: &str,
+        thumbnail: Vec<u8>,
+    ) -> Result<Vec<u8>, AppError> {
+        fs::create_dir_all(&self.dir).map_err(|e| {
+            log::error!("Failed to create directory for thumbnails: {}", e);
+            FileError::FileWriteError
+        })?;
+        fs::write(self.dir.join(file_name(world_id)), &thumbnail).map_err(|e| {
+            log::error!("Failed to write thumbnail of {}: {}", world_id, e);
+            FileError::FileWriteError
+        })?;
+
+        let mut entries = self
+            .entries
+            .lock()
+            .map_err(|_| ConcurrencyError::PoisonedLock)?;
+        entries.insert(
+            world_id.to_string(),
+            ThumbnailEntry {
+                source_url: source_url.to_string(),
+                size_bytes: thumbnail.len() as u64,
+                last_used: Utc::now(),
+            },
+        );
+        self.evict(&mut entries);
+        self.save(&entries);
+        Ok(thumbnail)
+    }
+
+    /// Drops the least recently shown thumbnails until the rest fit in the budget
+    fn evict(&self, entries: &mut HashMap<String, ThumbnailEntry>) {
+        let mut total: u64 = entries.values().map(|e| e.size_bytes).sum();
+        if total <= self.budget_bytes {
+            return;
+        }
+
+        let mut used: Vec<(String, DateTime<Utc>, u64)> = entries
+            .iter()
+            .map(|(id, e)| (id.clone(), e.last_used, e.size_bytes))
+            .collect();
+        used.sort_by_key(|(_, last_used, _)| *last_used);
+        for (world_id, _, size_bytes) in used {
+            if total <= self.budget_bytes {
+                break;
+            }
+            if let Err(e) = fs::remove_file(self.dir.join(file_name(&world_id))) {
+                log::warn!("Failed to delete thumbnail of {}: {}", world_id, e);
+            }
+            entries.remove(&world_id);
+            total -= size_bytes;
+        }
+    }
+
+    /// Get the thumbnail of a world, downloading it if it is not stored
+    ///
+    /// # Arguments
+    /// * `world_id` - The ID of the world
+    /// * `source_url` - The image URL of the world
+    /// * `client` - The client to download the image with
+    ///
+    /// # Returns
+    /// The JPEG bytes of the thumbnail
+    ///
+    /// # Errors
+    /// Returns an error if the image could not be downloaded or stored
+    pub async fn fetch(
+        &self,
+        world_id: &str,
+        source_url: &str,
+        client: &reqwest::Client,
+    ) -> Result<Vec<u8>, AppError> {
+        if let Some(thumbnail) = self.get(world_id, Some(source_url)) {
+            return Ok(thumbnail);
+        }
+
+        log::info!("Downloading thumbnail of {}", world_id);
+        let response = client.get(source_url).send().await.map_err(|e| {
+            log::error!("Failed to download thumbnail of {}: {}", world_id, e);
+            if e.is_timeout() {
+                NetworkError::Timeout
+            } else {
+                NetworkError::ConnectionFailed
+            }
+        })?;
+        let status = response.status();
+        if !status.is_success() {
+            log::warn!("Thumbnail of {} returned {}", world_id, status);
+            return Err(if status.as_u16() == 404 {
+                ApiError::NotFound.into()
+            } else {
+                NetworkError::HttpError(status.as_u16()).into()
+            });
+        }
+        let image = response.bytes().await.map_err(|e| {
+            log::error!("Failed to read thumbnail of {}: {}", world_id, e);
+            NetworkError::InvalidResponse
+        })?;
+
+        // Decoding and scaling the image takes a while, so it is kept off the async runtime
+        let thumbnail = tauri::async_runtime::spawn_blocking(move || resize(&image))
+            .await
+            .map_err(|e| {
+                log::error!("Failed to resize thumbnail of {}: {}", world_id, e);
+                StateError::InvalidOperation("thumbnail resize task failed")
+            })??;
+        self.insert(world_id, source_url, thumbnail)
+    }
+
+    /// Downloads the thumbnails of newly added worlds in the background,
+    /// one at a time, skipping the ones already stored
+    ///
+    /// # Arguments
+    /// * `worlds` - The worlds that were added
+    /// * `client` - The client to download the images with
+    pub fn prefetch(self: &Arc<Self>, worlds: &[WorldApiData], client: reqwest::Client) {
+        let missing: Vec<(String, String)> = worlds
+            .iter()
+            .filter(|w| !w.image_url.is_empty() && is_valid_world_id(&w.world_id))
+            .filter(|w| !self.contains(&w.world_id, &w.image_url))
+            .map(|w| (w.world_id.clone(), w.image_url.clone()))
+            .collect();
+        if missing.is_empty() {
+            return;
+        }
+
+        log::info!("Prefetching {} thumbnails", missing.len());
+        let cache = Arc::clone(self);
+        tauri::async_runtime::spawn(async move {
+            for (world_id, source_url) in missing {
+                if let Err(e) = cache.fetch(&world_id, &source_url, &client).await {
+                    log::warn!("Failed to prefetch thumbnail of {}: {}", world_id, e);
+                }
+                tokio::time::sleep(Duration::from_millis(PREFETCH_DELAY_MS)).await;
+            }
+        });
+    }
+
+    fn contains(&self, world_id: &str, source_url: &str) -> bool {
+        let entries = self.entries.lock().unwrap_or_else(|e| e.into_inner());
+        entries
+            .get(world_id)
+            .is_some_and(|e| e.source_url == source_url)
+    }
+
+    /// Drops every thumbnail
+    ///
+    /// # Errors
+    /// Returns an error if the entries lock is poisoned
+    pub fn clear(&self) -> Result<(), AppError> {
+        let mut entries = self
+            .entries
+            .lock()
+            .map_err(|_| ConcurrencyError::PoisonedLock)?;
+        for world_id in entries.keys() {
+            if let Err(e) = fs::remove_file(self.dir.join(file_name(world_id))) {
+                log::warn!("Failed to delete thumbnail of {}: {}", world_id, e);
+            }
+        }
+        entries.clear();
+        self.save(&entries);
+        log::info!("Thumbnail cache cleared");
+        Ok(())
+    }
+
+    pub fn stats(&self) -> ThumbnailCacheStats {
+        let entries = self.entries.lock().unwrap_or_else(|e| e.into_inner());
+        ThumbnailCacheStats {
+            entries: entries.len() as u32,
+            size_bytes: entries.values().map(|e| e.size_bytes).sum::<u64>() as u32,
+            budget_bytes: self.budget_bytes as u32,
+        }
+    }
+}
+
+/// Whether a world ID is safe to use as a file name
+/// The IDs come from the webview through the URI scheme, so they are never trusted
+pub fn is_valid_world_id(world_id: &str) -> bool {
+    world_id.starts_with("wrld_")
+        && world_id
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-')
+}
+
+/// Whether an image URL passed in by the webview may be downloaded
+/// Only images hosted by VRChat are accepted
+pub fn is_valid_source_url(source_url: &str) -> bool {
+    let Ok(url) = reqwest::Url::parse(source_url) else {
+        return false;
+    };
+    url.scheme() == "https"
+        && url.host_str().is_some_and(|host| {
+            ["vrchat.cloud", "vrchat.com"]
+                .iter()
+                .any(|domain| host == *domain || host.ends_with(&format!(".{}", domain)))
+        })
+}
+
+fn file_name(world_id: &str) -> String {
+    format!("{}.jpg", world_id)
+}
+
+/// Scales an image down to fit the thumbnail size, and encodes it as JPEG
+fn resize(image: &[u8]) -> Result<Vec<u8>, AppError> {
+    let image = image::load_from_memory(image).map_err(|e| {
+        log::error!("Failed to decode thumbnail: {}", e);
+        NetworkError::InvalidResponse
+    })?;
+    let image = if image.width() > MAX_WIDTH || image.height() > MAX_HEIGHT {
+        image.thumbnail(MAX_WIDTH, MAX_HEIGHT)
+    } else {
+        image
+    };
+
+    let mut thumbnail = Cursor::new(vec![]);
+    DynamicImage::ImageRgb8(image.to_rgb8())
+        .write_with_encoder(JpegEncoder::new_with_quality(&mut thumbnail, JPEG_QUALITY))
+        .map_err(|e| {
+            log::error!("Failed to encode thumbnail: {}", e);
+            FileError::FileWriteError
+        })?;
+    Ok(thumbnail.into_inner())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use image::{ImageFormat, RgbImage};
+    use tempfile::TempDir;
+
+    fn png(width: u32, height: u32) -> Vec<u8> {
+        let mut bytes = Cursor::new(vec![]);
+        RgbImage::new(width, height)
+            .write_to(&mut bytes, ImageFormat::Png)
+            .unwrap();
+        bytes.into_inner()
+    }
+
+    #[test]
+    fn test_images_are_resized_and_kept_across_restarts() {
+        let dir = TempDir::new().unwrap();
+        let cache = ThumbnailCache::load(dir.path().to_path_buf(), BUDGET_BYTES);
+
+        let thumbnail = cache
+            .insert("wrld_1", "https://a/1", resize(&png(1200, 900)).unwrap())
+            .unwrap();
+        let image = image::load_from_memory(&thumbnail).unwrap();
+        assert_eq!((image.width(), image.height()), (MAX_WIDTH, MAX_HEIGHT));
+
+        let cache = ThumbnailCache::load(dir.path().to_path_buf(), BUDGET_BYTES);
+        assert_eq!(cache.get("wrld_1", Some("https://a/1")), Some(thumbnail));
+        // The world got a new image, so the thumbnail is outdated
+        assert_eq!(cache.get("wrld_1", Some("https://a/2")), None);
+        assert!(cache.get("wrld_1", None).is_some());
+    }
+
+    #[test]
+    fn test_least_recently_shown_thumbnails_are_dropped_beyond_budget() {
+        let dir = TempDir::new().unwrap();
+        let size = resize(&png(64, 48)).unwrap().len() as u64;
+        let cache = ThumbnailCache::load(dir.path().to_path_buf(), size * 2);
+
+        cache
+            .insert("wrld_1", "https://a/1", resize(&png(64, 48)).unwrap())
+            .unwrap();
+        cache
+            .insert("wrld_2", "https://a/2", resize(&png(64, 48)).unwrap())
+            .unwrap();
+        assert!(cache.get("wrld_1", None).is_some());
+        cache
+            .insert("wrld_3", "https://a/3", resize(&png(64, 48)).unwrap())
+            .unwrap();
+
+        assert!(cache.get("wrld_1", None).is_some());
+        assert!(cache.get("wrld_2", None).is_none());
+        assert!(!dir.path().join("wrld_2.jpg").exists());
+        assert_eq!(cache.stats().entries, 2);
+
+        cache.clear().unwrap();
+        assert_eq!(cache.stats().entries, 0);
+        assert!(!dir.path().join("wrld_1.jpg").exists());
+    }
+
+    #[test]
+    fn test_world_ids_are_checked_before_use_as_file_names() {
+        assert!(is_valid_world_id(
+            "wrld_4e4683f3-2717-4a31-9aab-37f78ec68426"
+        ));
+        assert!(!is_valid_world_id("wrld_../index"));
+        assert!(!is_valid_world_id("index.json"));
+        assert!(!is_valid_world_id(""));
+    }
+
+    #[test]
+    fn test_only_vrchat_images_are_downloaded() {
+        assert!(is_valid_source_url(
+            "https://api.vrchat.cloud/api/1/file/file_123/1/file"
+        ));
+        assert!(!is_valid_source_url("http://api.vrchat.cloud/api/1/file"));
+        assert!(!is_valid_source_url("https://evilvrchat.cloud/image.png"));
+        assert!(!is_valid_source_url("https://localhost/image.png"));
+        assert!(!is_valid_source_url("not a url"));
+    }
+}
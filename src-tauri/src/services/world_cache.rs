@@ -0,0 +1,274 @@
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::time::Instant;
+
+use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::api::world::Validators;
+use crate::services::FileService;
+
+/// How long a cached world is shown without asking VRChat whether it changed
+const FRESH_FOR_MINUTES: i64 = 30;
+
+/// The most worlds kept, the ones checked the longest ago are dropped first
+const MAX_ENTRIES: usize = 1000;
+
+/// The least time between two writes of the cache file,
+/// the changes made in between are written together
+const SAVE_INTERVAL: std::time::Duration = std::time::Duration::from_secs(30);
+
+/// A world details response, as sent by VRChat
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CachedWorld {
+    pub body: String,
+    #[serde(default)]
+    pub etag: Option<String>,
+    #[serde(rename = "lastModified", default)]
+    pub last_modified: Option<String>,
+    /// When VRChat last sent or confirmed the response
+    #[serde(rename = "checkedAt")]
+    pub checked_at: DateTime<Utc>,
+}
+
+impl CachedWorld {
+    /// Whether the response is recent enough to be used without a request
+    pub fn is_fresh(&self, now: DateTime<Utc>) -> bool {
+        now - self.checked_at < Duration::minutes(FRESH_FOR_MINUTES)
+    }
+
+    /// The validators to revalidate the response with
+    pub fn validators(&self) -> Validators {
+        Validators {
+            etag: self.etag.clone(),
+            last_modified: self.last_modified.clone(),
+        }
+    }
+}
+
+/// How much the world cache holds, and how it served the worlds since the app started
+#[derive(Debug, Clone, Serialize, specta::Type)]
+pub struct WorldCacheStats {
+    entries: u32,
+    #[serde(rename = "sizeBytes")]
+    size_bytes: u32,
+    /// Worlds served from the cache without a request
+    hits: u32,
+    /// Worlds VRChat confirmed had not changed
+    revalidated: u32,
+    /// Worlds fetched in full
+    fetched: u32,
+}
+
+/// Keeps the world details responses of VRChat on disk, keyed by world ID,
+/// so opening the details of a world does not always cost a request
+///
+/// The file is written at most once per `SAVE_INTERVAL`, and when the app exits.
+/// A revalidation only changes when a world was checked, which is not worth a write.
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct WorldCache {
+    worlds: HashMap<String, CachedWorld>,
+    #[serde(skip)]
+    data_path: Option<PathBuf>,
+    /// Whether worlds were stored or dropped since the file was written
+    #[serde(skip)]
+    dirty: bool,
+    #[serde(skip)]
+    saved_at: Option<Instant>,
+    #[serde(skip)]
+    hits: u32,
+    #[serde(skip)]
+    revalidated: u32,
+    #[serde(skip)]
+    fetched: u32,
+}
+
+impl WorldCache {
+    /// Loads the cache, starting empty if it could not be read
+    pub fn load(path: PathBuf) -> Self {
+        let mut cache: Self = FileService::read_or_default(&path, "world cache");
+        cache.data_path = Some(path);
+        cache
+    }
+
+    /// Writes the worlds stored or dropped since the last write, e.g. when the app exits
+    pub fn flush(&mut self) {
+        if self.dirty {
+            self.save();
+        }
+    }
+
+    /// Writes the cache now if it was not written recently, or leaves it to a later write
+    fn changed(&mut self) {
+        self.dirty = true;
+        if self
+            .saved_at
+            .map_or(true, |saved_at| saved_at.elapsed() >= SAVE_INTERVAL)
+        {
+            self.save();
+        }
+    }
+
+    fn save(&mut self) {
+        if let Some(path) = &self.data_path {
+            if !FileService::write_json(path, self, "world cache") {
+                return;
+            }
+        }
+        self.dirty = false;
+        self.saved_at = Some(Instant::now());
+    }
+
+    pub fn get(&self, world_id: &str) -> Option<CachedWorld> {
+        self.worlds.get(world_id).cloned()
+    }
+
+    /// Counts a world served from the cache
+    pub fn record_hit(&mut self) {
+        self.hits += 1;
+    }
+
+    /// Stores a response VRChat sent in full, dropping the oldest worlds beyond `MAX_ENTRIES`
+    pub fn insert(
+        &mut self,
+        world_id: &str,
+        body: String,
+        validators: Validators,
+        now: DateTime<Utc>,
+    ) {
+        self.fetched += 1;
+        self.worlds.insert(
+            world_id.to_string(),
+            CachedWorld {
+                body,
+                etag: validators.etag,
+                last_modified: validators.last_modified,
+                checked_at: now,
+            },
+        );
+
+        if self.worlds.len() > MAX_ENTRIES {
+            let mut checked: Vec<(String, DateTime<Utc>)> = self
+                .worlds
+                .iter()
+                .map(|(id, world)| (id.clone(), world.checked_at))
+                .collect();
+            checked.sort_by_key(|(_, checked_at)| *checked_at);
+            let excess = self.worlds.len() - MAX_ENTRIES;
+            for (id, _) in checked.into_iter().take(excess) {
+                self.worlds.remove(&id);
+            }
+        }
+        self.changed();
+    }
+
+    /// Marks a response as confirmed by VRChat, so it is fresh again
+    /// Only the time of the check changes, which is written along with the next change
+    pub fn mark_revalidated(&mut self, world_id: &str, now: DateTime<Utc>) {
+        self.revalidated += 1;
+        if let Some(world) = self.worlds.get_mut(world_id) {
+            world.checked_at = now;
+        }
+    }
+
+    /// Drops a world, e.g. one that was deleted or whose response could not be read
+    pub fn remove(&mut self, world_id: &str) {
+        if self.worlds.remove(world_id).is_some() {
+            self.changed();
+        }
+    }
+
+    /// Drops every world, and resets the counters
+    pub fn clear(&mut self) {
+        self.worlds.clear();
+        self.hits = 0;
+        self.revalidated = 0;
+        self.fetched = 0;
+        self.save();
+        log::info!("World cache cleared");
+    }
+
+    pub fn stats(&self) -> WorldCacheStats {
+        WorldCacheStats {
+            entries: self.worlds.len() as u32,
+            size_bytes: self.worlds.values().map(|w| w.body.len() as u32).sum(),
+            hits: self.hits,
+            revalidated: self.revalidated,
+            fetched: self.fetched,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn validators(etag: &str) -> Validators {
+        Validators {
+            etag: Some(etag.to_string()),
+            last_modified: None,
+        }
+    }
+
+    #[test]
+    fn test_cached_worlds_go_stale_until_revalidated() {
+        let now = Utc::now();
+        let mut cache = WorldCache::default();
+        cache.insert("wrld_1", "{}".to_string(), validators("\"v1\""), now);
+
+        let later = now + Duration::minutes(FRESH_FOR_MINUTES + 1);
+        let world = cache.get("wrld_1").unwrap();
+        assert!(world.is_fresh(now));
+        assert!(!world.is_fresh(later));
+        assert_eq!(world.validators(), validators("\"v1\""));
+
+        cache.mark_revalidated("wrld_1", later);
+        assert!(cache.get("wrld_1").unwrap().is_fresh(later));
+
+        let stats = cache.stats();
+        assert_eq!(stats.entries, 1);
+        assert_eq!(stats.fetched, 1);
+        assert_eq!(stats.revalidated, 1);
+    }
+
+    #[test]
+    fn test_writes_are_batched() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let path = dir.path().join("world_cache.json");
+        let now = Utc::now();
+        let mut cache = WorldCache::load(path.clone());
+
+        cache.insert("wrld_1", "{}".to_string(), validators("\"v1\""), now);
+        assert!(WorldCache::load(path.clone()).get("wrld_1").is_some());
+
+        // Written recently, so the next changes wait for a later write
+        let later = now + Duration::minutes(FRESH_FOR_MINUTES + 1);
+        cache.insert("wrld_2", "{}".to_string(), validators("\"v1\""), now);
+        cache.mark_revalidated("wrld_1", later);
+        assert!(WorldCache::load(path.clone()).get("wrld_2").is_none());
+
+        cache.flush();
+        let reloaded = WorldCache::load(path);
+        assert!(reloaded.get("wrld_2").is_some());
+        assert!(reloaded.get("wrld_1").unwrap().is_fresh(later));
+    }
+
+    #[test]
+    fn test_oldest_worlds_are_dropped_beyond_limit() {
+        let now = Utc::now();
+        let mut cache = WorldCache::default();
+        for i in 0..=MAX_ENTRIES {
+            let checked_at = now + Duration::seconds(i as i64);
+            cache.insert(
+                &format!("wrld_{}", i),
+                "{}".to_string(),
+                Validators::default(),
+                checked_at,
+            );
+        }
+
+        assert_eq!(cache.stats().entries as usize, MAX_ENTRIES);
+        assert!(cache.get("wrld_0").is_none());
+        assert!(cache.get(&format!("wrld_{}", MAX_ENTRIES)).is_some());
+    }
+}
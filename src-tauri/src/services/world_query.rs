@@ -0,0 +1,784 @@
+use crate::definitions::{Platform, WorldAvailability, WorldModel};
+use crate::errors::QueryError;
+use chrono::{DateTime, Datelike, Duration, Local, NaiveDate, TimeZone, Utc};
+
+/// A parsed query over the worlds of the library
+///
+/// Terms are written as `field:value`, or `field>=value` for numeric fields.
+/// A term without a field matches the world name or author name. Terms are
+/// combined with `AND` (implied between terms), `OR`, `NOT` or a leading `-`,
+/// and can be grouped with parentheses. Values containing spaces are quoted.
+///
+/// Example: `author:"Waai!" tag:chill -tag:horror platform:android capacity>=16`
+///
+/// Supported fields:
+/// * `name` - The world name contains the value
+/// * `author` - The author name or author ID is the value
+/// * `tag` - The world has the tag, as an author tag, custom tag or plain tag
+/// * `folder` - The world is in the folder, given by its path for a subfolder (`Chill/Misc`)
+/// * `platform` - The world supports the platform (`pc`, `android` or `ios`)
+/// * `memo` - The memo contains the value
+/// * `id` - The world ID is the value
+/// * `hidden` - The world is hidden (`true` or `false`)
+/// * `status` - The availability of the world (`available`, `private`, `deleted`,
+///   `unknown`, or `unavailable` for both private and deleted worlds)
+/// * `capacity`, `visits`, `favorites` - Compared with `:`, `=`, `>`, `>=`, `<` or `<=`
+/// * `added` - The date the world was added, compared like the numeric fields.
+///   The value is a date (`2024-05-01`), a number of days ago (`30d`), or one
+///   of `today`, `this-week`, `this-month` and `this-year`. A date stands for the
+///   whole day, while the other values are compared with the moment they start.
+///
+/// All text comparisons are case-insensitive.
+#[derive(Debug, Clone, PartialEq)]
+pub struct WorldQuery {
+    root: Option<Expr>,
+}
+
+/// Fields compared as text, which only accept `:` and `=`
+const TEXT_FIELDS: [&str; 9] = [
+    "name", "author", "tag", "folder", "memo", "id", "platform", "hidden", "status",
+];
+
+#[derive(Debug, Clone, PartialEq)]
+enum Expr {
+    And(Box<Expr>, Box<Expr>),
+    Or(Box<Expr>, Box<Expr>),
+    Not(Box<Expr>),
+    Term(Term),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Term {
+    Text(String),
+    Name(String),
+    Author(String),
+    Tag(String),
+    Folder(String),
+    Platform(Platform),
+    Memo(String),
+    Id(String),
+    Hidden(bool),
+    /// None matches every unavailable world
+    Status(Option<WorldAvailability>),
+    Number(NumberField, Operator, i32),
+    Added(Operator, DateRange),
+}
+
+/// The period of time a date value refers to, in UTC
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct DateRange {
+    start: DateTime<Utc>,
+    end: Option<DateTime<Utc>>,
+}
+
+impl DateRange {
+    /// Resolves a date value relative to the current local time
+    fn parse(value: &str) -> Option<Self> {
+        let today = Local::now().date_naive();
+        let start_date = match value {
+            "today" => today,
+            "this-week" => today - Duration::days(today.weekday().num_days_from_monday() as i64),
+            "this-month" => today.with_day(1)?,
+            "this-year" => today.with_ordinal(1)?,
+            _ => {
+                if let Some(days) = value.strip_suffix('d') {
+                    if days.is_empty() || !days.chars().all(|c| c.is_ascii_digit()) {
+                        return None;
+                    }
+                    let days: i64 = days.parse().ok()?;
+                    return Some(Self {
+                        start: Utc::now().checked_sub_signed(Duration::try_days(days)?)?,
+                        end: None,
+                    });
+                }
+                let date = NaiveDate::parse_from_str(value, "%Y-%m-%d").ok()?;
+                return Some(Self {
+                    start: local_midnight(date)?,
+                    end: Some(local_midnight(date.succ_opt()?)?),
+                });
+            }
+        };
+        Some(Self {
+            start: local_midnight(start_date)?,
+            end: None,
+        })
+    }
+
+    /// Compares a date with the range
+    /// A range without an end, like `30d` or `this-month`, is compared with its start
+    fn compare(&self, op: Operator, date: DateTime<Utc>) -> bool {
+        let Some(end) = self.end else {
+            return match op {
+                Operator::Colon | Operator::Eq | Operator::Ge => date >= self.start,
+                Operator::Gt => date > self.start,
+                Operator::Lt => date < self.start,
+                Operator::Le => date <= self.start,
+            };
+        };
+        match op {
+            Operator::Colon | Operator::Eq => date >= self.start && date < end,
+            Operator::Gt => date >= end,
+            Operator::Ge => date >= self.start,
+            Operator::Lt => date < self.start,
+            Operator::Le => date < end,
+        }
+    }
+}
+
+fn local_midnight(date: NaiveDate) -> Option<DateTime<Utc>> {
+    Local
+        .from_local_datetime(&date.and_hms_opt(0, 0, 0)?)
+        .earliest()
+        .map(|d| d.with_timezone(&Utc))
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum NumberField {
+    Capacity,
+    Visits,
+    Favorites,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Operator {
+    Colon,
+    Eq,
+    Gt,
+    Ge,
+    Lt,
+    Le,
+}
+
+impl Operator {
+    fn as_str(self) -> &'static str {
+        match self {
+            Operator::Colon => ":",
+            Operator::Eq => "=",
+            Operator::Gt => ">",
+            Operator::Ge => ">=",
+            Operator::Lt => "<",
+            Operator::Le => "<=",
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    LParen,
+    RParen,
+    And,
+    Or,
+    Not,
+    Text(String),
+    Field(String, Operator, String),
+}
+
+impl Token {
+    fn describe(&self) -> String {
+        match self {
+            Token::LParen => "(".to_string(),
+            Token::RParen => ")".to_string(),
+            Token::And => "AND".to_string(),
+            Token::Or => "OR".to_string(),
+            Token::Not => "NOT".to_string(),
+            Token::Text(text) => text.clone(),
+            Token::Field(field, op, value) => format!("{}{}{}", field, op.as_str(), value),
+        }
+    }
+}
+
+impl WorldQuery {
+    /// Parses a query string
+    /// An empty query matches every world
+    ///
+    /// # Arguments
+    /// * `query` - The query to parse
+    ///
+    /// # Returns
+    /// The parsed query
+    ///
+    /// # Errors
+    /// Returns a QueryError describing the first problem found in the query
+    pub fn parse(query: &str) -> Result<Self, QueryError> {
+        let tokens = tokenize(query)?;
+        if tokens.is_empty() {
+            return Ok(Self { root: None });
+        }
+
+        let mut parser = Parser { tokens, index: 0 };
+        let root = parser.parse_or()?;
+        if let Some((position, token)) = parser.tokens.get(parser.index) {
+            return Err(QueryError::UnexpectedToken {
+                position: *position,
+                token: token.describe(),
+            });
+        }
+        Ok(Self { root: Some(root) })
+    }
+
+    /// Checks whether a world matches the query
+    ///
+    /// # Arguments
+    /// * `world` - The world to check
+    ///
+    /// # Returns
+    /// Returns true if the world matches
+    pub fn matches(&self, world: &WorldModel) -> bool {
+        self.root.as_ref().map_or(true, |root| root.matches(world))
+    }
+
+    /// Checks whether the query filters on the hidden flag
+    /// Hidden worlds are only returned by queries that ask for them
+    pub fn mentions_hidden(&self) -> bool {
+        self.root.as_ref().is_some_and(Expr::mentions_hidden)
+    }
+
+    /// Rewrites the `folder` terms of a query that refer to a renamed folder
+    ///
+    /// # Arguments
+    /// * `query` - The query to rewrite
+    /// * `old_path` - The path the folder had
+    /// * `new_path` - The path the folder has now
+    ///
+    /// # Returns
+    /// The rewritten query, or None if no term refers to the folder
+    /// None is also returned for a query that can't be parsed
+    pub fn rename_folder(query: &str, old_path: &str, new_path: &str) -> Option<String> {
+        let tokens = tokenize(query).ok()?;
+        let chars: Vec<char> = query.chars().collect();
+        let old_path = old_path.to_lowercase();
+        let new_value = if new_path
+            .chars()
+            .any(|c| c.is_whitespace() || matches!(c, '(' | ')' | '"'))
+        {
+            format!("\"{}\"", new_path)
+        } else {
+            new_path.to_string()
+        };
+
+        let mut rewritten = String::new();
+        let mut copied = 0;
+        for (position, token) in tokens {
+            let Token::Field(field, op, value) = token else {
+                continue;
+            };
+            if !field.eq_ignore_ascii_case("folder") || value.to_lowercase() != old_path {
+                continue;
+            }
+            let value_start = position + field.chars().count() + op.as_str().len();
+            let quoted = chars.get(value_start) == Some(&'"');
+            let value_end = value_start + value.chars().count() + if quoted { 2 } else { 0 };
+            rewritten.extend(&chars[copied..value_start]);
+            rewritten.push_str(&new_value);
+            copied = value_end;
+        }
+        if copied == 0 {
+            return None;
+        }
+        rewritten.extend(&chars[copied..]);
+        Some(rewritten)
+    }
+}
+
+impl Expr {
+    fn matches(&self, world: &WorldModel) -> bool {
+        match self {
+            Expr::And(left, right) => left.matches(world) && right.matches(world),
+            Expr::Or(left, right) => left.matches(world) || right.matches(world),
+            Expr::Not(inner) => !inner.matches(world),
+            Expr::Term(term) => term.matches(world),
+        }
+    }
+
+    fn mentions_hidden(&self) -> bool {
+        match self {
+            Expr::And(left, right) | Expr::Or(left, right) => {
+                left.mentions_hidden() || right.mentions_hidden()
+            }
+            Expr::Not(inner) => inner.mentions_hidden(),
+            Expr::Term(term) => matches!(term, Term::Hidden(_)),
+        }
+    }
+}
+
+impl Term {
+    fn from_field(field: &str, op: Operator, value: String) -> Result<Self, QueryError> {
+        let field = field.to_lowercase();
+        let number_field = match field.as_str() {
+            "capacity" => Some(NumberField::Capacity),
+            "visits" => Some(NumberField::Visits),
+            "favorites" => Some(NumberField::Favorites),
+            _ => None,
+        };
+        if field == "added" {
+            return DateRange::parse(&value.to_lowercase())
+                .map(|range| Term::Added(op, range))
+                .ok_or(QueryError::InvalidValue { field, value });
+        }
+        if let Some(number_field) = number_field {
+            let number = value
+                .parse()
+                .map_err(|_| QueryError::InvalidValue { field, value })?;
+            return Ok(Term::Number(number_field, op, number));
+        }
+
+        if !TEXT_FIELDS.contains(&field.as_str()) {
+            return Err(QueryError::UnknownField(field));
+        }
+        if !matches!(op, Operator::Colon | Operator::Eq) {
+            return Err(QueryError::InvalidOperator {
+                field,
+                operator: op.as_str().to_string(),
+            });
+        }
+        let lower = value.to_lowercase();
+        let term = match field.as_str() {
+            "name" => Term::Name(lower),
+            "author" => Term::Author(lower),
+            "tag" => Term::Tag(lower),
+            "folder" => Term::Folder(lower),
+            "memo" => Term::Memo(lower),
+            "id" => Term::Id(lower),
+            "platform" => match lower.as_str() {
+                "pc" | "windows" | "standalonewindows" => {
+                    Term::Platform(Platform::StandaloneWindows)
+                }
+                "android" | "quest" => Term::Platform(Platform::Android),
+                "ios" => Term::Platform(Platform::Ios),
+                _ => return Err(QueryError::InvalidValue { field, value }),
+            },
+            "hidden" => match lower.as_str() {
+                "true" => Term::Hidden(true),
+                "false" => Term::Hidden(false),
+                _ => return Err(QueryError::InvalidValue { field, value }),
+            },
+            "status" => match lower.as_str() {
+                "available" => Term::Status(Some(WorldAvailability::Available)),
+                "private" => Term::Status(Some(WorldAvailability::Private)),
+                "deleted" => Term::Status(Some(WorldAvailability::Deleted)),
+                "unknown" => Term::Status(Some(WorldAvailability::Unknown)),
+                "unavailable" => Term::Status(None),
+                _ => return Err(QueryError::InvalidValue { field, value }),
+            },
+            _ => unreachable!("checked against TEXT_FIELDS"),
+        };
+        Ok(term)
+    }
+
+    fn matches(&self, world: &WorldModel) -> bool {
+        let api = &world.api_data;
+        let user = &world.user_data;
+        match self {
+            Term::Text(text) => {
+                api.world_name.to_lowercase().contains(text)
+                    || api.author_name.to_lowercase().contains(text)
+            }
+            Term::Name(name) => api.world_name.to_lowercase().contains(name),
+            Term::Author(author) => {
+                api.author_name.to_lowercase() == *author || api.author_id.to_lowercase() == *author
+            }
+            Term::Tag(tag) => {
+                let author_tag = format!("author_tag_{}", tag);
+                let custom_tag = format!("custom:{}", tag);
+                api.tags.iter().chain(user.custom_tags.iter()).any(|t| {
+                    let t = t.to_lowercase();
+                    t == *tag || t == author_tag || t == custom_tag
+                })
+            }
+            Term::Folder(folder) => user.folders.iter().any(|f| f.to_lowercase() == *folder),
+            Term::Platform(platform) => api.platform.contains(platform),
+            Term::Memo(memo) => user.memo.to_lowercase().contains(memo),
+            Term::Id(id) => api.world_id.to_lowercase() == *id,
+            Term::Hidden(hidden) => user.hidden == *hidden,
+            Term::Status(Some(availability)) => user.availability == *availability,
+            Term::Status(None) => user.availability.is_unavailable(),
+            Term::Added(op, range) => range.compare(*op, user.date_added),
+            Term::Number(field, op, value) => {
+                let actual = match field {
+                    NumberField::Capacity => api.capacity,
+                    NumberField::Visits => api.visits.unwrap_or(0),
+                    NumberField::Favorites => api.favorites,
+                };
+                match op {
+                    Operator::Colon | Operator::Eq => actual == *value,
+                    Operator::Gt => actual > *value,
+                    Operator::Ge => actual >= *value,
+                    Operator::Lt => actual < *value,
+                    Operator::Le => actual <= *value,
+                }
+            }
+        }
+    }
+}
+
+/// Splits a query into tokens, paired with their character position
+fn tokenize(query: &str) -> Result<Vec<(usize, Token)>, QueryError> {
+    let chars: Vec<char> = query.chars().collect();
+    let mut tokens = Vec::new();
+    let mut i = 0;
+
+    while i < chars.len() {
+        let start = i;
+        match chars[i] {
+            c if c.is_whitespace() => {
+                i += 1;
+                continue;
+            }
+            '(' => {
+                tokens.push((start, Token::LParen));
+                i += 1;
+                continue;
+            }
+            ')' => {
+                tokens.push((start, Token::RParen));
+                i += 1;
+                continue;
+            }
+            '|' => {
+                tokens.push((start, Token::Or));
+                i += 1;
+                continue;
+            }
+            '-' if chars.get(i + 1).is_some_and(|c| !c.is_whitespace()) => {
+                tokens.push((start, Token::Not));
+                i += 1;
+                continue;
+            }
+            '"' => {
+                let text = read_quoted(&chars, &mut i)?;
+                tokens.push((start, Token::Text(text.to_lowercase())));
+                continue;
+            }
+            _ => {}
+        }
+
+        let word = read_while(&chars, &mut i, |c| {
+            !c.is_whitespace() && !matches!(c, '(' | ')' | '"' | ':' | '<' | '>' | '=')
+        });
+        let op = match (chars.get(i), chars.get(i + 1)) {
+            (Some(':'), _) => Some((Operator::Colon, 1)),
+            (Some('>'), Some('=')) => Some((Operator::Ge, 2)),
+            (Some('<'), Some('=')) => Some((Operator::Le, 2)),
+            (Some('>'), _) => Some((Operator::Gt, 1)),
+            (Some('<'), _) => Some((Operator::Lt, 1)),
+            (Some('='), _) => Some((Operator::Eq, 1)),
+            _ => None,
+        };
+
+        let token = match op {
+            Some((op, len)) => {
+                if word.is_empty() {
+                    return Err(QueryError::UnexpectedToken {
+                        position: start,
+                        token: op.as_str().to_string(),
+                    });
+                }
+                i += len;
+                let value = if chars.get(i) == Some(&'"') {
+                    read_quoted(&chars, &mut i)?
+                } else {
+                    read_while(&chars, &mut i, |c| {
+                        !c.is_whitespace() && !matches!(c, '(' | ')')
+                    })
+                };
+                if value.is_empty() {
+                    return Err(QueryError::InvalidValue { field: word, value });
+                }
+                Token::Field(word, op, value)
+            }
+            None => match word.as_str() {
+                "AND" => Token::And,
+                "OR" => Token::Or,
+                "NOT" => Token::Not,
+                _ => Token::Text(word.to_lowercase()),
+            },
+        };
+        tokens.push((start, token));
+    }
+
+    Ok(tokens)
+}
+
+fn read_while(chars: &[char], i: &mut usize, keep: impl Fn(char) -> bool) -> String {
+    let start = *i;
+    while *i < chars.len() && keep(chars[*i]) {
+        *i += 1;
+    }
+    chars[start..*i].iter().collect()
+}
+
+/// Reads a quoted value, starting at the opening quote
+fn read_quoted(chars: &[char], i: &mut usize) -> Result<String, QueryError> {
+    let start = *i;
+    *i += 1;
+    let value = read_while(chars, i, |c| c != '"');
+    if *i >= chars.len() {
+        return Err(QueryError::UnterminatedQuote(start));
+    }
+    *i += 1;
+    Ok(value)
+}
+
+/// Recursive descent parser, binding NOT tighter than AND, and AND tighter than OR
+struct Parser {
+    tokens: Vec<(usize, Token)>,
+    index: usize,
+}
+
+impl Parser {
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.index).map(|(_, token)| token)
+    }
+
+    fn parse_or(&mut self) -> Result<Expr, QueryError> {
+        let mut expr = self.parse_and()?;
+        while self.peek() == Some(&Token::Or) {
+            self.index += 1;
+            let right = self.parse_and()?;
+            expr = Expr::Or(Box::new(expr), Box::new(right));
+        }
+        Ok(expr)
+    }
+
+    fn parse_and(&mut self) -> Result<Expr, QueryError> {
+        let mut expr = self.parse_unary()?;
+        loop {
+            match self.peek() {
+                None | Some(Token::Or) | Some(Token::RParen) => break,
+                Some(Token::And) => self.index += 1,
+                _ => {}
+            }
+            let right = self.parse_unary()?;
+            expr = Expr::And(Box::new(expr), Box::new(right));
+        }
+        Ok(expr)
+    }
+
+    fn parse_unary(&mut self) -> Result<Expr, QueryError> {
+        let Some((position, token)) = self.tokens.get(self.index).cloned() else {
+            return Err(QueryError::UnexpectedEnd);
+        };
+        self.index += 1;
+
+        match token {
+            Token::Not => Ok(Expr::Not(Box::new(self.parse_unary()?))),
+            Token::LParen => {
+                let expr = self.parse_or()?;
+                if self.peek() != Some(&Token::RParen) {
+                    return Err(QueryError::UnexpectedEnd);
+                }
+                self.index += 1;
+                Ok(expr)
+            }
+            Token::Text(text) => Ok(Expr::Term(Term::Text(text))),
+            Token::Field(field, op, value) => Ok(Expr::Term(Term::from_field(&field, op, value)?)),
+            token => Err(QueryError::UnexpectedToken {
+                position,
+                token: token.describe(),
+            }),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::services::test_support;
+
+    fn test_world() -> WorldModel {
+        let mut world = test_support::test_world("wrld_1");
+        world.api_data.world_name = "Chill Lounge".to_string();
+        world.api_data.author_name = "Waai!".to_string();
+        world.api_data.author_id = "usr_1".to_string();
+        world.api_data.tags = vec![
+            "author_tag_chill".to_string(),
+            "system_approved".to_string(),
+        ];
+        world.api_data.capacity = 32;
+        world.api_data.visits = Some(1000);
+        world.api_data.favorites = 50;
+        world.api_data.platform = vec![Platform::StandaloneWindows, Platform::Android];
+        world.user_data.memo = "Good for events on Friday".to_string();
+        world.user_data.folders = vec!["Favs".to_string()];
+        world.user_data.custom_tags = vec!["custom:night".to_string()];
+        world
+    }
+
+    fn matches(query: &str) -> bool {
+        WorldQuery::parse(query).unwrap().matches(&test_world())
+    }
+
+    #[test]
+    fn test_example_query() {
+        assert!(matches(
+            r#"author:"Waai!" tag:chill -tag:horror platform:android capacity>=16 memo:"good for events" folder:Favs"#
+        ));
+        assert!(!matches(r#"author:"Waai!" tag:horror"#));
+    }
+
+    #[test]
+    fn test_empty_query_matches_everything() {
+        assert!(matches(""));
+        assert!(matches("   "));
+    }
+
+    #[test]
+    fn test_fields() {
+        assert!(matches("lounge"));
+        assert!(matches("\"chill lounge\""));
+        assert!(matches("name:CHILL"));
+        assert!(matches("author:usr_1"));
+        assert!(matches("tag:system_approved"));
+        assert!(matches("tag:night"));
+        assert!(matches("id:wrld_1"));
+        assert!(matches("hidden:false"));
+        assert!(matches("status:unknown"));
+        assert!(!matches("status:unavailable"));
+        assert!(!matches("author:Waai"));
+        assert!(!matches("platform:ios"));
+        assert!(!matches("folder:Other"));
+    }
+
+    #[test]
+    fn test_comparisons() {
+        assert!(matches("capacity:32"));
+        assert!(matches("capacity=32"));
+        assert!(matches("visits>999"));
+        assert!(matches("favorites<=50"));
+        assert!(!matches("favorites<50"));
+        assert!(!matches("visits>=1001"));
+    }
+
+    #[test]
+    fn test_date_added() {
+        let mut world = test_world();
+        world.user_data.date_added = local_midnight(NaiveDate::from_ymd_opt(2024, 5, 1).unwrap())
+            .unwrap()
+            + Duration::hours(12);
+        let matches = |query: &str| WorldQuery::parse(query).unwrap().matches(&world);
+
+        assert!(matches("added:2024-05-01"));
+        assert!(matches("added>=2024-05-01"));
+        assert!(matches("added<=2024-05-01"));
+        assert!(matches("added>2024-04-30"));
+        assert!(matches("added<2024-05-02"));
+        assert!(!matches("added>2024-05-01"));
+        assert!(!matches("added<2024-05-01"));
+        assert!(!matches("added:this-month"));
+        assert!(!matches("added>=30d"));
+
+        assert!(WorldQuery::parse("added:today")
+            .unwrap()
+            .matches(&test_world()));
+        assert!(WorldQuery::parse("added:this-week")
+            .unwrap()
+            .matches(&test_world()));
+        assert!(WorldQuery::parse("added>=7d")
+            .unwrap()
+            .matches(&test_world()));
+        assert!(matches!(
+            WorldQuery::parse("added:yesterday"),
+            Err(QueryError::InvalidValue { .. })
+        ));
+        assert!(matches!(
+            WorldQuery::parse("added>=100000000d"),
+            Err(QueryError::InvalidValue { .. })
+        ));
+        for query in ["added:-5d", "added>+5d", "added<d"] {
+            assert!(matches!(
+                WorldQuery::parse(query),
+                Err(QueryError::InvalidValue { .. })
+            ));
+        }
+    }
+
+    #[test]
+    fn test_open_date_ranges_compare_with_their_start() {
+        let mut recent = test_world();
+        recent.user_data.date_added = Utc::now() - Duration::days(10);
+        let mut old = test_world();
+        old.user_data.date_added = Utc::now() - Duration::days(60);
+        let query = |query: &str| WorldQuery::parse(query).unwrap();
+
+        assert!(query("added>30d").matches(&recent));
+        assert!(!query("added>30d").matches(&old));
+        assert!(query("added<=30d").matches(&old));
+        assert!(!query("added<=30d").matches(&recent));
+        assert!(query("added<30d").matches(&old));
+
+        // A world added today is after the start of today
+        assert!(!query("added<=today").matches(&test_world()));
+        assert!(query("added<=today").matches(&old));
+        assert!(query("added>today").matches(&test_world()));
+    }
+
+    #[test]
+    fn test_boolean_operators() {
+        assert!(matches("tag:horror OR tag:chill"));
+        assert!(matches("tag:horror | tag:chill"));
+        assert!(matches("NOT tag:horror"));
+        assert!(!matches("tag:chill AND tag:horror"));
+        assert!(!matches("-(tag:horror OR tag:chill)"));
+        // AND binds tighter than OR
+        assert!(matches("tag:horror tag:chill OR folder:Favs"));
+        assert!(!matches("tag:horror (tag:chill OR folder:Favs)"));
+    }
+
+    #[test]
+    fn test_mentions_hidden() {
+        assert!(WorldQuery::parse("-hidden:true").unwrap().mentions_hidden());
+        assert!(!WorldQuery::parse("tag:chill").unwrap().mentions_hidden());
+    }
+
+    #[test]
+    fn test_rename_folder() {
+        assert_eq!(
+            WorldQuery::rename_folder("folder:favs -folder:Old tag:favs", "favs", "Best"),
+            Some("folder:Best -folder:Old tag:favs".to_string())
+        );
+        assert_eq!(
+            WorldQuery::rename_folder("(FOLDER=\"My Favs\")", "my favs", "Chill/Quiet spots"),
+            Some("(FOLDER=\"Chill/Quiet spots\")".to_string())
+        );
+        assert_eq!(
+            WorldQuery::rename_folder("folder:Favs2 name:favs", "Favs", "Best"),
+            None
+        );
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        assert!(matches!(
+            WorldQuery::parse("color:red"),
+            Err(QueryError::UnknownField(field)) if field == "color"
+        ));
+        assert!(matches!(
+            WorldQuery::parse("color>red"),
+            Err(QueryError::UnknownField(_))
+        ));
+        assert!(matches!(
+            WorldQuery::parse("capacity>many"),
+            Err(QueryError::InvalidValue { .. })
+        ));
+        assert!(matches!(
+            WorldQuery::parse("tag>chill"),
+            Err(QueryError::InvalidOperator { .. })
+        ));
+        assert!(matches!(
+            WorldQuery::parse("memo:\"unfinished"),
+            Err(QueryError::UnterminatedQuote(5))
+        ));
+        assert!(matches!(
+            WorldQuery::parse("(tag:chill"),
+            Err(QueryError::UnexpectedEnd)
+        ));
+        assert!(matches!(
+            WorldQuery::parse("tag:chill)"),
+            Err(QueryError::UnexpectedToken { position: 9, .. })
+        ));
+        assert!(matches!(
+            WorldQuery::parse("tag:chill OR"),
+            Err(QueryError::UnexpectedEnd)
+        ));
+    }
+}
@@ -36,11 +36,11 @@ impl CancellableTask {
             let mut error = cloned_error.lock().await;
 
             if let Ok(result) = result {
-                if result.is_ok() {
-                    *status = TaskStatus::Completed;
-                } else {
+                if let Err(e) = result {
                     *status = TaskStatus::Failed;
-                    *error = Some(result.unwrap_err());
+                    *error = Some(e);
+                } else {
+                    *status = TaskStatus::Completed;
                 }
             } else {
                 *status = TaskStatus::Cancelled;
@@ -62,7 +62,7 @@ impl CancellableTask {
     }
 
     pub async fn get_status(&self) -> TaskStatus {
-        self.status.lock().await.clone()
+        *self.status.lock().await
     }
 
     pub async fn get_error(&self) -> Option<String> {
@@ -78,7 +78,7 @@ impl CancellableTask {
 
         self.abort_handle.abort();
 
-        return Ok(TaskStatus::Cancelled);
+        Ok(TaskStatus::Cancelled)
     }
 }
 
@@ -89,7 +89,7 @@ impl UpdateHandler {
         self.update_available = true;
         self.update_version = Some(update.version);
         self.initialized = true;
-        return Ok(true);
+        Ok(true)
     }
 
     pub async fn download_update(&mut self) -> tauri_plugin_updater::Result<()> {
@@ -147,7 +147,7 @@ impl UpdateHandler {
         let data = self.downloaded_update_data.as_ref().unwrap();
 
         update
-            .install(&data)
+            .install(data)
             .map_err(|e| format!("Failed to install update: {:?}", e))?;
 
         Ok(())
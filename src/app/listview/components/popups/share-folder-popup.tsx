@@ -99,7 +99,12 @@ export function ShareFolderPopup({
     setErrorMessage(null);
     setShareLoading(true);
     const id = await commands.shareFolder(folderName);
-    if (id.status === 'ok') {
+    if (id.status === 'ok' && id.data === null) {
+      // Offline: the share is uploaded from the outbox once the connection is back
+      info(`Queued sharing folder "${folderName}"`);
+      toast(t('share-folder:toast-queued'));
+      onOpenChange(false);
+    } else if (id.status === 'ok') {
       info(`Shared folder "${folderName}" as ${id.data}`);
       setShareId(id.data);
     } else {
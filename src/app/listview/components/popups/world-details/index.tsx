@@ -30,6 +30,7 @@ import {
 import { WorldDisplayData } from '@/lib/bindings';
 import { WorldDetails } from '@/lib/bindings';
 import { WorldCardPreview } from '@/components/world-card';
+import { WorldThumbnail } from '@/components/world-thumbnail';
 import { GroupInstanceCreator } from './group-instance-creator';
 import { GroupInstanceType, InstanceType } from '@/types/instances';
 import { InstanceRegion } from '@/lib/bindings';
@@ -802,8 +803,9 @@ export function WorldDetailPopup({
                               platform={worldDetails.platform}
                             />
                           </div>
-                          <img
-                            src={worldDetails.thumbnailUrl}
+                          <WorldThumbnail
+                            worldId={worldDetails.worldId}
+                            fallbackUrl={worldDetails.thumbnailUrl}
                             alt={worldDetails.name}
                             className="object-cover w-full h-full"
                             style={{
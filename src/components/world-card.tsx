@@ -4,6 +4,7 @@ import { CardSize, WorldDisplayData } from '@/lib/bindings';
 import { useLocalization } from '@/hooks/use-localization';
 import { usePatreonContext } from '@/contexts/patreon-context';
 import { PlatformIndicator } from './platform-indicator';
+import { WorldThumbnail } from './world-thumbnail';
 
 interface WorldCardPreviewProps {
   size: CardSize;
@@ -31,8 +32,9 @@ export function WorldCardPreview(props: WorldCardPreviewProps) {
           <PlatformIndicator platform={world.platform} />
         </div>
       </div>
-      <img
-        src={world.thumbnailUrl}
+      <WorldThumbnail
+        worldId={world.worldId}
+        fallbackUrl={world.thumbnailUrl}
         alt={world.name}
         className={`w-full h-2/3 object-cover rounded-t-lg`}
         draggable="false"
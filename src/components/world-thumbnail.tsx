@@ -0,0 +1,29 @@
+import React, { useEffect, useState } from 'react';
+import { thumbnailUrl } from '@/lib/utils';
+
+interface WorldThumbnailProps
+  extends Omit<React.ImgHTMLAttributes<HTMLImageElement>, 'src'> {
+  worldId: string;
+  /**
+   * The image URL of the world, from which the thumbnail is made, shown as is
+   * when the world has no cached thumbnail, e.g. before it is added
+   */
+  fallbackUrl: string;
+}
+
+export function WorldThumbnail(props: WorldThumbnailProps) {
+  const { worldId, fallbackUrl, ...imgProps } = props;
+  const [useFallback, setUseFallback] = useState(false);
+
+  useEffect(() => {
+    setUseFallback(false);
+  }, [worldId]);
+
+  return (
+    <img
+      {...imgProps}
+      src={useFallback ? fallbackUrl : thumbnailUrl(worldId, fallbackUrl)}
+      onError={() => setUseFallback(true)}
+    />
+  );
+}
@@ -73,6 +73,50 @@ export const commands = {
       else return { status: 'error', error: e as any };
     }
   },
+  /**
+   * Starts the background refresh of stale worlds, or returns the running one.
+   * The refresh is started on launch, so this is only needed after cancelling it.
+   */
+  async startWorldRefresh(): Promise<Result<string, string>> {
+    try {
+      return { status: 'ok', data: await TAURI_INVOKE('start_world_refresh') };
+    } catch (e) {
+      if (e instanceof Error) throw e;
+      else return { status: 'error', error: e as any };
+    }
+  },
+  /**
+   * Starts replaying the outbox, or wakes the running replay to try the queued actions now
+   */
+  async replayOutbox(): Promise<Result<string, string>> {
+    try {
+      return { status: 'ok', data: await TAURI_INVOKE('replay_outbox') };
+    } catch (e) {
+      if (e instanceof Error) throw e;
+      else return { status: 'error', error: e as any };
+    }
+  },
+  /**
+   * Starts polling a world for joinable instances with at least `min_users` users,
+   * emitting InstanceAvailable for each one found. Cancel the task to stop watching.
+   */
+  async watchWorldInstances(
+    worldId: string,
+    minUsers: number | null,
+  ): Promise<Result<string, string>> {
+    try {
+      return {
+        status: 'ok',
+        data: await TAURI_INVOKE('watch_world_instances', {
+          worldId,
+          minUsers,
+        }),
+      };
+    } catch (e) {
+      if (e instanceof Error) throw e;
+      else return { status: 'error', error: e as any };
+    }
+  },
   async checkForUpdate(): Promise<Result<boolean, string>> {
     try {
       return { status: 'ok', data: await TAURI_INVOKE('check_for_update') };
@@ -180,6 +224,34 @@ export const commands = {
       else return { status: 'error', error: e as any };
     }
   },
+  async createSmartFolder(
+    name: string,
+    query: string,
+  ): Promise<Result<string, string>> {
+    try {
+      return {
+        status: 'ok',
+        data: await TAURI_INVOKE('create_smart_folder', { name, query }),
+      };
+    } catch (e) {
+      if (e instanceof Error) throw e;
+      else return { status: 'error', error: e as any };
+    }
+  },
+  async updateSmartFolder(
+    folderName: string,
+    query: string,
+  ): Promise<Result<null, string>> {
+    try {
+      return {
+        status: 'ok',
+        data: await TAURI_INVOKE('update_smart_folder', { folderName, query }),
+      };
+    } catch (e) {
+      if (e instanceof Error) throw e;
+      else return { status: 'error', error: e as any };
+    }
+  },
   async deleteFolder(name: string): Promise<Result<null, string>> {
     try {
       return {
@@ -219,6 +291,61 @@ export const commands = {
       else return { status: 'error', error: e as any };
     }
   },
+  async createFolderAtPath(path: string): Promise<Result<string, string>> {
+    try {
+      return {
+        status: 'ok',
+        data: await TAURI_INVOKE('create_folder_at_path', { path }),
+      };
+    } catch (e) {
+      if (e instanceof Error) throw e;
+      else return { status: 'error', error: e as any };
+    }
+  },
+  async deleteFolderAtPath(path: string): Promise<Result<null, string>> {
+    try {
+      return {
+        status: 'ok',
+        data: await TAURI_INVOKE('delete_folder_at_path', { path }),
+      };
+    } catch (e) {
+      if (e instanceof Error) throw e;
+      else return { status: 'error', error: e as any };
+    }
+  },
+  async moveFolderAtPath(
+    path: string,
+    newParentPath: string | null,
+    newIndex: number,
+  ): Promise<Result<null, string>> {
+    try {
+      return {
+        status: 'ok',
+        data: await TAURI_INVOKE('move_folder_at_path', {
+          path,
+          newParentPath,
+          newIndex,
+        }),
+      };
+    } catch (e) {
+      if (e instanceof Error) throw e;
+      else return { status: 'error', error: e as any };
+    }
+  },
+  async renameFolderAtPath(
+    path: string,
+    newName: string,
+  ): Promise<Result<null, string>> {
+    try {
+      return {
+        status: 'ok',
+        data: await TAURI_INVOKE('rename_folder_at_path', { path, newName }),
+      };
+    } catch (e) {
+      if (e instanceof Error) throw e;
+      else return { status: 'error', error: e as any };
+    }
+  },
   async getWorlds(
     folderName: string,
   ): Promise<Result<WorldDisplayData[], string>> {
@@ -240,6 +367,162 @@ export const commands = {
       else return { status: 'error', error: e as any };
     }
   },
+  async queryWorlds(
+    query: string,
+  ): Promise<Result<WorldDisplayData[], string>> {
+    try {
+      return {
+        status: 'ok',
+        data: await TAURI_INVOKE('query_worlds', { query }),
+      };
+    } catch (e) {
+      if (e instanceof Error) throw e;
+      else return { status: 'error', error: e as any };
+    }
+  },
+  async getWorldHistory(
+    worldId: string,
+  ): Promise<Result<WorldChange[], string>> {
+    try {
+      return {
+        status: 'ok',
+        data: await TAURI_INVOKE('get_world_history', { worldId }),
+      };
+    } catch (e) {
+      if (e instanceof Error) throw e;
+      else return { status: 'error', error: e as any };
+    }
+  },
+  async getWorldsUpdatedSince(
+    since: string,
+  ): Promise<Result<WorldDisplayData[], string>> {
+    try {
+      return {
+        status: 'ok',
+        data: await TAURI_INVOKE('get_worlds_updated_since', { since }),
+      };
+    } catch (e) {
+      if (e instanceof Error) throw e;
+      else return { status: 'error', error: e as any };
+    }
+  },
+  async getUnavailableWorlds(): Promise<Result<WorldDisplayData[], string>> {
+    try {
+      return {
+        status: 'ok',
+        data: await TAURI_INVOKE('get_unavailable_worlds'),
+      };
+    } catch (e) {
+      if (e instanceof Error) throw e;
+      else return { status: 'error', error: e as any };
+    }
+  },
+  async setFolderFavoriteGroup(
+    folderName: string,
+    group: string | null,
+  ): Promise<Result<null, string>> {
+    try {
+      return {
+        status: 'ok',
+        data: await TAURI_INVOKE('set_folder_favorite_group', {
+          folderName,
+          group,
+        }),
+      };
+    } catch (e) {
+      if (e instanceof Error) throw e;
+      else return { status: 'error', error: e as any };
+    }
+  },
+  /**
+   * Lists the changes a sync would make, and the ones that conflict, without applying them
+   */
+  async previewFavoriteSync(): Promise<Result<FavoriteSyncPlan[], string>> {
+    try {
+      return {
+        status: 'ok',
+        data: await TAURI_INVOKE('preview_favorite_sync'),
+      };
+    } catch (e) {
+      if (e instanceof Error) throw e;
+      else return { status: 'error', error: e as any };
+    }
+  },
+  async applyFavoriteSync(): Promise<Result<FavoriteSyncPlan[], string>> {
+    try {
+      return { status: 'ok', data: await TAURI_INVOKE('apply_favorite_sync') };
+    } catch (e) {
+      if (e instanceof Error) throw e;
+      else return { status: 'error', error: e as any };
+    }
+  },
+  /**
+   * Files every favorite into a folder named after its favorite group
+   */
+  async importFavoriteGroups(): Promise<Result<FavoriteGroupImport[], string>> {
+    try {
+      return {
+        status: 'ok',
+        data: await TAURI_INVOKE('import_favorite_groups'),
+      };
+    } catch (e) {
+      if (e instanceof Error) throw e;
+      else return { status: 'error', error: e as any };
+    }
+  },
+  async getScheduledInstances(): Promise<Result<ScheduledInstance[], string>> {
+    try {
+      return {
+        status: 'ok',
+        data: await TAURI_INVOKE('get_scheduled_instances'),
+      };
+    } catch (e) {
+      if (e instanceof Error) throw e;
+      else return { status: 'error', error: e as any };
+    }
+  },
+  /**
+   * Schedules an instance of a world to be created with a preset at a later time
+   */
+  async scheduleInstance(
+    worldId: string,
+    presetName: string,
+    region: InstanceRegion | null,
+    groupId: string | null,
+    scheduledAt: string,
+    inviteSelf: boolean,
+  ): Promise<Result<ScheduledInstance, string>> {
+    try {
+      return {
+        status: 'ok',
+        data: await TAURI_INVOKE('schedule_instance', {
+          worldId,
+          presetName,
+          region,
+          groupId,
+          scheduledAt,
+          inviteSelf,
+        }),
+      };
+    } catch (e) {
+      if (e instanceof Error) throw e;
+      else return { status: 'error', error: e as any };
+    }
+  },
+  /**
+   * Cancels a pending schedule, or dismisses the result of a past one
+   */
+  async deleteScheduledInstance(id: string): Promise<Result<null, string>> {
+    try {
+      return {
+        status: 'ok',
+        data: await TAURI_INVOKE('delete_scheduled_instance', { id }),
+      };
+    } catch (e) {
+      if (e instanceof Error) throw e;
+      else return { status: 'error', error: e as any };
+    }
+  },
   async getUnclassifiedWorlds(): Promise<Result<WorldDisplayData[], string>> {
     try {
       return {
@@ -322,7 +605,15 @@ export const commands = {
       else return { status: 'error', error: e as any };
     }
   },
-  async shareFolder(folderName: string): Promise<Result<string, string>> {
+  /**
+   * Uploads a folder to the share worker
+   *
+   * # Returns
+   * The share ID of the folder, or None if the share was queued in the outbox while offline
+   */
+  async shareFolder(
+    folderName: string,
+  ): Promise<Result<string | null, string>> {
     try {
       return {
         status: 'ok',
@@ -551,6 +842,113 @@ export const commands = {
       else return { status: 'error', error: e as any };
     }
   },
+  async getRefreshSettings(): Promise<Result<[number, number], string>> {
+    try {
+      return { status: 'ok', data: await TAURI_INVOKE('get_refresh_settings') };
+    } catch (e) {
+      if (e instanceof Error) throw e;
+      else return { status: 'error', error: e as any };
+    }
+  },
+  async setRefreshSettings(
+    intervalMinutes: number,
+    batchSize: number,
+  ): Promise<Result<null, string>> {
+    try {
+      return {
+        status: 'ok',
+        data: await TAURI_INVOKE('set_refresh_settings', {
+          intervalMinutes,
+          batchSize,
+        }),
+      };
+    } catch (e) {
+      if (e instanceof Error) throw e;
+      else return { status: 'error', error: e as any };
+    }
+  },
+  async getOfflineMode(): Promise<Result<boolean, string>> {
+    try {
+      return { status: 'ok', data: await TAURI_INVOKE('get_offline_mode') };
+    } catch (e) {
+      if (e instanceof Error) throw e;
+      else return { status: 'error', error: e as any };
+    }
+  },
+  /**
+   * Switches offline mode, in which network-bound actions are queued in the outbox
+   * Leaving offline mode replays the outbox right away
+   */
+  async setOfflineMode(offline: boolean): Promise<Result<null, string>> {
+    try {
+      return {
+        status: 'ok',
+        data: await TAURI_INVOKE('set_offline_mode', { offline }),
+      };
+    } catch (e) {
+      if (e instanceof Error) throw e;
+      else return { status: 'error', error: e as any };
+    }
+  },
+  async getNetworkSettings(): Promise<Result<NetworkSettings, string>> {
+    try {
+      return { status: 'ok', data: await TAURI_INVOKE('get_network_settings') };
+    } catch (e) {
+      if (e instanceof Error) throw e;
+      else return { status: 'error', error: e as any };
+    }
+  },
+  /**
+   * Saves the proxy, timeouts and root certificates, applying them to the next requests
+   */
+  async setNetworkSettings(
+    settings: NetworkSettings,
+  ): Promise<Result<null, string>> {
+    try {
+      return {
+        status: 'ok',
+        data: await TAURI_INVOKE('set_network_settings', { settings }),
+      };
+    } catch (e) {
+      if (e instanceof Error) throw e;
+      else return { status: 'error', error: e as any };
+    }
+  },
+  async getInstancePresets(): Promise<Result<InstancePreset[], string>> {
+    try {
+      return { status: 'ok', data: await TAURI_INVOKE('get_instance_presets') };
+    } catch (e) {
+      if (e instanceof Error) throw e;
+      else return { status: 'error', error: e as any };
+    }
+  },
+  /**
+   * Saves a preset, replacing the preset with the same name if there is one
+   */
+  async saveInstancePreset(
+    preset: InstancePreset,
+  ): Promise<Result<null, string>> {
+    try {
+      return {
+        status: 'ok',
+        data: await TAURI_INVOKE('save_instance_preset', { preset }),
+      };
+    } catch (e) {
+      if (e instanceof Error) throw e;
+      else return { status: 'error', error: e as any };
+    }
+  },
+  async deleteInstancePreset(name: string): Promise<Result<null, string>> {
+    try {
+      return {
+        status: 'ok',
+        data: await TAURI_INVOKE('delete_instance_preset', { name }),
+      };
+    } catch (e) {
+      if (e instanceof Error) throw e;
+      else return { status: 'error', error: e as any };
+    }
+  },
   async tryLogin(): Promise<Result<null, string>> {
     try {
       return { status: 'ok', data: await TAURI_INVOKE('try_login') };
@@ -576,109 +974,386 @@ export const commands = {
       else return { status: 'error', error: e as any };
     }
   },
-  async loginWith2fa(
-    code: string,
-    twoFactorType: string,
-  ): Promise<Result<null, string>> {
+  async loginWith2fa(
+    code: string,
+    twoFactorType: string,
+  ): Promise<Result<null, string>> {
+    try {
+      return {
+        status: 'ok',
+        data: await TAURI_INVOKE('login_with_2fa', { code, twoFactorType }),
+      };
+    } catch (e) {
+      if (e instanceof Error) throw e;
+      else return { status: 'error', error: e as any };
+    }
+  },
+  async logout(): Promise<Result<null, string>> {
+    try {
+      return { status: 'ok', data: await TAURI_INVOKE('logout') };
+    } catch (e) {
+      if (e instanceof Error) throw e;
+      else return { status: 'error', error: e as any };
+    }
+  },
+  async listAccounts(): Promise<Result<AccountInfo[], string>> {
+    try {
+      return { status: 'ok', data: await TAURI_INVOKE('list_accounts') };
+    } catch (e) {
+      if (e instanceof Error) throw e;
+      else return { status: 'error', error: e as any };
+    }
+  },
+  /**
+   * Detaches the active account so another one can log in through the usual login flow
+   */
+  async addAccount(): Promise<Result<null, string>> {
+    try {
+      return { status: 'ok', data: await TAURI_INVOKE('add_account') };
+    } catch (e) {
+      if (e instanceof Error) throw e;
+      else return { status: 'error', error: e as any };
+    }
+  },
+  async switchAccount(userId: string): Promise<Result<null, string>> {
+    try {
+      return {
+        status: 'ok',
+        data: await TAURI_INVOKE('switch_account', { userId }),
+      };
+    } catch (e) {
+      if (e instanceof Error) throw e;
+      else return { status: 'error', error: e as any };
+    }
+  },
+  async removeAccount(userId: string): Promise<Result<null, string>> {
+    try {
+      return {
+        status: 'ok',
+        data: await TAURI_INVOKE('remove_account', { userId }),
+      };
+    } catch (e) {
+      if (e instanceof Error) throw e;
+      else return { status: 'error', error: e as any };
+    }
+  },
+  /**
+   * Whether the stored sessions are protected by a passphrase that was not entered yet
+   */
+  async isSecretStoreLocked(): Promise<boolean> {
+    return await TAURI_INVOKE('is_secret_store_locked');
+  },
+  async unlockSecretStore(passphrase: string): Promise<Result<null, string>> {
+    try {
+      return {
+        status: 'ok',
+        data: await TAURI_INVOKE('unlock_secret_store', { passphrase }),
+      };
+    } catch (e) {
+      if (e instanceof Error) throw e;
+      else return { status: 'error', error: e as any };
+    }
+  },
+  /**
+   * Protects the stored sessions with a passphrase, or removes it when none is given
+   */
+  async setSecretPassphrase(
+    passphrase: string | null,
+  ): Promise<Result<null, string>> {
+    try {
+      return {
+        status: 'ok',
+        data: await TAURI_INVOKE('set_secret_passphrase', { passphrase }),
+      };
+    } catch (e) {
+      if (e instanceof Error) throw e;
+      else return { status: 'error', error: e as any };
+    }
+  },
+  async getFavoriteWorlds(): Promise<Result<null, string>> {
+    try {
+      return { status: 'ok', data: await TAURI_INVOKE('get_favorite_worlds') };
+    } catch (e) {
+      if (e instanceof Error) throw e;
+      else return { status: 'error', error: e as any };
+    }
+  },
+  /**
+   * Get the details of a world, saving it to the library unless `dont_save_to_local` is set
+   *
+   * While VRChat can not be reached, the details are read from the library or the world cache,
+   * and the world is queued to be refreshed once the connection is back.
+   */
+  async getWorld(
+    worldId: string,
+    dontSaveToLocal: boolean | null,
+  ): Promise<Result<WorldDetails, string>> {
+    try {
+      return {
+        status: 'ok',
+        data: await TAURI_INVOKE('get_world', { worldId, dontSaveToLocal }),
+      };
+    } catch (e) {
+      if (e instanceof Error) throw e;
+      else return { status: 'error', error: e as any };
+    }
+  },
+  async checkWorldInfo(worldId: string): Promise<Result<WorldDetails, string>> {
+    try {
+      return {
+        status: 'ok',
+        data: await TAURI_INVOKE('check_world_info', { worldId }),
+      };
+    } catch (e) {
+      if (e instanceof Error) throw e;
+      else return { status: 'error', error: e as any };
+    }
+  },
+  /**
+   * Get how many worlds the world cache holds, and how it served them
+   */
+  async getWorldCacheStats(): Promise<Result<WorldCacheStats, string>> {
+    try {
+      return {
+        status: 'ok',
+        data: await TAURI_INVOKE('get_world_cache_stats'),
+      };
+    } catch (e) {
+      if (e instanceof Error) throw e;
+      else return { status: 'error', error: e as any };
+    }
+  },
+  /**
+   * Drops every cached world, so the next details opened are fetched in full
+   */
+  async clearWorldCache(): Promise<Result<null, string>> {
+    try {
+      return { status: 'ok', data: await TAURI_INVOKE('clear_world_cache') };
+    } catch (e) {
+      if (e instanceof Error) throw e;
+      else return { status: 'error', error: e as any };
+    }
+  },
+  /**
+   * Get how many thumbnails the thumbnail cache holds, and how much space they take
+   */
+  async getThumbnailCacheStats(): Promise<Result<ThumbnailCacheStats, string>> {
+    try {
+      return {
+        status: 'ok',
+        data: await TAURI_INVOKE('get_thumbnail_cache_stats'),
+      };
+    } catch (e) {
+      if (e instanceof Error) throw e;
+      else return { status: 'error', error: e as any };
+    }
+  },
+  /**
+   * Drops every thumbnail, so they are downloaded again when the worlds are shown
+   */
+  async clearThumbnailCache(): Promise<Result<null, string>> {
+    try {
+      return {
+        status: 'ok',
+        data: await TAURI_INVOKE('clear_thumbnail_cache'),
+      };
+    } catch (e) {
+      if (e instanceof Error) throw e;
+      else return { status: 'error', error: e as any };
+    }
+  },
+  /**
+   * Favorites a world in one of the user's VRChat favorite groups
+   *
+   * # Returns
+   * True if the world was favorited, false if it was queued in the outbox while offline
+   */
+  async addWorldToFavorites(
+    worldId: string,
+    group: string,
+  ): Promise<Result<boolean, string>> {
+    try {
+      return {
+        status: 'ok',
+        data: await TAURI_INVOKE('add_world_to_favorites', { worldId, group }),
+      };
+    } catch (e) {
+      if (e instanceof Error) throw e;
+      else return { status: 'error', error: e as any };
+    }
+  },
+  async getRecentlyVisitedWorlds(): Promise<
+    Result<WorldDisplayData[], string>
+  > {
+    try {
+      return {
+        status: 'ok',
+        data: await TAURI_INVOKE('get_recently_visited_worlds'),
+      };
+    } catch (e) {
+      if (e instanceof Error) throw e;
+      else return { status: 'error', error: e as any };
+    }
+  },
+  async searchWorlds(
+    sort: string,
+    tags: string[],
+    excludeTags: string[],
+    search: string,
+    page: number,
+  ): Promise<Result<WorldDisplayData[], string>> {
+    try {
+      return {
+        status: 'ok',
+        data: await TAURI_INVOKE('search_worlds', {
+          sort,
+          tags,
+          excludeTags,
+          search,
+          page,
+        }),
+      };
+    } catch (e) {
+      if (e instanceof Error) throw e;
+      else return { status: 'error', error: e as any };
+    }
+  },
+  async createWorldInstance(
+    worldId: string,
+    instanceTypeStr: string,
+    regionStr: string,
+  ): Promise<Result<InstanceInfo, string>> {
     try {
       return {
         status: 'ok',
-        data: await TAURI_INVOKE('login_with_2fa', { code, twoFactorType }),
+        data: await TAURI_INVOKE('create_world_instance', {
+          worldId,
+          instanceTypeStr,
+          regionStr,
+        }),
       };
     } catch (e) {
       if (e instanceof Error) throw e;
       else return { status: 'error', error: e as any };
     }
   },
-  async logout(): Promise<Result<null, string>> {
+  async getWorldInstances(
+    worldId: string,
+  ): Promise<Result<Instance[], string>> {
     try {
-      return { status: 'ok', data: await TAURI_INVOKE('logout') };
+      return {
+        status: 'ok',
+        data: await TAURI_INVOKE('get_world_instances', { worldId }),
+      };
     } catch (e) {
       if (e instanceof Error) throw e;
       else return { status: 'error', error: e as any };
     }
   },
-  async getFavoriteWorlds(): Promise<Result<null, string>> {
+  async createWorldInstanceWithOptions(
+    worldId: string,
+    options: InstanceOptions,
+  ): Promise<Result<InstanceInfo, string>> {
     try {
-      return { status: 'ok', data: await TAURI_INVOKE('get_favorite_worlds') };
+      return {
+        status: 'ok',
+        data: await TAURI_INVOKE('create_world_instance_with_options', {
+          worldId,
+          options,
+        }),
+      };
     } catch (e) {
       if (e instanceof Error) throw e;
       else return { status: 'error', error: e as any };
     }
   },
-  async getWorld(
+  /**
+   * Creates an instance of a world with the options of a saved preset
+   */
+  async createWorldInstanceFromPreset(
     worldId: string,
-    dontSaveToLocal: boolean | null,
-  ): Promise<Result<WorldDetails, string>> {
+    presetName: string,
+  ): Promise<Result<InstanceInfo, string>> {
     try {
       return {
         status: 'ok',
-        data: await TAURI_INVOKE('get_world', { worldId, dontSaveToLocal }),
+        data: await TAURI_INVOKE('create_world_instance_from_preset', {
+          worldId,
+          presetName,
+        }),
       };
     } catch (e) {
       if (e instanceof Error) throw e;
       else return { status: 'error', error: e as any };
     }
   },
-  async checkWorldInfo(worldId: string): Promise<Result<WorldDetails, string>> {
+  /**
+   * Creates an instance and invites every listed user to it, reporting each invite
+   */
+  async createWorldInstanceAndInvite(
+    worldId: string,
+    instanceTypeStr: string,
+    regionStr: string,
+    userIds: string[],
+  ): Promise<Result<InstanceInvites, string>> {
     try {
       return {
         status: 'ok',
-        data: await TAURI_INVOKE('check_world_info', { worldId }),
+        data: await TAURI_INVOKE('create_world_instance_and_invite', {
+          worldId,
+          instanceTypeStr,
+          regionStr,
+          userIds,
+        }),
       };
     } catch (e) {
       if (e instanceof Error) throw e;
       else return { status: 'error', error: e as any };
     }
   },
-  async getRecentlyVisitedWorlds(): Promise<
-    Result<WorldDisplayData[], string>
-  > {
+  async inviteUsersToInstance(
+    worldId: string,
+    instanceId: string,
+    userIds: string[],
+  ): Promise<Result<InviteResult[], string>> {
     try {
       return {
         status: 'ok',
-        data: await TAURI_INVOKE('get_recently_visited_worlds'),
+        data: await TAURI_INVOKE('invite_users_to_instance', {
+          worldId,
+          instanceId,
+          userIds,
+        }),
       };
     } catch (e) {
       if (e instanceof Error) throw e;
       else return { status: 'error', error: e as any };
     }
   },
-  async searchWorlds(
-    sort: string,
-    tags: string[],
-    excludeTags: string[],
-    search: string,
-    page: number,
-  ): Promise<Result<WorldDisplayData[], string>> {
+  async requestInvite(
+    userId: string,
+    requestSlot: number | null,
+  ): Promise<Result<null, string>> {
     try {
       return {
         status: 'ok',
-        data: await TAURI_INVOKE('search_worlds', {
-          sort,
-          tags,
-          excludeTags,
-          search,
-          page,
-        }),
+        data: await TAURI_INVOKE('request_invite', { userId, requestSlot }),
       };
     } catch (e) {
       if (e instanceof Error) throw e;
       else return { status: 'error', error: e as any };
     }
   },
-  async createWorldInstance(
-    worldId: string,
-    instanceTypeStr: string,
-    regionStr: string,
-  ): Promise<Result<InstanceInfo, string>> {
+  async respondToInvite(
+    notificationId: string,
+    responseSlot: number,
+  ): Promise<Result<null, string>> {
     try {
       return {
         status: 'ok',
-        data: await TAURI_INVOKE('create_world_instance', {
-          worldId,
-          instanceTypeStr,
-          regionStr,
+        data: await TAURI_INVOKE('respond_to_invite', {
+          notificationId,
+          responseSlot,
         }),
       };
     } catch (e) {
@@ -998,22 +1673,96 @@ export const commands = {
       else return { status: 'error', error: e as any };
     }
   },
+  /**
+   * Lists the queued network-bound actions, with the status of each
+   */
+  async getOutbox(): Promise<Result<OutboxItem[], string>> {
+    try {
+      return { status: 'ok', data: await TAURI_INVOKE('get_outbox') };
+    } catch (e) {
+      if (e instanceof Error) throw e;
+      else return { status: 'error', error: e as any };
+    }
+  },
+  /**
+   * Queues an action the server rejected again
+   */
+  async retryOutboxItem(id: string): Promise<Result<null, string>> {
+    try {
+      return {
+        status: 'ok',
+        data: await TAURI_INVOKE('retry_outbox_item', { id }),
+      };
+    } catch (e) {
+      if (e instanceof Error) throw e;
+      else return { status: 'error', error: e as any };
+    }
+  },
+  /**
+   * Drops a queued action, unless it is being sent
+   */
+  async removeOutboxItem(id: string): Promise<Result<boolean, string>> {
+    try {
+      return {
+        status: 'ok',
+        data: await TAURI_INVOKE('remove_outbox_item', { id }),
+      };
+    } catch (e) {
+      if (e instanceof Error) throw e;
+      else return { status: 'error', error: e as any };
+    }
+  },
+  /**
+   * Drops the actions that were sent
+   */
+  async clearCompletedOutbox(): Promise<Result<null, string>> {
+    try {
+      return {
+        status: 'ok',
+        data: await TAURI_INVOKE('clear_completed_outbox'),
+      };
+    } catch (e) {
+      if (e instanceof Error) throw e;
+      else return { status: 'error', error: e as any };
+    }
+  },
 };
 
 /** user-defined events **/
 
 export const events = __makeEvents__<{
+  instanceAvailable: InstanceAvailable;
+  outboxItemChanged: OutboxItemChanged;
+  scheduledInstanceResult: ScheduledInstanceResult;
+  sessionExpired: SessionExpired;
   taskStatusChanged: TaskStatusChanged;
   updateProgress: UpdateProgress;
+  worldRefreshProgress: WorldRefreshProgress;
 }>({
+  instanceAvailable: 'instance-available',
+  outboxItemChanged: 'outbox-item-changed',
+  scheduledInstanceResult: 'scheduled-instance-result',
+  sessionExpired: 'session-expired',
   taskStatusChanged: 'task-status-changed',
   updateProgress: 'update-progress',
+  worldRefreshProgress: 'world-refresh-progress',
 });
 
 /** user-defined constants **/
 
 /** user-defined types **/
 
+/**
+ * A stored VRChat account, as shown in the account switcher
+ */
+export type AccountInfo = {
+  user_id: string;
+  display_name: string;
+  /**
+   * Whether this is the account the API requests are made with
+   */
+  active: boolean;
+};
 export type BackupMetaData = {
   date: string;
   number_of_folders: number;
@@ -1021,12 +1770,107 @@ export type BackupMetaData = {
   app_version: string;
 };
 export type CardSize = 'Compact' | 'Normal' | 'Expanded' | 'Original';
+/**
+ * What importing a favorite group changed in the folder it is filed into
+ */
+export type FavoriteGroupImport = {
+  group: string;
+  folder_name: string;
+  /**
+   * Worlds filed into the folder
+   */
+  added: number;
+  /**
+   * Worlds taken out of the folder, as they left the group since the last sync
+   */
+  removed: number;
+  /**
+   * Worlds of the group that were already in the folder
+   */
+  unchanged: number;
+};
+export type FavoriteSyncConflict = {
+  world_id: string;
+  reason: FavoriteSyncConflictReason;
+};
+/**
+ * Why a change to a world can't be synced
+ */
+export type FavoriteSyncConflictReason =
+  /**
+   * The favorite group already holds `FAVORITE_GROUP_LIMIT` worlds
+   */
+  | 'GroupFull'
+  /**
+   * The world is favorited in another group, and VRChat allows only one
+   */
+  | { InOtherGroup: string }
+  /**
+   * The world was privated or deleted, so it can't be favorited or imported
+   */
+  | 'Unavailable';
+/**
+ * The changes that bring a folder and its favorite group back in sync
+ */
+export type FavoriteSyncPlan = {
+  folder_name: string;
+  group: string;
+  /**
+   * Worlds added to the group in game, to add to the folder
+   */
+  import: string[];
+  /**
+   * Worlds removed from the group in game, to remove from the folder
+   */
+  remove_local: string[];
+  /**
+   * Worlds added to the folder, to favorite in the group
+   */
+  push_add: string[];
+  /**
+   * Worlds removed from the folder, to unfavorite
+   */
+  push_remove: string[];
+  /**
+   * Changes that are left out, and why
+   */
+  conflicts: FavoriteSyncConflict[];
+  /**
+   * Worlds whose change could not be sent to VRChat, once the plan is applied
+   */
+  failed: string[];
+};
+/**
+ * A value of a world before and after a refresh
+ */
+export type FieldChange<T> = { old: T; new: T };
 export type FilterItemSelectorStarredType =
   | 'Author'
   | 'Tag'
   | 'ExcludeTag'
   | 'Folder';
-export type FolderData = { name: string; world_count: number };
+export type FolderData = {
+  name: string;
+  /**
+   * The names of the folder and its ancestors, joined with "/"
+   * This identifies the folder in commands taking a folder name
+   */
+  path: string;
+  world_count: number;
+  /**
+   * The number of distinct worlds in the folder and all of its subfolders
+   */
+  total_count: number;
+  /**
+   * The saved query, if this is a smart folder
+   */
+  query: string | null;
+  /**
+   * The VRChat favorite group the folder is synced with, if any
+   */
+  favorite_group: string | null;
+  children: FolderData[];
+};
 export type FolderRemovalPreference = 'ask' | 'alwaysRemove' | 'neverRemove';
 export type GroupInstanceCreateAllowedType = {
   normal: boolean;
@@ -1074,12 +1918,88 @@ export type GroupRole = {
   permissions: GroupPermission[];
   isManagementRole: boolean;
 };
+export type Instance = {
+  id: string;
+  instanceId: string;
+  ownerId: string | null;
+  photonRegion: InstanceRegion;
+  region: InstanceRegion;
+  shortName: string | null;
+  worldId: string;
+  /**
+   * The access type as VRChat names it: public, hidden, friends, private or group
+   */
+  type?: string;
+  /**
+   * The access type of a group instance: public, plus or members
+   */
+  groupAccessType?: string | null;
+  n_users?: number;
+  capacity?: number;
+};
+/**
+ * Emitted when a watched world gets an instance that can be joined
+ */
+export type InstanceAvailable = { instance: Instance };
 export type InstanceInfo = {
   world_id: string;
   instance_id: string;
   short_name: string | null;
 };
+export type InstanceInvites = {
+  instance: InstanceInfo;
+  invites: InviteResult[];
+};
+/**
+ * Everything needed to create an instance, apart from the world
+ */
+export type InstanceOptions = {
+  /**
+   * public, friends+, friends, invite+ or invite, or for a group instance
+   * public, group+ or group
+   */
+  instanceType: string;
+  region: InstanceRegion;
+  /**
+   * The group to create a group instance for
+   */
+  groupId?: string | null;
+  /**
+   * The roles allowed in a group only instance, or empty for every member
+   */
+  roleIds?: string[];
+  queueEnabled?: boolean;
+  displayName?: string | null;
+  ageGate?: boolean;
+  /**
+   * Minutes after creation at which the instance closes to new users
+   */
+  closeAfterMinutes?: number | null;
+  /**
+   * Removes the users still in the instance when it closes
+   */
+  hardClose?: boolean;
+  /**
+   * Overrides whether users can ask to be invited, which depends on the type otherwise
+   */
+  canRequestInvite?: boolean | null;
+  inviteOnly?: boolean;
+};
+/**
+ * Instance options saved under a name, to create instances of any world with
+ */
+export type InstancePreset = { name: string; options: InstanceOptions };
 export type InstanceRegion = 'us' | 'use' | 'eu' | 'jp';
+/**
+ * The outcome of inviting one user to an instance
+ */
+export type InviteResult = {
+  user_id: string;
+  /**
+   * Why the invite was not sent, or None if it was
+   */
+  error: string | null;
+};
 export type LocalizedChanges = {
   version: string;
   pre_release: boolean;
@@ -1087,6 +2007,76 @@ export type LocalizedChanges = {
   fixes: string[];
   others: string[];
 };
+/**
+ * How the app connects to VRChat and every other server
+ */
+export type NetworkSettings = {
+  /**
+   * The proxy every request goes through, e.g. http://proxy:8080 or socks5://proxy:1080
+   */
+  proxyUrl?: string | null;
+  /**
+   * Seconds to wait for a connection to be established
+   */
+  connectTimeoutSeconds?: number;
+  /**
+   * Seconds to wait for data once connected
+   */
+  readTimeoutSeconds?: number;
+  /**
+   * Paths of PEM certificates to trust on top of the system ones,
+   * e.g. the certificate of a proxy that inspects TLS traffic
+   */
+  extraRootCertificates?: string[];
+};
+export type OutboxItem = {
+  id: string;
+  operation: OutboxOperation;
+  status: OutboxStatus;
+  /**
+   * How many times the action was sent
+   */
+  attempts: number;
+  lastError: string | null;
+  queuedAt: string;
+};
+/**
+ * Emitted whenever a queued action is added, sent, or its status changes
+ */
+export type OutboxItemChanged = { item: OutboxItem };
+/**
+ * A network-bound action, queued while VRChat or the share worker could not be reached
+ */
+export type OutboxOperation =
+  /**
+   * Favorite a world in one of the user's favorite groups
+   */
+  | { type: 'AddFavorite'; worldId: string; group: string }
+  /**
+   * Fetch the latest data of a world in the library
+   */
+  | { type: 'RefreshWorld'; worldId: string }
+  /**
+   * Upload a folder to the share worker
+   */
+  | { type: 'ShareFolder'; folderName: string };
+export type OutboxStatus =
+  /**
+   * Waiting for the connection to come back
+   */
+  | 'Pending'
+  /**
+   * Being sent
+   */
+  | 'Running'
+  /**
+   * Sent successfully
+   */
+  | 'Completed'
+  /**
+   * Rejected by the server, and not retried unless the user asks to
+   */
+  | 'Failed';
 export type PatreonData = {
   platinumSupporter: string[];
   goldSupporter: string[];
@@ -1110,8 +2100,61 @@ export type PreviousMetadata = {
   number_of_folders: number;
   number_of_worlds: number;
 };
+/**
+ * What became of a scheduled instance
+ */
+export type ScheduleStatus =
+  | 'Pending'
+  /**
+   * The instance was created, with this instance ID
+   */
+  | { Created: string }
+  | { Failed: string }
+  /**
+   * The app was not running at the scheduled time, so nothing was created
+   */
+  | 'Missed';
+/**
+ * An instance to create at a later time
+ */
+export type ScheduledInstance = {
+  id: string;
+  worldId: string;
+  /**
+   * The preset whose options the instance is created with
+   */
+  presetName: string;
+  /**
+   * Overrides the region of the preset
+   */
+  region: InstanceRegion | null;
+  /**
+   * Overrides the group of the preset
+   */
+  groupId: string | null;
+  scheduledAt: string;
+  inviteSelf: boolean;
+  status: ScheduleStatus;
+};
+/**
+ * Emitted when a scheduled instance was created, failed, or was missed
+ */
+export type ScheduledInstanceResult = { schedule: ScheduledInstance };
+/**
+ * Emitted when VRChat rejected the session and it could not be verified again,
+ * so the user has to log in again
+ */
+export type SessionExpired = { user_id: string };
 export type TaskStatus = 'Running' | 'Completed' | 'Cancelled' | 'Failed';
 export type TaskStatusChanged = { id: string; status: TaskStatus };
+/**
+ * How much the thumbnail cache holds
+ */
+export type ThumbnailCacheStats = {
+  entries: number;
+  sizeBytes: number;
+  budgetBytes: number;
+};
 export type UpdateChannel = 'stable' | 'pre-release';
 export type UpdateProgress = { progress: number };
 export type UserGroup = {
@@ -1129,7 +2172,46 @@ export type UserGroup = {
   isRepresenting: boolean;
   mutualGroup: boolean;
 };
+/**
+ * Whether a world can still be visited, as found by the last refresh or favorites import
+ */
+export type WorldAvailability = 'available' | 'private' | 'deleted' | 'unknown';
 export type WorldBlacklist = { worlds: string[] };
+/**
+ * How much the world cache holds, and how it served the worlds since the app started
+ */
+export type WorldCacheStats = {
+  entries: number;
+  sizeBytes: number;
+  /**
+   * Worlds served from the cache without a request
+   */
+  hits: number;
+  /**
+   * Worlds VRChat confirmed had not changed
+   */
+  revalidated: number;
+  /**
+   * Worlds fetched in full
+   */
+  fetched: number;
+};
+/**
+ * What changed in a world between two refreshes
+ * Only the fields that changed are stored, to keep the history compact
+ */
+export type WorldChange = {
+  recordedAt: string;
+  updatedAt?: FieldChange<string> | null;
+  name?: FieldChange<string> | null;
+  description?: FieldChange<string> | null;
+  capacity?: FieldChange<number> | null;
+  platform?: FieldChange<Platform[]> | null;
+  tagsAdded: string[];
+  tagsRemoved: string[];
+  visitsDelta?: number;
+  favoritesDelta?: number;
+};
 export type WorldDetails = {
   worldId: string;
   name: string;
@@ -1159,6 +2241,28 @@ export type WorldDisplayData = {
   folders: string[];
   tags: string[];
   capacity: number;
+  availability: WorldAvailability;
+};
+/**
+ * Progress of the background world refresh, emitted after every world
+ */
+export type WorldRefreshProgress = {
+  /**
+   * Worlds refreshed in the current batch
+   */
+  refreshed: number;
+  /**
+   * Worlds which could not be fetched in the current batch
+   */
+  failed: number;
+  /**
+   * Stale worlds left, including the rest of the current batch
+   */
+  remaining: number;
+  /**
+   * Set while the refresh is waiting for a rate limit backoff to end
+   */
+  waiting_seconds: number | null;
 };
 
 /** tauri-specta globals **/
@@ -4,3 +4,17 @@ import { twMerge } from 'tailwind-merge';
 export function cn(...inputs: ClassValue[]) {
   return twMerge(clsx(inputs));
 }
+
+/**
+ * Returns the URL of the thumbnail of a world, served from the local thumbnail cache.
+ * The image URL of the world is passed along, so an outdated thumbnail is replaced.
+ * Custom protocols are served from `http://<scheme>.localhost` on Windows.
+ */
+export function thumbnailUrl(worldId: string, sourceUrl: string): string {
+  const isWindows =
+    typeof navigator !== 'undefined' && navigator.userAgent.includes('Windows');
+  const path = `${worldId}?source=${encodeURIComponent(sourceUrl)}`;
+  return isWindows
+    ? `http://thumbnail.localhost/${path}`
+    : `thumbnail://localhost/${path}`;
+}